@@ -0,0 +1,181 @@
+use super::{AuthorityError, AuthorityResult};
+use crate::core::Address;
+use crate::transaction::Transaction;
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+/// Admission filter configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdmissionConfig {
+    /// Senders whose transactions are rejected
+    pub denied_senders: Vec<Address>,
+}
+
+/// Rejects transactions from denylisted senders before execution. The
+/// denylist can be replaced at runtime.
+#[derive(Debug, Default)]
+pub struct AdmissionFilter {
+    /// Denied senders
+    denied_senders: RwLock<HashSet<Address>>,
+}
+
+impl AdmissionFilter {
+    /// Create new admission filter
+    pub fn new(config: AdmissionConfig) -> Self {
+        let filter = Self::default();
+        filter.reload(config);
+        filter
+    }
+
+    /// Replace the denylist
+    pub fn reload(&self, config: AdmissionConfig) {
+        *self.denied_senders.write().unwrap() = config.denied_senders.into_iter().collect();
+    }
+
+    /// Current denylist
+    pub fn config(&self) -> AdmissionConfig {
+        let mut denied_senders: Vec<_> = self.denied_senders.read().unwrap()
+            .iter()
+            .copied()
+            .collect();
+        denied_senders.sort_by_key(|address| *address.as_bytes());
+        AdmissionConfig { denied_senders }
+    }
+
+    /// Check that `transaction` may be admitted
+    pub fn check(&self, transaction: &Transaction) -> AuthorityResult<()> {
+        if self.denied_senders.read().unwrap().contains(&transaction.sender) {
+            return Err(AuthorityError::TransactionDenied(
+                format!("Sender {:?} is denylisted", transaction.sender)
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Rejection for an admin request without the admin token
+#[derive(Debug)]
+struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// Admin API for the denylist: `GET /admin/denylist` returns it and
+/// `PUT /admin/denylist` replaces it. Requests must carry
+/// `Authorization: Bearer <admin_token>`; others get 401.
+pub fn admission_routes(
+    filter: Arc<AdmissionFilter>,
+    admin_token: String,
+) -> impl warp::Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    use warp::Filter;
+
+    let admin_token: Arc<str> = admin_token.into();
+    let auth = warp::header::optional::<String>("authorization")
+        .and_then(move |header: Option<String>| {
+            let authorized = header.as_deref()
+                .and_then(|header| header.strip_prefix("Bearer "))
+                .is_some_and(|token| token_eq(token, &admin_token));
+            async move {
+                if authorized {
+                    Ok(())
+                } else {
+                    Err(warp::reject::custom(Unauthorized))
+                }
+            }
+        })
+        .untuple_one();
+
+    let get = {
+        let filter = filter.clone();
+        warp::path!("denylist")
+            .and(warp::get())
+            .map(move || warp::reply::json(&filter.config()))
+    };
+
+    let put = warp::path!("denylist")
+        .and(warp::put())
+        .and(warp::body::json())
+        .map(move |config: AdmissionConfig| {
+            filter.reload(config);
+            warp::reply::json(&filter.config())
+        });
+
+    warp::path("admin")
+        .and(auth)
+        .and(get.or(put))
+        .recover(|rejection: warp::Rejection| async move {
+            if rejection.find::<Unauthorized>().is_some() {
+                Ok(warp::http::StatusCode::UNAUTHORIZED)
+            } else {
+                Err(rejection)
+            }
+        })
+}
+
+/// Compare tokens in time independent of where they differ
+fn token_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{MoveTransaction, TransactionData};
+
+    fn transaction_from(sender: Address) -> Transaction {
+        Transaction::new(
+            TransactionData::Move(MoveTransaction {
+                module: None,
+                function: None,
+                type_arguments: vec![],
+                arguments: vec![],
+            }),
+            sender,
+            1_000,
+            1,
+            vec![],
+            0,
+            0,
+            "sui-local".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_denylisted_sender_rejected() {
+        let denied = Address::from_bytes([1; 20]);
+        let allowed = Address::from_bytes([2; 20]);
+        let filter = Arc::new(AdmissionFilter::new(AdmissionConfig {
+            denied_senders: vec![denied],
+        }));
+
+        assert!(matches!(
+            filter.check(&transaction_from(denied)),
+            Err(AuthorityError::TransactionDenied(_))
+        ));
+        assert!(filter.check(&transaction_from(allowed)).is_ok());
+
+        // The admin API refuses requests without the token
+        let routes = admission_routes(filter.clone(), "secret".to_string());
+        let response = warp::test::request()
+            .method("PUT")
+            .path("/admin/denylist")
+            .json(&AdmissionConfig { denied_senders: vec![allowed] })
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 401);
+        assert!(filter.check(&transaction_from(denied)).is_err());
+
+        // Reloading through the admin API takes effect immediately
+        let response = warp::test::request()
+            .method("PUT")
+            .path("/admin/denylist")
+            .header("authorization", "Bearer secret")
+            .json(&AdmissionConfig { denied_senders: vec![allowed] })
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 200);
+
+        assert!(filter.check(&transaction_from(denied)).is_ok());
+        assert!(filter.check(&transaction_from(allowed)).is_err());
+    }
+}
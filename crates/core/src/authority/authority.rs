@@ -1,12 +1,18 @@
 use super::{
     AuthorityError, AuthorityResult, AuthorityState, CommitteeInfo,
-    AuthorityStore, CheckpointStore, EpochManager, Validator,
+    AuthorityStore, CheckpointStore, EpochInfo, EpochManager, ReplayCache, StoreConfig, Validator,
 };
 use crate::protocol::{ProtocolError, ProtocolResult};
 use crate::crypto::{KeyPair, PublicKey, Signature};
-use crate::core::{Object, ObjectID};
-use crate::transaction::{Transaction, TransactionDigest, TransactionEffects};
-use std::sync::Arc;
+use crate::core::{Object, ObjectID, ObjectRef};
+use crate::transaction::{
+    ExecutionStatus, SystemTransaction, Transaction, TransactionData, TransactionDigest,
+    TransactionEffects,
+};
+use crate::metrics::HealthState;
+use crate::utils::bcs;
+use serde::{Serialize, Deserialize};
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 
 /// Authority configuration
@@ -22,6 +28,106 @@ pub struct AuthorityConfig {
     pub initial_stake: u64,
 }
 
+/// Transaction effects signed by a single authority. A quorum of these
+/// over the same effects forms an effects certificate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTransactionEffects {
+    /// Effects
+    pub effects: TransactionEffects,
+    /// Signing authority
+    pub authority: PublicKey,
+    /// Signature over the BCS encoding of the effects
+    pub signature: Signature,
+}
+
+impl SignedTransactionEffects {
+    /// Verify the signature against the signing authority's key
+    pub fn verify(&self) -> AuthorityResult<()> {
+        verify_bcs(&self.effects, &self.authority, &self.signature)
+    }
+}
+
+/// Status of an object reference against the authority's latest state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObjectRefStatus {
+    /// Reference is the latest version
+    Current,
+    /// A newer version exists
+    Stale {
+        /// Latest reference
+        current: ObjectRef,
+    },
+    /// Object or referenced version does not exist
+    NotFound,
+}
+
+impl ObjectRefStatus {
+    /// Status of `reference` given the latest stored version of the object
+    fn of(reference: &ObjectRef, latest: Option<&Object>) -> Self {
+        match latest {
+            Some(object) if object.version() == reference.version => Self::Current,
+            Some(object) if object.version() > reference.version => Self::Stale {
+                current: ObjectRef::new(object.id(), object.version()),
+            },
+            _ => Self::NotFound,
+        }
+    }
+}
+
+/// Signing key of the authority, with a replacement staged until the next
+/// epoch boundary so certificates formed in this epoch keep verifying
+#[derive(Debug)]
+struct KeyRotation {
+    /// Key used for signing
+    current: KeyPair,
+    /// Key taking over at the next epoch boundary
+    next: Option<KeyPair>,
+}
+
+impl KeyRotation {
+    /// Create with no rotation staged
+    fn new(current: KeyPair) -> Self {
+        Self { current, next: None }
+    }
+
+    /// Stage `next` and build the system transaction announcing it
+    fn stage(&mut self, next: KeyPair) -> SystemTransaction {
+        let announcement = SystemTransaction::rotate_key(self.current.public(), &next);
+        self.next = Some(next);
+        announcement
+    }
+
+    /// Switch to the staged key at an epoch boundary, returning its public
+    /// key. The switch waits for a committee that carries the staged key,
+    /// i.e. until the announcement has executed.
+    fn advance(&mut self, epoch: &EpochInfo) -> Option<PublicKey> {
+        let staged = self.next.as_ref()?.public();
+        epoch.get_stake(&staged)?;
+        self.current = self.next.take()?;
+        Some(staged)
+    }
+}
+
+/// Sign the BCS encoding of `value`
+fn sign_bcs<T: Serialize>(value: &T, keypair: &KeyPair) -> Signature {
+    let message = bcs::to_bytes(value).expect("Value is BCS serializable");
+    keypair.sign(&message)
+}
+
+/// Verify a signature over the BCS encoding of `value`
+fn verify_bcs<T: Serialize>(
+    value: &T,
+    public_key: &PublicKey,
+    signature: &Signature,
+) -> AuthorityResult<()> {
+    let message = bcs::to_bytes(value)
+        .map_err(|e| AuthorityError::SerializationError(e.to_string()))?;
+    if !signature.verify(&message, public_key) {
+        return Err(AuthorityError::InvalidSignature);
+    }
+    Ok(())
+}
+
 /// Authority implementation
 pub struct Authority {
     /// Configuration
@@ -36,13 +142,19 @@ pub struct Authority {
     validator: Arc<Validator>,
     /// Current state
     state: RwLock<AuthorityState>,
+    /// Recently executed transactions
+    replay_cache: ReplayCache,
+    /// Signing key, starting from the configured keypair
+    keys: Mutex<KeyRotation>,
 }
 
 impl Authority {
     pub fn new(config: AuthorityConfig) -> AuthorityResult<Self> {
         let store = Arc::new(AuthorityStore::new(config.store_config.clone())?);
         let checkpoint_store = Arc::new(CheckpointStore::new(store.clone())?);
-        let epoch_manager = Arc::new(EpochManager::new(store.clone())?);
+        let epoch_manager = Arc::new(
+            EpochManager::new(store.clone())?.with_checkpoint_store(checkpoint_store.clone())
+        );
         
         let validator = Arc::new(Validator::new(
             config.keypair.clone(),
@@ -57,32 +169,102 @@ impl Authority {
         });
 
         Ok(Self {
-            config,
             store,
             checkpoint_store,
             epoch_manager,
             validator,
             state,
+            replay_cache: ReplayCache::default(),
+            keys: Mutex::new(KeyRotation::new(config.keypair.clone())),
+            config,
         })
     }
 
+    /// Rotate the signing key at the next epoch boundary. The old key keeps
+    /// signing until then; the returned system transaction publishes the
+    /// new public key and should be submitted by the caller.
+    pub fn rotate_keypair(&self, new: KeyPair) -> SystemTransaction {
+        self.keys.lock().unwrap().stage(new)
+    }
+
+    /// Report locally stored checkpoints to the node's health probes
+    pub fn set_health(&self, health: HealthState) {
+        self.checkpoint_store.set_health(health);
+    }
+
+    /// Public key currently used for signing
+    pub fn public_key(&self) -> PublicKey {
+        self.keys.lock().unwrap().current.public()
+    }
+
+    /// Sign `message` with the current key
+    fn sign(&self, message: &[u8]) -> Signature {
+        self.keys.lock().unwrap().current.sign(message)
+    }
+
     /// Handle transaction
     pub async fn handle_transaction(
         &self,
         transaction: Transaction,
-    ) -> AuthorityResult<TransactionEffects> {
+    ) -> AuthorityResult<SignedTransactionEffects> {
+        // Claim the digest before doing any verification work, so of several
+        // concurrent submissions only one executes
+        let digest = transaction.digest();
+        if !self.replay_cache.try_insert(digest) {
+            return Err(AuthorityError::AlreadyExecuted(format!("{:?}", digest)));
+        }
+
+        let result = self.handle_claimed_transaction(transaction, digest).await;
+        if let Err(e) = &result {
+            // Executed before the cache remembered it: keep the claim
+            if !matches!(e, AuthorityError::AlreadyExecuted(_)) {
+                self.replay_cache.remove(&digest);
+            }
+        }
+        result
+    }
+
+    /// Handle a transaction whose digest this authority has claimed
+    async fn handle_claimed_transaction(
+        &self,
+        transaction: Transaction,
+        digest: TransactionDigest,
+    ) -> AuthorityResult<SignedTransactionEffects> {
+        // The cache is bounded; fall back to the effects store
+        if self.get_transaction_effects(&digest).await?.is_some() {
+            return Err(AuthorityError::AlreadyExecuted(format!("{:?}", digest)));
+        }
+
         // Verify transaction
         self.verify_transaction(&transaction).await?;
+        self.validator.check_admission(&transaction)?;
+
+        // Lock owned inputs so no conflicting transaction gets signed. The
+        // guard releases them on every path out, including cancellation.
+        let _locks = self.store.acquire_owned_locks(&transaction.input_object_refs(), digest).await?;
 
         // Execute transaction
+        let data = transaction.data.clone();
         let effects = self.validator.execute_transaction(transaction).await?;
+        self.apply_key_rotation(&data, &effects).await?;
 
         // Update state if needed
         if effects.epoch_change.is_some() {
             self.update_epoch(effects.epoch_change.as_ref().unwrap()).await?;
         }
 
-        Ok(effects)
+        // Sign effects so the client can trust this single response
+        let signature = self.sign_effects(&effects);
+        Ok(SignedTransactionEffects {
+            effects,
+            authority: self.public_key(),
+            signature,
+        })
+    }
+
+    /// Sign transaction effects
+    pub fn sign_effects(&self, effects: &TransactionEffects) -> Signature {
+        sign_bcs(effects, &self.keys.lock().unwrap().current)
     }
 
     /// Handle certificate
@@ -94,7 +276,9 @@ impl Authority {
         self.verify_certificate(&certificate).await?;
 
         // Execute certificate
+        let data = certificate.transaction.data.clone();
         let effects = self.validator.execute_certificate(certificate).await?;
+        self.apply_key_rotation(&data, &effects).await?;
 
         // Update state if needed
         if effects.epoch_change.is_some() {
@@ -104,6 +288,31 @@ impl Authority {
         Ok(effects)
     }
 
+    /// Execute certificates in the order consensus committed them
+    pub async fn handle_consensus_commit(
+        &self,
+        certificates: Vec<Certificate>,
+    ) -> AuthorityResult<Vec<TransactionEffects>> {
+        for certificate in &certificates {
+            self.verify_certificate(certificate).await?;
+        }
+
+        let transactions: Vec<_> = certificates.into_iter()
+            .map(|certificate| certificate.transaction)
+            .collect();
+        let data: Vec<_> = transactions.iter().map(|transaction| transaction.data.clone()).collect();
+        let effects = self.validator.handle_consensus_commit(transactions).await?;
+
+        for (data, effects) in data.iter().zip(&effects) {
+            self.apply_key_rotation(data, effects).await?;
+            if let Some(epoch_change) = &effects.epoch_change {
+                self.update_epoch(epoch_change).await?;
+            }
+        }
+
+        Ok(effects)
+    }
+
     /// Sign transaction
     pub async fn sign_transaction(
         &self,
@@ -111,9 +320,14 @@ impl Authority {
     ) -> AuthorityResult<Signature> {
         // Verify transaction first
         self.verify_transaction(transaction).await?;
+        self.validator.check_admission(transaction)?;
+
+        // Lock owned inputs until the certificate commits, so this
+        // authority never signs two transactions spending one version
+        self.store.lock_owned_objects(&transaction.input_object_refs(), transaction.digest()).await?;
 
         // Sign transaction
-        let signature = self.config.keypair.sign(transaction.digest().as_ref());
+        let signature = self.sign(transaction.digest().as_ref());
         Ok(signature)
     }
 
@@ -133,6 +347,24 @@ impl Authority {
             return Err(AuthorityError::InvalidSignature);
         }
 
+        // Only the holder of a key may rotate it
+        transaction.verify_key_rotation()
+            .map_err(|e| AuthorityError::InvalidKeyRotation(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Carry an executed key rotation into the next committee
+    async fn apply_key_rotation(
+        &self,
+        data: &TransactionData,
+        effects: &TransactionEffects,
+    ) -> AuthorityResult<()> {
+        if let TransactionData::System(SystemTransaction::RotateKey { old_key, new_key, .. }) = data {
+            if matches!(effects.status, ExecutionStatus::Success) {
+                self.epoch_manager.rotate_key(old_key.clone(), new_key.clone()).await?;
+            }
+        }
         Ok(())
     }
 
@@ -172,8 +404,21 @@ impl Authority {
 
     /// Update epoch
     async fn update_epoch(&self, new_epoch: &EpochInfo) -> AuthorityResult<()> {
+        // The last certified checkpoint seeds the new epoch's randomness;
+        // without one nodes could disagree on it, so the epoch can't advance
+        let committee = self.epoch_manager.get_committee().await?;
+        let checkpoint = self.checkpoint_store.get_latest_checkpoint().await?
+            .ok_or_else(|| AuthorityError::CheckpointError(
+                "No checkpoint to seed the next epoch".into()
+            ))?;
+        let beacon_seed = checkpoint.beacon_seed(&committee)?;
+        self.validator.update_epoch(new_epoch.epoch, &beacon_seed).await;
+
         let mut state = self.state.write().await;
         state.epoch = new_epoch.epoch;
+        if let Some(public_key) = self.keys.lock().unwrap().advance(new_epoch) {
+            state.public_key = public_key;
+        }
         state.stake = new_epoch.get_stake(&state.public_key)
             .ok_or_else(|| AuthorityError::InvalidStake("Validator not in committee".into()))?;
 
@@ -187,6 +432,20 @@ impl Authority {
         })
     }
 
+    /// Check whether each object reference is current, so clients can
+    /// refresh stale references before submitting
+    pub async fn check_object_refs(
+        &self,
+        refs: &[ObjectRef],
+    ) -> AuthorityResult<Vec<ObjectRefStatus>> {
+        let mut statuses = Vec::with_capacity(refs.len());
+        for reference in refs {
+            let latest = self.get_object(&reference.id).await?;
+            statuses.push(ObjectRefStatus::of(reference, latest.as_ref()));
+        }
+        Ok(statuses)
+    }
+
     /// Get transaction
     pub async fn get_transaction(
         &self,
@@ -216,4 +475,228 @@ impl Authority {
             AuthorityError::CheckpointError(e.to_string())
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Address;
+    use crate::crypto::SignatureScheme;
+    use crate::storage::{CacheConfig, RocksConfig, StorageConfig};
+    use crate::transaction::MoveTransaction;
+    use tempfile::TempDir;
+
+    fn test_authority(temp_dir: &TempDir) -> AuthorityResult<(Authority, KeyPair)> {
+        let path = temp_dir.path().to_str().unwrap().to_string();
+        let keypair = KeyPair::generate(SignatureScheme::Ed25519).unwrap();
+        let authority = Authority::new(AuthorityConfig {
+            keypair: keypair.clone(),
+            network_address: "127.0.0.1:0".to_string(),
+            store_config: StoreConfig {
+                storage: StorageConfig {
+                    data_dir: path.clone(),
+                    cache_config: CacheConfig::default(),
+                    rocks_config: RocksConfig {
+                        path,
+                        ..Default::default()
+                    },
+                },
+                cache_size: 128,
+            },
+            initial_stake: 100,
+        })?;
+        Ok((authority, keypair))
+    }
+
+    fn signed_transaction(keypair: &KeyPair) -> Transaction {
+        let mut transaction = Transaction::new(
+            TransactionData::Move(MoveTransaction {
+                module: None,
+                function: None,
+                type_arguments: vec![],
+                arguments: vec![],
+            }),
+            Address::from_bytes([1; 20]),
+            1_000,
+            1,
+            vec![],
+            0,
+            0,
+            "sui-local".to_string(),
+        );
+        transaction.sign(keypair);
+        transaction
+    }
+
+    #[tokio::test]
+    async fn test_signed_effects_verify() -> AuthorityResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let (authority, keypair) = test_authority(&temp_dir)?;
+
+        let signed = authority.handle_transaction(signed_transaction(&keypair)).await?;
+        assert_eq!(signed.authority, authority.public_key());
+        signed.verify()?;
+        assert_eq!(authority.sign_effects(&signed.effects), signed.signature);
+
+        // Tampered effects or another authority's key fail
+        let mut tampered = signed.clone();
+        tampered.effects.gas_used += 1;
+        assert!(tampered.verify().is_err());
+        let impostor = SignedTransactionEffects {
+            authority: KeyPair::generate(SignatureScheme::Ed25519).unwrap().public(),
+            ..signed
+        };
+        assert!(impostor.verify().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_ref_status() {
+        use crate::core::{Address, Owner, SequenceNumber, TypeTag};
+
+        let mut object = Object::new(
+            ObjectID::from_bytes([1; 32]),
+            Owner::AddressOwner(Address::from_bytes([1; 20])),
+            TypeTag::U64,
+            vec![],
+        );
+        object.set_version(SequenceNumber::new(3));
+
+        let current = ObjectRef::new(object.id(), SequenceNumber::new(3));
+        let stale = ObjectRef::new(object.id(), SequenceNumber::new(2));
+        let missing = ObjectRef::new(ObjectID::from_bytes([2; 32]), SequenceNumber::new(0));
+
+        assert_eq!(ObjectRefStatus::of(&current, Some(&object)), ObjectRefStatus::Current);
+        assert_eq!(
+            ObjectRefStatus::of(&stale, Some(&object)),
+            ObjectRefStatus::Stale { current }
+        );
+        assert_eq!(ObjectRefStatus::of(&missing, None), ObjectRefStatus::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_key_rotation_at_epoch_boundary() -> AuthorityResult<()> {
+        use crate::authority::EpochConfig;
+        use crate::storage::test_utils::temp_storage;
+
+        let (_temp_dir, storage) = temp_storage();
+        let store = Arc::new(AuthorityStore::new(StoreConfig { storage, cache_size: 128 })?);
+        let manager = EpochManager::new(
+            EpochConfig {
+                epoch_duration_ms: 1_000,
+                min_validator_stake: 100,
+                max_validator_count: 4,
+            },
+            store,
+        )?;
+
+        let old = KeyPair::generate(SignatureScheme::Ed25519).unwrap();
+        let new = KeyPair::generate(SignatureScheme::Ed25519).unwrap();
+        manager.prepare_next_epoch(vec![(old.public(), 100)]).await?;
+        let epoch = manager.start_new_epoch(0).await?;
+        let mut keys = KeyRotation::new(old.clone());
+
+        // The announcement is signed by the old key and proves the new one
+        let mut announcement = Transaction::new(
+            TransactionData::System(keys.stage(new.clone())),
+            Address::from_bytes([1; 20]),
+            1_000,
+            1,
+            vec![],
+            epoch.epoch,
+            0,
+            "sui-local".to_string(),
+        );
+        announcement.sign(&old);
+        announcement.verify_key_rotation().unwrap();
+
+        // Not executed yet: the committee doesn't carry the new key, so the
+        // old key keeps signing past the boundary
+        let epoch = manager.maybe_advance(1_000).await?.expect("epoch 2 due");
+        assert_eq!(keys.advance(&epoch), None);
+        assert!(keys.current.sign(b"message").verify(b"message", &old.public()));
+
+        // Once executed, the next committee carries the new key with the same stake
+        let TransactionData::System(SystemTransaction::RotateKey { old_key, new_key, .. }) = announcement.data else {
+            unreachable!()
+        };
+        manager.rotate_key(old_key, new_key).await?;
+        assert_eq!(manager.get_current_epoch().await.get_stake(&old.public()), Some(100));
+
+        let epoch = manager.maybe_advance(2_000).await?.expect("epoch 3 due");
+        assert_eq!(epoch.get_stake(&new.public()), Some(100));
+        assert_eq!(epoch.get_stake(&old.public()), None);
+        assert_eq!(keys.advance(&epoch), Some(new.public()));
+        let signature = keys.current.sign(b"message");
+        assert!(signature.verify(b"message", &new.public()));
+        assert!(!signature.verify(b"message", &old.public()));
+
+        // Nothing staged, nothing changes at the following boundary
+        let epoch = manager.maybe_advance(3_000).await?.expect("epoch 4 due");
+        assert_eq!(keys.advance(&epoch), None);
+        assert_eq!(epoch.get_stake(&new.public()), Some(100));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_submissions_execute_once() -> AuthorityResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let (authority, keypair) = test_authority(&temp_dir)?;
+        let transaction = signed_transaction(&keypair);
+
+        // Both submissions race; exactly one executes
+        let (first, second) = tokio::join!(
+            authority.handle_transaction(transaction.clone()),
+            authority.handle_transaction(transaction.clone()),
+        );
+        let replays = [&first, &second].iter()
+            .filter(|result| matches!(result, Err(AuthorityError::AlreadyExecuted(_))))
+            .count();
+        assert_eq!(replays, 1);
+        assert!(first.is_ok() || second.is_ok());
+
+        // A later resubmission is rejected too
+        assert!(matches!(
+            authority.handle_transaction(transaction).await,
+            Err(AuthorityError::AlreadyExecuted(_))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_execution_releases_locks() -> AuthorityResult<()> {
+        use crate::core::{Owner, SequenceNumber, TypeTag};
+        use crate::protocol::CallArg;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (authority, keypair) = test_authority(&temp_dir)?;
+
+        let mut object = Object::new(
+            ObjectID::random(),
+            Owner::AddressOwner(Address::from_bytes([1; 20])),
+            TypeTag::U64,
+            vec![],
+        );
+        object.set_version(SequenceNumber::new(1));
+        authority.store.put_object(object.clone()).await?;
+        let object_ref = ObjectRef::new(object.id(), object.version());
+
+        // Locked, then rejected for its gas budget
+        let mut transaction = signed_transaction(&keypair);
+        transaction.gas_budget = u64::MAX;
+        if let TransactionData::Move(move_tx) = &mut transaction.data {
+            move_tx.arguments.push(CallArg::Object(object_ref));
+        }
+        transaction.sign(&keypair);
+        assert!(authority.handle_transaction(transaction).await.is_err());
+
+        // Another transaction can take the input
+        let other = TransactionDigest::from_bytes([9; 32]);
+        authority.store.lock_owned_objects(&[object_ref], other).await?;
+
+        Ok(())
+    }
+}
@@ -1,9 +1,11 @@
 use super::{AuthorityError, AuthorityResult, CommitteeInfo};
-use crate::storage::{Storage, StorageConfig};
-use crate::core::{Object, ObjectID};
+use crate::storage::{Event, EventFilter, IndexKey, IndexValue, Storage, StorageConfig, StorageWrite};
+use crate::core::{Object, ObjectID, ObjectRef, SequenceNumber};
 use crate::transaction::{Transaction, TransactionDigest, TransactionEffects};
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 
 /// Store configuration 
 #[derive(Debug, Clone)]
@@ -24,6 +26,10 @@ pub struct AuthorityStore {
     tx_cache: Arc<Cache<TransactionDigest, Transaction>>,
     /// Effects cache
     effects_cache: Arc<Cache<TransactionDigest, TransactionEffects>>,
+    /// Owned object locks; never held across an await
+    object_locks: std::sync::Mutex<HashMap<ObjectRef, TransactionDigest>>,
+    /// Serializes effects commits
+    commit_lock: Mutex<()>,
 }
 
 impl AuthorityStore {
@@ -40,6 +46,8 @@ impl AuthorityStore {
             object_cache: Arc::new(object_cache),
             tx_cache: Arc::new(tx_cache),
             effects_cache: Arc::new(effects_cache),
+            object_locks: std::sync::Mutex::new(HashMap::new()),
+            commit_lock: Mutex::new(()),
         })
     }
 
@@ -62,6 +70,35 @@ impl AuthorityStore {
         Ok(object)
     }
 
+    /// Get several objects, reading every cache miss in one batch
+    pub async fn multi_get_objects(&self, ids: &[ObjectID]) -> AuthorityResult<Vec<Option<Object>>> {
+        let mut objects: Vec<Option<Object>> = ids.iter().map(|id| self.object_cache.get(id)).collect();
+
+        let missing: Vec<usize> = (0..ids.len()).filter(|&i| objects[i].is_none()).collect();
+        let keys: Vec<ObjectKey> = missing.iter().map(|&i| ObjectKey::latest(&ids[i])).collect();
+        let fetched = self.storage.multi_get_objects(&keys)
+            .map_err(|e| AuthorityError::StoreError(e.to_string()))?;
+
+        for (i, object) in missing.into_iter().zip(fetched) {
+            if let Some(object) = &object {
+                self.object_cache.insert(ids[i], object.clone());
+            }
+            objects[i] = object;
+        }
+
+        Ok(objects)
+    }
+
+    /// Latest stored version of each object. Read from storage, so a
+    /// cold cache doesn't hide anything.
+    pub async fn multi_get_latest_versions(
+        &self,
+        ids: &[ObjectID],
+    ) -> AuthorityResult<Vec<Option<SequenceNumber>>> {
+        self.storage.multi_latest_versions(ids)
+            .map_err(|e| AuthorityError::StoreError(e.to_string()))
+    }
+
     /// Put object
     pub async fn put_object(&self, object: Object) -> AuthorityResult<()> {
         let id = object.id();
@@ -140,8 +177,15 @@ impl AuthorityStore {
         }
 
         // Get from storage
-        let effects = self.storage.get_effects(digest)
-            .map_err(|e| AuthorityError::StoreError(e.to_string()))?;
+        let effects = match self.storage.get_index(&effects_key(digest))
+            .map_err(|e| AuthorityError::StoreError(e.to_string()))?
+        {
+            Some(IndexValue::Custom(bytes)) => Some(
+                bincode::deserialize::<TransactionEffects>(&bytes)
+                    .map_err(|e| AuthorityError::DeserializationError(e.to_string()))?
+            ),
+            _ => None,
+        };
 
         // Update cache
         if let Some(effects) = effects.clone() {
@@ -159,7 +203,7 @@ impl AuthorityStore {
         let digest = effects.transaction_digest;
 
         // Update storage
-        self.storage.put_effects(effects.clone())
+        self.storage.update_index(effects_key(&digest), encode_effects(&effects)?)
             .map_err(|e| AuthorityError::StoreError(e.to_string()))?;
 
         // Update cache
@@ -168,6 +212,133 @@ impl AuthorityStore {
         Ok(())
     }
 
+    /// Store effects and apply their object changes in one atomic batch,
+    /// so a crash leaves either the whole commit or none of it. Commits
+    /// are serialized so concurrent ones can't interleave their writes.
+    pub async fn commit_effects(&self, effects: TransactionEffects) -> AuthorityResult<()> {
+        let digest = effects.transaction_digest;
+        let _commit = self.commit_lock.lock().await;
+
+        let mut writes = vec![StorageWrite::Index(effects_key(&digest), encode_effects(&effects)?)];
+        writes.extend(effects.created_objects.iter()
+            .chain(effects.modified_objects.iter())
+            .map(|(id, object)| StorageWrite::PutObject(
+                ObjectKey::new(*id, object.version()),
+                object.clone(),
+            )));
+        writes.extend(effects.deleted_objects.iter()
+            .map(|id| StorageWrite::DeleteObject(ObjectKey::latest(id))));
+        self.storage.write_atomic(writes)
+            .map_err(|e| AuthorityError::StoreError(e.to_string()))?;
+
+        // Caches only change once the batch is durable
+        for (id, object) in effects.created_objects.iter().chain(effects.modified_objects.iter()) {
+            self.object_cache.insert(*id, object.clone());
+        }
+        for id in &effects.deleted_objects {
+            self.object_cache.remove(id);
+        }
+        self.effects_cache.insert(digest, effects);
+        self.clear_locks(&digest);
+
+        Ok(())
+    }
+
+    /// Lock an owned object version for a transaction. Fails if a different
+    /// transaction already holds the lock; re-locking by the same one is a no-op.
+    pub async fn lock_object(
+        &self,
+        object_ref: ObjectRef,
+        tx_digest: TransactionDigest,
+    ) -> AuthorityResult<()> {
+        let mut locks = self.object_locks.lock().unwrap();
+        match locks.get(&object_ref) {
+            Some(locked_by) if *locked_by != tx_digest => Err(AuthorityError::ObjectLocked {
+                object: format!("{:?}", object_ref),
+                locked_by: hex::encode(locked_by.as_bytes()),
+            }),
+            _ => {
+                locks.insert(object_ref, tx_digest);
+                Ok(())
+            }
+        }
+    }
+
+    /// Lock the owned objects among `refs` for a transaction, all or none.
+    /// Shared and immutable objects aren't locked. Fails if an object isn't
+    /// at the referenced version or another transaction holds its lock.
+    pub async fn lock_owned_objects(
+        &self,
+        refs: &[ObjectRef],
+        tx_digest: TransactionDigest,
+    ) -> AuthorityResult<()> {
+        let ids: Vec<ObjectID> = refs.iter().map(|object_ref| object_ref.id).collect();
+        let objects = self.multi_get_objects(&ids).await?;
+
+        let mut owned = Vec::new();
+        let mut missing = Vec::new();
+        for (object_ref, object) in refs.iter().zip(objects) {
+            let Some(object) = object else {
+                missing.push(object_ref.id);
+                continue;
+            };
+            if object.is_shared() || object.is_immutable() {
+                continue;
+            }
+            if object.version() != object_ref.version {
+                return Err(AuthorityError::ObjectVersionMismatch {
+                    object: object_ref.id,
+                    requested: object_ref.version,
+                    current: object.version(),
+                });
+            }
+            owned.push(*object_ref);
+        }
+        if !missing.is_empty() {
+            return Err(AuthorityError::MissingInputObjects(missing));
+        }
+
+        // Check every lock before taking any, under one guard
+        let mut locks = self.object_locks.lock().unwrap();
+        for object_ref in &owned {
+            if let Some(locked_by) = locks.get(object_ref).filter(|locked_by| **locked_by != tx_digest) {
+                return Err(AuthorityError::ObjectLocked {
+                    object: format!("{:?}", object_ref),
+                    locked_by: hex::encode(locked_by.as_bytes()),
+                });
+            }
+        }
+        for object_ref in owned {
+            locks.insert(object_ref, tx_digest);
+        }
+
+        Ok(())
+    }
+
+    /// Lock the owned objects among `refs` like `lock_owned_objects`,
+    /// releasing them when the returned guard is dropped
+    pub async fn acquire_owned_locks(
+        self: &Arc<Self>,
+        refs: &[ObjectRef],
+        tx_digest: TransactionDigest,
+    ) -> AuthorityResult<ObjectLockGuard> {
+        self.lock_owned_objects(refs, tx_digest).await?;
+        Ok(ObjectLockGuard {
+            store: self.clone(),
+            tx_digest,
+        })
+    }
+
+    /// Release the lock on an object version
+    pub async fn unlock(&self, object_ref: &ObjectRef) {
+        self.object_locks.lock().unwrap().remove(object_ref);
+    }
+
+    /// Release all locks held by a transaction
+    pub fn clear_locks(&self, tx_digest: &TransactionDigest) {
+        self.object_locks.lock().unwrap().retain(|_, locked_by| locked_by != tx_digest);
+    }
+
     /// Get committee info
     pub async fn get_committee(&self) -> AuthorityResult<CommitteeInfo> {
         self.storage.get_committee()
@@ -180,6 +351,18 @@ impl AuthorityStore {
             .map_err(|e| AuthorityError::StoreError(e.to_string()))
     }
 
+    /// Emit event
+    pub fn emit_event(&self, event: Event) -> AuthorityResult<()> {
+        self.storage.emit_event(event)
+            .map_err(|e| AuthorityError::StoreError(e.to_string()))
+    }
+
+    /// Get events by filter
+    pub fn get_events(&self, filter: &EventFilter) -> AuthorityResult<Vec<Event>> {
+        self.storage.get_events(filter)
+            .map_err(|e| AuthorityError::StoreError(e.to_string()))
+    }
+
     /// Clear caches
     pub fn clear_caches(&self) {
         self.object_cache.clear();
@@ -188,6 +371,36 @@ impl AuthorityStore {
     }
 }
 
+/// Object locks held by a transaction, released on drop so an attempt
+/// that fails or is cancelled never leaves its inputs locked
+pub struct ObjectLockGuard {
+    /// Store holding the locks
+    store: Arc<AuthorityStore>,
+    /// Transaction holding the locks
+    tx_digest: TransactionDigest,
+}
+
+impl Drop for ObjectLockGuard {
+    fn drop(&mut self) {
+        self.store.clear_locks(&self.tx_digest);
+    }
+}
+
+/// Index key of a transaction's effects
+fn effects_key(digest: &TransactionDigest) -> IndexKey {
+    IndexKey::Custom {
+        name: "effects".to_string(),
+        key: digest.as_bytes().to_vec(),
+    }
+}
+
+/// Encode effects as an index value
+fn encode_effects(effects: &TransactionEffects) -> AuthorityResult<IndexValue> {
+    bincode::serialize(effects)
+        .map(IndexValue::Custom)
+        .map_err(|e| AuthorityError::SerializationError(e.to_string()))
+}
+
 /// Object key for storage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectKey {
@@ -208,4 +421,143 @@ impl ObjectKey {
             version: SequenceNumber::MAX,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Address, Owner, TypeTag};
+    use crate::storage::{CacheConfig, RocksConfig};
+    use crate::transaction::ExecutionStatus;
+    use tempfile::TempDir;
+
+    fn test_config(temp_dir: &TempDir) -> StoreConfig {
+        let path = temp_dir.path().to_str().unwrap().to_string();
+        StoreConfig {
+            storage: StorageConfig {
+                data_dir: path.clone(),
+                cache_config: CacheConfig::default(),
+                rocks_config: RocksConfig {
+                    path,
+                    ..Default::default()
+                },
+            },
+            cache_size: 128,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commit_effects_writes_everything() -> AuthorityResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let object = Object::new(
+            ObjectID::random(),
+            Owner::AddressOwner(Address::from_bytes([1; 20])),
+            TypeTag::U64,
+            vec![1, 2, 3],
+        );
+        let id = object.id();
+        let effects = |digest: u8| TransactionEffects {
+            epoch: 0,
+            transaction_digest: TransactionDigest::from_bytes([digest; 32]),
+            status: ExecutionStatus::Success,
+            gas_used: 0,
+            modified_objects: Default::default(),
+            created_objects: Default::default(),
+            deleted_objects: Vec::new(),
+            events: Vec::new(),
+            dependencies: Vec::new(),
+            epoch_change: None,
+        };
+
+        {
+            let store = AuthorityStore::new(test_config(&temp_dir))?;
+            let object_ref = ObjectRef::new(id, SequenceNumber::new(1));
+            store.lock_object(object_ref, TransactionDigest::from_bytes([1; 32])).await?;
+
+            store.commit_effects(TransactionEffects {
+                created_objects: vec![(id, object)].into_iter().collect(),
+                ..effects(1)
+            }).await?;
+
+            // Committing releases the transaction's locks
+            store.lock_object(object_ref, TransactionDigest::from_bytes([2; 32])).await?;
+        }
+
+        // Effects and objects land together
+        let store = AuthorityStore::new(test_config(&temp_dir))?;
+        assert!(store.get_effects(&TransactionDigest::from_bytes([1; 32])).await?.is_some());
+        assert!(store.get_object(&id).await?.is_some());
+
+        store.commit_effects(TransactionEffects {
+            deleted_objects: vec![id],
+            ..effects(2)
+        }).await?;
+        assert!(store.get_object(&id).await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_object_lock_conflict() -> AuthorityResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let store = AuthorityStore::new(test_config(&temp_dir))?;
+
+        let object_ref = ObjectRef::new(ObjectID::random(), SequenceNumber::new(1));
+        let tx_a = TransactionDigest::from_bytes([1; 32]);
+        let tx_b = TransactionDigest::from_bytes([2; 32]);
+
+        store.lock_object(object_ref, tx_a).await?;
+        store.lock_object(object_ref, tx_a).await?;
+        assert!(matches!(
+            store.lock_object(object_ref, tx_b).await,
+            Err(AuthorityError::ObjectLocked { .. })
+        ));
+
+        // Once A's effects are committed the version can be locked again
+        store.clear_locks(&tx_a);
+        store.lock_object(object_ref, tx_b).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lock_owned_objects() -> AuthorityResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let store = AuthorityStore::new(test_config(&temp_dir))?;
+
+        let object = |owner: Owner| {
+            let mut object = Object::new(ObjectID::random(), owner, TypeTag::U64, vec![]);
+            object.set_version(SequenceNumber::new(1));
+            object
+        };
+        let owned = object(Owner::AddressOwner(Address::from_bytes([1; 20])));
+        let other = object(Owner::AddressOwner(Address::from_bytes([1; 20])));
+        let shared = object(Owner::Shared { initial_shared_version: SequenceNumber::new(1) });
+        for object in [&owned, &other, &shared] {
+            store.put_object(object.clone()).await?;
+        }
+        let owned_ref = ObjectRef::new(owned.id(), owned.version());
+        let other_ref = ObjectRef::new(other.id(), other.version());
+        let shared_ref = ObjectRef::new(shared.id(), shared.version());
+        let tx_a = TransactionDigest::from_bytes([1; 32]);
+        let tx_b = TransactionDigest::from_bytes([2; 32]);
+
+        store.lock_owned_objects(&[owned_ref, shared_ref], tx_a).await?;
+
+        // Shared inputs aren't locked, owned ones are, and a failed
+        // attempt takes none of its locks
+        assert!(matches!(
+            store.lock_owned_objects(&[other_ref, shared_ref, owned_ref], tx_b).await,
+            Err(AuthorityError::ObjectLocked { .. })
+        ));
+        store.lock_owned_objects(&[other_ref, shared_ref], tx_b).await?;
+
+        // Only the current version can be locked
+        assert!(matches!(
+            store.lock_owned_objects(&[ObjectRef::new(owned.id(), SequenceNumber::new(0))], tx_b).await,
+            Err(AuthorityError::ObjectVersionMismatch { .. })
+        ));
+
+        Ok(())
+    }
+}
@@ -1,8 +1,13 @@
+use super::merkle::{self, MerkleProof};
 use super::{AuthorityError, AuthorityResult, AuthorityStore};
 use crate::core::{Object, ObjectID};
+use crate::crypto::{PublicKey, Signature};
+use crate::metrics::HealthState;
+use crate::protocol::Committee;
 use crate::transaction::{Transaction, TransactionDigest, TransactionEffects};
 use serde::{Serialize, Deserialize};
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
 
 /// Checkpoint data
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +22,8 @@ pub struct Checkpoint {
     pub timestamp: u64,
     /// Transactions included
     pub transactions: Vec<TransactionDigest>,
+    /// Merkle root over the included transaction digests
+    pub tx_root: [u8; 32],
     /// Effects included
     pub effects: Vec<TransactionEffects>,
     /// State root
@@ -25,9 +32,23 @@ pub struct Checkpoint {
     pub epoch: u64,
     /// Next epoch committee
     pub next_epoch_committee: Option<CommitteeInfo>,
+    /// Validator signatures over the digest, set once a quorum certifies it
+    pub signatures: Vec<(PublicKey, Signature)>,
 }
 
 impl Checkpoint {
+    /// Committee output seeding the next epoch's randomness beacon: the
+    /// digest, once `committee` has certified it. Every node agrees on it,
+    /// unlike the set of signatures each one happened to collect
+    pub fn beacon_seed(&self, committee: &Committee) -> AuthorityResult<[u8; 32]> {
+        if !self.is_certified(committee) {
+            return Err(AuthorityError::CheckpointError(format!(
+                "Checkpoint {} is not certified", self.sequence
+            )));
+        }
+        Ok(self.digest)
+    }
+
     /// Create new checkpoint
     pub fn new(
         sequence: u64,
@@ -44,11 +65,13 @@ impl Checkpoint {
             digest: [0; 32],
             previous_digest,
             timestamp,
+            tx_root: merkle::transactions_root(&transactions),
             transactions,
             effects,
             state_root,
             epoch,
             next_epoch_committee,
+            signatures: Vec::new(),
         };
         checkpoint.digest = checkpoint.compute_digest();
         checkpoint
@@ -68,6 +91,7 @@ impl Checkpoint {
         for tx in &self.transactions {
             hasher.update(tx.as_ref());
         }
+        hasher.update(self.tx_root);
         
         for effect in &self.effects {
             hasher.update(effect.transaction_digest.as_ref());
@@ -79,9 +103,68 @@ impl Checkpoint {
         hasher.finalize().into()
     }
 
-    /// Verify checkpoint
+    /// Verify checkpoint: the transaction root must match the transactions
+    /// and the digest must match the contents
     pub fn verify(&self) -> bool {
-        self.digest == self.compute_digest()
+        self.tx_root == merkle::transactions_root(&self.transactions)
+            && self.digest == self.compute_digest()
+    }
+
+    /// Check whether valid signatures from `committee` reaching a quorum
+    /// certify the checkpoint
+    pub fn is_certified(&self, committee: &Committee) -> bool {
+        self.signed_stake(committee)
+            .is_ok_and(|stake| stake >= committee.quorum_threshold())
+    }
+
+    /// Attach `signatures` after checking they are valid signatures over the
+    /// digest from distinct committee members reaching a quorum
+    pub fn certify(
+        &mut self,
+        signatures: Vec<(PublicKey, Signature)>,
+        committee: &Committee,
+    ) -> AuthorityResult<()> {
+        let weight = self.verify_signatures(&signatures, committee)?;
+        let threshold = committee.quorum_threshold();
+        if weight < threshold {
+            return Err(AuthorityError::InsufficientQuorum { weight, threshold });
+        }
+
+        self.signatures = signatures;
+        Ok(())
+    }
+
+    /// Total committee stake of the validators that signed the checkpoint.
+    /// Fails unless every signature is valid over the digest and comes from
+    /// a distinct committee member.
+    pub fn signed_stake(&self, committee: &Committee) -> AuthorityResult<u64> {
+        self.verify_signatures(&self.signatures, committee)
+    }
+
+    /// Verify `signatures` over the digest and sum the signers' stake
+    fn verify_signatures(
+        &self,
+        signatures: &[(PublicKey, Signature)],
+        committee: &Committee,
+    ) -> AuthorityResult<u64> {
+        let mut signers = HashSet::new();
+        let mut weight = 0u64;
+        for (authority, signature) in signatures {
+            if !signers.insert(authority) {
+                return Err(AuthorityError::CheckpointError(
+                    "Duplicate checkpoint signature".into()
+                ));
+            }
+            let authority_weight = committee.weight(authority)
+                .ok_or_else(|| AuthorityError::CheckpointError(
+                    "Signer not in committee".into()
+                ))?;
+            if !signature.verify(&self.digest, authority) {
+                return Err(AuthorityError::InvalidSignature);
+            }
+            weight += authority_weight;
+        }
+        Ok(weight)
     }
 }
 
@@ -91,13 +174,21 @@ pub struct CheckpointStore {
     store: Arc<AuthorityStore>,
     /// Column family for checkpoints
     checkpoints_cf: String,
+    /// Health state told about every stored checkpoint
+    health: OnceLock<HealthState>,
 }
 
 impl CheckpointStore {
     pub fn new(store: Arc<AuthorityStore>) -> AuthorityResult<Self> {
+        let checkpoints_cf = "checkpoints".to_string();
+        store.storage()
+            .require_column_families(&[&checkpoints_cf])
+            .map_err(|e| AuthorityError::StoreError(e.to_string()))?;
+
         Ok(Self {
             store,
-            checkpoints_cf: "checkpoints".to_string(),
+            checkpoints_cf,
+            health: OnceLock::new(),
         })
     }
 
@@ -138,9 +229,116 @@ impl CheckpointStore {
             .put(&self.checkpoints_cf, &key, &value)
             .map_err(|e| AuthorityError::StoreError(e.to_string()))?;
 
+        if let Some(health) = self.health.get() {
+            health.set_local_checkpoint(checkpoint.sequence);
+        }
+
         Ok(())
     }
 
+    /// Report stored checkpoints to `health` for the readiness probe. Only
+    /// the first health state set is used.
+    pub fn set_health(&self, health: HealthState) {
+        let _ = self.health.set(health);
+    }
+
+    /// Put a certified checkpoint. Every signature is verified and the
+    /// signers must reach a quorum. If a different checkpoint is already
+    /// stored at the same sequence, the one with more signing stake is kept,
+    /// ties going to the lower digest so every node picks the same one.
+    pub async fn put_certified_checkpoint(
+        &self,
+        checkpoint: Checkpoint,
+        committee: &Committee,
+    ) -> AuthorityResult<()> {
+        if !checkpoint.verify() {
+            return Err(AuthorityError::InvalidCheckpoint(
+                "Invalid checkpoint digest".into()
+            ));
+        }
+
+        let stake = checkpoint.signed_stake(committee)?;
+        let threshold = committee.quorum_threshold();
+        if stake < threshold {
+            return Err(AuthorityError::InsufficientQuorum { weight: stake, threshold });
+        }
+
+        if let Some(existing) = self.get_checkpoint(checkpoint.sequence).await? {
+            if existing.digest != checkpoint.digest {
+                // A stored checkpoint whose signatures don't verify has no
+                // stake to defend it with
+                let existing_stake = existing.signed_stake(committee).unwrap_or(0);
+                log::warn!(
+                    "Conflicting checkpoints at sequence {}: {} with stake {} vs stored {} with stake {}",
+                    checkpoint.sequence,
+                    hex::encode(checkpoint.digest),
+                    stake,
+                    hex::encode(existing.digest),
+                    existing_stake,
+                );
+
+                let displaces = (stake, std::cmp::Reverse(checkpoint.digest))
+                    > (existing_stake, std::cmp::Reverse(existing.digest));
+                if !displaces {
+                    return Err(AuthorityError::ConflictingCheckpoint {
+                        sequence: checkpoint.sequence,
+                        stake,
+                        existing_stake,
+                    });
+                }
+            }
+        }
+
+        self.put_checkpoint(checkpoint).await
+    }
+
+    /// Prove that transaction `digest` is included in checkpoint `sequence`,
+    /// verifiable against the checkpoint's `tx_root`
+    pub async fn prove_transaction(
+        &self,
+        sequence: u64,
+        digest: &TransactionDigest,
+    ) -> AuthorityResult<MerkleProof> {
+        let checkpoint = self.get_checkpoint(sequence).await?
+            .ok_or_else(|| AuthorityError::CheckpointError(
+                format!("Checkpoint {} not found", sequence)
+            ))?;
+        let index = checkpoint.transactions.iter()
+            .position(|tx| tx == digest)
+            .ok_or_else(|| AuthorityError::CheckpointError(
+                format!("Transaction {:?} not in checkpoint {}", digest, sequence)
+            ))?;
+        Ok(merkle::prove(&checkpoint.transactions, index))
+    }
+
+    /// Get checkpoint by sequence only if a quorum of `committee` has
+    /// certified it
+    pub async fn get_certified_checkpoint(
+        &self,
+        sequence: u64,
+        committee: &Committee,
+    ) -> AuthorityResult<Option<Checkpoint>> {
+        Ok(self.get_checkpoint(sequence).await?
+            .filter(|checkpoint| checkpoint.is_certified(committee)))
+    }
+
+    /// Certify a stored checkpoint with a quorum of validator signatures
+    pub async fn certify_checkpoint(
+        &self,
+        sequence: u64,
+        signatures: Vec<(PublicKey, Signature)>,
+        committee: &Committee,
+    ) -> AuthorityResult<Checkpoint> {
+        let mut checkpoint = self.get_checkpoint(sequence).await?
+            .ok_or_else(|| AuthorityError::CheckpointError(
+                format!("Checkpoint {} not found", sequence)
+            ))?;
+
+        checkpoint.certify(signatures, committee)?;
+        self.put_checkpoint(checkpoint.clone()).await?;
+        Ok(checkpoint)
+    }
+
     /// Get latest checkpoint
     pub async fn get_latest_checkpoint(&self) -> AuthorityResult<Option<Checkpoint>> {
         let mut iter = self.store.storage()
@@ -213,4 +411,196 @@ impl CheckpointStore {
 
         Ok(state)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, SignatureScheme};
+
+    #[test]
+    fn test_certify_checkpoint() {
+        let validators: Vec<_> = (0..4)
+            .map(|_| KeyPair::generate(SignatureScheme::Ed25519).unwrap())
+            .collect();
+        let committee = Committee::new(validators.iter().map(|kp| (kp.public(), 1)).collect());
+
+        let mut checkpoint = Checkpoint::new(1, None, 100, vec![], vec![], [0; 32], 0, None);
+        let signatures: Vec<_> = validators.iter()
+            .map(|kp| (kp.public(), kp.sign(&checkpoint.digest)))
+            .collect();
+        assert!(!checkpoint.is_certified(&committee));
+
+        // A single signature is not a quorum
+        checkpoint.signatures = signatures[..1].to_vec();
+        assert!(!checkpoint.is_certified(&committee));
+        checkpoint.signatures.clear();
+
+        // Two of four is below the quorum of three
+        assert!(matches!(
+            checkpoint.certify(signatures[..2].to_vec(), &committee),
+            Err(AuthorityError::InsufficientQuorum { weight: 2, threshold: 3 })
+        ));
+        assert!(!checkpoint.is_certified(&committee));
+        assert!(checkpoint.beacon_seed(&committee).is_err());
+
+        checkpoint.certify(signatures[..3].to_vec(), &committee).unwrap();
+        assert!(checkpoint.is_certified(&committee));
+        assert!(checkpoint.verify());
+
+        // Any quorum yields the same seed
+        assert_eq!(checkpoint.beacon_seed(&committee).unwrap(), checkpoint.digest);
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_checkpoint_keeps_higher_stake() {
+        use crate::authority::StoreConfig;
+        use crate::storage::test_utils::temp_storage;
+
+        let (_temp_dir, storage) = temp_storage();
+        let store = AuthorityStore::new(StoreConfig {
+            storage,
+            cache_size: 128,
+        }).unwrap();
+        let checkpoints = CheckpointStore::new(Arc::new(store)).unwrap();
+
+        let validators: Vec<_> = (0..4)
+            .map(|_| KeyPair::generate(SignatureScheme::Ed25519).unwrap())
+            .collect();
+        let committee = Committee::new(validators.iter().map(|kp| (kp.public(), 1)).collect());
+        let certified = |state_root: [u8; 32], signers: usize| {
+            let mut checkpoint = Checkpoint::new(5, None, 100, vec![], vec![], state_root, 0, None);
+            let signatures = validators[..signers].iter()
+                .map(|kp| (kp.public(), kp.sign(&checkpoint.digest)))
+                .collect();
+            checkpoint.certify(signatures, &committee).unwrap();
+            checkpoint
+        };
+
+        let weaker = certified([1; 32], 3);
+        let stronger = certified([2; 32], 4);
+
+        checkpoints.put_certified_checkpoint(weaker.clone(), &committee).await.unwrap();
+        checkpoints.put_certified_checkpoint(stronger.clone(), &committee).await.unwrap();
+        assert_eq!(checkpoints.get_checkpoint(5).await.unwrap().unwrap().digest, stronger.digest);
+
+        // The weaker fork can't displace it again
+        assert!(matches!(
+            checkpoints.put_certified_checkpoint(weaker, &committee).await,
+            Err(AuthorityError::ConflictingCheckpoint { sequence: 5, stake: 3, existing_stake: 4 })
+        ));
+        assert_eq!(checkpoints.get_checkpoint(5).await.unwrap().unwrap().digest, stronger.digest);
+    }
+
+    #[tokio::test]
+    async fn test_forged_signatures_cannot_displace_checkpoint() {
+        use crate::authority::StoreConfig;
+        use crate::storage::test_utils::temp_storage;
+
+        let (_temp_dir, storage) = temp_storage();
+        let store = AuthorityStore::new(StoreConfig {
+            storage,
+            cache_size: 128,
+        }).unwrap();
+        let checkpoints = CheckpointStore::new(Arc::new(store)).unwrap();
+
+        let validators: Vec<_> = (0..4)
+            .map(|_| KeyPair::generate(SignatureScheme::Ed25519).unwrap())
+            .collect();
+        let committee = Committee::new(validators.iter().map(|kp| (kp.public(), 1)).collect());
+
+        let mut genuine = Checkpoint::new(5, None, 100, vec![], vec![], [1; 32], 0, None);
+        let signatures = validators[..3].iter()
+            .map(|kp| (kp.public(), kp.sign(&genuine.digest)))
+            .collect();
+        genuine.certify(signatures, &committee).unwrap();
+        checkpoints.put_certified_checkpoint(genuine.clone(), &committee).await.unwrap();
+
+        // Every validator's key, but signatures over some other message
+        let mut forged = Checkpoint::new(5, None, 100, vec![], vec![], [2; 32], 0, None);
+        forged.signatures = validators.iter()
+            .map(|kp| (kp.public(), kp.sign(b"something else")))
+            .collect();
+        assert!(matches!(
+            checkpoints.put_certified_checkpoint(forged.clone(), &committee).await,
+            Err(AuthorityError::InvalidSignature)
+        ));
+
+        // One real signature repeated doesn't add up to a quorum
+        let signature = (validators[0].public(), validators[0].sign(&forged.digest));
+        forged.signatures = vec![signature; 4];
+        assert!(matches!(
+            checkpoints.put_certified_checkpoint(forged, &committee).await,
+            Err(AuthorityError::CheckpointError(_))
+        ));
+
+        assert_eq!(checkpoints.get_checkpoint(5).await.unwrap().unwrap().digest, genuine.digest);
+    }
+
+    #[tokio::test]
+    async fn test_prove_transaction_inclusion() {
+        use crate::authority::{verify_transaction_proof, StoreConfig};
+        use crate::storage::test_utils::temp_storage;
+
+        let (_temp_dir, storage) = temp_storage();
+        let store = AuthorityStore::new(StoreConfig {
+            storage,
+            cache_size: 128,
+        }).unwrap();
+        let checkpoints = CheckpointStore::new(Arc::new(store)).unwrap();
+
+        let transactions: Vec<_> = (1..=5u8).map(|i| TransactionDigest::from_bytes([i; 32])).collect();
+        let checkpoint = Checkpoint::new(2, None, 100, transactions.clone(), vec![], [0; 32], 0, None);
+        checkpoints.put_checkpoint(checkpoint.clone()).await.unwrap();
+
+        // Every position verifies, including the unpaired fifth leaf
+        for digest in &transactions {
+            let proof = checkpoints.prove_transaction(2, digest).await.unwrap();
+            assert!(verify_transaction_proof(&checkpoint.tx_root, digest, &proof));
+        }
+
+        let proof = checkpoints.prove_transaction(2, &transactions[3]).await.unwrap();
+        assert!(!verify_transaction_proof(&checkpoint.tx_root, &transactions[2], &proof));
+        assert!(!verify_transaction_proof(&[0; 32], &transactions[3], &proof));
+
+        let outsider = TransactionDigest::from_bytes([9; 32]);
+        assert!(checkpoints.prove_transaction(2, &outsider).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stored_checkpoints_reach_health() {
+        use crate::authority::StoreConfig;
+        use crate::storage::test_utils::temp_storage;
+
+        let (_temp_dir, storage) = temp_storage();
+        let store = AuthorityStore::new(StoreConfig {
+            storage,
+            cache_size: 128,
+        }).unwrap();
+        let checkpoints = CheckpointStore::new(Arc::new(store)).unwrap();
+        let health = HealthState::new(2);
+        checkpoints.set_health(health.clone());
+        health.observe_peer_checkpoint(5);
+        assert!(!health.is_ready());
+
+        for sequence in [3, 1] {
+            let checkpoint = Checkpoint::new(sequence, None, 100, vec![], vec![], [0; 32], 0, None);
+            checkpoints.put_checkpoint(checkpoint).await.unwrap();
+        }
+        assert_eq!(health.checkpoint_lag(), 2);
+        assert!(health.is_ready());
+    }
+
+    #[test]
+    fn test_swapped_transactions_fail_verification() {
+        let transactions: Vec<_> = (1..=4u8).map(|i| TransactionDigest::from_bytes([i; 32])).collect();
+        let mut checkpoint = Checkpoint::new(3, None, 100, transactions, vec![], [0; 32], 0, None);
+        assert!(checkpoint.verify());
+
+        // Swap in another transaction list and recompute the digest; the
+        // root still commits to the original list
+        checkpoint.transactions[0] = TransactionDigest::from_bytes([9; 32]);
+        checkpoint.digest = checkpoint.compute_digest();
+        assert!(!checkpoint.verify());
+    }
+}
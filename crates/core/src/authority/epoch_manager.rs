@@ -1,7 +1,14 @@
-use super::{AuthorityError, AuthorityResult, AuthorityStore, CommitteeInfo};
+use super::{
+    AuthorityError, AuthorityResult, AuthorityState, AuthorityStore, CheckpointStore, CommitteeInfo,
+};
+use crate::core::{ObjectID, SequenceNumber};
 use crate::crypto::PublicKey;
+use crate::storage::{Event, EventType, SystemEvent};
+use crate::swift_system::ValidatorSet;
+use chrono::Utc;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 /// Epoch configuration
 #[derive(Debug, Clone)]
@@ -64,6 +71,13 @@ impl EpochInfo {
     }
 }
 
+/// Listener invoked after the epoch advances
+#[async_trait::async_trait]
+pub trait EpochChangeListener: Send + Sync {
+    /// Handle start of a new epoch
+    async fn on_epoch_change(&self, epoch: &EpochInfo) -> AuthorityResult<()>;
+}
+
 /// Epoch manager
 pub struct EpochManager {
     /// Configuration
@@ -74,6 +88,17 @@ pub struct EpochManager {
     current_epoch: RwLock<EpochInfo>,
     /// Next epoch committee
     next_committee: RwLock<Option<CommitteeInfo>>,
+    /// Epoch change listeners
+    listeners: RwLock<Vec<Arc<dyn EpochChangeListener>>>,
+    /// Validator set the next committee is selected from
+    validator_set: Option<Arc<ValidatorSet>>,
+    /// Serializes scheduled epoch changes and key rotations
+    advance_lock: Mutex<()>,
+    /// Checkpoints object versions are checked against at each boundary
+    checkpoint_store: Option<Arc<CheckpointStore>>,
+    /// Keys rotated this epoch, old key to new key, applied to the next
+    /// committee
+    key_rotations: RwLock<HashMap<PublicKey, PublicKey>>,
 }
 
 impl EpochManager {
@@ -91,9 +116,33 @@ impl EpochManager {
             store,
             current_epoch: RwLock::new(current_epoch),
             next_committee: RwLock::new(None),
+            listeners: RwLock::new(Vec::new()),
+            validator_set: None,
+            advance_lock: Mutex::new(()),
+            checkpoint_store: None,
+            key_rotations: RwLock::new(HashMap::new()),
         })
     }
 
+    /// Check object version continuity against the latest checkpoint of
+    /// `checkpoint_store` before every epoch change
+    pub fn with_checkpoint_store(mut self, checkpoint_store: Arc<CheckpointStore>) -> Self {
+        self.checkpoint_store = Some(checkpoint_store);
+        self
+    }
+
+    /// Select each new committee from `validator_set`; without one the
+    /// current committee carries over
+    pub fn with_validator_set(mut self, validator_set: Arc<ValidatorSet>) -> Self {
+        self.validator_set = Some(validator_set);
+        self
+    }
+
+    /// Register epoch change listener
+    pub async fn register_listener(&self, listener: Arc<dyn EpochChangeListener>) {
+        self.listeners.write().await.push(listener);
+    }
+
     /// Get current epoch
     pub async fn get_current_epoch(&self) -> EpochInfo {
         self.current_epoch.read().await.clone()
@@ -104,6 +153,24 @@ impl EpochManager {
         self.next_committee.read().await.clone()
     }
 
+    /// Replace a committee member's key from the next epoch on. The old key
+    /// stays in the current committee so this epoch's certificates keep
+    /// verifying.
+    pub async fn rotate_key(&self, old_key: PublicKey, new_key: PublicKey) -> AuthorityResult<()> {
+        let _guard = self.advance_lock.lock().await;
+        if self.current_epoch.read().await.get_stake(&old_key).is_none() {
+            return Err(AuthorityError::InvalidStake("Validator not in committee".into()));
+        }
+
+        if let Some(validator_set) = &self.validator_set {
+            validator_set.rotate_key(&old_key, new_key.clone()).await
+                .map_err(|e| AuthorityError::EpochChangeError(e.to_string()))?;
+        }
+        self.key_rotations.write().await.insert(old_key, new_key);
+
+        Ok(())
+    }
+
     /// Prepare next epoch
     pub async fn prepare_next_epoch(
         &self,
@@ -116,8 +183,10 @@ impl EpochManager {
 
         let mut committee_validators = Vec::new();
         let mut total_stake = 0;
+        let key_rotations = self.key_rotations.read().await.clone();
 
         for (public_key, stake) in validators {
+            let public_key = key_rotations.get(&public_key).cloned().unwrap_or(public_key);
             if stake < self.config.min_validator_stake {
                 return Err(AuthorityError::InsufficientValidatorStake);
             }
@@ -144,12 +213,71 @@ impl EpochManager {
         Ok(committee)
     }
 
-    /// Start new epoch
+    /// Check that the latest checkpoint belongs to `epoch` and that no
+    /// object it touched is now stored at an older version
+    async fn check_boundary_continuity(&self, epoch: u64) -> AuthorityResult<()> {
+        let Some(checkpoint_store) = &self.checkpoint_store else {
+            return Ok(());
+        };
+        let Some(checkpoint) = checkpoint_store.get_latest_checkpoint().await? else {
+            return Ok(());
+        };
+
+        if checkpoint.epoch > epoch {
+            return Err(AuthorityError::EpochChangeError(format!(
+                "Checkpoint {} is from epoch {}, ahead of current epoch {}",
+                checkpoint.sequence, checkpoint.epoch, epoch
+            )));
+        }
+
+        let checkpointed = checkpoint_store.get_state_at_checkpoint(checkpoint.sequence).await?
+            .into_iter()
+            .map(|(id, object)| (id, object.version()))
+            .collect();
+        self.verify_version_continuity(&checkpointed).await
+    }
+
+    /// Fail if any object is stored at a version older than `checkpointed`.
+    /// Objects deleted since are fine.
+    pub async fn verify_version_continuity(
+        &self,
+        checkpointed: &HashMap<ObjectID, SequenceNumber>,
+    ) -> AuthorityResult<()> {
+        let ids: Vec<_> = checkpointed.keys().copied().collect();
+        let versions = self.store.multi_get_latest_versions(&ids).await?;
+
+        for (id, current) in ids.iter().zip(versions) {
+            let Some(current) = current else { continue };
+            let checkpointed = checkpointed[id];
+            if current < checkpointed {
+                return Err(AuthorityError::VersionRegression {
+                    object: *id,
+                    checkpointed,
+                    current,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Start new epoch. Aborts if object versions regressed since the
+    /// latest checkpoint.
     pub async fn start_new_epoch(&self, timestamp: u64) -> AuthorityResult<EpochInfo> {
         let mut current = self.current_epoch.write().await;
+        self.check_boundary_continuity(current.epoch).await?;
         let next_committee = self.next_committee.write().await.take()
             .ok_or(AuthorityError::NoNextEpochCommittee)?;
 
+        // Drop the rotations the committee took in; one recorded after it
+        // was prepared carries over to the next committee instead
+        let mut key_rotations = self.key_rotations.write().await;
+        let rotations = std::mem::take(&mut *key_rotations);
+        key_rotations.extend(rotations.into_iter().filter(|(_, new_key)| {
+            !next_committee.validators.iter().any(|v| v.public_key == *new_key)
+        }));
+        drop(key_rotations);
+
         let new_epoch = EpochInfo {
             epoch: current.epoch + 1,
             start_timestamp: timestamp,
@@ -165,10 +293,48 @@ impl EpochManager {
 
         // Update current epoch
         *current = new_epoch.clone();
+        drop(current);
+
+        // The epoch is committed; a failing listener mustn't report otherwise
+        for listener in self.listeners.read().await.iter() {
+            if let Err(e) = listener.on_epoch_change(&new_epoch).await {
+                log::error!("Epoch {} listener failed: {}", new_epoch.epoch, e);
+            }
+        }
 
         Ok(new_epoch)
     }
 
+    /// Advance to the next epoch if the current one has run its configured
+    /// duration by `now`. Returns the new epoch, or `None` if it's not due.
+    pub async fn maybe_advance(&self, now: u64) -> AuthorityResult<Option<EpochInfo>> {
+        let _guard = self.advance_lock.lock().await;
+        if !self.should_end_epoch(now).await {
+            return Ok(None);
+        }
+
+        let validators = match &self.validator_set {
+            Some(validator_set) => validator_set.select_committee().await,
+            None => self.current_epoch.read().await.committee.validators.iter()
+                .map(|v| (v.public_key.clone(), v.stake))
+                .collect(),
+        };
+        self.prepare_next_epoch(validators).await?;
+        let new_epoch = self.start_new_epoch(now).await?;
+
+        self.store.emit_event(Event {
+            id: String::new(),
+            type_: EventType::System(SystemEvent::EpochAdvanced {
+                old_epoch: new_epoch.epoch - 1,
+                new_epoch: new_epoch.epoch,
+            }),
+            timestamp: Utc::now(),
+            metadata: None,
+        })?;
+
+        Ok(Some(new_epoch))
+    }
+
     /// Update epoch statistics
     pub async fn update_statistics(
         &self,
@@ -197,4 +363,279 @@ impl EpochManager {
         self.store.get_epoch(epoch)
             .map_err(|e| AuthorityError::StoreError(e.to_string()))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::StoreConfig;
+    use crate::crypto::{KeyPair, SignatureScheme};
+    use crate::storage::{CacheConfig, EventFilter, RocksConfig, StorageConfig, StorageManager};
+    use crate::swift_system::{TxContext, ValidatorConfig};
+    use tempfile::TempDir;
+
+    fn storage_config(path: String) -> StorageConfig {
+        StorageConfig {
+            data_dir: path.clone(),
+            cache_config: CacheConfig::default(),
+            rocks_config: RocksConfig {
+                path,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_maybe_advance_on_schedule() -> AuthorityResult<()> {
+        let store_dir = TempDir::new().unwrap();
+        let store = Arc::new(AuthorityStore::new(StoreConfig {
+            storage: storage_config(store_dir.path().to_str().unwrap().to_string()),
+            cache_size: 128,
+        })?);
+
+        let system_dir = TempDir::new().unwrap();
+        let system_storage = StorageManager::new(
+            storage_config(system_dir.path().to_str().unwrap().to_string())
+        ).unwrap();
+        let validator_set = Arc::new(ValidatorSet::new(
+            ValidatorConfig {
+                min_stake_amount: 100,
+                max_validator_count: 2,
+                performance_window: 100,
+                min_performance_threshold: 0.5,
+            },
+            Arc::new(system_storage),
+        ));
+        let mut ctx = TxContext::new(crate::transaction::TransactionDigest::from_bytes([1; 32]));
+        for stake in [100, 300, 200] {
+            let public_key = KeyPair::generate(SignatureScheme::Ed25519).unwrap().public();
+            validator_set.register_validator(&mut ctx, public_key, String::new(), stake, 0.1).await.unwrap();
+        }
+
+        let manager = EpochManager::new(
+            EpochConfig {
+                epoch_duration_ms: 1_000,
+                min_validator_stake: 100,
+                max_validator_count: 2,
+            },
+            store.clone(),
+        )?.with_validator_set(validator_set);
+
+        // Genesis has already ended, so the first tick advances
+        let epoch = manager.maybe_advance(5_000).await?.expect("epoch 1 due");
+        assert_eq!(epoch.epoch, 1);
+        assert_eq!(epoch.end_timestamp, 6_000);
+        let stakes: Vec<_> = epoch.committee.validators.iter().map(|v| v.stake).collect();
+        assert_eq!(stakes, vec![300, 200]);
+
+        assert!(manager.maybe_advance(5_999).await?.is_none());
+
+        let epoch = manager.maybe_advance(6_000).await?.expect("epoch 2 due");
+        assert_eq!(epoch.epoch, 2);
+        assert!(manager.maybe_advance(6_000).await?.is_none());
+        assert_eq!(manager.get_current_epoch().await.epoch, 2);
+
+        let events = store.get_events(&EventFilter {
+            types: Some(vec![EventType::System(SystemEvent::EpochAdvanced {
+                old_epoch: 0,
+                new_epoch: 0,
+            })]),
+            start_time: None,
+            end_time: None,
+            limit: None,
+        })?;
+        assert_eq!(events.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_version_regression_aborts_epoch_change() -> AuthorityResult<()> {
+        use crate::core::{Address, Object, Owner, TypeTag};
+
+        let store_dir = TempDir::new().unwrap();
+        let store = Arc::new(AuthorityStore::new(StoreConfig {
+            storage: storage_config(store_dir.path().to_str().unwrap().to_string()),
+            cache_size: 128,
+        })?);
+        let manager = EpochManager::new(
+            EpochConfig {
+                epoch_duration_ms: 1_000,
+                min_validator_stake: 100,
+                max_validator_count: 2,
+            },
+            store.clone(),
+        )?;
+
+        let mut object = Object::new(
+            ObjectID::random(),
+            Owner::AddressOwner(Address::from_bytes([1; 20])),
+            TypeTag::U64,
+            vec![],
+        );
+        object.set_version(SequenceNumber::new(3));
+        store.put_object(object.clone()).await?;
+
+        // Same or newer than the checkpoint is continuous; deleted objects are skipped
+        let mut checkpointed = HashMap::from([
+            (object.id(), SequenceNumber::new(3)),
+            (ObjectID::random(), SequenceNumber::new(9)),
+        ]);
+        manager.verify_version_continuity(&checkpointed).await?;
+
+        // The checkpoint saw version 5, but the store is back at 3
+        checkpointed.insert(object.id(), SequenceNumber::new(5));
+        assert!(matches!(
+            manager.verify_version_continuity(&checkpointed).await,
+            Err(AuthorityError::VersionRegression { object: id, .. }) if id == object.id()
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_start_new_epoch_fails_on_regressed_checkpoint() -> AuthorityResult<()> {
+        use crate::authority::{Checkpoint, CheckpointStore};
+        use crate::core::{Address, Object, Owner, TypeTag};
+        use crate::transaction::{ExecutionStatus, TransactionDigest, TransactionEffects};
+
+        let store_dir = TempDir::new().unwrap();
+        let config = StoreConfig {
+            storage: storage_config(store_dir.path().to_str().unwrap().to_string()),
+            cache_size: 128,
+        };
+
+        let mut object = Object::new(
+            ObjectID::random(),
+            Owner::AddressOwner(Address::from_bytes([1; 20])),
+            TypeTag::U64,
+            vec![],
+        );
+        object.set_version(SequenceNumber::new(3));
+
+        {
+            // The store holds version 3, but the checkpoint already saw version 5
+            let store = Arc::new(AuthorityStore::new(config.clone())?);
+            store.put_object(object.clone()).await?;
+
+            let mut checkpointed = object.clone();
+            checkpointed.set_version(SequenceNumber::new(5));
+            let effects = TransactionEffects {
+                epoch: 0,
+                transaction_digest: TransactionDigest::from_bytes([7; 32]),
+                status: ExecutionStatus::Success,
+                gas_used: 0,
+                modified_objects: vec![(object.id(), checkpointed)].into_iter().collect(),
+                created_objects: Default::default(),
+                deleted_objects: Vec::new(),
+                events: Vec::new(),
+                dependencies: Vec::new(),
+                epoch_change: None,
+            };
+            CheckpointStore::new(store)?
+                .put_checkpoint(Checkpoint::new(1, None, 100, vec![], vec![effects], [0; 32], 0, None))
+                .await?;
+        }
+
+        // Reopen with a cold cache
+        let store = Arc::new(AuthorityStore::new(config)?);
+        let checkpoint_store = Arc::new(CheckpointStore::new(store.clone())?);
+        let manager = EpochManager::new(
+            EpochConfig {
+                epoch_duration_ms: 1_000,
+                min_validator_stake: 100,
+                max_validator_count: 2,
+            },
+            store,
+        )?.with_checkpoint_store(checkpoint_store);
+
+        let public_key = KeyPair::generate(SignatureScheme::Ed25519).unwrap().public();
+        manager.prepare_next_epoch(vec![(public_key, 100)]).await?;
+
+        assert!(matches!(
+            manager.start_new_epoch(1_000).await,
+            Err(AuthorityError::VersionRegression { object: id, .. }) if id == object.id()
+        ));
+        assert_eq!(manager.get_current_epoch().await.epoch, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_listener_error_keeps_epoch_change() -> AuthorityResult<()> {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        /// Listener failing every notification, counting them
+        struct FailingListener(AtomicU64);
+
+        #[async_trait::async_trait]
+        impl EpochChangeListener for FailingListener {
+            async fn on_epoch_change(&self, _epoch: &EpochInfo) -> AuthorityResult<()> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Err(AuthorityError::EpochChangeError("listener failed".into()))
+            }
+        }
+
+        let store_dir = TempDir::new().unwrap();
+        let store = Arc::new(AuthorityStore::new(StoreConfig {
+            storage: storage_config(store_dir.path().to_str().unwrap().to_string()),
+            cache_size: 128,
+        })?);
+        let manager = EpochManager::new(
+            EpochConfig {
+                epoch_duration_ms: 1_000,
+                min_validator_stake: 100,
+                max_validator_count: 2,
+            },
+            store,
+        )?;
+        let first = Arc::new(FailingListener(AtomicU64::new(0)));
+        let second = Arc::new(FailingListener(AtomicU64::new(0)));
+        manager.register_listener(first.clone()).await;
+        manager.register_listener(second.clone()).await;
+
+        let public_key = KeyPair::generate(SignatureScheme::Ed25519).unwrap().public();
+        manager.prepare_next_epoch(vec![(public_key, 100)]).await?;
+
+        // The epoch advances and every listener still hears about it
+        assert_eq!(manager.start_new_epoch(1_000).await?.epoch, 1);
+        assert_eq!(manager.get_current_epoch().await.epoch, 1);
+        assert_eq!(first.0.load(Ordering::SeqCst), 1);
+        assert_eq!(second.0.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rotation_after_prepare_carries_over() -> AuthorityResult<()> {
+        let store_dir = TempDir::new().unwrap();
+        let store = Arc::new(AuthorityStore::new(StoreConfig {
+            storage: storage_config(store_dir.path().to_str().unwrap().to_string()),
+            cache_size: 128,
+        })?);
+        let manager = EpochManager::new(
+            EpochConfig {
+                epoch_duration_ms: 1_000,
+                min_validator_stake: 100,
+                max_validator_count: 2,
+            },
+            store,
+        )?;
+        let old_key = KeyPair::generate(SignatureScheme::Ed25519).unwrap().public();
+        let new_key = KeyPair::generate(SignatureScheme::Ed25519).unwrap().public();
+        manager.prepare_next_epoch(vec![(old_key.clone(), 100)]).await?;
+        manager.start_new_epoch(1_000).await?;
+
+        // Rotated once the next committee is already prepared
+        manager.prepare_next_epoch(vec![(old_key.clone(), 100)]).await?;
+        manager.rotate_key(old_key.clone(), new_key.clone()).await?;
+        let epoch = manager.start_new_epoch(2_000).await?;
+        assert_eq!(epoch.committee.validators[0].public_key, old_key);
+
+        // The rotation isn't lost; the following committee picks it up
+        let committee = manager.prepare_next_epoch(vec![(old_key, 100)]).await?;
+        assert_eq!(committee.validators[0].public_key, new_key);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use crate::transaction::TransactionDigest;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+/// Proof that a transaction is included under a checkpoint's transaction
+/// root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// Sibling hashes from the leaf up, each flagged whether it sits on the
+    /// left. Levels where the node is carried up unpaired have no entry.
+    pub path: Vec<([u8; 32], bool)>,
+}
+
+/// Leaf hash of a transaction digest
+fn leaf_hash(digest: &TransactionDigest) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]); // Leaf prefix
+    hasher.update(digest.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Hash of an internal node
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]); // Internal prefix
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Fold one level pairwise; an odd node is carried up as is
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level.chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Merkle root over transaction digests in checkpoint order
+pub fn transactions_root(transactions: &[TransactionDigest]) -> [u8; 32] {
+    if transactions.is_empty() {
+        return [0; 32];
+    }
+
+    let mut level: Vec<_> = transactions.iter().map(leaf_hash).collect();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Build the inclusion proof for the transaction at `index`
+pub(crate) fn prove(transactions: &[TransactionDigest], mut index: usize) -> MerkleProof {
+    let mut level: Vec<_> = transactions.iter().map(leaf_hash).collect();
+    let mut path = Vec::new();
+
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            path.push((level[sibling], sibling < index));
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+
+    MerkleProof { path }
+}
+
+/// Check that `proof` places `digest` under `tx_root`
+pub fn verify_transaction_proof(
+    tx_root: &[u8; 32],
+    digest: &TransactionDigest,
+    proof: &MerkleProof,
+) -> bool {
+    let root = proof.path.iter().fold(leaf_hash(digest), |hash, (sibling, is_left)| {
+        if *is_left {
+            node_hash(sibling, &hash)
+        } else {
+            node_hash(&hash, sibling)
+        }
+    });
+    root == *tx_root
+}
@@ -1,17 +1,24 @@
 //! Authority module for validator node management.
 
+mod admission;
 mod authority;
 mod authority_store;
 mod checkpoint_store;
 mod epoch_manager;
+mod merkle;
+mod replay_cache;
 mod validator;
 
-pub use authority::{Authority, AuthorityConfig};
-pub use authority_store::{AuthorityStore, StoreConfig};
+pub use admission::{admission_routes, AdmissionConfig, AdmissionFilter};
+pub use authority::{Authority, AuthorityConfig, ObjectRefStatus, SignedTransactionEffects};
+pub use authority_store::{AuthorityStore, ObjectLockGuard, StoreConfig};
 pub use checkpoint_store::{CheckpointStore, Checkpoint};
-pub use epoch_manager::{EpochManager, EpochInfo};
+pub use merkle::{transactions_root, verify_transaction_proof, MerkleProof};
+pub use epoch_manager::{EpochManager, EpochConfig, EpochInfo, EpochChangeListener};
+pub use replay_cache::ReplayCache;
 pub use validator::{Validator, ValidatorConfig};
 
+use crate::core::{ObjectID, SequenceNumber};
 use crate::protocol::{ProtocolError, ProtocolResult};
 use crate::crypto::KeyPair;
 use std::sync::Arc;
@@ -74,8 +81,53 @@ pub enum AuthorityError {
     #[error("Checkpoint error: {0}")]
     CheckpointError(String),
 
+    #[error("Invalid checkpoint: {0}")]
+    InvalidCheckpoint(String),
+
+    #[error("Conflicting checkpoint {sequence}: stake {stake} doesn't displace existing stake {existing_stake}")]
+    ConflictingCheckpoint { sequence: u64, stake: u64, existing_stake: u64 },
+
+    #[error("Insufficient quorum: weight {weight} below threshold {threshold}")]
+    InsufficientQuorum { weight: u64, threshold: u64 },
+
+    #[error("Invalid key rotation: {0}")]
+    InvalidKeyRotation(String),
+
+    #[error("Transaction denied: {0}")]
+    TransactionDenied(String),
+
     #[error("Store error: {0}")]
     StoreError(String),
+
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
+
+    #[error("Epoch change error: {0}")]
+    EpochChangeError(String),
+
+    #[error("Shared object error: {0}")]
+    SharedObjectError(String),
+
+    #[error("Object {object} already locked by transaction {locked_by}")]
+    ObjectLocked { object: String, locked_by: String },
+
+    #[error("Transaction {0} already executed")]
+    AlreadyExecuted(String),
+
+    #[error("Epoch gas limit exceeded: {used} used, {budget} requested, limit {limit}")]
+    EpochGasLimitExceeded { used: u64, budget: u64, limit: u64 },
+
+    #[error("Object {object:?} regressed from version {checkpointed:?} to {current:?} across the epoch boundary")]
+    VersionRegression { object: ObjectID, checkpointed: SequenceNumber, current: SequenceNumber },
+
+    #[error("Input objects not found: {0:?}")]
+    MissingInputObjects(Vec<ObjectID>),
+
+    #[error("Immutable object {0:?} can't be mutated or deleted")]
+    ImmutableObject(ObjectID),
+
+    #[error("Object {object:?} is at version {current:?}, not {requested:?}")]
+    ObjectVersionMismatch { object: ObjectID, requested: SequenceNumber, current: SequenceNumber },
 }
 
 pub type AuthorityResult<T> = Result<T, AuthorityError>;
\ No newline at end of file
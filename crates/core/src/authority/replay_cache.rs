@@ -0,0 +1,88 @@
+use crate::transaction::TransactionDigest;
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+/// Default number of executed digests remembered
+pub const DEFAULT_REPLAY_CACHE_SIZE: usize = 100_000;
+
+/// Bounded set of recently executed transaction digests, used to reject
+/// resubmissions without touching the effects store
+pub struct ReplayCache {
+    /// Executed digests, least recently seen evicted first
+    executed: Mutex<LruCache<TransactionDigest, ()>>,
+}
+
+impl ReplayCache {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            executed: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Whether `digest` is known to be executed
+    pub fn contains(&self, digest: &TransactionDigest) -> bool {
+        self.executed.lock().unwrap().get(digest).is_some()
+    }
+
+    /// Record `digest` as executed
+    pub fn insert(&self, digest: TransactionDigest) {
+        self.executed.lock().unwrap().put(digest, ());
+    }
+
+    /// Record `digest` unless it is already known, returning whether it was
+    /// recorded. Checking and recording under one lock lets only one of
+    /// several concurrent submissions claim a digest.
+    pub fn try_insert(&self, digest: TransactionDigest) -> bool {
+        let mut executed = self.executed.lock().unwrap();
+        if executed.get(&digest).is_some() {
+            return false;
+        }
+        executed.put(digest, ());
+        true
+    }
+
+    /// Forget `digest`, releasing a claim whose execution failed
+    pub fn remove(&self, digest: &TransactionDigest) {
+        self.executed.lock().unwrap().pop(digest);
+    }
+}
+
+impl Default for ReplayCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_CACHE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_submission_hits_cache() {
+        let cache = ReplayCache::new(2);
+        let digest = TransactionDigest::from_bytes([1; 32]);
+
+        // First submission executes
+        assert!(!cache.contains(&digest));
+        cache.insert(digest);
+
+        // Second submission is a replay
+        assert!(cache.contains(&digest));
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let cache = ReplayCache::new(2);
+        let digests: Vec<_> = (0..3u8).map(|i| TransactionDigest::from_bytes([i; 32])).collect();
+        for digest in &digests {
+            cache.insert(*digest);
+        }
+
+        // Oldest entry evicted; a miss falls back to the effects store
+        assert!(!cache.contains(&digests[0]));
+        assert!(cache.contains(&digests[1]));
+        assert!(cache.contains(&digests[2]));
+    }
+}
@@ -1,9 +1,13 @@
-use super::{AuthorityError, AuthorityResult, AuthorityStore};
+use super::{AdmissionConfig, AdmissionFilter, AuthorityError, AuthorityResult, AuthorityStore};
 use crate::protocol::{ProtocolError, ProtocolResult};
 use crate::crypto::{KeyPair, PublicKey, Signature};
 use crate::runtime::{Runtime, RuntimeConfig};
-use crate::transaction::{Transaction, TransactionEffects, ExecutionStatus};
-use crate::core::{Object, ObjectID};
+use crate::transaction::{Transaction, TransactionDigest, TransactionEffects, ExecutionStatus};
+use crate::consensus::SharedObjectSequencer;
+use crate::core::{Address, Object, ObjectID, SequenceNumber};
+use crate::storage::{self, EventType, TransactionEvent};
+use chrono::Utc;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -16,15 +20,23 @@ pub struct ValidatorConfig {
     pub max_gas_per_tx: u64,
     /// Maximum concurrent transactions
     pub max_concurrent_txs: usize,
+    /// Maximum total gas per epoch
+    pub max_gas_per_epoch: u64,
+    /// Initial transaction admission denylist
+    pub admission: AdmissionConfig,
 }
 
 /// Validator state
 #[derive(Debug)]
 struct ValidatorState {
+    /// Current epoch
+    epoch: u64,
     /// Last executed sequence
     last_sequence: u64,
     /// Gas used in current epoch
     gas_used: u64,
+    /// Budgets of transactions still executing, held against the epoch limit
+    gas_reserved: u64,
     /// Transaction count in current epoch
     tx_count: u64,
 }
@@ -41,6 +53,10 @@ pub struct Validator {
     runtime: Arc<Runtime>,
     /// State
     state: RwLock<ValidatorState>,
+    /// Consensus-assigned shared object versions
+    sequencer: Arc<RwLock<SharedObjectSequencer>>,
+    /// Transaction admission filter
+    admission: Arc<AdmissionFilter>,
 }
 
 impl Validator {
@@ -53,38 +69,250 @@ impl Validator {
             .map_err(|e| AuthorityError::RuntimeError(e.to_string()))?;
 
         let state = ValidatorState {
+            epoch: 0,
             last_sequence: 0,
             gas_used: 0,
+            gas_reserved: 0,
             tx_count: 0,
         };
 
+        let admission = Arc::new(AdmissionFilter::new(config.admission.clone()));
+
         Ok(Self {
             config,
             keypair,
             store,
             runtime: Arc::new(runtime),
             state: RwLock::new(state),
+            sequencer: Arc::new(RwLock::new(SharedObjectSequencer::new())),
+            admission,
         })
     }
 
+    /// Admission filter, shared with the admin API to reload the denylist
+    pub fn admission_filter(&self) -> Arc<AdmissionFilter> {
+        self.admission.clone()
+    }
+
+    /// Check a transaction against the local denylist. Only applied before
+    /// this validator signs or executes a transaction on its own; certified
+    /// transactions always execute so the node doesn't fork.
+    pub fn check_admission(&self, transaction: &Transaction) -> AuthorityResult<()> {
+        self.admission.check(transaction)
+    }
+
+    /// Shared object sequencer fed by consensus output
+    pub fn sequencer(&self) -> Arc<RwLock<SharedObjectSequencer>> {
+        self.sequencer.clone()
+    }
+
+    /// Sequence and execute transactions in the order consensus committed
+    /// them. Shared object versions are assigned for the whole commit before
+    /// any of it executes. A transaction that fails is recorded as failed
+    /// rather than dropping the rest of the commit.
+    pub async fn handle_consensus_commit(
+        &self,
+        transactions: Vec<Transaction>,
+    ) -> AuthorityResult<Vec<TransactionEffects>> {
+        for transaction in &transactions {
+            self.sequence_shared_objects(transaction).await?;
+        }
+
+        let mut effects = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            let executed = match self.execute_verified_transaction(transaction.clone()).await {
+                Ok(executed) => executed,
+                Err(e) => self.commit_failed_transaction(&transaction, e.to_string()).await?,
+            };
+            effects.push(executed);
+        }
+        Ok(effects)
+    }
+
+    /// Record a sequenced transaction that couldn't execute. Its shared
+    /// inputs still move past their assigned versions, so transactions
+    /// consensus ordered after it can run.
+    async fn commit_failed_transaction(
+        &self,
+        transaction: &Transaction,
+        error: String,
+    ) -> AuthorityResult<TransactionEffects> {
+        let digest = transaction.digest();
+        let shared = self.sequencer.read().await.assigned_versions(&digest);
+        let ids: Vec<ObjectID> = shared.iter().map(|(id, _)| *id).collect();
+        let mut modified_objects = HashMap::new();
+        for object in self.store.multi_get_objects(&ids).await?.into_iter().flatten() {
+            modified_objects.insert(object.id(), object);
+        }
+        bump_shared_versions(&mut modified_objects, &shared);
+
+        let effects = TransactionEffects {
+            epoch: self.state.read().await.epoch,
+            transaction_digest: digest,
+            status: ExecutionStatus::Failure { error: error.clone() },
+            gas_used: 0,
+            modified_objects,
+            created_objects: Default::default(),
+            deleted_objects: Vec::new(),
+            events: Vec::new(),
+            dependencies: transaction.dependencies.clone(),
+            epoch_change: None,
+        };
+        self.store.commit_effects(effects.clone()).await?;
+        self.sequencer.write().await.mark_executed(&digest);
+        self.emit_event(effects_event(&transaction.sender, &effects, Some(error)));
+
+        Ok(effects)
+    }
+
+    /// Assign the next consensus versions of the transaction's shared inputs
+    async fn sequence_shared_objects(&self, transaction: &Transaction) -> AuthorityResult<()> {
+        let ids = transaction.input_objects();
+        let shared: Vec<_> = self.store.multi_get_objects(&ids).await?
+            .into_iter()
+            .flatten()
+            .filter(|object| object.is_shared())
+            .map(|object| (object.id(), object.version()))
+            .collect();
+
+        if !shared.is_empty() {
+            self.sequencer.write().await.assign(transaction.digest(), &shared);
+        }
+        Ok(())
+    }
+
+    /// Check that every shared input has a consensus-assigned version and
+    /// that it is the object's next version, so shared transactions execute
+    /// in the order consensus fixed. Returns the assigned versions.
+    async fn check_shared_objects(
+        &self,
+        transaction: &Transaction,
+    ) -> AuthorityResult<Vec<(ObjectID, SequenceNumber)>> {
+        let digest = transaction.digest();
+        let sequencer = self.sequencer.read().await;
+        let mut shared = Vec::new();
+
+        for id in transaction.input_objects() {
+            let object = match self.store.get_object(&id).await? {
+                Some(object) if object.is_shared() => object,
+                _ => continue,
+            };
+
+            let assigned = sequencer.assigned_version(&digest, &id)
+                .ok_or_else(|| AuthorityError::SharedObjectError(
+                    format!("Shared object {:?} not sequenced by consensus", id)
+                ))?;
+            if object.version() != assigned {
+                return Err(AuthorityError::SharedObjectError(format!(
+                    "Shared object {:?} at version {:?}, transaction assigned {:?}",
+                    id, object.version(), assigned
+                )));
+            }
+            shared.push((id, assigned));
+        }
+
+        Ok(shared)
+    }
+
     /// Execute transaction
     pub async fn execute_transaction(
         &self,
         transaction: Transaction,
+    ) -> AuthorityResult<TransactionEffects> {
+        self.execute_sequenced_transaction(transaction, &[]).await
+    }
+
+    /// Execute a transaction whose shared inputs consensus assigned the
+    /// `shared` versions
+    async fn execute_sequenced_transaction(
+        &self,
+        transaction: Transaction,
+        shared: &[(ObjectID, SequenceNumber)],
     ) -> AuthorityResult<TransactionEffects> {
         // Check gas limit
         if transaction.gas_budget > self.config.max_gas_per_tx {
             return Err(AuthorityError::ExceedGasLimit);
         }
 
+        // Hold the full budget against the epoch limit until execution ends,
+        // so concurrent transactions can't overrun it together
+        let budget = transaction.gas_budget;
+        self.reserve_epoch_gas(budget).await?;
+        let result = self.execute_reserved_transaction(transaction, shared).await;
+        self.release_epoch_gas(budget).await;
+        result
+    }
+
+    /// Reserve `budget` if the epoch still has room for it
+    async fn reserve_epoch_gas(&self, budget: u64) -> AuthorityResult<()> {
+        let mut state = self.state.write().await;
+        let used = state.gas_used.saturating_add(state.gas_reserved);
+        if used.saturating_add(budget) > self.config.max_gas_per_epoch {
+            return Err(AuthorityError::EpochGasLimitExceeded {
+                used,
+                budget,
+                limit: self.config.max_gas_per_epoch,
+            });
+        }
+        state.gas_reserved += budget;
+        Ok(())
+    }
+
+    /// Release a reservation made by `reserve_epoch_gas`
+    async fn release_epoch_gas(&self, budget: u64) {
+        let mut state = self.state.write().await;
+        state.gas_reserved = state.gas_reserved.saturating_sub(budget);
+    }
+
+    /// Execute a transaction whose budget is reserved
+    async fn execute_reserved_transaction(
+        &self,
+        transaction: Transaction,
+        shared: &[(ObjectID, SequenceNumber)],
+    ) -> AuthorityResult<TransactionEffects> {
+        // Load every input up front so the VM never waits on storage
+        let input_objects = self.prefetch_input_objects(&transaction).await?;
+        let shared_inputs: Vec<Object> = shared.iter()
+            .filter_map(|(id, _)| input_objects.get(id).cloned())
+            .collect();
+
         // Create execution context
         let mut context = self.create_execution_context().await?;
+        context.set_input_objects(input_objects);
+        let digest = transaction.digest();
 
-        // Execute transaction
-        let result = self.runtime.execute_transaction(
+        // Execute transaction, recording aborts before surfacing them
+        let result = match self.runtime.execute_transaction(
             transaction.clone(),
             &mut context,
-        ).await.map_err(|e| AuthorityError::ExecutionError(e.to_string()))?;
+        ).await {
+            Ok(result) => result,
+            Err(e) => {
+                let error = e.to_string();
+                self.emit_event(executed_event(&digest, Some(error.clone())));
+                return Err(AuthorityError::ExecutionError(error));
+            }
+        };
+
+        // The runtime's changes must leave immutable objects alone
+        let written: Vec<ObjectID> = result.modified_objects.keys()
+            .chain(result.deleted_objects.iter())
+            .copied()
+            .collect();
+        if let Err(e) = self.check_mutable(&written).await {
+            self.emit_event(executed_event(&digest, Some(e.to_string())));
+            return Err(e);
+        }
+
+        // Shared inputs the transaction kept move past their assigned
+        // versions, whether or not it changed them
+        let mut modified_objects = result.modified_objects;
+        for object in shared_inputs {
+            if !result.deleted_objects.contains(&object.id()) {
+                modified_objects.entry(object.id()).or_insert(object);
+            }
+        }
+        bump_shared_versions(&mut modified_objects, shared);
 
         // Update state
         let mut state = self.state.write().await;
@@ -94,10 +322,11 @@ impl Validator {
 
         // Create effects
         let effects = TransactionEffects {
+            epoch: state.epoch,
             transaction_digest: transaction.digest(),
             status: result.status,
             gas_used: result.gas_used,
-            modified_objects: result.modified_objects,
+            modified_objects,
             created_objects: result.created_objects,
             deleted_objects: result.deleted_objects,
             events: result.events,
@@ -105,13 +334,57 @@ impl Validator {
             epoch_change: None,
         };
 
-        // Store effects
-        self.store.put_effects(&effects).await
-            .map_err(|e| AuthorityError::StoreError(e.to_string()))?;
+        // Store effects and their object changes together
+        self.store.commit_effects(effects.clone()).await?;
+
+        let error = match &effects.status {
+            ExecutionStatus::Failure { error } => Some(error.clone()),
+            _ => None,
+        };
+        // The transaction is committed; a lost notification mustn't fail it
+        self.emit_event(effects_event(&transaction.sender, &effects, error.clone()));
+        self.emit_event(executed_event(&digest, error));
 
         Ok(effects)
     }
 
+    /// Publish `event`, logging rather than failing if it can't be stored
+    fn emit_event(&self, event: storage::Event) {
+        if let Err(e) = self.store.emit_event(event) {
+            log::warn!("Failed to emit event: {}", e);
+        }
+    }
+
+    /// Fail if any of `ids` is a stored immutable object
+    async fn check_mutable(&self, ids: &[ObjectID]) -> AuthorityResult<()> {
+        let objects = self.store.multi_get_objects(ids).await?;
+        match ids.iter().zip(objects).find(|(_, object)| object.as_ref().is_some_and(|object| object.owner().is_immutable())) {
+            Some((id, _)) => Err(AuthorityError::ImmutableObject(*id)),
+            None => Ok(()),
+        }
+    }
+
+    /// Load all of the transaction's input objects in one batch, failing
+    /// if any is missing
+    async fn prefetch_input_objects(
+        &self,
+        transaction: &Transaction,
+    ) -> AuthorityResult<HashMap<ObjectID, Object>> {
+        let ids = transaction.input_objects();
+        let objects = self.store.multi_get_objects(&ids).await?;
+
+        let missing: Vec<ObjectID> = ids.iter()
+            .zip(&objects)
+            .filter(|(_, object)| object.is_none())
+            .map(|(id, _)| *id)
+            .collect();
+        if !missing.is_empty() {
+            return Err(AuthorityError::MissingInputObjects(missing));
+        }
+
+        Ok(ids.into_iter().zip(objects.into_iter().flatten()).collect())
+    }
+
     /// Execute certificate
     pub async fn execute_certificate(
         &self,
@@ -121,7 +394,30 @@ impl Validator {
         self.verify_certificate(&certificate).await?;
 
         // Execute transaction
-        self.execute_transaction(certificate.transaction).await
+        self.execute_verified_transaction(certificate.transaction).await
+    }
+
+    /// Execute a certified transaction, reusing stored effects from the
+    /// current epoch when the certificate is re-delivered
+    async fn execute_verified_transaction(
+        &self,
+        transaction: Transaction,
+    ) -> AuthorityResult<TransactionEffects> {
+        let cached = self.store.get_effects(&transaction.digest()).await?;
+        if let Some(effects) = cached {
+            if effects.epoch == self.state.read().await.epoch {
+                return Ok(effects);
+            }
+        }
+
+        let digest = transaction.digest();
+        let shared = self.check_shared_objects(&transaction).await?;
+        let effects = self.execute_sequenced_transaction(transaction, &shared).await?;
+        if !shared.is_empty() {
+            self.sequencer.write().await.mark_executed(&digest);
+        }
+
+        Ok(effects)
     }
 
     /// Create execution context
@@ -181,6 +477,16 @@ impl Validator {
             .map_err(|e| AuthorityError::StoreError(e.to_string()))
     }
 
+    /// Start a new epoch, resetting per-epoch counters and reseeding the
+    /// randomness beacon from `committee_output`
+    pub async fn update_epoch(&self, epoch: u64, committee_output: &[u8]) {
+        self.runtime.set_epoch(epoch, committee_output);
+        let mut state = self.state.write().await;
+        state.epoch = epoch;
+        state.gas_used = 0;
+        state.tx_count = 0;
+    }
+
     /// Get validator metrics
     pub async fn get_metrics(&self) -> ValidatorMetrics {
         let state = self.state.read().await;
@@ -192,6 +498,58 @@ impl Validator {
     }
 }
 
+/// Write each shared object in `objects` at the version after the one
+/// consensus assigned it
+fn bump_shared_versions(
+    objects: &mut HashMap<ObjectID, Object>,
+    shared: &[(ObjectID, SequenceNumber)],
+) {
+    for (id, assigned) in shared {
+        if let Some(object) = objects.get_mut(id) {
+            let mut next = *assigned;
+            next.increment();
+            object.set_version(next);
+        }
+    }
+}
+
+/// Build the `Executed` event for a finished transaction; `error` is set
+/// when it aborted
+fn executed_event(digest: &TransactionDigest, error: Option<String>) -> storage::Event {
+    storage::Event {
+        id: String::new(),
+        type_: EventType::Transaction(TransactionEvent::Executed {
+            tx_digest: hex::encode(digest.as_bytes()),
+            success: error.is_none(),
+            error,
+        }),
+        timestamp: Utc::now(),
+        metadata: None,
+    }
+}
+
+/// Event carrying committed effects to live subscribers
+fn effects_event(
+    sender: &Address,
+    effects: &TransactionEffects,
+    error: Option<String>,
+) -> storage::Event {
+    storage::Event {
+        id: String::new(),
+        type_: EventType::Transaction(TransactionEvent::EffectsCommitted {
+            tx_digest: hex::encode(effects.transaction_digest.as_bytes()),
+            sender: hex::encode(sender.as_bytes()),
+            created: effects.created_objects.iter().map(|(id, _)| id.to_hex()).collect(),
+            modified: effects.modified_objects.iter().map(|(id, _)| id.to_hex()).collect(),
+            deleted: effects.deleted_objects.iter().map(|id| id.to_hex()).collect(),
+            gas_used: effects.gas_used,
+            error,
+        }),
+        timestamp: Utc::now(),
+        metadata: None,
+    }
+}
+
 /// Validator metrics
 #[derive(Debug, Clone)]
 pub struct ValidatorMetrics {
@@ -211,6 +569,8 @@ pub struct ExecutionContext {
     store: Arc<AuthorityStore>,
     /// Gas limit
     gas_limit: u64,
+    /// Prefetched input objects
+    input_objects: HashMap<ObjectID, Object>,
     /// Modified objects
     modified_objects: Vec<Object>,
     /// Created objects
@@ -231,6 +591,7 @@ impl ExecutionContext {
             sequence,
             store,
             gas_limit,
+            input_objects: HashMap::new(),
             modified_objects: Vec::new(),
             created_objects: Vec::new(),
             deleted_objects: Vec::new(),
@@ -246,6 +607,16 @@ impl ExecutionContext {
         self.gas_limit
     }
 
+    /// Provide the prefetched input objects
+    pub fn set_input_objects(&mut self, objects: HashMap<ObjectID, Object>) {
+        self.input_objects = objects;
+    }
+
+    /// Get a prefetched input object
+    pub fn input_object(&self, id: &ObjectID) -> Option<&Object> {
+        self.input_objects.get(id)
+    }
+
     pub fn add_modified_object(&mut self, object: Object) {
         self.modified_objects.push(object);
     }
@@ -261,4 +632,290 @@ impl ExecutionContext {
     pub fn add_event(&mut self, event: Event) {
         self.events.push(event);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::StoreConfig;
+    use crate::crypto::SignatureScheme;
+    use crate::framework::FrameworkConfig;
+    use crate::runtime::execution::ExecutionConfig;
+    use crate::storage::{CacheConfig, RocksConfig, StorageConfig};
+    use crate::transaction::{MoveTransaction, TransactionData};
+    use tempfile::TempDir;
+
+    fn test_validator(temp_dir: &TempDir, max_gas_per_epoch: u64) -> AuthorityResult<Validator> {
+        let path = temp_dir.path().to_str().unwrap().to_string();
+        let store = Arc::new(AuthorityStore::new(StoreConfig {
+            storage: StorageConfig {
+                data_dir: path.clone(),
+                cache_config: CacheConfig::default(),
+                rocks_config: RocksConfig {
+                    path,
+                    ..Default::default()
+                },
+            },
+            cache_size: 128,
+        })?);
+        Validator::new(
+            ValidatorConfig {
+                runtime_config: RuntimeConfig {
+                    execution: ExecutionConfig {
+                        max_gas_per_tx: 1_000_000,
+                        max_events: 128,
+                        execution_threads: 1,
+                        complexity_budget: 1_000_000,
+                    },
+                    framework: FrameworkConfig::default(),
+                },
+                max_gas_per_tx: 1_000_000,
+                max_concurrent_txs: 16,
+                max_gas_per_epoch,
+                admission: AdmissionConfig::default(),
+            },
+            KeyPair::generate(SignatureScheme::Ed25519).unwrap(),
+            store,
+        )
+    }
+
+    fn test_transaction(gas_budget: u64, sender: u8) -> Transaction {
+        Transaction::new(
+            TransactionData::Move(MoveTransaction {
+                module: None,
+                function: None,
+                type_arguments: vec![],
+                arguments: vec![],
+            }),
+            Address::from_bytes([sender; 20]),
+            gas_budget,
+            1,
+            vec![],
+            0,
+            0,
+            "sui-local".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_redelivered_certificate_executes_once() -> AuthorityResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let validator = test_validator(&temp_dir, u64::MAX)?;
+        let transaction = test_transaction(1_000, 1);
+
+        let first = validator.execute_verified_transaction(transaction.clone()).await?;
+        let second = validator.execute_verified_transaction(transaction).await?;
+
+        assert_eq!(first.transaction_digest, second.transaction_digest);
+        assert_eq!(validator.get_metrics().await.tx_count, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_epoch_gas_limit() -> AuthorityResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let validator = test_validator(&temp_dir, 10_000)?;
+
+        // Fill the epoch up to the ceiling
+        validator.execute_transaction(test_transaction(1_000, 1)).await?;
+        validator.state.write().await.gas_used = 9_500;
+
+        // The next budget no longer fits
+        assert!(matches!(
+            validator.execute_transaction(test_transaction(1_000, 2)).await,
+            Err(AuthorityError::EpochGasLimitExceeded { used: 9_500, budget: 1_000, limit: 10_000 })
+        ));
+
+        // A new epoch starts from zero
+        validator.update_epoch(1, &[]).await;
+        let metrics = validator.get_metrics().await;
+        assert_eq!(metrics.gas_used, 0);
+        assert_eq!(metrics.tx_count, 0);
+        validator.execute_transaction(test_transaction(1_000, 2)).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_budgets_count_against_epoch_limit() -> AuthorityResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let validator = test_validator(&temp_dir, 10_000)?;
+
+        // Another transaction is still executing with this budget
+        validator.reserve_epoch_gas(6_000).await?;
+        assert!(matches!(
+            validator.execute_transaction(test_transaction(6_000, 1)).await,
+            Err(AuthorityError::EpochGasLimitExceeded { used: 6_000, budget: 6_000, limit: 10_000 })
+        ));
+
+        // Once it finishes, only its actual gas stays charged
+        validator.release_epoch_gas(6_000).await;
+        validator.execute_transaction(test_transaction(6_000, 1)).await?;
+        let state = validator.state.read().await;
+        assert_eq!(state.gas_reserved, 0);
+        assert!(state.gas_used <= 6_000);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_transaction_emits_executed_event() -> AuthorityResult<()> {
+        use crate::storage::EventFilter;
+
+        let temp_dir = TempDir::new().unwrap();
+        let validator = test_validator(&temp_dir, u64::MAX)?;
+
+        // A budget of one gas unit can't cover execution
+        let transaction = test_transaction(1, 1);
+        let digest = hex::encode(transaction.digest().as_bytes());
+        let _ = validator.execute_transaction(transaction).await;
+
+        let events = validator.store.get_events(&EventFilter {
+            types: None,
+            start_time: None,
+            end_time: None,
+            limit: None,
+        })?;
+        assert_eq!(events.len(), 1);
+        match &events[0].type_ {
+            EventType::Transaction(TransactionEvent::Executed { tx_digest, success, error }) => {
+                assert_eq!(*tx_digest, digest);
+                assert!(!success);
+                assert!(error.as_ref().is_some_and(|e| !e.is_empty()));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_input_fails_before_execution() -> AuthorityResult<()> {
+        use crate::core::{ObjectRef, SequenceNumber};
+        use crate::protocol::CallArg;
+
+        let temp_dir = TempDir::new().unwrap();
+        let validator = test_validator(&temp_dir, u64::MAX)?;
+
+        let missing = ObjectRef::new(ObjectID::random(), SequenceNumber::new(1));
+        let mut transaction = test_transaction(1_000, 1);
+        if let TransactionData::Move(move_tx) = &mut transaction.data {
+            move_tx.arguments.push(CallArg::Object(missing));
+        }
+
+        assert!(matches!(
+            validator.execute_transaction(transaction).await,
+            Err(AuthorityError::MissingInputObjects(ids)) if ids == vec![missing.id]
+        ));
+
+        // Nothing ran, so no sequence number was consumed
+        assert_eq!(validator.get_metrics().await.last_sequence, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shared_object_executes_once_sequenced() -> AuthorityResult<()> {
+        use crate::core::{ObjectRef, Owner, SequenceNumber, TypeTag};
+        use crate::protocol::CallArg;
+
+        let temp_dir = TempDir::new().unwrap();
+        let validator = test_validator(&temp_dir, u64::MAX)?;
+
+        let mut shared = Object::new(
+            ObjectID::random(),
+            Owner::Shared { initial_shared_version: SequenceNumber::new(1) },
+            TypeTag::U64,
+            vec![],
+        );
+        shared.set_version(SequenceNumber::new(1));
+        validator.store.put_object(shared.clone()).await?;
+
+        let mut transaction = test_transaction(1_000, 1);
+        if let TransactionData::Move(move_tx) = &mut transaction.data {
+            move_tx.arguments.push(CallArg::Object(ObjectRef::new(shared.id(), shared.version())));
+        }
+
+        // Without a consensus assignment the transaction can't execute
+        assert!(matches!(
+            validator.execute_verified_transaction(transaction.clone()).await,
+            Err(AuthorityError::SharedObjectError(_))
+        ));
+
+        let effects = validator.handle_consensus_commit(vec![transaction.clone()]).await?;
+        assert_eq!(effects.len(), 1);
+        assert_eq!(effects[0].transaction_digest, transaction.digest());
+
+        // Executed, so the assignment is released
+        assert_eq!(validator.sequencer.read().await.next_ready(), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_immutable_objects_stay_untouched() -> AuthorityResult<()> {
+        use crate::core::{Owner, SequenceNumber, TypeTag};
+
+        let temp_dir = TempDir::new().unwrap();
+        let validator = test_validator(&temp_dir, u64::MAX)?;
+
+        let object = |owner: Owner| {
+            let mut object = Object::new(ObjectID::random(), owner, TypeTag::U64, vec![]);
+            object.set_version(SequenceNumber::new(1));
+            object
+        };
+        let frozen = object(Owner::Immutable);
+        let owned = object(Owner::AddressOwner(Address::from_bytes([1; 20])));
+        validator.store.put_object(frozen.clone()).await?;
+        validator.store.put_object(owned.clone()).await?;
+
+        validator.check_mutable(&[owned.id(), ObjectID::random()]).await?;
+        assert!(matches!(
+            validator.check_mutable(&[owned.id(), frozen.id()]).await,
+            Err(AuthorityError::ImmutableObject(id)) if id == frozen.id()
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shared_object_sequenced_twice() -> AuthorityResult<()> {
+        use crate::core::{ObjectRef, Owner, TypeTag};
+        use crate::protocol::CallArg;
+
+        let temp_dir = TempDir::new().unwrap();
+        let validator = test_validator(&temp_dir, u64::MAX)?;
+
+        let mut shared = Object::new(
+            ObjectID::random(),
+            Owner::Shared { initial_shared_version: SequenceNumber::new(1) },
+            TypeTag::U64,
+            vec![],
+        );
+        shared.set_version(SequenceNumber::new(1));
+        validator.store.put_object(shared.clone()).await?;
+
+        let touch_shared = |sender: u8| {
+            let mut transaction = test_transaction(1_000, sender);
+            if let TransactionData::Move(move_tx) = &mut transaction.data {
+                move_tx.arguments.push(CallArg::Object(ObjectRef::new(shared.id(), shared.version())));
+            }
+            transaction
+        };
+
+        // Both land in one commit; the second runs on the first's output
+        let effects = validator.handle_consensus_commit(vec![touch_shared(1), touch_shared(2)]).await?;
+        assert_eq!(effects.len(), 2);
+        for (effects, version) in effects.iter().zip([2, 3]) {
+            assert!(matches!(effects.status, ExecutionStatus::Success));
+            assert_eq!(effects.modified_objects[&shared.id()].version(), SequenceNumber::new(version));
+        }
+
+        let stored = validator.store.get_object(&shared.id()).await?.unwrap();
+        assert_eq!(stored.version(), SequenceNumber::new(3));
+        assert_eq!(validator.sequencer.read().await.next_ready(), None);
+
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use crate::core::{Object, ObjectID};
+use crate::core::{Address, Object, ObjectID};
 use crate::crypto::PublicKey;
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
@@ -16,6 +16,10 @@ pub struct GenesisConfig {
     pub objects: Vec<GenesisObject>,
     /// Framework modules
     pub framework_modules: Vec<FrameworkModule>,
+    /// Addresses allowed to issue privileged system transactions, such as
+    /// reference gas price changes
+    #[serde(default)]
+    pub system_senders: Vec<Address>,
 }
 
 /// Validator configuration
@@ -61,6 +65,7 @@ impl Default for GenesisConfig {
             validators: vec![],
             objects: vec![],
             framework_modules: vec![],
+            system_senders: vec![],
         }
     }
 }
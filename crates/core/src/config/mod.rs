@@ -26,6 +26,9 @@ pub struct Config {
     pub storage: StorageConfig,
     /// Metrics configuration
     pub metrics: MetricsConfig,
+    /// Node API configuration
+    #[serde(default)]
+    pub api: ApiConfig,
 }
 
 /// Network configuration
@@ -102,14 +105,103 @@ pub struct MetricsConfig {
     pub listen_address: String,
 }
 
+/// Node API configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// HTTP API listen address
+    pub listen_address: String,
+    /// Bearer token for the `/admin` routes; the admin API is off when unset
+    pub admin_token: Option<String>,
+    /// gRPC effects stream listen address; the stream is off when unset
+    pub grpc_listen_address: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            listen_address: "127.0.0.1:9000".to_string(),
+            admin_token: None,
+            grpc_listen_address: None,
+        }
+    }
+}
+
 impl Config {
     /// Load configuration from file
     pub fn load_from_file(path: &str) -> Result<Self, ConfigError> {
         let content = std::fs::read_to_string(path)
             .map_err(|e| ConfigError::IoError(e.to_string()))?;
         
-        toml::from_str(&content)
-            .map_err(|e| ConfigError::ParseError(e.to_string()))
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| ConfigError::ParseError(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check invariants the TOML schema can't express, reporting the first
+    /// violation
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.chain_id.is_empty() {
+            return Err(invalid("chain_id", "must not be empty"));
+        }
+        if self.genesis.chain_id != self.chain_id {
+            return Err(invalid("genesis.chain_id", format!(
+                "{:?} does not match chain_id {:?}",
+                self.genesis.chain_id, self.chain_id
+            )));
+        }
+
+        // Addresses
+        check_address("network.listen_address", &self.network.listen_address)?;
+        check_address("network.external_address", &self.network.external_address)?;
+        check_address("authority.network_address", &self.authority.network_address)?;
+        if self.metrics.enabled {
+            check_address("metrics.listen_address", &self.metrics.listen_address)?;
+        }
+        check_address("api.listen_address", &self.api.listen_address)?;
+        if let Some(address) = &self.api.grpc_listen_address {
+            check_address("api.grpc_listen_address", address)?;
+        }
+        if self.api.admin_token.as_ref().is_some_and(|token| token.is_empty()) {
+            return Err(invalid("api.admin_token", "must not be empty"));
+        }
+        for validator in &self.genesis.validators {
+            check_address("genesis.validators.network_address", &validator.network_address)?;
+        }
+
+        // Limits and timeouts
+        check_positive("network.connection_timeout_ms", self.network.connection_timeout_ms)?;
+        check_positive("network.max_peers", self.network.max_peers as u64)?;
+        check_positive("consensus.block_time_ms", self.consensus.block_time_ms)?;
+        check_positive("consensus.max_batch_size", self.consensus.max_batch_size as u64)?;
+
+        // Consensus parameters
+        let narwhal = &self.consensus.narwhal;
+        if narwhal.block_time_ms != self.consensus.block_time_ms {
+            return Err(invalid("consensus.narwhal.block_time_ms", format!(
+                "{} differs from consensus.block_time_ms {}",
+                narwhal.block_time_ms, self.consensus.block_time_ms
+            )));
+        }
+        if narwhal.max_batch_size > self.consensus.max_batch_size {
+            return Err(invalid("consensus.narwhal.max_batch_size", format!(
+                "{} exceeds consensus.max_batch_size {}",
+                narwhal.max_batch_size, self.consensus.max_batch_size
+            )));
+        }
+        let validators = self.genesis.validators.len();
+        if validators > 0 && narwhal.parents_count > validators {
+            return Err(invalid("consensus.narwhal.parents_count", format!(
+                "{} exceeds the {} genesis validators",
+                narwhal.parents_count, validators
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.consensus.bullshark.reputation_threshold) {
+            return Err(invalid("consensus.bullshark.reputation_threshold", "must be between 0 and 1"));
+        }
+
+        Ok(())
     }
 
     /// Save configuration to file
@@ -157,10 +249,40 @@ impl Config {
                 enabled: true,
                 listen_address: "127.0.0.1:9184".to_string(),
             },
+            api: ApiConfig::default(),
         }
     }
 }
 
+/// Error for an invalid field
+fn invalid(field: &str, reason: impl Into<String>) -> ConfigError {
+    ConfigError::InvalidValue {
+        field: field.to_string(),
+        reason: reason.into(),
+    }
+}
+
+/// Check that `value` is `host:port`. The host may be a DNS name, so only
+/// the port is validated here; names resolve when the address is dialed.
+fn check_address(field: &str, value: &str) -> Result<(), ConfigError> {
+    let (host, port) = value.rsplit_once(':')
+        .ok_or_else(|| invalid(field, format!("{:?} is not host:port", value)))?;
+    if host.is_empty() {
+        return Err(invalid(field, format!("{:?} has no host", value)));
+    }
+    port.parse::<u16>()
+        .map(|_| ())
+        .map_err(|e| invalid(field, format!("{:?} has an invalid port: {}", value, e)))
+}
+
+/// Check that `value` is non-zero
+fn check_positive(field: &str, value: u64) -> Result<(), ConfigError> {
+    if value == 0 {
+        return Err(invalid(field, "must be positive"));
+    }
+    Ok(())
+}
+
 /// Configuration error
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
@@ -172,4 +294,62 @@ pub enum ConfigError {
 
     #[error("Serialization error: {0}")]
     SerializeError(String),
+
+    #[error("Invalid {field}: {reason}")]
+    InvalidValue { field: String, reason: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_invalid(config: Config, expected: &str) {
+        match config.validate() {
+            Err(ConfigError::InvalidValue { field, .. }) => assert_eq!(field, expected),
+            other => panic!("expected {} to be invalid, got {:?}", expected, other),
+        }
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        Config::default().validate().unwrap();
+    }
+
+    #[test]
+    fn test_dns_addresses_are_valid() {
+        let mut config = Config::default();
+        config.network.external_address = "validator.example.com:8080".to_string();
+        config.authority.network_address = "[::1]:9000".to_string();
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        let mut config = Config::default();
+        config.chain_id = String::new();
+        assert_invalid(config, "chain_id");
+
+        let mut config = Config::default();
+        config.network.external_address = "localhost".to_string();
+        assert_invalid(config, "network.external_address");
+
+        let mut config = Config::default();
+        config.network.external_address = "validator.example.com:99999".to_string();
+        assert_invalid(config, "network.external_address");
+
+        let mut config = Config::default();
+        config.network.connection_timeout_ms = 0;
+        assert_invalid(config, "network.connection_timeout_ms");
+
+        let mut config = Config::default();
+        config.consensus.narwhal.block_time_ms = config.consensus.block_time_ms + 1;
+        assert_invalid(config, "consensus.narwhal.block_time_ms");
+
+        // Disabled metrics don't need a usable address
+        let mut config = Config::default();
+        config.metrics.listen_address = String::new();
+        assert_invalid(config.clone(), "metrics.listen_address");
+        config.metrics.enabled = false;
+        config.validate().unwrap();
+    }
 }
\ No newline at end of file
@@ -110,11 +110,11 @@ impl Consensus for BullShark {
         result
     }
 
-    fn current_round(&self) -> Round {
-        self.narwhal.current_round()
+    async fn current_round(&self) -> Round {
+        self.narwhal.current_round().await
     }
 
-    fn state(&self) -> ConsensusState {
-        self.narwhal.state()
+    async fn state(&self) -> ConsensusState {
+        self.narwhal.state().await
     }
 }
\ No newline at end of file
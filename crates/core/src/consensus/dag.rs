@@ -13,25 +13,50 @@ pub struct DagNode {
     pub committed: bool,
 }
 
+/// Default number of committed rounds retained below the commit watermark
+pub const DEFAULT_GC_DEPTH: Round = 50;
+
 /// Directed acyclic graph
 pub struct Dag {
     /// Nodes by digest
     nodes: HashMap<TransactionDigest, DagNode>,
     /// Nodes by round
     rounds: HashMap<Round, HashSet<TransactionDigest>>,
+    /// Rounds kept below the last committed round
+    gc_depth: Round,
+    /// Highest round containing a committed node
+    last_committed_round: Round,
+    /// Rounds below this have been garbage collected
+    gc_round: Round,
 }
 
 impl Dag {
     pub fn new() -> Self {
+        Self::with_gc_depth(DEFAULT_GC_DEPTH)
+    }
+
+    /// Create DAG retaining `gc_depth` rounds below the commit watermark
+    pub fn with_gc_depth(gc_depth: Round) -> Self {
         Self {
             nodes: HashMap::new(),
             rounds: HashMap::new(),
+            gc_depth,
+            last_committed_round: 0,
+            gc_round: 0,
         }
     }
 
     /// Add a new proposal to the DAG
     pub fn add_proposal(&mut self, proposal: Proposal) -> ProtocolResult<()> {
         let digest = proposal.digest();
+
+        // Parents of proposals below the GC round are no longer available
+        if proposal.round < self.gc_round {
+            return Err(ProtocolError::InvalidProposal(format!(
+                "Proposal round {} below GC round {}",
+                proposal.round, self.gc_round
+            )));
+        }
         
         // Check if already exists
         if self.nodes.contains_key(&digest) {
@@ -64,6 +89,53 @@ impl Dag {
         Ok(())
     }
 
+    /// Mark a node as committed
+    pub fn mark_committed(&mut self, digest: &TransactionDigest) {
+        if let Some(node) = self.nodes.get_mut(digest) {
+            node.committed = true;
+            self.last_committed_round = self.last_committed_round.max(node.proposal.round);
+        }
+    }
+
+    /// Remove rounds below `below_round`, keeping `gc_depth` rounds under the
+    /// last committed round. Stops at the first round that still has an
+    /// uncommitted node or an uncommitted child. Returns removed node count.
+    pub fn gc(&mut self, below_round: Round) -> usize {
+        let cutoff = below_round.min(self.last_committed_round.saturating_sub(self.gc_depth));
+
+        let mut rounds: Vec<Round> = self.rounds.keys()
+            .copied()
+            .filter(|round| *round < cutoff)
+            .collect();
+        rounds.sort_unstable();
+
+        let mut removed = 0;
+        for round in rounds {
+            let digests = &self.rounds[&round];
+            let collectable = digests.iter().all(|digest| {
+                self.nodes.get(digest).is_none_or(|node| {
+                    node.committed && node.children.iter().all(|child| {
+                        self.nodes.get(child).is_none_or(|child| child.committed)
+                    })
+                })
+            });
+            if !collectable {
+                break;
+            }
+
+            if let Some(digests) = self.rounds.remove(&round) {
+                for digest in digests {
+                    if self.nodes.remove(&digest).is_some() {
+                        removed += 1;
+                    }
+                }
+            }
+            self.gc_round = round + 1;
+        }
+
+        removed
+    }
+
     /// Find nodes that can be committed
     pub fn find_commit_candidates(&self) -> ProtocolResult<Vec<Proposal>> {
         let mut candidates = Vec::new();
@@ -110,4 +182,52 @@ impl Dag {
             false
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proposal(round: Round, parents: HashSet<TransactionDigest>) -> Proposal {
+        Proposal {
+            round,
+            author: "validator".to_string(),
+            transactions: vec![],
+            parents,
+            signature: vec![],
+        }
+    }
+
+    #[test]
+    fn test_gc_below_commit_watermark() {
+        let mut dag = Dag::with_gc_depth(2);
+
+        let mut parents = HashSet::new();
+        let mut digests = Vec::new();
+        for round in 1..=10 {
+            let proposal = proposal(round, parents.clone());
+            let digest = proposal.digest();
+            dag.add_proposal(proposal).unwrap();
+            digests.push(digest);
+            parents = HashSet::from([digest]);
+        }
+
+        // Commit up to round 7
+        for digest in &digests[..7] {
+            dag.mark_committed(digest);
+        }
+
+        // Watermark 7 minus gap 2 drops rounds 1..=4
+        assert_eq!(dag.gc(7), 4);
+        for round in 1..=4 {
+            assert!(!dag.rounds.contains_key(&round));
+        }
+        for round in 5..=10 {
+            assert!(dag.rounds.contains_key(&round));
+        }
+
+        // Uncommitted rounds are never collected
+        assert_eq!(dag.gc(Round::MAX), 0);
+        assert!(dag.add_proposal(proposal(3, HashSet::new())).is_err());
+    }
+}
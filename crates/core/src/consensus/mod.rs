@@ -1,15 +1,19 @@
 //! Consensus module implementing Narwhal-Bullshark consensus protocol.
 
+mod block_producer;
 mod narwhal;
 mod bullshark;
 mod dag;
 mod safety_rules;
+mod shared_objects;
 mod types;
 
+pub use block_producer::{Block, BlockProducer};
 pub use narwhal::{NarwhalConsensus, NarwhalConfig};
 pub use bullshark::{BullShark, BullSharkConfig};
 pub use dag::{Dag, DagNode, Round};
 pub use safety_rules::{SafetyRules, Vote};
+pub use shared_objects::SharedObjectSequencer;
 pub use types::{ConsensusState, Proposal, Certificate};
 
 use crate::protocol::{ProtocolError, ProtocolResult};
@@ -44,8 +48,8 @@ pub trait Consensus: Send + Sync {
     async fn process_vote(&self, vote: Vote) -> ProtocolResult<()>;
     
     /// Get current round
-    fn current_round(&self) -> Round;
+    async fn current_round(&self) -> Round;
     
     /// Get consensus state
-    fn state(&self) -> ConsensusState;
+    async fn state(&self) -> ConsensusState;
 }
\ No newline at end of file
@@ -12,6 +12,8 @@ pub struct NarwhalConfig {
     pub max_batch_size: usize,
     /// Number of parents per proposal
     pub parents_count: usize,
+    /// Committed rounds retained in the DAG before garbage collection
+    pub gc_depth: Round,
 }
 
 /// Narwhal consensus implementation
@@ -37,19 +39,20 @@ impl NarwhalConsensus {
             pending_proposals: HashSet::new(),
             committed_certificates: Vec::new(),
         };
+        let dag = Dag::with_gc_depth(config.gc_depth);
 
         Self {
             config,
             state: Arc::new(RwLock::new(state)),
             safety_rules,
-            dag: Arc::new(RwLock::new(Dag::new())),
+            dag: Arc::new(RwLock::new(dag)),
         }
     }
 
     /// Process a new proposal
     async fn process_proposal_internal(&self, proposal: Proposal) -> ProtocolResult<()> {
         // Verify proposal
-        self.safety_rules.verify_proposal(&proposal)?;
+        self.safety_rules.verify_proposal(&proposal).await?;
 
         // Add to DAG
         let mut dag = self.dag.write().await;
@@ -57,10 +60,17 @@ impl NarwhalConsensus {
 
         // Try to commit
         if let Some(certificates) = self.try_commit(&dag).await? {
+            for cert in &certificates {
+                dag.mark_committed(&cert.proposal.digest());
+            }
+
             // Update state
             let mut state = self.state.write().await;
             state.committed_certificates.extend(certificates);
             state.last_committed_round = proposal.round;
+
+            // Drop rounds that are no longer needed
+            dag.gc(state.last_committed_round);
         }
 
         Ok(())
@@ -88,7 +98,7 @@ impl NarwhalConsensus {
     /// Create certificate for a proposal
     async fn create_certificate(&self, proposal: Proposal) -> ProtocolResult<Certificate> {
         // Collect signatures
-        let signatures = self.safety_rules.sign_proposal(&proposal)?;
+        let signatures = self.safety_rules.sign_proposal(&proposal).await?;
         
         Ok(Certificate {
             proposal,
@@ -107,11 +117,11 @@ impl Consensus for NarwhalConsensus {
         self.safety_rules.process_vote(vote).await
     }
 
-    fn current_round(&self) -> Round {
-        self.state.blocking_read().round
+    async fn current_round(&self) -> Round {
+        self.state.read().await.round
     }
 
-    fn state(&self) -> ConsensusState {
-        self.state.blocking_read().clone()
+    async fn state(&self) -> ConsensusState {
+        self.state.read().await.clone()
     }
 }
\ No newline at end of file
@@ -1,8 +1,24 @@
 use super::{Proposal, Vote, Round, Certificate};
 use crate::protocol::{ProtocolError, ProtocolResult};
+use crate::storage::RocksStore;
+use serde::{Serialize, Deserialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Column family holding safety state
+const SAFETY_CF: &str = "consensus";
+/// Key of the persisted safety state
+const SAFETY_STATE_KEY: &[u8] = b"safety_state";
+
+/// Safety state persisted across restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SafetyState {
+    /// Highest voted round
+    highest_voted_round: Round,
+    /// Locked round
+    locked_round: Option<Round>,
+}
+
 /// Safety rules configuration
 #[derive(Debug, Clone)]
 pub struct SafetyRulesConfig {
@@ -22,22 +38,67 @@ pub struct SafetyRules {
     highest_certified_round: Arc<RwLock<Round>>,
     /// Locked round
     locked_round: Arc<RwLock<Option<Round>>>,
+    /// Durable store for safety state
+    store: Option<Arc<RocksStore>>,
 }
 
 impl SafetyRules {
     pub fn new(config: SafetyRulesConfig) -> Self {
+        Self::with_state(config, SafetyState::default(), None)
+    }
+
+    /// Open safety rules backed by RocksDB, restoring persisted state
+    pub fn open(config: SafetyRulesConfig, store: Arc<RocksStore>) -> ProtocolResult<Self> {
+        store.require_column_families(&[SAFETY_CF])?;
+
+        let state = match store.get(SAFETY_CF, SAFETY_STATE_KEY)? {
+            Some(bytes) => bincode::deserialize(&bytes)
+                .map_err(|e| ProtocolError::DeserializationError(e.to_string()))?,
+            None => SafetyState::default(),
+        };
+
+        Ok(Self::with_state(config, state, Some(store)))
+    }
+
+    fn with_state(
+        config: SafetyRulesConfig,
+        state: SafetyState,
+        store: Option<Arc<RocksStore>>,
+    ) -> Self {
         Self {
             config,
-            highest_voted_round: Arc::new(RwLock::new(0)),
+            highest_voted_round: Arc::new(RwLock::new(state.highest_voted_round)),
             highest_certified_round: Arc::new(RwLock::new(0)),
-            locked_round: Arc::new(RwLock::new(None)),
+            locked_round: Arc::new(RwLock::new(state.locked_round)),
+            store,
         }
     }
 
+    /// Persist safety state
+    fn persist(&self, state: &SafetyState) -> ProtocolResult<()> {
+        if let Some(store) = &self.store {
+            let bytes = bincode::serialize(state)
+                .map_err(|e| ProtocolError::SystemError(e.to_string()))?;
+            store.put(SAFETY_CF, SAFETY_STATE_KEY, &bytes)?;
+        }
+        Ok(())
+    }
+
     /// Verify proposal safety rules
-    pub fn verify_proposal(&self, proposal: &Proposal) -> ProtocolResult<()> {
+    pub async fn verify_proposal(&self, proposal: &Proposal) -> ProtocolResult<()> {
+        let highest_voted = *self.highest_voted_round.read().await;
+        let locked = *self.locked_round.read().await;
+        self.check_proposal(proposal, highest_voted, locked)
+    }
+
+    /// Check a proposal against the given voting state
+    fn check_proposal(
+        &self,
+        proposal: &Proposal,
+        highest_voted: Round,
+        locked: Option<Round>,
+    ) -> ProtocolResult<()> {
         // Check round monotonicity
-        let highest_voted = *self.highest_voted_round.blocking_read();
         if proposal.round <= highest_voted {
             return Err(ProtocolError::InvalidProposal(
                 "Round not monotonic".into()
@@ -52,7 +113,7 @@ impl SafetyRules {
         }
 
         // Check locked round
-        if let Some(locked) = *self.locked_round.blocking_read() {
+        if let Some(locked) = locked {
             if proposal.round <= locked {
                 return Err(ProtocolError::InvalidProposal(
                     "Violates locked round".into()
@@ -72,18 +133,31 @@ impl SafetyRules {
         }
 
         // Check if we should lock the round
+        let mut locked = self.locked_round.write().await;
         if self.has_quorum_votes(&vote) {
-            let mut locked = self.locked_round.write().await;
             *locked = Some(vote.round);
         }
 
-        Ok(())
+        self.persist(&SafetyState {
+            highest_voted_round: *highest_voted,
+            locked_round: *locked,
+        })
     }
 
     /// Sign proposal
-    pub fn sign_proposal(&self, proposal: &Proposal) -> ProtocolResult<Vec<(String, Vec<u8>)>> {
-        // Verify proposal first
-        self.verify_proposal(proposal)?;
+    pub async fn sign_proposal(&self, proposal: &Proposal) -> ProtocolResult<Vec<(String, Vec<u8>)>> {
+        // Hold the voting state from the check until the vote is recorded,
+        // so two concurrent proposals can't both pass for one round
+        let mut highest_voted = self.highest_voted_round.write().await;
+        let locked = *self.locked_round.read().await;
+        self.check_proposal(proposal, *highest_voted, locked)?;
+
+        // Record the vote durably before signing so a restart can't double-vote
+        self.persist(&SafetyState {
+            highest_voted_round: proposal.round,
+            locked_round: locked,
+        })?;
+        *highest_voted = proposal.round;
 
         // Generate signatures (simplified)
         let signatures = vec![
@@ -107,4 +181,70 @@ impl SafetyRules {
             *highest = round;
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::RocksConfig;
+    use std::collections::HashSet;
+    use tempfile::TempDir;
+
+    fn config() -> SafetyRulesConfig {
+        SafetyRulesConfig {
+            quorum_size: 3,
+            max_round_gap: 10,
+        }
+    }
+
+    fn proposal(round: Round) -> Proposal {
+        Proposal {
+            round,
+            author: "validator".to_string(),
+            transactions: vec![],
+            parents: HashSet::new(),
+            signature: vec![],
+        }
+    }
+
+    fn open_store(temp_dir: &TempDir) -> Arc<RocksStore> {
+        Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        }).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_no_double_vote_after_restart() {
+        let temp_dir = TempDir::new().unwrap();
+
+        {
+            let rules = SafetyRules::open(config(), open_store(&temp_dir)).unwrap();
+            assert!(rules.sign_proposal(&proposal(5)).await.is_ok());
+        }
+
+        // Restart with the persisted state
+        let rules = SafetyRules::open(config(), open_store(&temp_dir)).unwrap();
+        assert!(rules.sign_proposal(&proposal(5)).await.is_err());
+        assert!(rules.sign_proposal(&proposal(4)).await.is_err());
+        assert!(rules.sign_proposal(&proposal(6)).await.is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_votes_on_one_round() {
+        let rules = Arc::new(SafetyRules::new(config()));
+
+        let attempts: Vec<_> = (0..8)
+            .map(|_| {
+                let rules = rules.clone();
+                tokio::spawn(async move { rules.sign_proposal(&proposal(1)).await.is_ok() })
+            })
+            .collect();
+
+        let mut signed = 0;
+        for attempt in attempts {
+            signed += attempt.await.unwrap() as usize;
+        }
+        assert_eq!(signed, 1);
+    }
+}
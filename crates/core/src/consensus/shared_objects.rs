@@ -0,0 +1,100 @@
+use crate::core::{ObjectID, SequenceNumber};
+use crate::transaction::TransactionDigest;
+use std::collections::{HashMap, VecDeque};
+
+/// Assigns versions of shared objects to transactions in consensus order
+#[derive(Debug, Default)]
+pub struct SharedObjectSequencer {
+    /// Next version to hand out per shared object
+    next_versions: HashMap<ObjectID, SequenceNumber>,
+    /// Versions assigned to each transaction
+    assignments: HashMap<TransactionDigest, Vec<(ObjectID, SequenceNumber)>>,
+    /// Transactions in the order they were sequenced
+    order: VecDeque<TransactionDigest>,
+}
+
+impl SharedObjectSequencer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assign versions for the shared objects a committed transaction
+    /// touches. `shared_objects` carries each object's current version, used
+    /// the first time the object is seen. Re-delivered transactions keep
+    /// their original assignment.
+    pub fn assign(
+        &mut self,
+        digest: TransactionDigest,
+        shared_objects: &[(ObjectID, SequenceNumber)],
+    ) -> Vec<(ObjectID, SequenceNumber)> {
+        if let Some(assigned) = self.assignments.get(&digest) {
+            return assigned.clone();
+        }
+
+        let assigned: Vec<_> = shared_objects.iter()
+            .map(|(id, current)| {
+                let next = self.next_versions.entry(*id).or_insert(*current);
+                let version = *next;
+                next.increment();
+                (*id, version)
+            })
+            .collect();
+
+        self.assignments.insert(digest, assigned.clone());
+        self.order.push_back(digest);
+        assigned
+    }
+
+    /// Get the version assigned to a shared object for a transaction
+    pub fn assigned_version(
+        &self,
+        digest: &TransactionDigest,
+        id: &ObjectID,
+    ) -> Option<SequenceNumber> {
+        self.assignments.get(digest)?
+            .iter()
+            .find(|(object_id, _)| object_id == id)
+            .map(|(_, version)| *version)
+    }
+
+    /// Get every version assigned to a transaction
+    pub fn assigned_versions(&self, digest: &TransactionDigest) -> Vec<(ObjectID, SequenceNumber)> {
+        self.assignments.get(digest).cloned().unwrap_or_default()
+    }
+
+    /// Next sequenced transaction awaiting execution
+    pub fn next_ready(&self) -> Option<TransactionDigest> {
+        self.order.front().copied()
+    }
+
+    /// Drop the assignment once the transaction has executed
+    pub fn mark_executed(&mut self, digest: &TransactionDigest) {
+        self.assignments.remove(digest);
+        self.order.retain(|d| d != digest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_object_ordering() {
+        let mut sequencer = SharedObjectSequencer::new();
+        let object = ObjectID::random();
+        let tx_a = TransactionDigest::from_bytes([1; 32]);
+        let tx_b = TransactionDigest::from_bytes([2; 32]);
+
+        let a = sequencer.assign(tx_a, &[(object, SequenceNumber::new(5))]);
+        let b = sequencer.assign(tx_b, &[(object, SequenceNumber::new(5))]);
+        assert_eq!(a, vec![(object, SequenceNumber::new(5))]);
+        assert_eq!(b, vec![(object, SequenceNumber::new(6))]);
+
+        // Re-delivery doesn't consume another version
+        assert_eq!(sequencer.assign(tx_a, &[(object, SequenceNumber::new(5))]), a);
+
+        assert_eq!(sequencer.next_ready(), Some(tx_a));
+        sequencer.mark_executed(&tx_a);
+        assert_eq!(sequencer.next_ready(), Some(tx_b));
+    }
+}
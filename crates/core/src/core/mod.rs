@@ -3,7 +3,7 @@
 mod object;
 mod types;
 
-pub use object::{Object, ObjectID, Owner};
+pub use object::{Object, ObjectID, ObjectRef, Owner};
 pub use types::{Address, Balance, Coin, SequenceNumber, TypeTag};
 
 use serde::{Serialize, Deserialize};
@@ -1,6 +1,8 @@
 use super::{Address, CoreError, CoreResult, SequenceNumber, TypeTag};
+use crate::transaction::TransactionDigest;
 use serde::{Serialize, Deserialize};
 use std::fmt;
+use std::str::FromStr;
 
 /// Object ID
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
@@ -16,6 +18,16 @@ impl ObjectID {
         Self(bytes)
     }
 
+    /// Derive the ID of the `creation_index`-th object created by a
+    /// transaction, identical on every validator
+    pub fn derive(tx_digest: &TransactionDigest, creation_index: u64) -> Self {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(tx_digest.as_bytes());
+        hasher.update(creation_index.to_le_bytes());
+        Self(hasher.finalize().into())
+    }
+
     /// Create from bytes
     pub fn from_bytes(bytes: [u8; 32]) -> Self {
         Self(bytes)
@@ -25,6 +37,36 @@ impl ObjectID {
     pub fn as_bytes(&self) -> &[u8; 32] {
         &self.0
     }
+
+    /// Parse from hex, with or without a `0x` prefix
+    pub fn from_hex(s: &str) -> CoreResult<Self> {
+        let bytes = hex::decode(s.trim_start_matches("0x"))
+            .map_err(|e| CoreError::InvalidObject(e.to_string()))?;
+        let bytes: [u8; 32] = bytes.try_into()
+            .map_err(|_| CoreError::InvalidObject(format!("Invalid object id length: {}", s)))?;
+        Ok(Self(bytes))
+    }
+
+    /// Hex encoding with `0x` prefix
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.0))
+    }
+}
+
+/// Reference to a specific object version
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ObjectRef {
+    /// Object ID
+    pub id: ObjectID,
+    /// Version
+    pub version: SequenceNumber,
+}
+
+impl ObjectRef {
+    /// Create new object reference
+    pub fn new(id: ObjectID, version: SequenceNumber) -> Self {
+        Self { id, version }
+    }
 }
 
 /// Object owner
@@ -38,7 +80,7 @@ pub enum Owner {
     Shared {
         initial_shared_version: SequenceNumber
     },
-    /// Immutable object
+    /// Immutable object, readable by anyone but never mutated or deleted
     Immutable,
 }
 
@@ -70,6 +112,45 @@ impl Owner {
     }
 }
 
+/// Stable form kept in the owner field of stored objects
+impl fmt::Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddressOwner(addr) => write!(f, "0x{}", hex::encode(addr.as_bytes())),
+            Self::ObjectOwner(id) => write!(f, "{}", id.to_hex()),
+            Self::Shared { initial_shared_version } => write!(f, "shared@{}", initial_shared_version.value()),
+            Self::Immutable => write!(f, "immutable"),
+        }
+    }
+}
+
+/// Parse the stored form written by `Display`
+impl FromStr for Owner {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> CoreResult<Self> {
+        if s == "immutable" {
+            return Ok(Self::Immutable);
+        }
+        if let Some(version) = s.strip_prefix("shared@") {
+            let version = version.parse()
+                .map_err(|_| CoreError::InvalidObject(format!("Invalid owner: {}", s)))?;
+            return Ok(Self::Shared { initial_shared_version: SequenceNumber::new(version) });
+        }
+        match s.trim_start_matches("0x").len() {
+            40 => {
+                let bytes = hex::decode(s.trim_start_matches("0x"))
+                    .map_err(|e| CoreError::InvalidAddress(e.to_string()))?;
+                let bytes: [u8; 20] = bytes.try_into()
+                    .map_err(|_| CoreError::InvalidAddress(s.to_string()))?;
+                Ok(Self::AddressOwner(Address::from_bytes(bytes)))
+            }
+            64 => Ok(Self::ObjectOwner(ObjectID::from_hex(s)?)),
+            _ => Err(CoreError::InvalidObject(format!("Invalid owner: {}", s))),
+        }
+    }
+}
+
 /// Object data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Object {
@@ -169,4 +250,36 @@ impl Object {
     pub fn is_immutable(&self) -> bool {
         self.owner.is_immutable()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_object_id() {
+        let digest = TransactionDigest::from_bytes([7; 32]);
+
+        // Two validators deriving from the same inputs agree
+        assert_eq!(ObjectID::derive(&digest, 0), ObjectID::derive(&digest, 0));
+
+        // Different indices or digests give different IDs
+        assert_ne!(ObjectID::derive(&digest, 0), ObjectID::derive(&digest, 1));
+        let other = TransactionDigest::from_bytes([8; 32]);
+        assert_ne!(ObjectID::derive(&digest, 0), ObjectID::derive(&other, 0));
+    }
+
+    #[test]
+    fn test_owner_round_trips_stored_form() {
+        let owners = [
+            Owner::AddressOwner(Address::from_bytes([1; 20])),
+            Owner::ObjectOwner(ObjectID::from_bytes([2; 32])),
+            Owner::Shared { initial_shared_version: SequenceNumber::new(3) },
+            Owner::Immutable,
+        ];
+        for owner in owners {
+            assert_eq!(owner.to_string().parse::<Owner>().unwrap(), owner);
+        }
+        assert!("alice".parse::<Owner>().is_err());
+    }
+}
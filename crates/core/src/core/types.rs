@@ -22,6 +22,9 @@ impl Address {
 pub struct SequenceNumber(u64);
 
 impl SequenceNumber {
+    /// Highest sequence number, used in keys to mean the latest version
+    pub const MAX: Self = Self(u64::MAX);
+
     /// Create new sequence number
     pub fn new(value: u64) -> Self {
         Self(value)
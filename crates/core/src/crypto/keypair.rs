@@ -1,5 +1,5 @@
-use super::{CryptoError, CryptoResult, SignatureScheme};
-use ed25519_dalek::{Keypair as Ed25519Keypair, PublicKey as Ed25519PublicKey, SecretKey};
+use super::{CryptoError, CryptoResult, MultiSigPublicKey, Signature, SignatureScheme};
+use ed25519_dalek::{Keypair as Ed25519Keypair, PublicKey as Ed25519PublicKey, SecretKey, Signer};
 use rand::rngs::OsRng;
 use serde::{Serialize, Deserialize};
 use std::fmt;
@@ -7,37 +7,34 @@ use std::fmt;
 /// Key pair
 #[derive(Clone)]
 pub struct KeyPair {
-    /// Signature scheme
-    scheme: SignatureScheme,
+    /// Secret key material
+    keys: SecretKeys,
+}
+
+/// Secret key material of a key pair. Multi-sig has no secret of its own, so
+/// it can't be a key pair.
+#[derive(Clone)]
+enum SecretKeys {
     /// Ed25519 key pair
-    ed25519: Option<Ed25519Keypair>,
-    /// BLS key pair
-    bls: Option<blst::min_pk::SecretKey>,
+    Ed25519(Ed25519Keypair),
+    /// BLS secret key
+    Bls(blst::min_pk::SecretKey),
 }
 
 impl KeyPair {
     /// Generate new key pair
-    pub fn generate(scheme: SignatureScheme) -> Self {
-        match scheme {
-            SignatureScheme::Ed25519 => {
-                let mut rng = OsRng;
-                let keypair = Ed25519Keypair::generate(&mut rng);
-                Self {
-                    scheme,
-                    ed25519: Some(keypair),
-                    bls: None,
-                }
+    pub fn generate(scheme: SignatureScheme) -> CryptoResult<Self> {
+        let mut rng = OsRng;
+        let keys = match scheme {
+            SignatureScheme::Ed25519 => SecretKeys::Ed25519(Ed25519Keypair::generate(&mut rng)),
+            SignatureScheme::BLS => SecretKeys::Bls(blst::min_pk::SecretKey::new(&mut rng)),
+            SignatureScheme::MultiSig => {
+                return Err(CryptoError::InvalidScheme(
+                    "Multi-sig keys are composed from member keys with MultiSigPublicKey::new".into()
+                ));
             }
-            SignatureScheme::BLS => {
-                let mut rng = OsRng;
-                let secret = blst::min_pk::SecretKey::new(&mut rng);
-                Self {
-                    scheme,
-                    ed25519: None,
-                    bls: Some(secret),
-                }
-            }
-        }
+        };
+        Ok(Self { keys })
     }
 
     /// Create from private key bytes
@@ -45,60 +42,46 @@ impl KeyPair {
         scheme: SignatureScheme,
         bytes: &[u8],
     ) -> CryptoResult<Self> {
-        match scheme {
+        let keys = match scheme {
             SignatureScheme::Ed25519 => {
                 let secret = SecretKey::from_bytes(bytes)
                     .map_err(|e| CryptoError::InvalidKey(e.to_string()))?;
                 let public = Ed25519PublicKey::from(&secret);
-                let keypair = Ed25519Keypair { secret, public };
-                Ok(Self {
-                    scheme,
-                    ed25519: Some(keypair),
-                    bls: None,
-                })
+                SecretKeys::Ed25519(Ed25519Keypair { secret, public })
             }
             SignatureScheme::BLS => {
                 let secret = blst::min_pk::SecretKey::from_bytes(bytes)
                     .map_err(|e| CryptoError::InvalidKey(e.to_string()))?;
-                Ok(Self {
-                    scheme,
-                    ed25519: None,
-                    bls: Some(secret),
-                })
+                SecretKeys::Bls(secret)
             }
-        }
+            SignatureScheme::MultiSig => {
+                return Err(CryptoError::InvalidScheme("Multi-sig has no private key".into()));
+            }
+        };
+        Ok(Self { keys })
     }
 
     /// Get signature scheme
     pub fn scheme(&self) -> SignatureScheme {
-        self.scheme
+        match &self.keys {
+            SecretKeys::Ed25519(_) => SignatureScheme::Ed25519,
+            SecretKeys::Bls(_) => SignatureScheme::BLS,
+        }
     }
 
     /// Get public key
     pub fn public(&self) -> PublicKey {
-        match self.scheme {
-            SignatureScheme::Ed25519 => {
-                let public = self.ed25519.as_ref().unwrap().public;
-                PublicKey::Ed25519(public)
-            }
-            SignatureScheme::BLS => {
-                let public = self.bls.as_ref().unwrap().sk_to_pk();
-                PublicKey::BLS(public)
-            }
+        match &self.keys {
+            SecretKeys::Ed25519(keypair) => PublicKey::Ed25519(keypair.public),
+            SecretKeys::Bls(secret) => PublicKey::BLS(secret.sk_to_pk()),
         }
     }
 
     /// Sign message
     pub fn sign(&self, message: &[u8]) -> Signature {
-        match self.scheme {
-            SignatureScheme::Ed25519 => {
-                let signature = self.ed25519.as_ref().unwrap().sign(message);
-                Signature::Ed25519(signature)
-            }
-            SignatureScheme::BLS => {
-                let signature = self.bls.as_ref().unwrap().sign(message, &[]);
-                Signature::BLS(signature)
-            }
+        match &self.keys {
+            SecretKeys::Ed25519(keypair) => Signature::Ed25519(keypair.sign(message)),
+            SecretKeys::Bls(secret) => Signature::BLS(secret.sign(message, &[])),
         }
     }
 }
@@ -110,6 +93,8 @@ pub enum PublicKey {
     Ed25519(Ed25519PublicKey),
     /// BLS public key
     BLS(blst::min_pk::PublicKey),
+    /// Multi-sig public key
+    MultiSig(MultiSigPublicKey),
 }
 
 impl PublicKey {
@@ -118,6 +103,7 @@ impl PublicKey {
         match self {
             Self::Ed25519(_) => SignatureScheme::Ed25519,
             Self::BLS(_) => SignatureScheme::BLS,
+            Self::MultiSig(_) => SignatureScheme::MultiSig,
         }
     }
 
@@ -130,6 +116,7 @@ impl PublicKey {
             (Self::BLS(pk), Signature::BLS(sig)) => {
                 sig.verify(true, message, &[], pk, &[]).is_ok()
             }
+            (Self::MultiSig(pk), Signature::MultiSig(sig)) => pk.verify(message, sig),
             _ => false,
         }
     }
@@ -139,6 +126,7 @@ impl PublicKey {
         match self {
             Self::Ed25519(pk) => pk.to_bytes().to_vec(),
             Self::BLS(pk) => pk.to_bytes().to_vec(),
+            Self::MultiSig(pk) => bcs::to_bytes(pk).expect("Multi-sig key is BCS serializable"),
         }
     }
 }
@@ -148,6 +136,7 @@ impl fmt::Display for PublicKey {
         match self {
             Self::Ed25519(pk) => write!(f, "ed25519:{}", hex::encode(pk.to_bytes())),
             Self::BLS(pk) => write!(f, "bls:{}", hex::encode(pk.to_bytes())),
+            Self::MultiSig(_) => write!(f, "multisig:{}", hex::encode(self.to_bytes())),
         }
     }
 }
\ No newline at end of file
@@ -1,9 +1,11 @@
 //! Cryptographic primitives for the Sui blockchain.
 
 mod keypair;
+mod multisig;
 mod signature;
 
 pub use keypair::{KeyPair, PublicKey, PrivateKey};
+pub use multisig::{MultiSig, MultiSigPublicKey, MAX_MULTISIG_KEYS};
 pub use signature::Signature;
 
 use crate::protocol::{ProtocolError, ProtocolResult};
@@ -33,4 +35,6 @@ pub enum SignatureScheme {
     Ed25519,
     /// BLS signatures
     BLS,
+    /// Weighted M-of-N multi-sig, composed from member keys
+    MultiSig,
 }
\ No newline at end of file
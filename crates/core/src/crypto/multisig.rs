@@ -0,0 +1,213 @@
+use super::{CryptoError, CryptoResult, KeyPair, PublicKey, Signature};
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+
+/// Maximum member keys in a multi-sig
+pub const MAX_MULTISIG_KEYS: usize = 10;
+
+/// Multi-sig public key: weighted member keys and the total weight of
+/// valid member signatures required. Deserialization goes through
+/// `MultiSigPublicKey::new`, so decoded keys are validated too.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "RawMultiSigPublicKey")]
+pub struct MultiSigPublicKey {
+    /// Member keys with their weights
+    keys: Vec<(PublicKey, u16)>,
+    /// Weight required for a valid signature
+    threshold: u16,
+}
+
+/// Unvalidated wire form of `MultiSigPublicKey`
+#[derive(Serialize, Deserialize)]
+struct RawMultiSigPublicKey {
+    keys: Vec<(PublicKey, u16)>,
+    threshold: u16,
+}
+
+impl TryFrom<RawMultiSigPublicKey> for MultiSigPublicKey {
+    type Error = CryptoError;
+
+    fn try_from(raw: RawMultiSigPublicKey) -> CryptoResult<Self> {
+        Self::new(raw.keys, raw.threshold)
+    }
+}
+
+impl MultiSigPublicKey {
+    /// Create a multi-sig key, validating member weights and threshold
+    pub fn new(keys: Vec<(PublicKey, u16)>, threshold: u16) -> CryptoResult<Self> {
+        if keys.is_empty() || keys.len() > MAX_MULTISIG_KEYS {
+            return Err(CryptoError::InvalidKey(format!(
+                "Multi-sig needs 1 to {} keys, got {}", MAX_MULTISIG_KEYS, keys.len()
+            )));
+        }
+
+        let mut seen = HashSet::new();
+        for (key, weight) in &keys {
+            if matches!(key, PublicKey::MultiSig(_)) {
+                return Err(CryptoError::InvalidKey("Multi-sig keys can't be nested".into()));
+            }
+            if *weight == 0 {
+                return Err(CryptoError::InvalidKey(format!("Zero weight for {}", key)));
+            }
+            if !seen.insert(key) {
+                return Err(CryptoError::InvalidKey(format!("Duplicate key {}", key)));
+            }
+        }
+
+        let total: u32 = keys.iter().map(|(_, weight)| *weight as u32).sum();
+        if threshold == 0 || threshold as u32 > total {
+            return Err(CryptoError::InvalidKey(format!(
+                "Threshold {} must be within 1..={}", threshold, total
+            )));
+        }
+
+        Ok(Self { keys, threshold })
+    }
+
+    /// Member keys with their weights
+    pub fn keys(&self) -> &[(PublicKey, u16)] {
+        &self.keys
+    }
+
+    /// Weight required for a valid signature
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// Sign `message` with each of `signers`, which must be members
+    pub fn sign(&self, message: &[u8], signers: &[KeyPair]) -> CryptoResult<MultiSig> {
+        let mut multisig = MultiSig::default();
+        for signer in signers {
+            let public = signer.public();
+            let index = self.keys.iter()
+                .position(|(key, _)| *key == public)
+                .ok_or_else(|| CryptoError::InvalidKey(format!("{} is not a member", public)))?;
+            multisig.signatures.push((index as u8, signer.sign(message)));
+        }
+        Ok(multisig)
+    }
+
+    /// Verify that valid member signatures over `message` reach the
+    /// threshold. Any invalid or repeated member signature fails the whole
+    /// multi-sig.
+    pub fn verify(&self, message: &[u8], multisig: &MultiSig) -> bool {
+        let mut signed = HashSet::new();
+        let mut weight = 0u32;
+
+        for (index, signature) in &multisig.signatures {
+            let Some((key, key_weight)) = self.keys.get(*index as usize) else {
+                return false;
+            };
+            if !signed.insert(*index) || !key.verify(message, signature) {
+                return false;
+            }
+            weight += *key_weight as u32;
+        }
+
+        weight >= self.threshold as u32
+    }
+}
+
+/// Member signatures of a multi-sig, each tagged with the index of its key
+/// in the `MultiSigPublicKey`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiSig {
+    /// Key index and signature of each member that signed
+    signatures: Vec<(u8, Signature)>,
+}
+
+impl MultiSig {
+    /// Member signatures
+    pub fn signatures(&self) -> &[(u8, Signature)] {
+        &self.signatures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::SignatureScheme;
+
+    fn members(n: usize) -> Vec<KeyPair> {
+        (0..n).map(|_| KeyPair::generate(SignatureScheme::Ed25519).unwrap()).collect()
+    }
+
+    fn equal_weights(keypairs: &[KeyPair], threshold: u16) -> MultiSigPublicKey {
+        let keys = keypairs.iter().map(|k| (k.public(), 1)).collect();
+        MultiSigPublicKey::new(keys, threshold).unwrap()
+    }
+
+    #[test]
+    fn test_two_of_three() {
+        let keypairs = members(3);
+        let key = equal_weights(&keypairs, 2);
+
+        let multisig = key.sign(b"message", &keypairs[1..]).unwrap();
+        assert!(key.verify(b"message", &multisig));
+        assert!(!key.verify(b"other", &multisig));
+
+        // One signature short of the threshold
+        let multisig = key.sign(b"message", &keypairs[..1]).unwrap();
+        assert!(!key.verify(b"message", &multisig));
+
+        // Repeating a member's signature doesn't add weight
+        let multisig = key.sign(b"message", &[keypairs[0].clone(), keypairs[0].clone()]).unwrap();
+        assert!(!key.verify(b"message", &multisig));
+    }
+
+    #[test]
+    fn test_weighted_keys() {
+        let keypairs = members(3);
+        let key = MultiSigPublicKey::new(vec![
+            (keypairs[0].public(), 2),
+            (keypairs[1].public(), 1),
+            (keypairs[2].public(), 1),
+        ], 2).unwrap();
+
+        assert!(key.verify(b"message", &key.sign(b"message", &keypairs[..1]).unwrap()));
+        assert!(!key.verify(b"message", &key.sign(b"message", &keypairs[2..]).unwrap()));
+
+        let outsider = KeyPair::generate(SignatureScheme::Ed25519).unwrap();
+        assert!(key.sign(b"message", &[outsider]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_multisig_keys() {
+        let keypairs = members(2);
+        let public = |i: usize| keypairs[i].public();
+
+        assert!(MultiSigPublicKey::new(vec![], 1).is_err());
+        assert!(MultiSigPublicKey::new(vec![(public(0), 1), (public(1), 1)], 3).is_err());
+        assert!(MultiSigPublicKey::new(vec![(public(0), 1), (public(0), 1)], 1).is_err());
+        assert!(MultiSigPublicKey::new(vec![(public(0), 0), (public(1), 1)], 1).is_err());
+
+        let nested = PublicKey::MultiSig(equal_weights(&keypairs, 1));
+        assert!(MultiSigPublicKey::new(vec![(nested, 1)], 1).is_err());
+    }
+
+    #[test]
+    fn test_deserialization_validates_key() {
+        let keypairs = members(2);
+        let key = equal_weights(&keypairs, 2);
+        let decoded: MultiSigPublicKey = bcs::from_bytes(&bcs::to_bytes(&key).unwrap()).unwrap();
+        assert_eq!(decoded, key);
+
+        // A zero threshold would accept an empty multi-sig
+        let zero_threshold = RawMultiSigPublicKey {
+            keys: keypairs.iter().map(|k| (k.public(), 1)).collect(),
+            threshold: 0,
+        };
+        assert!(bcs::from_bytes::<MultiSigPublicKey>(&bcs::to_bytes(&zero_threshold).unwrap()).is_err());
+
+        let no_keys = RawMultiSigPublicKey { keys: vec![], threshold: 1 };
+        assert!(bcs::from_bytes::<MultiSigPublicKey>(&bcs::to_bytes(&no_keys).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_multisig_key_pair_is_an_error() {
+        assert!(matches!(
+            KeyPair::generate(SignatureScheme::MultiSig),
+            Err(CryptoError::InvalidScheme(_))
+        ));
+    }
+}
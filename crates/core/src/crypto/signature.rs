@@ -1,4 +1,4 @@
-use super::{CryptoError, CryptoResult, PublicKey, SignatureScheme};
+use super::{CryptoError, CryptoResult, MultiSig, PublicKey, SignatureScheme};
 use ed25519_dalek::Signature as Ed25519Signature;
 use serde::{Serialize, Deserialize};
 use std::fmt;
@@ -10,6 +10,8 @@ pub enum Signature {
     Ed25519(Ed25519Signature),
     /// BLS signature
     BLS(blst::min_sig::Signature),
+    /// Multi-sig member signatures
+    MultiSig(MultiSig),
 }
 
 impl Signature {
@@ -18,6 +20,7 @@ impl Signature {
         match self {
             Self::Ed25519(_) => SignatureScheme::Ed25519,
             Self::BLS(_) => SignatureScheme::BLS,
+            Self::MultiSig(_) => SignatureScheme::MultiSig,
         }
     }
 
@@ -31,6 +34,7 @@ impl Signature {
         match self {
             Self::Ed25519(sig) => sig.to_bytes().to_vec(),
             Self::BLS(sig) => sig.to_bytes().to_vec(),
+            Self::MultiSig(sig) => bcs::to_bytes(sig).expect("Multi-sig is BCS serializable"),
         }
     }
 
@@ -50,6 +54,11 @@ impl Signature {
                     .map_err(|e| CryptoError::InvalidSignature(e.to_string()))?;
                 Ok(Self::BLS(sig))
             }
+            SignatureScheme::MultiSig => {
+                let sig = bcs::from_bytes(bytes)
+                    .map_err(|e| CryptoError::InvalidSignature(e.to_string()))?;
+                Ok(Self::MultiSig(sig))
+            }
         }
     }
 }
@@ -59,6 +68,7 @@ impl fmt::Display for Signature {
         match self {
             Self::Ed25519(sig) => write!(f, "ed25519:{}", hex::encode(sig.to_bytes())),
             Self::BLS(sig) => write!(f, "bls:{}", hex::encode(sig.to_bytes())),
+            Self::MultiSig(_) => write!(f, "multisig:{}", hex::encode(self.to_bytes())),
         }
     }
 }
\ No newline at end of file
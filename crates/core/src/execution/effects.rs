@@ -1,5 +1,6 @@
 use crate::core::{Object, ObjectID};
 use crate::protocol::Event;
+use move_core_types::language_storage::ModuleId;
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 
@@ -10,6 +11,12 @@ pub enum ExecutionStatus {
     Success,
     /// Failure with error message
     Failure { error: String },
+    /// Move abort with its code and the aborting module and function index
+    MoveAbort {
+        module: ModuleId,
+        function: u16,
+        code: u64,
+    },
 }
 
 /// Execution effects
@@ -27,10 +34,19 @@ pub struct ExecutionEffects {
     pub created_objects: HashMap<ObjectID, Object>,
     /// Deleted objects
     pub deleted_objects: Vec<ObjectID>,
+    /// Objects wrapped inside another object; they still exist but are no
+    /// longer independently addressable
+    pub wrapped_objects: Vec<ObjectID>,
+    /// Previously wrapped objects returned to top-level storage
+    pub unwrapped_objects: Vec<ObjectID>,
     /// Events
     pub events: Vec<Event>,
     /// Dependencies
     pub dependencies: Vec<[u8; 32]>,
+    /// Reference gas price set by the transaction, applied when the effects
+    /// are committed
+    #[serde(default)]
+    pub reference_gas_price: Option<u64>,
 }
 
 impl ExecutionEffects {
@@ -43,8 +59,11 @@ impl ExecutionEffects {
             modified_objects: HashMap::new(),
             created_objects: HashMap::new(),
             deleted_objects: Vec::new(),
+            wrapped_objects: Vec::new(),
+            unwrapped_objects: Vec::new(),
             events: Vec::new(),
             dependencies: Vec::new(),
+            reference_gas_price: None,
         }
     }
 
@@ -68,6 +87,16 @@ impl ExecutionEffects {
         self.deleted_objects.push(id);
     }
 
+    /// Add wrapped object
+    pub fn add_wrapped_object(&mut self, id: ObjectID) {
+        self.wrapped_objects.push(id);
+    }
+
+    /// Add unwrapped object
+    pub fn add_unwrapped_object(&mut self, id: ObjectID) {
+        self.unwrapped_objects.push(id);
+    }
+
     /// Add event
     pub fn add_event(&mut self, event: Event) {
         self.events.push(event);
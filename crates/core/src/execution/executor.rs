@@ -1,13 +1,44 @@
 use super::{
-    ExecutionEffects, ExecutionError, ExecutionResult,
-    GasSchedule, GasStatus, TransactionValidator,
+    state_version, ExecutionEffects, ExecutionError, ExecutionResult, ExecutionStatus,
+    GasSchedule, GasStatus, SimulationCache, SimulationCacheConfig, TransactionValidator,
 };
-use crate::core::{Object, ObjectID};
+use crate::core::{Address, Object, ObjectID, ObjectRef, Owner};
+use crate::protocol::CallArg;
 use crate::runtime::{Runtime, RuntimeConfig};
-use crate::storage::Storage;
-use crate::transaction::{Transaction, TransactionData};
+use crate::storage::{ObjectKey, ObjectValue, Storage, StorageWrite};
+use crate::transaction::{SignatureCache, SystemTransaction, Transaction, TransactionData};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+/// How the runtime wrote an object to top-level storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteKind {
+    /// An existing top-level object was mutated
+    Mutate,
+    /// The object is new
+    Create,
+    /// The object was taken out of another object
+    Unwrap,
+}
+
+/// How the runtime removed an object from top-level storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteKind {
+    /// The object was destroyed
+    Delete,
+    /// The object was stored inside another object
+    Wrap,
+}
+
+/// Object changes the runtime made while executing a transaction
+#[derive(Debug, Default)]
+pub struct ObjectChanges {
+    /// Objects written at top level
+    pub written: Vec<(Object, WriteKind)>,
+    /// Objects removed from top level
+    pub deleted: Vec<(ObjectID, DeleteKind)>,
+}
+
 /// Execution context
 pub struct ExecutionContext {
     /// Storage
@@ -20,8 +51,19 @@ pub struct ExecutionContext {
     created_objects: Vec<Object>,
     /// Deleted objects
     deleted_objects: Vec<ObjectID>,
+    /// Objects wrapped inside another object
+    wrapped_objects: Vec<ObjectID>,
+    /// Objects unwrapped back to top-level storage
+    unwrapped_objects: Vec<ObjectID>,
     /// Events
     events: Vec<Event>,
+    /// Object arguments loaded from storage
+    input_objects: Vec<(ObjectRef, ObjectValue)>,
+    /// Objects written by this transaction, read before storage so later
+    /// steps see earlier writes
+    write_buffer: HashMap<ObjectKey, ObjectValue>,
+    /// Reference gas price set by this transaction
+    reference_gas_price: Option<u64>,
 }
 
 impl ExecutionContext {
@@ -37,8 +79,66 @@ impl ExecutionContext {
             modified_objects: Vec::new(),
             created_objects: Vec::new(),
             deleted_objects: Vec::new(),
+            wrapped_objects: Vec::new(),
+            unwrapped_objects: Vec::new(),
             events: Vec::new(),
+            input_objects: Vec::new(),
+            write_buffer: HashMap::new(),
+            reference_gas_price: None,
+        }
+    }
+
+    /// Resolve call arguments to the bytes passed to the VM. Object
+    /// arguments are loaded at their exact version and recorded as inputs.
+    pub fn resolve_arguments(&mut self, arguments: &[CallArg]) -> ExecutionResult<Vec<Vec<u8>>> {
+        arguments.iter()
+            .map(|arg| match arg {
+                CallArg::Pure(bytes) => Ok(bytes.clone()),
+                CallArg::Object(object_ref) => self.load_object(object_ref),
+                CallArg::ObjVec(refs) => {
+                    let objects = refs.iter()
+                        .map(|object_ref| self.load_object(object_ref))
+                        .collect::<ExecutionResult<Vec<_>>>()?;
+                    bcs::to_bytes(&objects)
+                        .map_err(|e| ExecutionError::ExecutionError(e.to_string()))
+                }
+            })
+            .collect()
+    }
+
+    /// Buffer an object written by this transaction
+    pub fn write_object(&mut self, key: ObjectKey, value: ObjectValue) {
+        self.write_buffer.insert(key, value);
+    }
+
+    /// Read an object, preferring this transaction's own writes over storage
+    pub fn read_object(&self, key: &ObjectKey) -> ExecutionResult<Option<ObjectValue>> {
+        if let Some(value) = self.write_buffer.get(key) {
+            return Ok(Some(value.clone()));
         }
+        self.storage.get_object(key)
+            .map_err(|e| ExecutionError::StorageError(e.to_string()))
+    }
+
+    /// Load an object argument
+    fn load_object(&mut self, object_ref: &ObjectRef) -> ExecutionResult<Vec<u8>> {
+        let key = ObjectKey {
+            id: object_ref.id,
+            version: object_ref.version,
+        };
+        let value = self.read_object(&key)?
+            .ok_or_else(|| ExecutionError::ValidationError(
+                format!("Object {:?} not found at version {:?}", object_ref.id, object_ref.version)
+            ))?;
+
+        let data = value.data.clone();
+        self.input_objects.push((*object_ref, value));
+        Ok(data)
+    }
+
+    /// Get object arguments loaded so far
+    pub fn input_objects(&self) -> &[(ObjectRef, ObjectValue)] {
+        &self.input_objects
     }
 
     /// Get gas status
@@ -51,25 +151,170 @@ impl ExecutionContext {
         &mut self.gas_status
     }
 
-    /// Add modified object
-    pub fn add_modified_object(&mut self, object: Object) {
+    /// Check whether an object loaded as an input is immutable
+    fn is_immutable_input(&self, id: &ObjectID) -> bool {
+        self.input_objects.iter().any(|(object_ref, value)| {
+            object_ref.id == *id && value.owner.parse::<Owner>().is_ok_and(|owner| owner.is_immutable())
+        })
+    }
+
+    /// Add modified object. Immutable objects can't be mutated.
+    pub fn add_modified_object(&mut self, object: Object) -> ExecutionResult<()> {
+        if object.owner().is_immutable() || self.is_immutable_input(&object.id()) {
+            return Err(ExecutionError::ImmutableObject(object.id()));
+        }
+        self.buffer_object(&object);
         self.modified_objects.push(object);
+        Ok(())
     }
 
-    /// Add created object
-    pub fn add_created_object(&mut self, object: Object) {
+    /// Add created object, charging its storage gas
+    pub fn add_created_object(&mut self, object: Object) -> ExecutionResult<()> {
+        self.gas_status.charge_object_creation(1)?;
+        self.buffer_object(&object);
         self.created_objects.push(object);
+        Ok(())
     }
 
-    /// Add deleted object
-    pub fn add_deleted_object(&mut self, id: ObjectID) {
+    /// Add deleted object. Immutable objects can't be deleted.
+    pub fn add_deleted_object(&mut self, id: ObjectID) -> ExecutionResult<()> {
+        if self.is_immutable_input(&id) {
+            return Err(ExecutionError::ImmutableObject(id));
+        }
+        self.write_buffer.retain(|key, _| key.id != id);
         self.deleted_objects.push(id);
+        Ok(())
+    }
+
+    /// Buffer `object` so later reads in this transaction see it
+    fn buffer_object(&mut self, object: &Object) {
+        let key = ObjectKey { id: object.id(), version: object.version() };
+        self.write_object(key, object_value(object));
+    }
+
+    /// Storage writes that commit this transaction's object changes:
+    /// every buffered object, and removal of deleted and wrapped ones
+    fn buffered_writes(&self) -> Vec<StorageWrite> {
+        let puts = self.write_buffer.iter()
+            .map(|(key, value)| StorageWrite::PutObject(key.clone(), value.clone()));
+        let deletes = self.deleted_objects.iter()
+            .chain(&self.wrapped_objects)
+            .map(|id| StorageWrite::DeleteObject(ObjectKey::latest(id)));
+        puts.chain(deletes).collect()
+    }
+
+    /// Record an object wrapped inside another. It leaves top-level storage
+    /// but isn't deleted. Rewrapping an object unwrapped earlier in the same
+    /// transaction leaves no net change.
+    pub fn add_wrapped_object(&mut self, id: ObjectID) {
+        self.write_buffer.retain(|key, _| key.id != id);
+        if let Some(index) = self.unwrapped_objects.iter().position(|unwrapped| *unwrapped == id) {
+            self.unwrapped_objects.remove(index);
+        } else {
+            self.wrapped_objects.push(id);
+        }
+    }
+
+    /// Record a wrapped object returned to top-level storage. Unwrapping an
+    /// object wrapped earlier in the same transaction leaves no net change.
+    pub fn add_unwrapped_object(&mut self, id: ObjectID) {
+        if let Some(index) = self.wrapped_objects.iter().position(|wrapped| *wrapped == id) {
+            self.wrapped_objects.remove(index);
+        } else {
+            self.unwrapped_objects.push(id);
+        }
+    }
+
+    /// Record the runtime's object changes. Unwrapped objects are written
+    /// back like modified ones, on top of being recorded as unwrapped.
+    pub fn apply_object_changes(&mut self, changes: ObjectChanges) -> ExecutionResult<()> {
+        for (object, kind) in changes.written {
+            match kind {
+                WriteKind::Mutate => self.add_modified_object(object)?,
+                WriteKind::Create => self.add_created_object(object)?,
+                WriteKind::Unwrap => {
+                    self.add_unwrapped_object(object.id());
+                    self.add_modified_object(object)?;
+                }
+            }
+        }
+        for (id, kind) in changes.deleted {
+            match kind {
+                DeleteKind::Delete => self.add_deleted_object(id)?,
+                DeleteKind::Wrap => {
+                    if self.is_immutable_input(&id) {
+                        return Err(ExecutionError::ImmutableObject(id));
+                    }
+                    self.add_wrapped_object(id);
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Add event
     pub fn add_event(&mut self, event: Event) {
         self.events.push(event);
     }
+
+    /// Set the reference gas price once the transaction commits
+    pub fn set_reference_gas_price(&mut self, price: u64) {
+        self.reference_gas_price = Some(price);
+    }
+
+    /// Turn the context into effects. A failed transaction commits only
+    /// its gas charge; its object changes and events are discarded.
+    fn into_effects(
+        self,
+        transaction_digest: [u8; 32],
+        result: ExecutionResult<()>,
+    ) -> ExecutionEffects {
+        let mut effects = ExecutionEffects::new(transaction_digest);
+        effects.gas_used = self.gas_status.gas_used().value();
+
+        if let Err(e) = result {
+            effects.status = match e {
+                ExecutionError::MoveAbort { module, function, code } => {
+                    ExecutionStatus::MoveAbort { module, function, code }
+                }
+                e => ExecutionStatus::Failure {
+                    error: e.to_string(),
+                },
+            };
+            return effects;
+        }
+
+        // Add modified objects
+        for object in self.modified_objects {
+            effects.add_modified_object(object);
+        }
+
+        // Add created objects
+        for object in self.created_objects {
+            effects.add_created_object(object);
+        }
+
+        // Add deleted objects
+        for id in self.deleted_objects {
+            effects.add_deleted_object(id);
+        }
+
+        // Add wrapped and unwrapped objects
+        for id in self.wrapped_objects {
+            effects.add_wrapped_object(id);
+        }
+        for id in self.unwrapped_objects {
+            effects.add_unwrapped_object(id);
+        }
+
+        // Add events
+        for event in self.events {
+            effects.add_event(event);
+        }
+
+        effects.reference_gas_price = self.reference_gas_price;
+        effects
+    }
 }
 
 /// Transaction executor
@@ -80,6 +325,12 @@ pub struct Executor {
     validator: Arc<TransactionValidator>,
     /// Storage
     storage: Arc<dyn Storage>,
+    /// Senders allowed to issue privileged system transactions
+    system_senders: HashSet<Address>,
+    /// Cache of dry-run effects (optional)
+    simulation_cache: Option<SimulationCache>,
+    /// Maximum events a transaction may emit
+    max_events: usize,
 }
 
 impl Executor {
@@ -88,18 +339,89 @@ impl Executor {
         runtime_config: RuntimeConfig,
         storage: Arc<dyn Storage>,
     ) -> ExecutionResult<Self> {
+        let max_events = runtime_config.execution.max_events;
         let runtime = Runtime::new(runtime_config)
-            .map_err(|e| ExecutionError::ExecutionError(e.to_string()))?;
+            .map_err(|e| ExecutionError::ExecutionError(e.to_string()))?
+            .with_storage(storage.clone());
         
-        let validator = TransactionValidator::new();
+        let mut validator = TransactionValidator::new();
+        validator.set_max_events(max_events);
 
         Ok(Self {
             runtime: Arc::new(runtime),
             validator: Arc::new(validator),
             storage,
+            system_senders: HashSet::new(),
+            simulation_cache: None,
+            max_events,
         })
     }
 
+    /// Share `cache` of verified signatures with the other validators of
+    /// the node
+    pub fn set_signature_cache(&mut self, cache: Arc<SignatureCache>) {
+        let mut validator = TransactionValidator::new().with_signature_cache(cache);
+        validator.set_max_events(self.max_events);
+        self.validator = Arc::new(validator);
+    }
+
+    /// Set the validator and governance addresses allowed to issue
+    /// privileged system transactions
+    pub fn set_system_senders(&mut self, senders: HashSet<Address>) {
+        self.system_senders = senders;
+    }
+
+    /// Cache dry-run effects until the transaction's inputs change or
+    /// `config.ttl` passes
+    pub fn set_simulation_cache(&mut self, config: SimulationCacheConfig) {
+        self.simulation_cache = Some(SimulationCache::new(config));
+    }
+
+    /// Dry-run cache, if enabled
+    pub fn simulation_cache(&self) -> Option<&SimulationCache> {
+        self.simulation_cache.as_ref()
+    }
+
+    /// Simulate a transaction without committing its effects. Repeated
+    /// dry-runs against unchanged inputs are served from the simulation
+    /// cache when it is enabled. System transactions are never cached.
+    pub async fn dry_run(&self, transaction: Transaction) -> ExecutionResult<ExecutionEffects> {
+        let cache = match &self.simulation_cache {
+            Some(cache) if !matches!(transaction.data, TransactionData::System(_)) => cache,
+            _ => return self.simulate_transaction(&transaction, transaction.gas_budget()).await,
+        };
+
+        let digest = transaction.digest();
+        let version = state_version(self.storage.as_ref(), &transaction)?;
+        if let Some(effects) = cache.get(&digest, version) {
+            return Ok(effects);
+        }
+
+        let effects = self.simulate_transaction(&transaction, transaction.gas_budget()).await?;
+        cache.insert(digest, version, effects.clone());
+        Ok(effects)
+    }
+
+    /// Simulate a transaction with `gas_budget` in place of its own. The
+    /// signature isn't checked, so the budget can be overridden without
+    /// invalidating it, and unsigned transactions can be simulated.
+    pub async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+        gas_budget: u64,
+    ) -> ExecutionResult<ExecutionEffects> {
+        self.validator.validate_for_simulation(transaction)?;
+
+        let mut context = ExecutionContext::new(
+            self.storage.clone(),
+            GasSchedule::default(),
+            gas_budget,
+        );
+        let result = self.execute_transaction_impl(transaction, &mut context).await;
+
+        Ok(checked_effects(&self.validator, context, *transaction.digest().as_bytes(), result))
+    }
+
     /// Execute transaction
     pub async fn execute_transaction(
         &self,
@@ -116,36 +438,17 @@ impl Executor {
         );
 
         // Execute transaction
-        let status = match self.execute_transaction_impl(&transaction, &mut context).await {
-            Ok(_) => ExecutionStatus::Success,
-            Err(e) => ExecutionStatus::Failure {
-                error: e.to_string(),
-            },
-        };
+        let result = self.execute_transaction_impl(&transaction, &mut context).await;
+        let writes = context.buffered_writes();
 
         // Create effects
-        let mut effects = ExecutionEffects::new(transaction.digest());
-        effects.status = status;
-        effects.gas_used = context.gas_status.gas_used().value();
+        let effects = checked_effects(&self.validator, context, *transaction.digest().as_bytes(), result);
 
-        // Add modified objects
-        for object in context.modified_objects {
-            effects.add_modified_object(object);
-        }
-
-        // Add created objects
-        for object in context.created_objects {
-            effects.add_created_object(object);
-        }
-
-        // Add deleted objects
-        for id in context.deleted_objects {
-            effects.add_deleted_object(id);
-        }
-
-        // Add events
-        for event in context.events {
-            effects.add_event(event);
+        // Flush the buffered writes once the transaction is known to succeed;
+        // a failed one commits only its gas charge
+        if matches!(effects.status, ExecutionStatus::Success) {
+            self.storage.write_atomic(writes)
+                .map_err(|e| ExecutionError::StorageError(e.to_string()))?;
         }
 
         Ok(effects)
@@ -159,11 +462,496 @@ impl Executor {
     ) -> ExecutionResult<()> {
         match &transaction.data {
             TransactionData::Move(move_tx) => {
-                self.runtime.execute_move_transaction(move_tx, context).await
+                let args = context.resolve_arguments(&move_tx.arguments)?;
+                let changes = self.runtime.execute_move_transaction(move_tx, args, context).await?;
+                context.apply_object_changes(changes)
+            }
+            TransactionData::System(SystemTransaction::SetGasPrice(price)) => {
+                set_gas_price(context, &self.system_senders, transaction.sender, *price)
             }
             TransactionData::System(system_tx) => {
                 self.runtime.execute_system_transaction(system_tx, context).await
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Storage representation of `object`
+fn object_value(object: &Object) -> ObjectValue {
+    ObjectValue {
+        data: object.data().to_vec(),
+        owner: object.owner().to_string(),
+        type_: format!("{:?}", object.type_()),
+        created_at: 0,
+        modified_at: 0,
+    }
+}
+
+/// Turn `context` into effects, failing the transaction if they exceed the
+/// validator's limits. Like any failure, only the gas charge is kept.
+fn checked_effects(
+    validator: &TransactionValidator,
+    context: ExecutionContext,
+    transaction_digest: [u8; 32],
+    result: ExecutionResult<()>,
+) -> ExecutionEffects {
+    let effects = context.into_effects(transaction_digest, result);
+    match validator.validate_effects(&effects) {
+        Ok(()) => effects,
+        Err(e) => ExecutionEffects {
+            status: ExecutionStatus::Failure { error: e.to_string() },
+            gas_used: effects.gas_used,
+            ..ExecutionEffects::new(transaction_digest)
+        },
+    }
+}
+
+/// Record a reference gas price change in the effects if `sender` is
+/// authorized. Storage is only updated when the effects are committed.
+fn set_gas_price(
+    context: &mut ExecutionContext,
+    system_senders: &HashSet<Address>,
+    sender: Address,
+    price: u64,
+) -> ExecutionResult<()> {
+    if !system_senders.contains(&sender) {
+        return Err(ExecutionError::ValidationError(
+            format!("Sender {:?} may not set the gas price", sender)
+        ));
+    }
+
+    context.set_reference_gas_price(price);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::SequenceNumber;
+    use crate::storage::{self, MemStorage};
+
+    #[test]
+    fn test_resolve_object_and_pure_arguments() {
+        let storage = Arc::new(MemStorage::new());
+        let object_ref = ObjectRef::new(ObjectID::random(), SequenceNumber::new(1));
+        storage.put_object(
+            ObjectKey { id: object_ref.id, version: object_ref.version },
+            ObjectValue {
+                data: vec![1, 2, 3],
+                owner: "alice".to_string(),
+                type_: "0x2::coin::Coin<0x2::sui::SUI>".to_string(),
+                created_at: 0,
+                modified_at: 0,
+            },
+        ).unwrap();
+
+        let mut context = ExecutionContext::new(storage, GasSchedule::default(), 1_000);
+        let amount = bcs::to_bytes(&42u64).unwrap();
+        let args = context.resolve_arguments(&[
+            CallArg::Object(object_ref),
+            CallArg::Pure(amount.clone()),
+        ]).unwrap();
+
+        assert_eq!(args, vec![vec![1, 2, 3], amount]);
+        assert_eq!(context.input_objects().len(), 1);
+        assert_eq!(context.input_objects()[0].0, object_ref);
+
+        // Unknown versions are rejected
+        let missing = ObjectRef::new(object_ref.id, SequenceNumber::new(2));
+        assert!(matches!(
+            context.resolve_arguments(&[CallArg::Object(missing)]),
+            Err(ExecutionError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_set_gas_price_requires_system_sender() {
+        let storage = Arc::new(MemStorage::new());
+        let validator = Address::from_bytes([1; 20]);
+        let senders = HashSet::from([validator]);
+
+        let mut context = ExecutionContext::new(storage.clone(), GasSchedule::default(), 1_000);
+        let result = set_gas_price(&mut context, &senders, Address::from_bytes([2; 20]), 10);
+        assert!(matches!(result, Err(ExecutionError::ValidationError(_))));
+        assert_eq!(context.into_effects([0; 32], result).reference_gas_price, None);
+
+        // The change is carried in the effects; storage is untouched until commit
+        let mut context = ExecutionContext::new(storage.clone(), GasSchedule::default(), 1_000);
+        let result = set_gas_price(&mut context, &senders, validator, 10);
+        assert_eq!(context.into_effects([0; 32], result).reference_gas_price, Some(10));
+        assert_eq!(storage::reference_gas_price(storage.as_ref()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_mass_object_creation_aborts() {
+        use crate::core::{Owner, TypeTag};
+
+        let storage = Arc::new(MemStorage::new());
+        let schedule = GasSchedule::default();
+        let per_object = schedule.object_creation_cost.value();
+        let mut context = ExecutionContext::new(storage, schedule, per_object * 10);
+
+        // The budget covers ten objects; the eleventh runs out of gas
+        let result = (0..100).try_for_each(|_| {
+            context.add_created_object(Object::new(
+                ObjectID::random(),
+                Owner::AddressOwner(Address::from_bytes([1; 20])),
+                TypeTag::U64,
+                vec![],
+            ))
+        });
+        assert!(matches!(result, Err(ExecutionError::GasError(_))));
+
+        let effects = context.into_effects([0; 32], result);
+        assert!(matches!(effects.status, ExecutionStatus::Failure { .. }));
+        assert!(effects.created_objects.is_empty());
+        assert_eq!(effects.gas_used, per_object * 10);
+    }
+
+    #[test]
+    fn test_oversized_effects_fail_transaction() {
+        use crate::core::{Owner, TypeTag};
+
+        let mut validator = TransactionValidator::new();
+        validator.set_max_object_size(1024);
+        let context_with = |size: usize| {
+            let mut context = ExecutionContext::new(Arc::new(MemStorage::new()), GasSchedule::default(), 1_000_000);
+            context.add_created_object(Object::new(
+                ObjectID::random(),
+                Owner::AddressOwner(Address::from_bytes([1; 20])),
+                TypeTag::U8,
+                vec![0u8; size],
+            )).unwrap();
+            context
+        };
+
+        let effects = checked_effects(&validator, context_with(16), [0; 32], Ok(()));
+        assert!(matches!(effects.status, ExecutionStatus::Success));
+        assert_eq!(effects.created_objects.len(), 1);
+
+        let effects = checked_effects(&validator, context_with(4096), [0; 32], Ok(()));
+        assert!(matches!(effects.status, ExecutionStatus::Failure { .. }));
+        assert!(effects.created_objects.is_empty());
+        assert!(effects.gas_used > 0);
+    }
+
+    #[test]
+    fn test_events_over_limit_fail_transaction() {
+        use move_core_types::language_storage::TypeTag;
+
+        let mut validator = TransactionValidator::new();
+        validator.set_max_events(2);
+        let context_with = |count: u64| {
+            let mut context = ExecutionContext::new(Arc::new(MemStorage::new()), GasSchedule::default(), 1_000);
+            for sequence in 0..count {
+                context.add_event((vec![], sequence, TypeTag::U64, vec![]));
+            }
+            context
+        };
+
+        let effects = checked_effects(&validator, context_with(2), [0; 32], Ok(()));
+        assert!(matches!(effects.status, ExecutionStatus::Success));
+        assert_eq!(effects.events.len(), 2);
+
+        let effects = checked_effects(&validator, context_with(3), [0; 32], Ok(()));
+        assert!(matches!(effects.status, ExecutionStatus::Failure { .. }));
+        assert!(effects.events.is_empty());
+    }
+
+    #[test]
+    fn test_reads_see_earlier_writes() {
+        let storage = Arc::new(MemStorage::new());
+        let value = |data: Vec<u8>| ObjectValue {
+            data,
+            owner: "alice".to_string(),
+            type_: "0x2::counter::Counter".to_string(),
+            created_at: 0,
+            modified_at: 0,
+        };
+
+        // Storage still holds the value from before this transaction
+        let modified = ObjectRef::new(ObjectID::random(), SequenceNumber::new(1));
+        let modified_key = ObjectKey { id: modified.id, version: modified.version };
+        storage.put_object(modified_key.clone(), value(vec![0])).unwrap();
+
+        let mut context = ExecutionContext::new(storage, GasSchedule::default(), 1_000);
+
+        // Step 1 creates one object and modifies another
+        let created = ObjectRef::new(ObjectID::random(), SequenceNumber::new(0));
+        context.write_object(ObjectKey { id: created.id, version: created.version }, value(vec![1]));
+        context.write_object(modified_key, value(vec![2]));
+
+        // Step 2 reads both back
+        let args = context.resolve_arguments(&[
+            CallArg::Object(created),
+            CallArg::Object(modified),
+        ]).unwrap();
+        assert_eq!(args, vec![vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn test_object_changes_buffered_for_flush() {
+        use crate::core::TypeTag;
+
+        let storage = Arc::new(MemStorage::new());
+        let mut context = ExecutionContext::new(storage.clone(), GasSchedule::default(), 1_000_000);
+        let object = |data: u8| Object::new(
+            ObjectID::random(),
+            Owner::AddressOwner(Address::from_bytes([1; 20])),
+            TypeTag::U8,
+            vec![data],
+        );
+
+        let (kept, dropped) = (object(1), object(2));
+        let kept_key = ObjectKey { id: kept.id(), version: kept.version() };
+        context.add_created_object(kept).unwrap();
+        context.add_created_object(dropped.clone()).unwrap();
+
+        // Created objects are readable before anything reaches storage
+        assert_eq!(context.read_object(&kept_key).unwrap().unwrap().data, vec![1]);
+        assert!(storage.get_object(&kept_key).unwrap().is_none());
+
+        // A later delete drops the buffered write
+        context.add_deleted_object(dropped.id()).unwrap();
+        storage.write_atomic(context.buffered_writes()).unwrap();
+        assert_eq!(storage.get_object(&kept_key).unwrap().unwrap().data, vec![1]);
+        assert!(storage.get_object(&ObjectKey::latest(&dropped.id())).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_move_abort_code_surfaced() {
+        use move_binary_format::errors::{Location, PartialVMError};
+        use move_binary_format::file_format::FunctionDefinitionIndex;
+        use move_core_types::account_address::AccountAddress;
+        use move_core_types::identifier::Identifier;
+        use move_core_types::language_storage::ModuleId;
+        use move_core_types::vm_status::StatusCode;
+
+        let module = ModuleId::new(AccountAddress::TWO, Identifier::new("coin").unwrap());
+        let vm_error = PartialVMError::new(StatusCode::ABORTED)
+            .with_sub_status(42)
+            .at_code_offset(FunctionDefinitionIndex(3), 7)
+            .finish(Location::Module(module.clone()));
+
+        let context = ExecutionContext::new(Arc::new(MemStorage::new()), GasSchedule::default(), 1_000);
+        let effects = context.into_effects([0; 32], Err(vm_error.into()));
+        assert!(matches!(
+            effects.status,
+            ExecutionStatus::MoveAbort { module: m, function: 3, code: 42 } if m == module
+        ));
+
+        // Other VM errors keep the plain failure status
+        let vm_error = PartialVMError::new(StatusCode::OUT_OF_GAS).finish(Location::Undefined);
+        let context = ExecutionContext::new(Arc::new(MemStorage::new()), GasSchedule::default(), 1_000);
+        let effects = context.into_effects([0; 32], Err(vm_error.into()));
+        assert!(matches!(effects.status, ExecutionStatus::Failure { .. }));
+    }
+
+    #[test]
+    fn test_wrapped_object_not_deleted() {
+        let mut context = ExecutionContext::new(Arc::new(MemStorage::new()), GasSchedule::default(), 1_000);
+        let wrapped = ObjectID::random();
+        let unwrapped = ObjectID::random();
+        let transient = ObjectID::random();
+
+        context.add_wrapped_object(wrapped);
+        context.add_unwrapped_object(unwrapped);
+
+        // Wrapped and unwrapped again within the transaction
+        context.add_wrapped_object(transient);
+        context.add_unwrapped_object(transient);
+
+        let effects = context.into_effects([0; 32], Ok(()));
+        assert_eq!(effects.wrapped_objects, vec![wrapped]);
+        assert_eq!(effects.unwrapped_objects, vec![unwrapped]);
+        assert!(effects.deleted_objects.is_empty());
+    }
+
+    #[test]
+    fn test_runtime_object_changes_recorded() {
+        use crate::core::TypeTag;
+
+        let object = |id: ObjectID| Object::new(
+            id,
+            Owner::AddressOwner(Address::from_bytes([1; 20])),
+            TypeTag::U8,
+            vec![],
+        );
+        let (mutated, created, unwrapped, deleted, wrapped) = (
+            ObjectID::random(), ObjectID::random(), ObjectID::random(),
+            ObjectID::random(), ObjectID::random(),
+        );
+
+        let mut context = ExecutionContext::new(Arc::new(MemStorage::new()), GasSchedule::default(), 1_000);
+        context.apply_object_changes(ObjectChanges {
+            written: vec![
+                (object(mutated), WriteKind::Mutate),
+                (object(created), WriteKind::Create),
+                (object(unwrapped), WriteKind::Unwrap),
+            ],
+            deleted: vec![(deleted, DeleteKind::Delete), (wrapped, DeleteKind::Wrap)],
+        }).unwrap();
+
+        let effects = context.into_effects([0; 32], Ok(()));
+        assert!(effects.modified_objects.contains_key(&mutated));
+        assert!(effects.created_objects.contains_key(&created));
+        assert_eq!(effects.unwrapped_objects, vec![unwrapped]);
+        assert!(effects.modified_objects.contains_key(&unwrapped));
+        assert_eq!(effects.deleted_objects, vec![deleted]);
+        assert_eq!(effects.wrapped_objects, vec![wrapped]);
+    }
+
+    #[test]
+    fn test_immutable_object_read_only() {
+        use crate::core::TypeTag;
+
+        let storage = Arc::new(MemStorage::new());
+        let object_ref = ObjectRef::new(ObjectID::random(), SequenceNumber::new(1));
+        storage.put_object(
+            ObjectKey { id: object_ref.id, version: object_ref.version },
+            ObjectValue {
+                data: vec![7],
+                owner: Owner::Immutable.to_string(),
+                type_: "0x2::package::Package".to_string(),
+                created_at: 0,
+                modified_at: 0,
+            },
+        ).unwrap();
+
+        // Reading is fine
+        let mut context = ExecutionContext::new(storage, GasSchedule::default(), 1_000);
+        let args = context.resolve_arguments(&[CallArg::Object(object_ref)]).unwrap();
+        assert_eq!(args, vec![vec![7]]);
+
+        // Mutating or deleting it aborts
+        let mutated = Object::new(
+            object_ref.id,
+            Owner::AddressOwner(Address::from_bytes([1; 20])),
+            TypeTag::U8,
+            vec![8],
+        );
+        assert!(matches!(
+            context.add_modified_object(mutated),
+            Err(ExecutionError::ImmutableObject(id)) if id == object_ref.id
+        ));
+        assert!(matches!(
+            context.add_deleted_object(object_ref.id),
+            Err(ExecutionError::ImmutableObject(_))
+        ));
+
+        let frozen = Object::new(ObjectID::random(), Owner::Immutable, TypeTag::U8, vec![]);
+        assert!(context.add_modified_object(frozen).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_cached_until_inputs_change() {
+        use crate::crypto::{KeyPair, SignatureScheme};
+        use crate::framework::FrameworkConfig;
+        use crate::runtime::execution::ExecutionConfig;
+        use crate::transaction::{MoveFunction, MoveTransaction};
+        use move_binary_format::file_format::Visibility;
+
+        let storage = Arc::new(MemStorage::new());
+        let id = ObjectID::random();
+        let value = ObjectValue {
+            data: vec![1],
+            owner: "alice".to_string(),
+            type_: "0x2::coin::Coin<0x2::sui::SUI>".to_string(),
+            created_at: 0,
+            modified_at: 0,
+        };
+        storage.put_object(ObjectKey { id, version: SequenceNumber::new(1) }, value.clone()).unwrap();
+
+        let config = RuntimeConfig {
+            execution: ExecutionConfig {
+                max_gas_per_tx: 1_000_000,
+                max_events: 256,
+                execution_threads: 1,
+                complexity_budget: 1_000_000,
+            },
+            framework: FrameworkConfig::default(),
+        };
+        let mut executor = Executor::new(config, storage.clone()).unwrap();
+        executor.set_simulation_cache(SimulationCacheConfig::default());
+
+        let mut transaction = Transaction::new(
+            TransactionData::Move(MoveTransaction {
+                module: None,
+                function: Some(MoveFunction {
+                    name: "balance".to_string(),
+                    visibility: Visibility::Public,
+                }),
+                type_arguments: vec![],
+                arguments: vec![CallArg::Object(ObjectRef::new(id, SequenceNumber::new(1)))],
+            }),
+            Address::from_bytes([1; 20]),
+            1_000,
+            1,
+            vec![],
+            0,
+            0,
+            "sui-local".to_string(),
+        );
+        transaction.sign(&KeyPair::generate(SignatureScheme::Ed25519).unwrap());
+
+        // Same state: the second dry-run is served from the cache
+        executor.dry_run(transaction.clone()).await.unwrap();
+        executor.dry_run(transaction.clone()).await.unwrap();
+        let cache = executor.simulation_cache().unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        // A new version of the input recomputes
+        storage.put_object(ObjectKey { id, version: SequenceNumber::new(2) }, value).unwrap();
+        executor.dry_run(transaction.clone()).await.unwrap();
+        let cache = executor.simulation_cache().unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+        // So does a reference gas price change
+        storage::set_reference_gas_price(storage.as_ref(), 1).unwrap();
+        executor.dry_run(transaction).await.unwrap();
+        let cache = executor.simulation_cache().unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+    }
+
+    #[tokio::test]
+    async fn test_system_dry_run_commits_nothing() {
+        use crate::framework::FrameworkConfig;
+        use crate::runtime::execution::ExecutionConfig;
+
+        let storage = Arc::new(MemStorage::new());
+        let config = RuntimeConfig {
+            execution: ExecutionConfig {
+                max_gas_per_tx: 1_000_000,
+                max_events: 256,
+                execution_threads: 1,
+                complexity_budget: 1_000_000,
+            },
+            framework: FrameworkConfig::default(),
+        };
+        let sender = Address::from_bytes([1; 20]);
+        let mut executor = Executor::new(config, storage.clone()).unwrap();
+        executor.set_system_senders(HashSet::from([sender]));
+        executor.set_simulation_cache(SimulationCacheConfig::default());
+
+        let transaction = Transaction::new(
+            TransactionData::System(SystemTransaction::SetGasPrice(10)),
+            sender,
+            1_000,
+            1,
+            vec![],
+            0,
+            0,
+            "sui-local".to_string(),
+        );
+
+        for _ in 0..2 {
+            let effects = executor.dry_run(transaction.clone()).await.unwrap();
+            assert_eq!(effects.reference_gas_price, Some(10));
+        }
+        assert_eq!(storage::reference_gas_price(storage.as_ref()).unwrap(), None);
+
+        // Neither run went through the cache
+        let cache = executor.simulation_cache().unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (0, 0));
+    }
+}
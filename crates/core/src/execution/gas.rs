@@ -1,5 +1,5 @@
 use super::{ExecutionError, ExecutionResult};
-use std::ops::{Add, Sub};
+use std::ops::{Add, Mul, Sub};
 
 /// Gas unit
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -33,6 +33,14 @@ impl Sub for GasUnit {
     }
 }
 
+impl Mul<u64> for GasUnit {
+    type Output = Self;
+
+    fn mul(self, units: u64) -> Self {
+        Self(self.0.saturating_mul(units))
+    }
+}
+
 /// Gas schedule
 #[derive(Debug, Clone)]
 pub struct GasSchedule {
@@ -44,6 +52,11 @@ pub struct GasSchedule {
     pub event_cost: GasUnit,
     /// Cross-contract call cost
     pub cross_contract_call_cost: GasUnit,
+    /// Storage cost per created object, regardless of its size, so
+    /// transactions creating many tiny objects pay for the state they add
+    pub object_creation_cost: GasUnit,
+    /// Cost of verifying a transaction signature
+    pub signature_verification_cost: GasUnit,
 }
 
 impl Default for GasSchedule {
@@ -53,10 +66,20 @@ impl Default for GasSchedule {
             storage_cost: GasUnit::new(10),
             event_cost: GasUnit::new(5),
             cross_contract_call_cost: GasUnit::new(20),
+            object_creation_cost: GasUnit::new(100),
+            signature_verification_cost: GasUnit::new(50),
         }
     }
 }
 
+impl GasSchedule {
+    /// Least gas any transaction costs: verifying its signature plus one
+    /// unit of computation
+    pub fn base_transaction_cost(&self) -> GasUnit {
+        self.signature_verification_cost + self.computation_cost
+    }
+}
+
 /// Gas status
 pub struct GasStatus {
     /// Gas schedule
@@ -65,6 +88,12 @@ pub struct GasStatus {
     limit: GasUnit,
     /// Gas used
     used: GasUnit,
+    /// Part of the gas used paid as a tip to the proposer
+    tip: GasUnit,
+    /// Complexity units execution may use, independent of gas
+    complexity_budget: Option<u64>,
+    /// Complexity units used so far
+    complexity: u64,
 }
 
 impl GasStatus {
@@ -74,9 +103,18 @@ impl GasStatus {
             schedule,
             limit,
             used: GasUnit::new(0),
+            tip: GasUnit::new(0),
+            complexity_budget: None,
+            complexity: 0,
         }
     }
 
+    /// Bound the complexity units execution may use
+    pub fn with_complexity_budget(mut self, budget: u64) -> Self {
+        self.complexity_budget = Some(budget);
+        self
+    }
+
     /// Deduct gas
     pub fn deduct_gas(&mut self, amount: GasUnit) -> ExecutionResult<()> {
         let new_used = self.used + amount;
@@ -107,6 +145,11 @@ impl GasStatus {
         self.deduct_gas(self.schedule.storage_cost * size)
     }
 
+    /// Charge storage for `count` created objects
+    pub fn charge_object_creation(&mut self, count: u64) -> ExecutionResult<()> {
+        self.deduct_gas(self.schedule.object_creation_cost * count)
+    }
+
     /// Charge event
     pub fn charge_event(&mut self) -> ExecutionResult<()> {
         self.deduct_gas(self.schedule.event_cost)
@@ -116,4 +159,84 @@ impl GasStatus {
     pub fn charge_cross_contract_call(&mut self) -> ExecutionResult<()> {
         self.deduct_gas(self.schedule.cross_contract_call_cost)
     }
-}
\ No newline at end of file
+
+    /// Charge the sender's tip. It comes out of the gas budget like any
+    /// other charge but is paid to the proposer.
+    pub fn charge_tip(&mut self, tip: u64) -> ExecutionResult<()> {
+        self.deduct_gas(GasUnit::new(tip))?;
+        self.tip = self.tip + GasUnit::new(tip);
+        Ok(())
+    }
+
+    /// Get tip charged
+    pub fn tip_charged(&self) -> GasUnit {
+        self.tip
+    }
+
+    /// Allocation hook the VM calls for each generic instantiation, with
+    /// the number of type nodes instantiated
+    pub fn charge_instantiation(&mut self, type_nodes: u64) -> ExecutionResult<()> {
+        self.charge_complexity(type_nodes)
+    }
+
+    /// Allocation hook the VM calls as it loads arguments and packs
+    /// vectors, with the number of bytes allocated
+    pub fn charge_allocation(&mut self, bytes: u64) -> ExecutionResult<()> {
+        self.charge_complexity(bytes)
+    }
+
+    /// Get complexity units used
+    pub fn complexity_used(&self) -> u64 {
+        self.complexity
+    }
+
+    /// Fail if the complexity budget was exceeded, including by a charge
+    /// whose error the VM swallowed
+    pub fn check_complexity(&self) -> ExecutionResult<()> {
+        match self.complexity_budget {
+            Some(budget) if self.complexity > budget => {
+                Err(ExecutionError::ComplexityBudgetExceeded { budget })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn charge_complexity(&mut self, units: u64) -> ExecutionResult<()> {
+        self.complexity = self.complexity.saturating_add(units);
+        self.check_complexity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tip_comes_out_of_budget() {
+        let mut status = GasStatus::new(GasSchedule::default(), GasUnit::new(100));
+        status.charge_tip(40).unwrap();
+        assert_eq!(status.tip_charged(), GasUnit::new(40));
+        assert_eq!(status.remaining_gas(), GasUnit::new(60));
+
+        // A tip the remaining budget can't cover is rejected and not recorded
+        assert!(status.charge_tip(61).is_err());
+        assert_eq!(status.tip_charged(), GasUnit::new(40));
+    }
+
+    #[test]
+    fn test_allocation_hooks_enforce_complexity_budget() {
+        let mut status = GasStatus::new(GasSchedule::default(), GasUnit::new(100))
+            .with_complexity_budget(1_000);
+        status.charge_instantiation(3).unwrap();
+        status.charge_allocation(16).unwrap();
+        assert_eq!(status.complexity_used(), 19);
+
+        // A large vector uses no gas but blows the budget
+        assert!(matches!(
+            status.charge_allocation(4_096),
+            Err(ExecutionError::ComplexityBudgetExceeded { budget: 1_000 })
+        ));
+        assert!(status.check_complexity().is_err());
+        assert_eq!(status.gas_used(), GasUnit::new(0));
+    }
+}
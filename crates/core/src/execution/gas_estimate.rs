@@ -0,0 +1,135 @@
+use super::{ExecutionError, ExecutionStatus, Executor};
+use crate::protocol::{ProtocolResult, MAX_GAS_BUDGET};
+use crate::transaction::Transaction;
+
+/// Gas estimation configuration
+#[derive(Debug, Clone)]
+pub struct GasEstimateConfig {
+    /// Safety margin added on top of the simulated gas, in percent
+    pub margin_percent: u64,
+}
+
+impl Default for GasEstimateConfig {
+    fn default() -> Self {
+        Self {
+            margin_percent: 20,
+        }
+    }
+}
+
+/// Estimate a gas budget for a transaction before signing it
+pub async fn estimate_gas(tx: &Transaction, engine: &Executor) -> ProtocolResult<u64> {
+    estimate_gas_with_config(tx, engine, &GasEstimateConfig::default()).await
+}
+
+/// Estimate a gas budget with a custom margin
+///
+/// The transaction is simulated with the maximum budget so the estimate is
+/// not capped by whatever budget the caller filled in. The transaction itself
+/// is left untouched, so a signed one stays valid. Effects are discarded.
+pub async fn estimate_gas_with_config(
+    tx: &Transaction,
+    engine: &Executor,
+    config: &GasEstimateConfig,
+) -> ProtocolResult<u64> {
+    let effects = engine
+        .simulate_transaction(tx, MAX_GAS_BUDGET)
+        .await?;
+
+    match effects.status {
+        ExecutionStatus::Success => {}
+        ExecutionStatus::Failure { error } => return Err(ExecutionError::Aborted(error).into()),
+        ExecutionStatus::MoveAbort { module, function, code } => {
+            return Err(ExecutionError::Aborted(format!(
+                "Move abort {} in {}, function {}",
+                code, module, function
+            )).into());
+        }
+    }
+
+    let margin = effects.gas_used.saturating_mul(config.margin_percent) / 100;
+    Ok(effects.gas_used.saturating_add(margin).min(MAX_GAS_BUDGET))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Address;
+    use crate::crypto::{KeyPair, SignatureScheme};
+    use crate::framework::FrameworkConfig;
+    use crate::runtime::execution::ExecutionConfig;
+    use crate::runtime::RuntimeConfig;
+    use crate::storage::MemStorage;
+    use crate::transaction::{MoveFunction, MoveTransaction, TransactionData};
+    use move_binary_format::file_format::Visibility;
+    use std::sync::Arc;
+
+    fn test_executor() -> Executor {
+        let config = RuntimeConfig {
+            execution: ExecutionConfig {
+                max_gas_per_tx: MAX_GAS_BUDGET,
+                max_events: 256,
+                execution_threads: 1,
+                complexity_budget: 1_000_000,
+            },
+            framework: FrameworkConfig::default(),
+        };
+        Executor::new(config, Arc::new(MemStorage::new())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas_for_function_call() {
+        let mut transaction = Transaction::new(
+            TransactionData::Move(MoveTransaction {
+                module: None,
+                function: Some(MoveFunction {
+                    name: "transfer".to_string(),
+                    visibility: Visibility::Public,
+                }),
+                type_arguments: vec![],
+                arguments: vec![],
+            }),
+            Address::from_bytes([1; 20]),
+            1_000,
+            1,
+            vec![],
+            0,
+            0,
+            "sui-local".to_string(),
+        );
+        transaction.sign(&KeyPair::generate(SignatureScheme::Ed25519).unwrap());
+
+        let estimate = estimate_gas(&transaction, &test_executor()).await.unwrap();
+        assert!(estimate > 0);
+        assert!(estimate < MAX_GAS_BUDGET);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas_ignores_signed_budget() {
+        let mut transaction = Transaction::new(
+            TransactionData::Move(MoveTransaction {
+                module: None,
+                function: Some(MoveFunction {
+                    name: "transfer".to_string(),
+                    visibility: Visibility::Public,
+                }),
+                type_arguments: vec![],
+                arguments: vec![],
+            }),
+            Address::from_bytes([1; 20]),
+            1,
+            1,
+            vec![],
+            0,
+            0,
+            "sui-local".to_string(),
+        );
+        transaction.sign(&KeyPair::generate(SignatureScheme::Ed25519).unwrap());
+
+        // A budget too small to run is no obstacle, and the signature
+        // still covers the transaction afterwards
+        let estimate = estimate_gas(&transaction, &test_executor()).await.unwrap();
+        assert!(estimate > 1);
+        assert!(transaction.verify_signature());
+    }
+}
@@ -3,14 +3,21 @@
 mod effects;
 mod executor;
 mod gas;
+mod gas_estimate;
+mod simulation;
 mod validator;
 
 pub use effects::{ExecutionEffects, ExecutionStatus};
-pub use executor::{Executor, ExecutionContext};
+pub use executor::{DeleteKind, Executor, ExecutionContext, ObjectChanges, WriteKind};
 pub use gas::{GasStatus, GasSchedule, GasUnit};
+pub use gas_estimate::{estimate_gas, estimate_gas_with_config, GasEstimateConfig};
+pub use simulation::{state_version, SimulationCache, SimulationCacheConfig};
 pub use validator::TransactionValidator;
 
+use crate::core::ObjectID;
 use crate::protocol::{ProtocolError, ProtocolResult};
+use move_binary_format::errors::{Location, VMError};
+use move_core_types::{account_address::AccountAddress, language_storage::ModuleId, vm_status::StatusCode};
 
 /// Execution error types
 #[derive(Debug, thiserror::Error)]
@@ -26,6 +33,62 @@ pub enum ExecutionError {
 
     #[error("Storage error: {0}")]
     StorageError(String),
+
+    #[error("Too many events: limit is {max}")]
+    TooManyEvents { max: usize },
+
+    #[error("Complexity budget of {budget} exceeded")]
+    ComplexityBudgetExceeded { budget: u64 },
+
+    #[error("Too many modules at {address}: limit is {max}")]
+    TooManyModules { address: AccountAddress, max: usize },
+
+    #[error("Object {0:?} is immutable")]
+    ImmutableObject(ObjectID),
+
+    #[error("Type argument has more than {max} nodes")]
+    TooManyTypeNodes { max: usize },
+
+    #[error("Execution aborted: {0}")]
+    Aborted(String),
+
+    #[error("Move abort {code} in {module}, function {function}")]
+    MoveAbort { module: ModuleId, function: u16, code: u64 },
 }
 
-pub type ExecutionResult<T> = Result<T, ExecutionError>;
\ No newline at end of file
+pub type ExecutionResult<T> = Result<T, ExecutionError>;
+
+impl From<ProtocolError> for ExecutionError {
+    /// Unwrap execution errors raised by the runtime, so a Move abort keeps
+    /// its code and location
+    fn from(error: ProtocolError) -> Self {
+        match error {
+            ProtocolError::ExecutionError(error) => error,
+            error => ExecutionError::ExecutionError(error.to_string()),
+        }
+    }
+}
+
+impl From<VMError> for ExecutionError {
+    /// Keep the abort code and location of Move aborts; other VM errors
+    /// are reported as text
+    fn from(error: VMError) -> Self {
+        if error.major_status() == StatusCode::ABORTED {
+            if let (Some(code), Location::Module(module)) = (error.sub_status(), error.location()) {
+                let function = error.offsets().first().map_or(0, |(index, _)| index.0);
+                return ExecutionError::MoveAbort {
+                    module: module.clone(),
+                    function,
+                    code,
+                };
+            }
+        }
+        ExecutionError::ExecutionError(error.to_string())
+    }
+}
+
+impl From<VMError> for ProtocolError {
+    fn from(error: VMError) -> Self {
+        ProtocolError::ExecutionError(error.into())
+    }
+}
@@ -0,0 +1,120 @@
+use super::{ExecutionEffects, ExecutionError, ExecutionResult};
+use crate::storage::{self, Storage};
+use crate::transaction::{Transaction, TransactionDigest};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Dry-run cache configuration
+#[derive(Debug, Clone)]
+pub struct SimulationCacheConfig {
+    /// How long a cached dry-run stays valid
+    pub ttl: Duration,
+    /// Maximum number of cached dry-runs
+    pub capacity: usize,
+}
+
+impl Default for SimulationCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(2),
+            capacity: 10_000,
+        }
+    }
+}
+
+/// Short-lived cache of dry-run effects keyed by transaction digest and the
+/// state version of its inputs, so repeated read-only queries against
+/// unchanged state skip execution
+#[derive(Debug)]
+pub struct SimulationCache {
+    /// Configuration
+    config: SimulationCacheConfig,
+    /// Cached effects with their insertion time
+    entries: Mutex<HashMap<(TransactionDigest, u64), (Instant, ExecutionEffects)>>,
+    /// Lookups served from the cache
+    hits: AtomicU64,
+    /// Lookups that had to execute
+    misses: AtomicU64,
+}
+
+impl SimulationCache {
+    /// Create new simulation cache
+    pub fn new(config: SimulationCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cached effects for `digest` at `state_version`, if still fresh
+    pub fn get(&self, digest: &TransactionDigest, state_version: u64) -> Option<ExecutionEffects> {
+        let mut entries = self.entries.lock().unwrap();
+        let key = (*digest, state_version);
+        let cached = match entries.get(&key) {
+            Some((inserted, _)) if inserted.elapsed() >= self.config.ttl => {
+                entries.remove(&key);
+                None
+            }
+            Some((_, effects)) => Some(effects.clone()),
+            None => None,
+        };
+
+        let counter = if cached.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    /// Cache `effects` for `digest` at `state_version`
+    pub fn insert(&self, digest: TransactionDigest, state_version: u64, effects: ExecutionEffects) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.config.capacity {
+            let ttl = self.config.ttl;
+            entries.retain(|_, (inserted, _)| inserted.elapsed() < ttl);
+        }
+        if entries.len() >= self.config.capacity {
+            let oldest = entries.iter()
+                .min_by_key(|(_, (inserted, _))| *inserted)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert((digest, state_version), (Instant::now(), effects));
+    }
+
+    /// Lookups served from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Lookups that had to execute
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+/// Version of the state a transaction reads: a hash of the reference gas
+/// price and the latest version of each of its input objects. Any write to
+/// an input or a gas price change changes it.
+pub fn state_version(storage: &dyn Storage, transaction: &Transaction) -> ExecutionResult<u64> {
+    let mut inputs = transaction.input_objects();
+    inputs.sort_by_key(|id| *id.as_bytes());
+    inputs.dedup();
+
+    let mut hasher = DefaultHasher::new();
+    storage::reference_gas_price(storage)
+        .map_err(|e| ExecutionError::StorageError(e.to_string()))?
+        .hash(&mut hasher);
+    for id in inputs {
+        let version = storage.latest_version(&id)
+            .map_err(|e| ExecutionError::StorageError(e.to_string()))?;
+        (id, version).hash(&mut hasher);
+    }
+    Ok(hasher.finish())
+}
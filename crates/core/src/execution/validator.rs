@@ -1,7 +1,9 @@
-use super::{ExecutionError, ExecutionResult};
-use crate::core::{Object, ObjectID};
-use crate::transaction::{Transaction, TransactionData};
-use crate::storage::Storage;
+use super::{ExecutionEffects, ExecutionError, ExecutionResult};
+use crate::core::{Object, ObjectID, ObjectRef};
+use crate::framework::FrameworkConfig;
+use crate::protocol::CallArg;
+use crate::transaction::{SignatureCache, Transaction, TransactionData};
+use crate::storage::{ObjectValue, Storage};
 use std::sync::Arc;
 
 /// Transaction validator
@@ -14,19 +16,62 @@ pub struct TransactionValidator {
     max_input_objects: usize,
     /// Maximum created objects
     max_created_objects: usize,
+    /// Maximum serialized size of a single object
+    max_object_size: usize,
+    /// Maximum events a transaction may emit
+    max_events: usize,
+    /// Maximum number of call arguments
+    max_arguments: usize,
+    /// Maximum nesting depth of a type argument
+    max_type_argument_depth: usize,
+    /// Cache of already verified signatures
+    signature_cache: Option<Arc<SignatureCache>>,
 }
 
 impl TransactionValidator {
     /// Create new validator
     pub fn new() -> Self {
+        // Argument limits mirror what the framework accepts
+        let framework = FrameworkConfig::default();
         Self {
             max_gas_budget: 1_000_000,
             max_transaction_size: 128 * 1024, // 128KB
             max_input_objects: 2048,
             max_created_objects: 1024,
+            max_object_size: 256 * 1024, // 256KB
+            max_events: 256,
+            max_arguments: framework.max_function_parameters as usize,
+            max_type_argument_depth: framework.max_type_argument_depth as usize,
+            signature_cache: None,
         }
     }
 
+    /// Skip re-verifying signatures already verified through `cache`
+    pub fn with_signature_cache(mut self, cache: Arc<SignatureCache>) -> Self {
+        self.signature_cache = Some(cache);
+        self
+    }
+
+    /// Set maximum number of call arguments
+    pub fn set_max_arguments(&mut self, max_arguments: usize) {
+        self.max_arguments = max_arguments;
+    }
+
+    /// Set maximum type argument nesting depth
+    pub fn set_max_type_argument_depth(&mut self, max_type_argument_depth: usize) {
+        self.max_type_argument_depth = max_type_argument_depth;
+    }
+
+    /// Set maximum object size
+    pub fn set_max_object_size(&mut self, max_object_size: usize) {
+        self.max_object_size = max_object_size;
+    }
+
+    /// Set maximum events per transaction
+    pub fn set_max_events(&mut self, max_events: usize) {
+        self.max_events = max_events;
+    }
+
     /// Validate transaction
     pub fn validate_transaction(
         &self,
@@ -41,6 +86,19 @@ impl TransactionValidator {
         // Validate signature
         self.validate_signature(transaction)?;
 
+        self.validate_contents(transaction)
+    }
+
+    /// Validate a transaction for simulation. The signature and gas budget
+    /// aren't checked: the transaction may be unsigned, and the simulation
+    /// runs with a budget chosen by the caller.
+    pub fn validate_for_simulation(&self, transaction: &Transaction) -> ExecutionResult<()> {
+        self.validate_transaction_size(transaction)?;
+        self.validate_contents(transaction)
+    }
+
+    /// Validate input objects and transaction specific data
+    fn validate_contents(&self, transaction: &Transaction) -> ExecutionResult<()> {
         // Validate input objects
         self.validate_input_objects(transaction)?;
 
@@ -50,13 +108,56 @@ impl TransactionValidator {
                 self.validate_move_transaction(move_tx)?;
             }
             TransactionData::System(system_tx) => {
-                self.validate_system_transaction(system_tx)?;
+                self.validate_system_transaction(transaction, system_tx)?;
             }
         }
 
         Ok(())
     }
 
+    /// Validate execution effects
+    pub fn validate_effects(&self, effects: &ExecutionEffects) -> ExecutionResult<()> {
+        // Events the VM session emitted land here however it collected them
+        if effects.events.len() > self.max_events {
+            return Err(ExecutionError::TooManyEvents { max: self.max_events });
+        }
+
+        if effects.created_objects.len() > self.max_created_objects {
+            return Err(ExecutionError::ValidationError(
+                format!("Too many created objects: {}", effects.created_objects.len())
+            ));
+        }
+
+        // Objects a transaction grows count as much as new ones
+        for (id, object) in effects.created_objects.iter().chain(&effects.modified_objects) {
+            self.validate_object_size(id, object)?;
+        }
+
+        Ok(())
+    }
+
+    /// Validate size of an object as it will be stored
+    fn validate_object_size(&self, id: &ObjectID, object: &Object) -> ExecutionResult<()> {
+        let value = ObjectValue {
+            data: object.data().to_vec(),
+            owner: object.owner().to_string(),
+            type_: format!("{:?}", object.type_()),
+            created_at: 0,
+            modified_at: 0,
+        };
+        let size = bincode::serialize(&value)
+            .map_err(|e| ExecutionError::ValidationError(format!("Serialization error: {}", e)))?
+            .len();
+
+        if size > self.max_object_size {
+            return Err(ExecutionError::ValidationError(
+                format!("Object {:?} too large: {} bytes", id, size)
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Validate transaction size
     fn validate_transaction_size(&self, transaction: &Transaction) -> ExecutionResult<()> {
         let size = bincode::serialize(transaction)
@@ -85,7 +186,11 @@ impl TransactionValidator {
 
     /// Validate signature
     fn validate_signature(&self, transaction: &Transaction) -> ExecutionResult<()> {
-        if !transaction.verify_signature() {
+        let valid = match &self.signature_cache {
+            Some(cache) => cache.verify(transaction),
+            None => transaction.verify_signature(),
+        };
+        if !valid {
             return Err(ExecutionError::ValidationError(
                 "Invalid transaction signature".into()
             ));
@@ -189,13 +294,20 @@ impl TransactionValidator {
     /// Validate type arguments
     fn validate_type_arguments(&self, type_args: &[TypeTag]) -> ExecutionResult<()> {
         for type_arg in type_args {
-            self.validate_type_argument(type_arg)?;
+            self.validate_type_argument(type_arg, 1)?;
         }
         Ok(())
     }
 
-    /// Validate type argument
-    fn validate_type_argument(&self, type_arg: &TypeTag) -> ExecutionResult<()> {
+    /// Validate type argument nested `depth` levels deep
+    fn validate_type_argument(&self, type_arg: &TypeTag, depth: usize) -> ExecutionResult<()> {
+        if depth > self.max_type_argument_depth {
+            return Err(ExecutionError::ValidationError(format!(
+                "Type argument nested deeper than {} levels",
+                self.max_type_argument_depth
+            )));
+        }
+
         match type_arg {
             TypeTag::Struct(struct_tag) => {
                 // Validate struct tag
@@ -211,12 +323,12 @@ impl TransactionValidator {
                 }
                 // Recursively validate type parameters
                 for type_param in &struct_tag.type_params {
-                    self.validate_type_argument(type_param)?;
+                    self.validate_type_argument(type_param, depth + 1)?;
                 }
             }
             TypeTag::Vector(inner) => {
                 // Recursively validate inner type
-                self.validate_type_argument(inner)?;
+                self.validate_type_argument(inner, depth + 1)?;
             }
             _ => {} // Other primitive types are always valid
         }
@@ -224,19 +336,34 @@ impl TransactionValidator {
     }
 
     /// Validate arguments
-    fn validate_arguments(&self, arguments: &[Vec<u8>]) -> ExecutionResult<()> {
+    fn validate_arguments(&self, arguments: &[CallArg]) -> ExecutionResult<()> {
+        if arguments.len() > self.max_arguments {
+            return Err(ExecutionError::ValidationError(format!(
+                "Too many arguments: {} exceeds limit {}",
+                arguments.len(),
+                self.max_arguments
+            )));
+        }
+
+        // Object arguments are checked with the input objects
         for arg in arguments {
-            if arg.len() > self.max_transaction_size {
-                return Err(ExecutionError::ValidationError(
-                    "Argument too large".into()
-                ));
+            if let CallArg::Pure(bytes) = arg {
+                if bytes.len() > self.max_transaction_size {
+                    return Err(ExecutionError::ValidationError(
+                        "Argument too large".into()
+                    ));
+                }
             }
         }
         Ok(())
     }
 
     /// Validate system transaction
-    fn validate_system_transaction(&self, system_tx: &SystemTransaction) -> ExecutionResult<()> {
+    fn validate_system_transaction(
+        &self,
+        transaction: &Transaction,
+        system_tx: &SystemTransaction,
+    ) -> ExecutionResult<()> {
         // Validate system transaction specific rules
         match system_tx {
             SystemTransaction::ChangeEpoch(epoch_change) => {
@@ -245,6 +372,17 @@ impl TransactionValidator {
             SystemTransaction::Genesis(genesis) => {
                 self.validate_genesis(genesis)?;
             }
+            SystemTransaction::SetGasPrice(price) => {
+                if *price == 0 {
+                    return Err(ExecutionError::ValidationError(
+                        "Reference gas price must be positive".into()
+                    ));
+                }
+            }
+            SystemTransaction::RotateKey { .. } => {
+                transaction.verify_key_rotation()
+                    .map_err(|e| ExecutionError::ValidationError(e.to_string()))?;
+            }
         }
 
         Ok(())
@@ -287,4 +425,91 @@ impl TransactionValidator {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Address, Owner, TypeTag};
+
+    fn created_object(size: usize) -> Object {
+        Object::new(
+            ObjectID::random(),
+            Owner::AddressOwner(Address::from_bytes([1; 20])),
+            TypeTag::U8,
+            vec![0u8; size],
+        )
+    }
+
+    #[test]
+    fn test_validate_effects_object_size() {
+        let mut validator = TransactionValidator::new();
+        validator.set_max_object_size(1024);
+
+        let mut effects = ExecutionEffects::new([0; 32]);
+        effects.add_created_object(created_object(16));
+        assert!(validator.validate_effects(&effects).is_ok());
+
+        let oversized = created_object(4096);
+        let oversized_id = oversized.id();
+        effects.add_created_object(oversized);
+
+        match validator.validate_effects(&effects) {
+            Err(ExecutionError::ValidationError(msg)) => {
+                assert!(msg.contains(&format!("{:?}", oversized_id)));
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_effects_grown_object_size() {
+        let mut validator = TransactionValidator::new();
+        validator.set_max_object_size(1024);
+
+        // An existing object grown past the limit is rejected like a new one
+        let existing = created_object(16);
+        let mut effects = ExecutionEffects::new([0; 32]);
+        effects.add_modified_object(existing.clone());
+        assert!(validator.validate_effects(&effects).is_ok());
+
+        let grown = Object::new(existing.id(), existing.owner().clone(), TypeTag::U8, vec![0u8; 4096]);
+        effects.add_modified_object(grown);
+        assert!(matches!(
+            validator.validate_effects(&effects),
+            Err(ExecutionError::ValidationError(msg)) if msg.contains(&format!("{:?}", existing.id()))
+        ));
+    }
+
+    #[test]
+    fn test_too_many_arguments() {
+        let mut validator = TransactionValidator::new();
+        validator.set_max_arguments(2);
+
+        let args = vec![CallArg::Pure(vec![1]); 2];
+        assert!(validator.validate_arguments(&args).is_ok());
+
+        let args = vec![CallArg::Pure(vec![1]); 3];
+        match validator.validate_arguments(&args) {
+            Err(ExecutionError::ValidationError(msg)) => assert!(msg.contains("Too many arguments")),
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_type_argument_too_deep() {
+        let mut validator = TransactionValidator::new();
+        validator.set_max_type_argument_depth(3);
+
+        // vector<vector<u8>> is 3 levels deep
+        let nested = |levels: usize| {
+            (0..levels).fold(TypeTag::U8, |inner, _| TypeTag::Vector(Box::new(inner)))
+        };
+        assert!(validator.validate_type_arguments(&[nested(2)]).is_ok());
+
+        match validator.validate_type_arguments(&[nested(3)]) {
+            Err(ExecutionError::ValidationError(msg)) => assert!(msg.contains("nested deeper")),
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+}
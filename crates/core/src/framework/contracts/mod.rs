@@ -1,6 +1,8 @@
+use super::check_upgrade_compat;
 use crate::core::{Object, ObjectID};
 use crate::protocol::{ProtocolError, ProtocolResult};
 use crate::storage::Storage;
+use crate::transaction::TransactionDigest;
 use move_vm_runtime::session::Session;
 use std::sync::Arc;
 
@@ -12,6 +14,19 @@ pub struct ContractContext<'a> {
     pub session: &'a mut Session<'a>,
     /// Gas meter
     pub gas_meter: &'a mut GasMeter,
+    /// Digest of the transaction being executed
+    pub tx_digest: TransactionDigest,
+    /// Number of objects created so far by this transaction
+    pub creation_index: u64,
+}
+
+impl ContractContext<'_> {
+    /// Next deterministic object ID for this transaction
+    pub fn fresh_object_id(&mut self) -> ObjectID {
+        let id = ObjectID::derive(&self.tx_digest, self.creation_index);
+        self.creation_index += 1;
+        id
+    }
 }
 
 /// Move contract
@@ -93,7 +108,7 @@ impl MoveContract {
         let module_id = verify_module(&module)?;
 
         // Create contract address
-        let address = ObjectID::random();
+        let address = context.fresh_object_id();
 
         // Create initial state
         let state = Object::new_contract_state(address);
@@ -114,6 +129,34 @@ impl MoveContract {
             state,
         })
     }
+
+    /// Upgrade contract module, rejecting incompatible changes
+    pub async fn upgrade(
+        &mut self,
+        module: Vec<u8>,
+        context: &mut ContractContext<'_>,
+    ) -> ProtocolResult<()> {
+        // Verify module
+        verify_module(&module)?;
+
+        // Check compatibility with the current module
+        let old = CompiledModule::deserialize(&self.module)
+            .map_err(|e| ProtocolError::InvalidModule(e.to_string()))?;
+        let new = CompiledModule::deserialize(&module)
+            .map_err(|e| ProtocolError::InvalidModule(e.to_string()))?;
+        check_upgrade_compat(&old, &new)
+            .map_err(|e| ProtocolError::InvalidModule(e.to_string()))?;
+
+        // Publish upgraded module
+        context.session.publish_module(
+            module.clone(),
+            self.address.into(),
+            context.gas_meter,
+        )?;
+
+        self.module = module;
+        Ok(())
+    }
 }
 
 /// Verify Move module
@@ -2,9 +2,11 @@
 
 mod abilities;
 mod contracts;
+mod upgrade;
 
 pub use abilities::{Ability, ObjectCapabilities};
 pub use contracts::{MoveContract, ContractContext};
+pub use upgrade::check_upgrade_compat;
 
 use crate::protocol::{ProtocolError, ProtocolResult};
 
@@ -19,6 +21,9 @@ pub enum FrameworkError {
 
     #[error("Execution error: {0}")]
     ExecutionError(String),
+
+    #[error("Incompatible upgrade: {0}")]
+    UpgradeError(String),
 }
 
 pub type FrameworkResult<T> = Result<T, FrameworkError>;
@@ -0,0 +1,219 @@
+use super::{FrameworkError, FrameworkResult};
+use move_binary_format::access::ModuleAccess;
+use move_binary_format::file_format::{
+    CompiledModule, SignatureToken, StructFieldInformation, Visibility,
+};
+
+/// Check that `new` can replace `old` without breaking existing callers.
+///
+/// Rules:
+/// - the module must keep its address and name
+/// - every struct keeps its abilities, type parameters and field layout
+/// - every public function keeps its signature and stays public
+pub fn check_upgrade_compat(old: &CompiledModule, new: &CompiledModule) -> FrameworkResult<()> {
+    if old.self_id() != new.self_id() {
+        return Err(FrameworkError::UpgradeError(format!(
+            "Module id changed from {} to {}",
+            old.self_id(),
+            new.self_id()
+        )));
+    }
+
+    check_structs(old, new)?;
+    check_functions(old, new)
+}
+
+/// Struct layouts must be preserved
+fn check_structs(old: &CompiledModule, new: &CompiledModule) -> FrameworkResult<()> {
+    for old_def in old.struct_defs() {
+        let old_handle = old.struct_handle_at(old_def.struct_handle);
+        let name = old.identifier_at(old_handle.name);
+
+        let new_def = new.struct_defs().iter()
+            .find(|def| new.identifier_at(new.struct_handle_at(def.struct_handle).name) == name)
+            .ok_or_else(|| FrameworkError::UpgradeError(format!("Struct {} removed", name)))?;
+        let new_handle = new.struct_handle_at(new_def.struct_handle);
+
+        if old_handle.abilities != new_handle.abilities {
+            return Err(FrameworkError::UpgradeError(format!("Struct {} abilities changed", name)));
+        }
+        if old_handle.type_parameters != new_handle.type_parameters {
+            return Err(FrameworkError::UpgradeError(format!(
+                "Struct {} type parameters changed",
+                name
+            )));
+        }
+        if struct_layout(old, &old_def.field_information)
+            != struct_layout(new, &new_def.field_information)
+        {
+            return Err(FrameworkError::UpgradeError(format!("Struct {} layout changed", name)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Public functions must keep their signatures
+fn check_functions(old: &CompiledModule, new: &CompiledModule) -> FrameworkResult<()> {
+    for old_def in old.function_defs() {
+        if old_def.visibility != Visibility::Public {
+            continue;
+        }
+        let old_handle = old.function_handle_at(old_def.function);
+        let name = old.identifier_at(old_handle.name);
+
+        let (new_def, new_handle) = new.function_defs().iter()
+            .map(|def| (def, new.function_handle_at(def.function)))
+            .find(|(_, handle)| new.identifier_at(handle.name) == name)
+            .ok_or_else(|| {
+                FrameworkError::UpgradeError(format!("Public function {} removed", name))
+            })?;
+
+        if new_def.visibility != Visibility::Public {
+            return Err(FrameworkError::UpgradeError(format!(
+                "Public function {} visibility reduced",
+                name
+            )));
+        }
+
+        let old_signature = (
+            &old_handle.type_parameters,
+            signature_names(old, &old.signature_at(old_handle.parameters).0),
+            signature_names(old, &old.signature_at(old_handle.return_).0),
+        );
+        let new_signature = (
+            &new_handle.type_parameters,
+            signature_names(new, &new.signature_at(new_handle.parameters).0),
+            signature_names(new, &new.signature_at(new_handle.return_).0),
+        );
+        if old_signature != new_signature {
+            return Err(FrameworkError::UpgradeError(format!(
+                "Public function {} signature changed",
+                name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Field names and types of a struct, `None` for native structs
+fn struct_layout(
+    module: &CompiledModule,
+    info: &StructFieldInformation,
+) -> Option<Vec<(String, String)>> {
+    match info {
+        StructFieldInformation::Native => None,
+        StructFieldInformation::Declared(fields) => Some(
+            fields.iter()
+                .map(|field| {
+                    (
+                        module.identifier_at(field.name).to_string(),
+                        token_name(module, &field.signature.0),
+                    )
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn signature_names(module: &CompiledModule, tokens: &[SignatureToken]) -> Vec<String> {
+    tokens.iter().map(|token| token_name(module, token)).collect()
+}
+
+/// Render a type with struct handles resolved to fully qualified names, so
+/// types can be compared across modules with different handle tables
+fn token_name(module: &CompiledModule, token: &SignatureToken) -> String {
+    match token {
+        SignatureToken::Vector(inner) => format!("vector<{}>", token_name(module, inner)),
+        SignatureToken::Reference(inner) => format!("&{}", token_name(module, inner)),
+        SignatureToken::MutableReference(inner) => format!("&mut {}", token_name(module, inner)),
+        SignatureToken::Struct(idx) => struct_name(module, *idx),
+        SignatureToken::StructInstantiation(idx, args) => format!(
+            "{}<{}>",
+            struct_name(module, *idx),
+            signature_names(module, args).join(", ")
+        ),
+        other => format!("{:?}", other),
+    }
+}
+
+fn struct_name(
+    module: &CompiledModule,
+    idx: move_binary_format::file_format::StructHandleIndex,
+) -> String {
+    let handle = module.struct_handle_at(idx);
+    let module_handle = module.module_handle_at(handle.module);
+    format!(
+        "{}::{}::{}",
+        module.address_identifier_at(module_handle.address),
+        module.identifier_at(module_handle.name),
+        module.identifier_at(handle.name)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::file_format::{
+        empty_module, Bytecode, CodeUnit, FunctionDefinition, FunctionHandle,
+        FunctionHandleIndex, IdentifierIndex, ModuleHandleIndex, SignatureIndex,
+    };
+    use move_core_types::identifier::Identifier;
+
+    /// Module with the given functions, all `fun f()`
+    fn module_with_functions(functions: &[(&str, Visibility)]) -> CompiledModule {
+        let mut module = empty_module();
+        for (name, visibility) in functions {
+            module.identifiers.push(Identifier::new(*name).unwrap());
+            module.function_handles.push(FunctionHandle {
+                module: ModuleHandleIndex(0),
+                name: IdentifierIndex((module.identifiers.len() - 1) as u16),
+                parameters: SignatureIndex(0),
+                return_: SignatureIndex(0),
+                type_parameters: vec![],
+            });
+            module.function_defs.push(FunctionDefinition {
+                function: FunctionHandleIndex((module.function_handles.len() - 1) as u16),
+                visibility: *visibility,
+                is_entry: false,
+                acquires_global_resources: vec![],
+                code: Some(CodeUnit {
+                    locals: SignatureIndex(0),
+                    code: vec![Bytecode::Ret],
+                }),
+            });
+        }
+        module
+    }
+
+    #[test]
+    fn test_added_function_is_compatible() {
+        let old = module_with_functions(&[("transfer", Visibility::Public)]);
+        let new = module_with_functions(&[
+            ("transfer", Visibility::Public),
+            ("split", Visibility::Public),
+        ]);
+
+        assert!(check_upgrade_compat(&old, &new).is_ok());
+    }
+
+    #[test]
+    fn test_removed_public_function_is_rejected() {
+        let old = module_with_functions(&[
+            ("transfer", Visibility::Public),
+            ("helper", Visibility::Private),
+        ]);
+
+        // Dropping a private function is fine
+        let new = module_with_functions(&[("transfer", Visibility::Public)]);
+        assert!(check_upgrade_compat(&old, &new).is_ok());
+
+        // Dropping a public one breaks callers
+        let new = module_with_functions(&[("helper", Visibility::Private)]);
+        assert!(matches!(
+            check_upgrade_compat(&old, &new),
+            Err(FrameworkError::UpgradeError(_))
+        ));
+    }
+}
@@ -1,5 +1,8 @@
 use super::store::{IndexStore, IndexKey, IndexValue};
+use crate::core::ObjectID;
 use crate::protocol::{ProtocolError, ProtocolResult};
+use crate::storage::ObjectEvent;
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 
 /// Index builder configuration
@@ -9,6 +12,15 @@ pub struct IndexConfig {
     pub max_batch_size: usize,
     /// Index types to build
     pub index_types: Vec<IndexType>,
+    /// Worker tasks used to backfill historical checkpoints
+    pub backfill_workers: usize,
+}
+
+/// Historical checkpoint contents read during backfill
+#[async_trait::async_trait]
+pub trait CheckpointSource: Send + Sync {
+    /// Object events produced by checkpoint `sequence`
+    async fn object_events(&self, sequence: u64) -> ProtocolResult<Vec<ObjectEvent>>;
 }
 
 /// Index types
@@ -108,6 +120,44 @@ impl IndexBuilder {
         Ok(())
     }
 
+    /// Index a created object by its type tag, and drop a deleted one
+    pub async fn index_object_event(&mut self, event: &ObjectEvent) -> ProtocolResult<()> {
+        if !self.config.index_types.contains(&IndexType::Object) {
+            return Ok(());
+        }
+
+        match event {
+            ObjectEvent::Created { object_id, type_, .. } => {
+                let id = parse_object_id(object_id)?;
+                let key = IndexKey::ObjectType {
+                    type_: type_.clone(),
+                    id,
+                };
+                self.add_to_batch(key, IndexValue::ObjectId(id))?;
+                self.add_to_batch(IndexKey::TypeOfObject { id }, IndexValue::ObjectType(type_.clone()))?;
+            }
+            ObjectEvent::Deleted { object_id, .. } => {
+                self.remove_object_type(parse_object_id(object_id)?).await?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Remove a deleted object from the type index
+    async fn remove_object_type(&mut self, id: ObjectID) -> ProtocolResult<()> {
+        // The object may have been created earlier in this batch
+        self.flush_batch().await?;
+
+        let type_key = IndexKey::TypeOfObject { id };
+        if let Some(IndexValue::ObjectType(type_)) = self.store.get(&type_key).await? {
+            self.store.delete(&IndexKey::ObjectType { type_, id }).await?;
+            self.store.delete(&type_key).await?;
+        }
+        Ok(())
+    }
+
     /// Index event
     pub async fn index_event(&mut self, event: &Event) -> ProtocolResult<()> {
         if !self.config.index_types.contains(&IndexType::Event) {
@@ -125,6 +175,97 @@ impl IndexBuilder {
         Ok(())
     }
 
+    /// Index the checkpoints in `range`, split into contiguous partitions
+    /// across `backfill_workers` tasks. Object type entries are written once
+    /// per created object, so workers never race on a key; deletions are
+    /// applied after the workers finish, once the objects they remove have
+    /// been indexed. The cursor only advances over partitions that finished,
+    /// in order, so a retry resumes after the last fully indexed checkpoint.
+    /// A range starting past the checkpoint after the cursor is rejected,
+    /// since advancing the cursor over it would skip the gap.
+    pub async fn backfill(
+        &self,
+        source: Arc<dyn CheckpointSource>,
+        range: RangeInclusive<u64>,
+    ) -> ProtocolResult<Option<u64>> {
+        let previous = self.backfill_cursor().await?;
+        let next = previous.map_or(0, |previous| previous + 1);
+        if *range.start() > next {
+            return Err(ProtocolError::BackfillGap { next, start: *range.start() });
+        }
+        let sequences: Vec<u64> = range.collect();
+        if sequences.is_empty() {
+            return Ok(previous);
+        }
+        let partition_size = sequences.len().div_ceil(self.config.backfill_workers.max(1));
+
+        let mut workers = Vec::new();
+        for partition in sequences.chunks(partition_size) {
+            let partition = partition.to_vec();
+            let config = self.config.clone();
+            let store = self.store.clone();
+            let source = source.clone();
+            workers.push(tokio::spawn(async move {
+                let mut builder = IndexBuilder::new(config, store)?;
+                let mut deleted = Vec::new();
+                for sequence in &partition {
+                    for event in source.object_events(*sequence).await? {
+                        match event {
+                            ObjectEvent::Deleted { .. } => deleted.push(event),
+                            event => builder.index_object_event(&event).await?,
+                        }
+                    }
+                }
+                builder.flush().await?;
+                Ok::<_, ProtocolError>((*partition.last().unwrap(), deleted))
+            }));
+        }
+
+        // Merge the cursor over the leading run of finished partitions
+        let mut cursor = None;
+        let mut deleted = Vec::new();
+        let mut first_error = None;
+        for worker in workers {
+            let result = worker.await
+                .map_err(|e| ProtocolError::SystemError(e.to_string()))
+                .and_then(|result| result);
+            match result {
+                Ok((last, partition_deleted)) if first_error.is_none() => {
+                    cursor = Some(last);
+                    deleted.extend(partition_deleted);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        let mut builder = IndexBuilder::new(self.config.clone(), self.store.clone())?;
+        for event in &deleted {
+            builder.index_object_event(event).await?;
+        }
+
+        if let Some(cursor) = cursor {
+            if previous.is_none_or(|previous| cursor > previous) {
+                self.store.put(IndexKey::BackfillCursor, IndexValue::Checkpoint(cursor)).await?;
+            }
+        }
+
+        match first_error {
+            Some(e) => Err(e),
+            None => self.backfill_cursor().await,
+        }
+    }
+
+    /// Last checkpoint covered by backfill
+    pub async fn backfill_cursor(&self) -> ProtocolResult<Option<u64>> {
+        match self.store.get(&IndexKey::BackfillCursor).await? {
+            Some(IndexValue::Checkpoint(sequence)) => Ok(Some(sequence)),
+            _ => Ok(None),
+        }
+    }
+
     /// Add to batch
     fn add_to_batch(&mut self, key: IndexKey, value: IndexValue) -> ProtocolResult<()> {
         self.batch.push((key, value));
@@ -136,6 +277,11 @@ impl IndexBuilder {
         Ok(())
     }
 
+    /// Write any buffered entries
+    pub async fn flush(&mut self) -> ProtocolResult<()> {
+        self.flush_batch().await
+    }
+
     /// Flush batch
     async fn flush_batch(&mut self) -> ProtocolResult<()> {
         if self.batch.is_empty() {
@@ -147,4 +293,102 @@ impl IndexBuilder {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Parse an object ID carried in an object event
+fn parse_object_id(object_id: &str) -> ProtocolResult<ObjectID> {
+    ObjectID::from_hex(object_id)
+        .map_err(|e| ProtocolError::InvalidTransaction(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{CacheConfig, RocksConfig, StorageConfig};
+    use tempfile::TempDir;
+
+    const TYPES: [&str; 2] = ["0x2::coin::Coin<0x2::sui::SUI>", "0x2::nft::Nft"];
+
+    /// Checkpoint `n` creates one object, alternating between two types
+    struct History;
+
+    #[async_trait::async_trait]
+    impl CheckpointSource for History {
+        async fn object_events(&self, sequence: u64) -> ProtocolResult<Vec<ObjectEvent>> {
+            let mut id = [0u8; 32];
+            id[..8].copy_from_slice(&sequence.to_le_bytes());
+            Ok(vec![ObjectEvent::Created {
+                object_id: ObjectID::from_bytes(id).to_hex(),
+                owner: "alice".to_string(),
+                type_: TYPES[sequence as usize % 2].to_string(),
+            }])
+        }
+    }
+
+    fn test_builder(temp_dir: &TempDir, backfill_workers: usize) -> ProtocolResult<IndexBuilder> {
+        let path = temp_dir.path().to_str().unwrap().to_string();
+        let store = Arc::new(IndexStore::new(StorageConfig {
+            data_dir: path.clone(),
+            cache_config: CacheConfig::default(),
+            rocks_config: RocksConfig { path, ..Default::default() },
+        })?);
+        IndexBuilder::new(
+            IndexConfig { max_batch_size: 8, index_types: vec![IndexType::Object], backfill_workers },
+            store,
+        )
+    }
+
+    async fn ids_by_type(builder: &IndexBuilder, type_: &str) -> ProtocolResult<Vec<ObjectID>> {
+        let mut iter = builder.store.iter_prefix(&IndexKey::object_type_prefix(type_)).await?;
+        let mut ids = Vec::new();
+        while let Some((key, _)) = iter.next().await? {
+            if let IndexKey::ObjectType { id, .. } = key {
+                ids.push(id);
+            }
+        }
+        ids.sort_by_key(|id| *id.as_bytes());
+        Ok(ids)
+    }
+
+    #[tokio::test]
+    async fn test_parallel_backfill_matches_serial() -> ProtocolResult<()> {
+        let serial_dir = TempDir::new().unwrap();
+        let parallel_dir = TempDir::new().unwrap();
+        let serial = test_builder(&serial_dir, 1)?;
+        let parallel = test_builder(&parallel_dir, 4)?;
+
+        assert_eq!(serial.backfill(Arc::new(History), 0..=99).await?, Some(99));
+        assert_eq!(parallel.backfill(Arc::new(History), 0..=99).await?, Some(99));
+
+        for type_ in TYPES {
+            let expected = ids_by_type(&serial, type_).await?;
+            assert_eq!(expected.len(), 50);
+            assert_eq!(ids_by_type(&parallel, type_).await?, expected);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_backfill_rejects_gap_after_cursor() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let builder = test_builder(&temp_dir, 4)?;
+
+        assert!(matches!(
+            builder.backfill(Arc::new(History), 5..=9).await,
+            Err(ProtocolError::BackfillGap { next: 0, start: 5 })
+        ));
+        assert_eq!(builder.backfill(Arc::new(History), 0..=9).await?, Some(9));
+
+        assert!(matches!(
+            builder.backfill(Arc::new(History), 20..=29).await,
+            Err(ProtocolError::BackfillGap { next: 10, start: 20 })
+        ));
+        assert_eq!(builder.backfill_cursor().await?, Some(9));
+
+        assert_eq!(builder.backfill(Arc::new(History), 5..=29).await?, Some(29));
+        assert_eq!(ids_by_type(&builder, TYPES[0]).await?.len(), 15);
+
+        Ok(())
+    }
+}
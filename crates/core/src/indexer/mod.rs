@@ -4,8 +4,8 @@ mod builder;
 mod reader;
 mod store;
 
-pub use builder::{IndexBuilder, IndexConfig};
-pub use reader::{IndexReader, QueryOptions};
+pub use builder::{CheckpointSource, IndexBuilder, IndexConfig, IndexType};
+pub use reader::{IndexReader, Page, QueryOptions};
 pub use store::{IndexStore, IndexKey, IndexValue};
 
 use crate::protocol::{ProtocolError, ProtocolResult};
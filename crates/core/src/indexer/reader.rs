@@ -1,5 +1,5 @@
 use super::store::{IndexStore, IndexKey, IndexValue};
-use crate::protocol::{ProtocolError, ProtocolResult};
+use crate::protocol::{ProtocolError, ProtocolResult, StructTag};
 use std::sync::Arc;
 
 /// Query options
@@ -13,6 +13,15 @@ pub struct QueryOptions {
     pub descending: bool,
 }
 
+/// A page of query results
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    /// Results
+    pub data: Vec<T>,
+    /// Cursor for the next page, if more results remain
+    pub next_cursor: Option<String>,
+}
+
 /// Index reader
 pub struct IndexReader {
     /// Index store
@@ -131,6 +140,47 @@ impl IndexReader {
         Ok(results)
     }
 
+    /// List objects of an exact type, e.g. `0x2::coin::Coin<0x2::sui::SUI>`.
+    /// The cursor is the hex id of the last object on the previous page.
+    pub async fn objects_by_type(
+        &self,
+        type_: &StructTag,
+        options: QueryOptions,
+    ) -> ProtocolResult<Page<ObjectID>> {
+        let prefix = IndexKey::object_type_prefix(&type_.to_string());
+        let limit = options.limit.unwrap_or(usize::MAX);
+
+        // Seek straight to the cursor's entry rather than scanning up to it
+        let cursor = options.cursor.as_deref()
+            .map(ObjectID::from_hex)
+            .transpose()
+            .map_err(|e| ProtocolError::InvalidTransaction(e.to_string()))?;
+        let mut iter = match cursor {
+            Some(id) => {
+                let start = bincode::serialize(&IndexKey::ObjectType { type_: type_.to_string(), id })?;
+                self.store.iter_from(&prefix, &start).await?
+            }
+            None => self.store.iter_prefix(&prefix).await?,
+        };
+        let mut data = Vec::new();
+        let mut next_cursor = None;
+
+        while let Some((_, value)) = iter.next().await? {
+            if let IndexValue::ObjectId(id) = value {
+                if Some(id) == cursor {
+                    continue;
+                }
+                if data.len() == limit {
+                    next_cursor = data.last().map(ObjectID::to_hex);
+                    break;
+                }
+                data.push(id);
+            }
+        }
+
+        Ok(Page { data, next_cursor })
+    }
+
     /// Get events by type
     pub async fn get_events_by_type(
         &self,
@@ -163,4 +213,105 @@ impl IndexReader {
 
         Ok(results)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::{IndexBuilder, IndexConfig, IndexType};
+    use crate::protocol::TypeTag;
+    use crate::storage::test_utils::temp_storage;
+    use crate::storage::ObjectEvent;
+
+    fn coin_type(currency: &str) -> StructTag {
+        StructTag {
+            address: "0x2".to_string(),
+            module: "coin".to_string(),
+            name: "Coin".to_string(),
+            type_args: vec![TypeTag::Struct(StructTag {
+                address: "0x2".to_string(),
+                module: currency.to_lowercase(),
+                name: currency.to_string(),
+                type_args: vec![],
+            })],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_objects_by_type() -> ProtocolResult<()> {
+        let (_temp_dir, config) = temp_storage();
+        let store = Arc::new(IndexStore::new(config)?);
+        let mut builder = IndexBuilder::new(
+            IndexConfig {
+                max_batch_size: 16,
+                index_types: vec![IndexType::Object],
+                backfill_workers: 1,
+            },
+            store.clone(),
+        )?;
+
+        let sui = ObjectID::random();
+        let usdc = ObjectID::random();
+        for (id, currency) in [(sui, "SUI"), (usdc, "USDC")] {
+            builder.index_object_event(&ObjectEvent::Created {
+                object_id: id.to_hex(),
+                owner: "alice".to_string(),
+                type_: coin_type(currency).to_string(),
+            }).await?;
+        }
+        builder.flush().await?;
+
+        let reader = IndexReader::new(store);
+        let options = QueryOptions { limit: None, cursor: None, descending: false };
+        let page = reader.objects_by_type(&coin_type("SUI"), options).await?;
+        assert_eq!(page.data, vec![sui]);
+        assert!(page.next_cursor.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_objects_by_type_pages_and_deletes() -> ProtocolResult<()> {
+        let (_temp_dir, config) = temp_storage();
+        let store = Arc::new(IndexStore::new(config)?);
+        let mut builder = IndexBuilder::new(
+            IndexConfig {
+                max_batch_size: 16,
+                index_types: vec![IndexType::Object],
+                backfill_workers: 1,
+            },
+            store.clone(),
+        )?;
+
+        let mut coins: Vec<ObjectID> = (0..3).map(|_| ObjectID::random()).collect();
+        coins.sort_by_key(|id| *id.as_bytes());
+        for id in &coins {
+            builder.index_object_event(&ObjectEvent::Created {
+                object_id: id.to_hex(),
+                owner: "alice".to_string(),
+                type_: coin_type("SUI").to_string(),
+            }).await?;
+        }
+        builder.flush().await?;
+
+        let reader = IndexReader::new(store);
+        let page = |cursor| QueryOptions { limit: Some(2), cursor, descending: false };
+        let first = reader.objects_by_type(&coin_type("SUI"), page(None)).await?;
+        assert_eq!(first.data, coins[..2]);
+        let second = reader.objects_by_type(&coin_type("SUI"), page(first.next_cursor)).await?;
+        assert_eq!(second.data, coins[2..]);
+        assert!(second.next_cursor.is_none());
+
+        // A deleted object leaves the index
+        builder.index_object_event(&ObjectEvent::Deleted {
+            object_id: coins[1].to_hex(),
+            version: 2,
+        }).await?;
+        builder.flush().await?;
+        let all = QueryOptions { limit: None, cursor: None, descending: false };
+        let page = reader.objects_by_type(&coin_type("SUI"), all).await?;
+        assert_eq!(page.data, vec![coins[0], coins[2]]);
+
+        Ok(())
+    }
+}
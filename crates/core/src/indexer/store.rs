@@ -29,6 +29,17 @@ pub enum IndexKey {
         type_: AddressIndexType,
         timestamp: u64,
     },
+    /// Object type index, keyed on the full type tag string
+    ObjectType {
+        type_: String,
+        id: ObjectID,
+    },
+    /// Last checkpoint covered by backfill
+    BackfillCursor,
+    /// Type an object is indexed under, to find its entry on delete
+    TypeOfObject {
+        id: ObjectID,
+    },
 }
 
 /// Address index types
@@ -55,6 +66,10 @@ pub enum IndexValue {
     ObjectId(ObjectID),
     /// Event
     Event(Event),
+    /// Checkpoint sequence number
+    Checkpoint(u64),
+    /// Full type tag string of an object
+    ObjectType(String),
 }
 
 /// Index store implementation
@@ -128,6 +143,14 @@ impl IndexStore {
         Ok(IndexIterator { inner: iter })
     }
 
+    /// Create iterator over keys with `prefix`, starting at `start`
+    pub async fn iter_from(&self, prefix: &[u8], start: &[u8]) -> ProtocolResult<IndexIterator> {
+        let prefix = prefix.to_vec();
+        let iter = self.storage.iter_from(&self.indexes_cf, start)?
+            .take_while(move |item| !matches!(item, Ok((key, _)) if !key.starts_with(&prefix)));
+        Ok(IndexIterator { inner: Box::new(iter) })
+    }
+
     /// Clear all indexes
     pub async fn clear(&self) -> ProtocolResult<()> {
         let batch = self.storage.batch();
@@ -185,6 +208,18 @@ impl IndexKey {
         bincode::serialize(&prefix).unwrap()
     }
 
+    /// Create object type prefix. The type string is length-prefixed, so
+    /// `Coin<SUI>` never matches `Coin<SUI2>` or `Coin<USDC>`.
+    pub fn object_type_prefix(type_: &str) -> Vec<u8> {
+        let key = IndexKey::ObjectType {
+            type_: type_.to_string(),
+            id: ObjectID::from_bytes([0; 32]),
+        };
+        let mut bytes = bincode::serialize(&key).unwrap();
+        bytes.truncate(bytes.len() - 32);
+        bytes
+    }
+
     /// Create event prefix
     pub fn event_prefix(type_: &str) -> Vec<u8> {
         let prefix = IndexKey::Event {
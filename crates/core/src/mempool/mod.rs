@@ -3,8 +3,8 @@
 mod pool;
 mod prioritizer;
 
-pub use pool::{Mempool, MempoolConfig};
-pub use prioritizer::{Priority, TransactionPrioritizer};
+pub use pool::{gas_price_routes, Mempool, MempoolConfig};
+pub use prioritizer::{OrderingMode, Priority, TransactionPrioritizer};
 
 use crate::protocol::{SignedTransaction, TransactionDigest};
 
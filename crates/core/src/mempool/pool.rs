@@ -1,6 +1,9 @@
-use super::{MempoolError, MempoolResult, Priority, TransactionPrioritizer};
+use super::{MempoolError, MempoolResult, OrderingMode, Priority, TransactionPrioritizer};
+use crate::metrics::MempoolMetrics;
 use crate::protocol::{SignedTransaction, TransactionDigest};
-use std::collections::{HashMap, BTreeMap};
+use crate::utils::current_timestamp_ms;
+use std::collections::{HashMap, BTreeMap, VecDeque};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
@@ -13,6 +16,18 @@ pub struct MempoolConfig {
     pub transaction_timeout: Duration,
     /// Maximum size per account
     pub per_account_limit: usize,
+    /// Interval between expiry scans
+    pub expiry_interval: Duration,
+    /// Expiry metrics (optional)
+    pub metrics: Option<MempoolMetrics>,
+    /// Transaction ordering mode
+    pub ordering_mode: OrderingMode,
+    /// Percentile of recently admitted gas prices suggested to clients
+    pub gas_price_percentile: u8,
+    /// Number of recently admitted gas prices the suggestion is drawn from
+    pub gas_price_window: usize,
+    /// Lowest gas price ever suggested
+    pub gas_price_floor: u64,
 }
 
 impl Default for MempoolConfig {
@@ -21,6 +36,12 @@ impl Default for MempoolConfig {
             capacity: 10_000,
             transaction_timeout: Duration::from_secs(30),
             per_account_limit: 100,
+            expiry_interval: Duration::from_secs(1),
+            metrics: None,
+            ordering_mode: OrderingMode::default(),
+            gas_price_percentile: 50,
+            gas_price_window: 1_000,
+            gas_price_floor: 1,
         }
     }
 }
@@ -33,10 +54,14 @@ pub struct Mempool {
     transactions: RwLock<HashMap<TransactionDigest, TransactionInfo>>,
     /// Priority queue
     priority_queue: RwLock<BTreeMap<Priority, Vec<TransactionDigest>>>,
+    /// Per-sender arrival queues, used instead in `FairQueue` mode
+    sender_queues: RwLock<SenderQueues>,
     /// Per-account transaction count
     account_txs: RwLock<HashMap<String, usize>>,
     /// Transaction prioritizer
     prioritizer: TransactionPrioritizer,
+    /// Gas prices of the most recently admitted transactions, oldest first
+    recent_gas_prices: RwLock<VecDeque<u64>>,
 }
 
 /// Transaction information
@@ -47,15 +72,75 @@ struct TransactionInfo {
     insertion_time: Instant,
 }
 
+/// Per-sender FIFO queues served round-robin
+#[derive(Debug, Default)]
+struct SenderQueues {
+    /// Queued transactions of each sender, in arrival order
+    queues: HashMap<String, VecDeque<(TransactionDigest, Priority)>>,
+    /// Senders with queued transactions, in service order
+    ring: VecDeque<String>,
+}
+
+impl SenderQueues {
+    /// Queue a transaction behind the sender's earlier ones
+    fn push(&mut self, sender: String, digest: TransactionDigest, priority: Priority) {
+        let queue = self.queues.entry(sender.clone()).or_default();
+        if queue.is_empty() {
+            self.ring.push_back(sender);
+        }
+        queue.push_back((digest, priority));
+    }
+
+    /// Drop queue heads that are no longer `live`, and senders left empty
+    fn prune(&mut self, live: impl Fn(&TransactionDigest) -> bool) {
+        let queues = &mut self.queues;
+        self.ring.retain(|sender| {
+            let queue = queues.get_mut(sender).expect("senders in the ring have a queue");
+            while queue.front().is_some_and(|(digest, _)| !live(digest)) {
+                queue.pop_front();
+            }
+            if queue.is_empty() {
+                queues.remove(sender);
+                return false;
+            }
+            true
+        });
+    }
+
+    /// Priority of each sender's oldest transaction, in service order
+    fn heads(&self) -> Vec<Priority> {
+        self.ring.iter()
+            .filter_map(|sender| self.queues.get(sender)?.front().map(|(_, priority)| *priority))
+            .collect()
+    }
+
+    /// Take the oldest transaction of the `index`th sender in the ring,
+    /// moving that sender to the back
+    fn pop(&mut self, index: usize) -> Option<TransactionDigest> {
+        let sender = self.ring.remove(index)?;
+        let queue = self.queues.get_mut(&sender)?;
+        let (digest, _) = queue.pop_front()?;
+        if queue.is_empty() {
+            self.queues.remove(&sender);
+        } else {
+            self.ring.push_back(sender);
+        }
+        Some(digest)
+    }
+}
+
 impl Mempool {
     /// Create new mempool
     pub fn new(config: MempoolConfig) -> Self {
+        let prioritizer = TransactionPrioritizer::with_mode(config.ordering_mode);
         Self {
             config,
             transactions: RwLock::new(HashMap::new()),
             priority_queue: RwLock::new(BTreeMap::new()),
+            sender_queues: RwLock::new(SenderQueues::default()),
             account_txs: RwLock::new(HashMap::new()),
-            prioritizer: TransactionPrioritizer::new(),
+            prioritizer,
+            recent_gas_prices: RwLock::new(VecDeque::new()),
         }
     }
 
@@ -86,6 +171,7 @@ impl Mempool {
 
         // Calculate priority
         let priority = self.prioritizer.calculate_priority(&transaction);
+        let gas_price = transaction.data.gas_price;
 
         // Add transaction
         let info = TransactionInfo {
@@ -95,22 +181,63 @@ impl Mempool {
         };
 
         self.transactions.write().await.insert(digest, info.clone());
-        self.priority_queue.write().await
-            .entry(priority)
-            .or_insert_with(Vec::new)
-            .push(digest);
+        if let OrderingMode::FairQueue { .. } = self.prioritizer.mode() {
+            self.sender_queues.write().await.push(sender, digest, priority);
+        } else {
+            self.priority_queue.write().await
+                .entry(priority)
+                .or_insert_with(Vec::new)
+                .push(digest);
+        }
         *count += 1;
 
+        let mut recent_gas_prices = self.recent_gas_prices.write().await;
+        recent_gas_prices.push_back(gas_price);
+        while recent_gas_prices.len() > self.config.gas_price_window {
+            recent_gas_prices.pop_front();
+        }
+
         Ok(())
     }
 
+    /// Gas price to suggest to clients: the configured percentile of the
+    /// last `gas_price_window` admitted gas prices, whether or not those
+    /// transactions are still pending, or the floor if none were admitted
+    pub async fn suggested_gas_price(&self) -> u64 {
+        let prices: Vec<u64> = self.recent_gas_prices.read().await.iter().copied().collect();
+
+        percentile(prices, self.config.gas_price_percentile)
+            .map_or(self.config.gas_price_floor, |price| price.max(self.config.gas_price_floor))
+    }
+
     /// Get next batch of transactions
     pub async fn get_batch(&self, max_size: usize) -> Vec<SignedTransaction> {
         let mut batch = Vec::new();
-        let mut priority_queue = self.priority_queue.write().await;
         let transactions = self.transactions.read().await;
         let now = Instant::now();
 
+        // Fair ordering takes turns across senders, each served in arrival
+        // order; only the head of each sender's queue is a candidate
+        if let OrderingMode::FairQueue { .. } = self.prioritizer.mode() {
+            let mut queues = self.sender_queues.write().await;
+            while batch.len() < max_size {
+                queues.prune(|digest| {
+                    transactions.get(digest).is_some_and(|info| {
+                        now.duration_since(info.insertion_time) <= self.config.transaction_timeout
+                    })
+                });
+                let Some(next) = self.prioritizer.fair_next(&queues.heads()) else {
+                    break;
+                };
+                if let Some(info) = queues.pop(next).and_then(|digest| transactions.get(&digest)) {
+                    batch.push(info.transaction.clone());
+                }
+            }
+            return batch;
+        }
+
+        let mut priority_queue = self.priority_queue.write().await;
+
         // Collect transactions by priority
         for (_priority, digests) in priority_queue.iter_mut().rev() {
             for digest in digests.drain(..).collect::<Vec<_>>() {
@@ -146,6 +273,47 @@ impl Mempool {
         }
     }
 
+    /// Check whether a transaction is in the mempool
+    pub async fn contains(&self, digest: &TransactionDigest) -> bool {
+        self.transactions.read().await.contains_key(digest)
+    }
+
+    /// Remove transactions whose expiration has passed, returning the count
+    pub async fn expire_transactions(&self) -> usize {
+        let now = current_timestamp_ms();
+        let expired: Vec<_> = self.transactions.read().await
+            .iter()
+            // An expiration of zero means the transaction never expires
+            .filter(|(_, info)| {
+                let expiration = info.transaction.data.expiration;
+                expiration != 0 && expiration <= now
+            })
+            .map(|(digest, _)| digest.clone())
+            .collect();
+
+        self.remove_transactions(&expired).await;
+
+        if let Some(metrics) = &self.config.metrics {
+            metrics.expired_transactions.inc_by(expired.len() as f64);
+        }
+
+        expired.len()
+    }
+
+    /// Start background task expiring transactions every `expiry_interval`
+    pub fn start_expiry_task(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.expiry_interval);
+            loop {
+                interval.tick().await;
+                let expired = self.expire_transactions().await;
+                if expired > 0 {
+                    log::debug!("Expired {} transactions from mempool", expired);
+                }
+            }
+        })
+    }
+
     /// Garbage collect expired transactions
     pub async fn garbage_collect(&self) {
         let now = Instant::now();
@@ -160,4 +328,187 @@ impl Mempool {
 
         self.remove_transactions(&to_remove).await;
     }
-}
\ No newline at end of file
+}
+
+/// Nearest-rank percentile of `values`
+fn percentile(mut values: Vec<u64>, percentile: u8) -> Option<u64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let rank = (values.len() * percentile.min(100) as usize).div_ceil(100);
+    Some(values[rank.saturating_sub(1)])
+}
+
+/// `GET /gas_price` answers with the mempool's suggested gas price
+pub fn gas_price_routes(
+    mempool: Arc<Mempool>,
+) -> impl warp::Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    use warp::Filter;
+
+    warp::path!("gas_price")
+        .and(warp::get())
+        .then(move || {
+            let mempool = mempool.clone();
+            async move {
+                let gas_price = mempool.suggested_gas_price().await;
+                warp::reply::json(&serde_json::json!({ "gas_price": gas_price }))
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::MetricsResult;
+    use crate::crypto::{KeyPair, SignatureScheme};
+    use crate::transaction::test_utils::{signed_transaction, signed_transaction_by};
+
+    fn test_transaction(expiration: u64) -> SignedTransaction {
+        priced_transaction(1, expiration)
+    }
+
+    fn priced_transaction(gas_price: u64, expiration: u64) -> SignedTransaction {
+        signed_transaction(gas_price, 0, expiration)
+    }
+
+    #[tokio::test]
+    async fn test_expiry_task() -> MetricsResult<()> {
+        let registry = prometheus::Registry::new();
+        let metrics = MempoolMetrics::new(&registry)?;
+        let mempool = Arc::new(Mempool::new(MempoolConfig {
+            expiry_interval: Duration::from_millis(10),
+            metrics: Some(metrics.clone()),
+            ..Default::default()
+        }));
+
+        let expired = test_transaction(current_timestamp_ms() - 1_000);
+        let live = test_transaction(0);
+        mempool.add_transaction(expired.clone()).await.unwrap();
+        mempool.add_transaction(live.clone()).await.unwrap();
+
+        let handle = mempool.clone().start_expiry_task();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.abort();
+
+        assert!(!mempool.contains(&expired.digest()).await);
+        assert!(mempool.contains(&live.digest()).await);
+        assert_eq!(metrics.expired_transactions.get(), 1.0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fair_queue_keeps_arrival_order() {
+        let first = priced_transaction(100, 0);
+        let outbid = priced_transaction(101, 0);
+
+        // Price priority lets the single-unit outbid jump ahead
+        let mempool = Mempool::new(MempoolConfig::default());
+        mempool.add_transaction(first.clone()).await.unwrap();
+        mempool.add_transaction(outbid.clone()).await.unwrap();
+        let batch = mempool.get_batch(2).await;
+        assert_eq!(batch[0].digest(), outbid.digest());
+
+        // Within the epsilon the earlier transaction is served first
+        let mempool = Mempool::new(MempoolConfig {
+            ordering_mode: OrderingMode::FairQueue { price_epsilon: 5 },
+            ..Default::default()
+        });
+        mempool.add_transaction(first.clone()).await.unwrap();
+        mempool.add_transaction(outbid.clone()).await.unwrap();
+        let batch = mempool.get_batch(2).await;
+        assert_eq!(batch[0].digest(), first.digest());
+        assert_eq!(batch[1].digest(), outbid.digest());
+
+        // Bids either side of a multiple of the epsilon are no exception
+        let mempool = Mempool::new(MempoolConfig {
+            ordering_mode: OrderingMode::FairQueue { price_epsilon: 5 },
+            ..Default::default()
+        });
+        let below = priced_transaction(107, 0);
+        let above = priced_transaction(108, 0);
+        mempool.add_transaction(below.clone()).await.unwrap();
+        mempool.add_transaction(above.clone()).await.unwrap();
+        let batch = mempool.get_batch(1).await;
+        assert_eq!(batch[0].digest(), below.digest());
+        assert_eq!(mempool.get_batch(1).await[0].digest(), above.digest());
+    }
+
+    #[tokio::test]
+    async fn test_fair_queue_takes_turns_across_senders() {
+        let mempool = Mempool::new(MempoolConfig {
+            ordering_mode: OrderingMode::FairQueue { price_epsilon: 5 },
+            ..Default::default()
+        });
+        let busy = KeyPair::generate(SignatureScheme::Ed25519).unwrap();
+        let busy_txs: Vec<_> = [100, 101, 102]
+            .into_iter()
+            .map(|price| signed_transaction_by(&busy, price, 0, 0))
+            .collect();
+        let other = priced_transaction(100, 0);
+        for transaction in busy_txs.iter().chain([&other]) {
+            mempool.add_transaction(transaction.clone()).await.unwrap();
+        }
+
+        // The later sender is served before the busy sender's second turn
+        let digests: Vec<_> = mempool.get_batch(4).await.iter().map(|tx| tx.digest()).collect();
+        assert_eq!(digests, vec![
+            busy_txs[0].digest(),
+            other.digest(),
+            busy_txs[1].digest(),
+            busy_txs[2].digest(),
+        ]);
+
+        // Removed transactions are skipped and served ones are gone
+        let removed = priced_transaction(100, 0);
+        let kept = priced_transaction(100, 0);
+        mempool.add_transaction(removed.clone()).await.unwrap();
+        mempool.add_transaction(kept.clone()).await.unwrap();
+        mempool.remove_transactions(&[removed.digest()]).await;
+        let batch = mempool.get_batch(4).await;
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].digest(), kept.digest());
+    }
+
+    #[tokio::test]
+    async fn test_suggested_gas_price() {
+        let mempool = Arc::new(Mempool::new(MempoolConfig {
+            gas_price_floor: 5,
+            ..Default::default()
+        }));
+        assert_eq!(mempool.suggested_gas_price().await, 5);
+
+        for price in [40, 10, 30, 50, 20] {
+            mempool.add_transaction(priced_transaction(price, 0)).await.unwrap();
+        }
+        assert_eq!(mempool.suggested_gas_price().await, 30);
+
+        let response = warp::test::request()
+            .path("/gas_price")
+            .reply(&gas_price_routes(mempool.clone()))
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["gas_price"], 30);
+
+        // Draining the pool keeps the recent prices
+        let pending: Vec<_> = mempool.get_batch(5).await.iter().map(|tx| tx.digest()).collect();
+        mempool.remove_transactions(&pending).await;
+        assert_eq!(mempool.suggested_gas_price().await, 30);
+
+        // Only the last `gas_price_window` prices count
+        let windowed = Mempool::new(MempoolConfig { gas_price_window: 3, ..Default::default() });
+        for price in [90, 80, 10, 20, 30] {
+            windowed.add_transaction(priced_transaction(price, 0)).await.unwrap();
+        }
+        assert_eq!(windowed.suggested_gas_price().await, 20);
+
+        assert_eq!(percentile(vec![1, 2, 3], 100), Some(3));
+        assert_eq!(percentile(vec![1, 2, 3], 0), Some(1));
+
+        // Prices below the floor are never suggested
+        let cheap = Mempool::new(MempoolConfig { gas_price_floor: 5, ..Default::default() });
+        cheap.add_transaction(priced_transaction(1, 0)).await.unwrap();
+        assert_eq!(cheap.suggested_gas_price().await, 5);
+    }
+}
@@ -5,32 +5,99 @@ use std::cmp::Ordering;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Priority(u64);
 
+/// How queued transactions are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrderingMode {
+    /// Strictly by effective gas price per byte
+    #[default]
+    PricePriority,
+    /// By effective gas price, but a transaction only overtakes earlier
+    /// ones bidding more than `price_epsilon` below it, so outbidding by a
+    /// single unit does not jump the queue
+    FairQueue {
+        /// Largest price difference served first come first served
+        price_epsilon: u64,
+    },
+}
+
 /// Transaction prioritizer
 pub struct TransactionPrioritizer {
-    // 可以添加配置参数
+    /// Ordering mode
+    mode: OrderingMode,
 }
 
 impl TransactionPrioritizer {
     pub fn new() -> Self {
-        Self {}
+        Self::with_mode(OrderingMode::default())
+    }
+
+    /// Create prioritizer with the given ordering mode
+    pub fn with_mode(mode: OrderingMode) -> Self {
+        Self { mode }
+    }
+
+    /// Ordering mode in use
+    pub fn mode(&self) -> OrderingMode {
+        self.mode
     }
 
     /// Calculate transaction priority based on:
-    /// 1. Gas price
+    /// 1. Gas price plus tip
     /// 2. Transaction size
     /// 3. Account nonce
     /// 4. Dependencies
+    ///
+    /// In `FairQueue` mode the priority is the effective price only; see
+    /// `fair_select` for how close bids are ordered.
     pub fn calculate_priority(&self, transaction: &SignedTransaction) -> Priority {
-        let gas_price = transaction.gas_price();
+        let effective_price = transaction.data.gas_price.saturating_add(transaction.data.tip);
+        if let OrderingMode::FairQueue { .. } = self.mode {
+            return Priority(effective_price);
+        }
+
         let size = transaction.encoded_size();
         
         // 基础优先级计算
-        let base_priority = gas_price.saturating_mul(1_000_000) / size as u64;
+        let base_priority = effective_price.saturating_mul(1_000_000) / size as u64;
         
         // 可以添加更多优先级因素
         Priority(base_priority)
     }
 
+    /// Tip owed to the proposer that includes the transaction
+    pub fn tip(&self, transaction: &SignedTransaction) -> u64 {
+        transaction.data.tip
+    }
+
+    /// Index of the candidate to serve next, given in service order. In
+    /// `FairQueue` mode the first candidate bidding within `price_epsilon`
+    /// of the highest bid goes next, so the exact bids decide and no fixed
+    /// band boundary can be crossed by one unit. Otherwise the highest
+    /// priority goes first.
+    pub fn fair_next(&self, candidates: &[Priority]) -> Option<usize> {
+        let epsilon = match self.mode {
+            OrderingMode::FairQueue { price_epsilon } => price_epsilon,
+            OrderingMode::PricePriority => 0,
+        };
+
+        let highest = candidates.iter().map(|priority| priority.0).max()?;
+        candidates.iter().position(|priority| priority.0 >= highest.saturating_sub(epsilon))
+    }
+
+    /// Take up to `limit` transactions from `queued`, given in arrival
+    /// order, picking each with `fair_next`
+    pub fn fair_select<T>(&self, queued: &mut Vec<(T, Priority)>, limit: usize) -> Vec<T> {
+        let mut selected = Vec::new();
+        while selected.len() < limit {
+            let priorities: Vec<_> = queued.iter().map(|(_, priority)| *priority).collect();
+            let Some(next) = self.fair_next(&priorities) else {
+                break;
+            };
+            selected.push(queued.remove(next).0);
+        }
+        selected
+    }
+
     /// Compare two transactions for ordering
     pub fn compare(
         &self,
@@ -41,4 +108,40 @@ impl TransactionPrioritizer {
         let p2 = self.calculate_priority(tx2);
         p1.cmp(&p2)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::test_utils::signed_transaction;
+
+    fn transaction(gas_price: u64, tip: u64) -> SignedTransaction {
+        signed_transaction(gas_price, tip, 0)
+    }
+
+    #[test]
+    fn test_tip_outranks_gas_price() {
+        let prioritizer = TransactionPrioritizer::new();
+        let tipped = transaction(1, 100);
+        let expensive = transaction(50, 0);
+
+        assert_eq!(prioritizer.compare(&tipped, &expensive), Ordering::Greater);
+        assert_eq!(prioritizer.tip(&tipped), 100);
+        assert_eq!(prioritizer.tip(&expensive), 0);
+    }
+
+    #[test]
+    fn test_fair_queue_orders_exact_bids() {
+        let prioritizer = TransactionPrioritizer::with_mode(OrderingMode::FairQueue { price_epsilon: 9 });
+        let mut queued: Vec<_> = [("first", 19), ("outbid", 20), ("expensive", 30)]
+            .into_iter()
+            .map(|(name, price)| (name, prioritizer.calculate_priority(&transaction(price, 0))))
+            .collect();
+
+        // Outbidding by one unit never overtakes, even across a multiple of
+        // the epsilon; outbidding every earlier bid by more than it does
+        assert_eq!(prioritizer.fair_select(&mut queued, 2), vec!["expensive", "first"]);
+        assert_eq!(prioritizer.fair_select(&mut queued, 2), vec!["outbid"]);
+        assert!(queued.is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+/// Node health used by the liveness and readiness probes
+#[derive(Clone)]
+pub struct HealthState {
+    /// Latest checkpoint executed locally
+    local_checkpoint: Arc<AtomicU64>,
+    /// Highest checkpoint reported by any peer
+    peer_checkpoint: Arc<AtomicU64>,
+    /// Checkpoints the node may trail the network by and still be ready
+    max_checkpoint_lag: u64,
+}
+
+impl HealthState {
+    pub fn new(max_checkpoint_lag: u64) -> Self {
+        Self {
+            local_checkpoint: Arc::new(AtomicU64::new(0)),
+            peer_checkpoint: Arc::new(AtomicU64::new(0)),
+            max_checkpoint_lag,
+        }
+    }
+
+    /// Record a locally executed checkpoint; only the highest is kept, so
+    /// backfilling older checkpoints doesn't lower it
+    pub fn set_local_checkpoint(&self, sequence: u64) {
+        self.local_checkpoint.fetch_max(sequence, Ordering::Relaxed);
+    }
+
+    /// Record a checkpoint a peer reported; only the highest is kept
+    pub fn observe_peer_checkpoint(&self, sequence: u64) {
+        self.peer_checkpoint.fetch_max(sequence, Ordering::Relaxed);
+    }
+
+    /// Checkpoints the node trails the highest known peer by
+    pub fn checkpoint_lag(&self) -> u64 {
+        self.peer_checkpoint.load(Ordering::Relaxed)
+            .saturating_sub(self.local_checkpoint.load(Ordering::Relaxed))
+    }
+
+    /// Whether the node has caught up with the network
+    pub fn is_ready(&self) -> bool {
+        self.checkpoint_lag() <= self.max_checkpoint_lag
+    }
+}
+
+/// `/healthz` answers whenever the process is up; `/readyz` answers 503
+/// until the node is within the configured lag of the network
+pub fn routes(state: HealthState) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let healthz = warp::path!("healthz")
+        .map(|| warp::reply::with_status("ok", StatusCode::OK));
+
+    let readyz = warp::path!("readyz")
+        .map(move || {
+            if state.is_ready() {
+                warp::reply::with_status("ready".to_string(), StatusCode::OK)
+            } else {
+                warp::reply::with_status(
+                    format!("behind by {} checkpoints", state.checkpoint_lag()),
+                    StatusCode::SERVICE_UNAVAILABLE,
+                )
+            }
+        });
+
+    healthz.or(readyz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_readiness_follows_checkpoint_lag() {
+        let state = HealthState::new(5);
+        let routes = routes(state.clone());
+
+        let status = |path: &'static str| {
+            let routes = routes.clone();
+            async move {
+                warp::test::request().path(path).reply(&routes).await.status()
+            }
+        };
+
+        state.set_local_checkpoint(10);
+        state.observe_peer_checkpoint(100);
+        assert_eq!(status("/healthz").await, StatusCode::OK);
+        assert_eq!(status("/readyz").await, StatusCode::SERVICE_UNAVAILABLE);
+
+        // A lower peer report doesn't reset the network's height
+        state.observe_peer_checkpoint(50);
+        state.set_local_checkpoint(96);
+        assert_eq!(status("/readyz").await, StatusCode::OK);
+    }
+}
@@ -1,11 +1,16 @@
-use super::{MetricsError, MetricsResult};
+use super::{health, HealthState, MetricsError, MetricsResult};
 use prometheus::{
     Counter as PrometheusCounter,
+    CounterVec as PrometheusCounterVec,
     Gauge as PrometheusGauge,
+    GaugeVec as PrometheusGaugeVec,
     Histogram as PrometheusHistogram,
+    HistogramOpts,
+    HistogramVec as PrometheusHistogramVec,
     Registry,
     Opts,
 };
+use std::fmt;
 use std::sync::Arc;
 
 /// Metrics configuration
@@ -19,6 +24,10 @@ pub struct MetricsConfig {
     pub push_gateway: Option<String>,
     /// Push interval (seconds)
     pub push_interval: u64,
+    /// Consecutive push failures before logging at error level
+    pub push_failure_threshold: u32,
+    /// Checkpoints the node may trail its peers by and still report ready
+    pub max_checkpoint_lag: u64,
 }
 
 /// Counter metric
@@ -43,6 +52,10 @@ impl Counter {
     pub fn inc_by(&self, v: f64) {
         self.inner.inc_by(v);
     }
+
+    pub fn get(&self) -> f64 {
+        self.inner.get()
+    }
 }
 
 /// Gauge metric
@@ -71,6 +84,10 @@ impl Gauge {
     pub fn dec(&self) {
         self.inner.dec();
     }
+
+    pub fn get(&self) -> f64 {
+        self.inner.get()
+    }
 }
 
 /// Histogram metric
@@ -94,6 +111,93 @@ impl Histogram {
     }
 }
 
+/// Labeled counter metric
+#[derive(Clone)]
+pub struct CounterVec {
+    inner: Arc<PrometheusCounterVec>,
+}
+
+impl CounterVec {
+    pub fn new(name: &str, help: &str, labels: &[&str]) -> MetricsResult<Self> {
+        let counter = PrometheusCounterVec::new(Opts::new(name, help), labels)
+            .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
+        Ok(Self {
+            inner: Arc::new(counter),
+        })
+    }
+
+    pub fn inc(&self, label_values: &[&str]) {
+        self.inner.with_label_values(label_values).inc();
+    }
+
+    pub fn get(&self, label_values: &[&str]) -> f64 {
+        self.inner.with_label_values(label_values).get()
+    }
+}
+
+/// Labeled gauge metric
+#[derive(Clone)]
+pub struct GaugeVec {
+    inner: Arc<PrometheusGaugeVec>,
+}
+
+impl GaugeVec {
+    pub fn new(name: &str, help: &str, labels: &[&str]) -> MetricsResult<Self> {
+        let gauge = PrometheusGaugeVec::new(Opts::new(name, help), labels)
+            .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
+        Ok(Self {
+            inner: Arc::new(gauge),
+        })
+    }
+
+    pub fn set(&self, label_values: &[&str], v: f64) {
+        self.inner.with_label_values(label_values).set(v);
+    }
+
+    pub fn inc(&self, label_values: &[&str]) {
+        self.inner.with_label_values(label_values).inc();
+    }
+
+    pub fn dec(&self, label_values: &[&str]) {
+        self.inner.with_label_values(label_values).dec();
+    }
+
+    pub fn get(&self, label_values: &[&str]) -> f64 {
+        self.inner.with_label_values(label_values).get()
+    }
+}
+
+/// Labeled histogram metric
+#[derive(Clone)]
+pub struct HistogramVec {
+    inner: Arc<PrometheusHistogramVec>,
+}
+
+impl HistogramVec {
+    pub fn new(
+        name: &str,
+        help: &str,
+        labels: &[&str],
+        buckets: Vec<f64>,
+    ) -> MetricsResult<Self> {
+        let histogram = PrometheusHistogramVec::new(
+            HistogramOpts::new(name, help).buckets(buckets),
+            labels,
+        ).map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
+        Ok(Self {
+            inner: Arc::new(histogram),
+        })
+    }
+
+    pub fn observe(&self, label_values: &[&str], v: f64) {
+        self.inner.with_label_values(label_values).observe(v);
+    }
+
+    pub fn sample_count(&self, label_values: &[&str]) -> u64 {
+        self.inner.with_label_values(label_values).get_sample_count()
+    }
+}
+
 /// Metrics system
 pub struct Metrics {
     /// Configuration
@@ -108,6 +212,12 @@ pub struct Metrics {
     pub network: NetworkMetrics,
     /// Storage metrics
     pub storage: StorageMetrics,
+    /// Mempool metrics
+    pub mempool: MempoolMetrics,
+    /// Push client metrics
+    pub push: PushMetrics,
+    /// Health state behind the probe endpoints
+    pub health: HealthState,
 }
 
 impl Metrics {
@@ -119,6 +229,9 @@ impl Metrics {
         let consensus = ConsensusMetrics::new(&registry)?;
         let network = NetworkMetrics::new(&registry)?;
         let storage = StorageMetrics::new(&registry)?;
+        let mempool = MempoolMetrics::new(&registry)?;
+        let push = PushMetrics::new(&registry)?;
+        let health = HealthState::new(config.max_checkpoint_lag);
 
         Ok(Self {
             config,
@@ -127,10 +240,83 @@ impl Metrics {
             consensus,
             network,
             storage,
+            mempool,
+            push,
+            health,
         })
     }
 
-    /// Start metrics server
+    /// Gather all registered metric families
+    pub fn gather(&self) -> Vec<prometheus::proto::MetricFamily> {
+        self.registry.gather()
+    }
+
+    /// Record a processed transaction
+    pub fn record_tx_processed(&self) {
+        self.transactions.total_transactions.inc();
+    }
+
+    /// Record a processed transaction by type
+    pub fn record_tx_type(&self, tx_type: &str) {
+        self.transactions.transactions_by_type.inc(&[tx_type]);
+    }
+
+    /// Observe transaction processing time (seconds)
+    pub fn observe_tx_processing_time(&self, duration: f64) {
+        self.transactions.transaction_latency.observe(duration);
+    }
+
+    /// Set mempool size
+    pub fn set_mempool_size(&self, size: usize) {
+        self.transactions.pending_transactions.set(size as f64);
+    }
+
+    /// Record a consensus round
+    pub fn record_consensus_round(&self) {
+        self.consensus.consensus_rounds.inc();
+    }
+
+    /// Set active validator count
+    pub fn set_validator_count(&self, count: usize) {
+        self.consensus.active_validators.set(count as f64);
+    }
+
+    /// Observe consensus round latency (seconds)
+    pub fn observe_consensus_latency(&self, duration: f64) {
+        self.consensus.consensus_latency.observe(duration);
+    }
+
+    /// Set connected peer count
+    pub fn set_peer_count(&self, count: usize) {
+        self.network.connected_peers.set(count as f64);
+    }
+
+    /// Record a network message by type
+    pub fn record_network_message(&self, message_type: &str) {
+        self.network.network_messages.inc(&[message_type]);
+    }
+
+    /// Set bandwidth usage by direction
+    pub fn set_bandwidth_usage(&self, direction: &str, bytes: u64) {
+        self.network.bandwidth_usage.set(&[direction], bytes as f64);
+    }
+
+    /// Set object count
+    pub fn set_object_count(&self, count: usize) {
+        self.storage.total_objects.set(count as f64);
+    }
+
+    /// Set storage size by type
+    pub fn set_storage_size(&self, storage_type: &str, bytes: u64) {
+        self.storage.storage_size.set(&[storage_type], bytes as f64);
+    }
+
+    /// Record a storage operation against a column family
+    pub fn record_storage_operation(&self, operation: &str, cf: &str) {
+        self.storage.storage_operations.inc(&[operation, cf]);
+    }
+
+    /// Start metrics server, which also serves the health probes
     pub async fn start_server(&self) -> MetricsResult<()> {
         use warp::Filter;
 
@@ -147,7 +333,8 @@ impl Metrics {
         let addr = self.config.listen_address.parse()
             .map_err(|e| MetricsError::ExportError(e.to_string()))?;
 
-        tokio::spawn(warp::serve(metrics).run(addr));
+        let routes = metrics.or(health::routes(self.health.clone()));
+        tokio::spawn(warp::serve(routes).run(addr));
 
         Ok(())
     }
@@ -158,28 +345,16 @@ impl Metrics {
             let gateway = gateway.clone();
             let registry = self.registry.clone();
             let interval = self.config.push_interval;
+            let threshold = self.config.push_failure_threshold;
+            let push = self.push.clone();
 
             tokio::spawn(async move {
                 let client = reqwest::Client::new();
+                let mut consecutive_failures = 0u32;
                 loop {
                     tokio::time::sleep(tokio::time::Duration::from_secs(interval)).await;
-                    
-                    let metrics = {
-                        use prometheus::Encoder;
-                        let encoder = prometheus::TextEncoder::new();
-                        let mut buffer = Vec::new();
-                        encoder.encode(&registry.gather(), &mut buffer)
-                            .expect("Failed to encode metrics");
-                        buffer
-                    };
-
-                    if let Err(e) = client.post(&gateway)
-                        .body(metrics)
-                        .send()
-                        .await
-                    {
-                        log::error!("Failed to push metrics: {}", e);
-                    }
+                    push_once(&client, &gateway, &registry, &push, &mut consecutive_failures, threshold)
+                        .await;
                 }
             });
         }
@@ -188,10 +363,65 @@ impl Metrics {
     }
 }
 
+/// Push the registry to the gateway once, recording the outcome
+async fn push_once(
+    client: &reqwest::Client,
+    gateway: &str,
+    registry: &Registry,
+    push: &PushMetrics,
+    consecutive_failures: &mut u32,
+    threshold: u32,
+) {
+    match push_registry(client, gateway, registry).await {
+        Ok(()) => {
+            *consecutive_failures = 0;
+            push.last_success_timestamp.set(crate::utils::current_timestamp() as f64);
+        }
+        Err(e) => {
+            *consecutive_failures += 1;
+            push.failures_total.inc();
+            if *consecutive_failures >= threshold {
+                log::error!(
+                    "Failed to push metrics ({} consecutive failures): {}",
+                    consecutive_failures, e
+                );
+            } else {
+                log::warn!("Failed to push metrics: {}", e);
+            }
+        }
+    }
+}
+
+/// Encode the registry and post it to the gateway
+async fn push_registry(
+    client: &reqwest::Client,
+    gateway: &str,
+    registry: &Registry,
+) -> MetricsResult<()> {
+    let metrics = {
+        use prometheus::Encoder;
+        let encoder = prometheus::TextEncoder::new();
+        let mut buffer = Vec::new();
+        encoder.encode(&registry.gather(), &mut buffer)
+            .map_err(|e| MetricsError::ExportError(e.to_string()))?;
+        buffer
+    };
+
+    client.post(gateway)
+        .body(metrics)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| MetricsError::ExportError(e.to_string()))?;
+
+    Ok(())
+}
+
 /// Transaction metrics
 #[derive(Clone)]
 pub struct TransactionMetrics {
     pub total_transactions: Counter,
+    pub transactions_by_type: CounterVec,
     pub pending_transactions: Gauge,
     pub transaction_latency: Histogram,
 }
@@ -199,6 +429,11 @@ pub struct TransactionMetrics {
 impl TransactionMetrics {
     fn new(registry: &Registry) -> MetricsResult<Self> {
         let total_transactions = Counter::new("total_transactions", "Total transactions processed")?;
+        let transactions_by_type = CounterVec::new(
+            "transactions_by_type",
+            "Transactions processed by type",
+            &["type"],
+        )?;
         let pending_transactions = Gauge::new("pending_transactions", "Pending transactions")?;
         let transaction_latency = Histogram::new(
             "transaction_latency",
@@ -208,6 +443,8 @@ impl TransactionMetrics {
 
         registry.register(Box::new(total_transactions.inner.clone()))
             .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
+        registry.register(Box::new(transactions_by_type.inner.clone()))
+            .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
         registry.register(Box::new(pending_transactions.inner.clone()))
             .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
         registry.register(Box::new(transaction_latency.inner.clone()))
@@ -215,6 +452,7 @@ impl TransactionMetrics {
 
         Ok(Self {
             total_transactions,
+            transactions_by_type,
             pending_transactions,
             transaction_latency,
         })
@@ -258,14 +496,24 @@ impl ConsensusMetrics {
 #[derive(Clone)]
 pub struct NetworkMetrics {
     pub connected_peers: Gauge,
-    pub network_messages: Counter,
+    pub network_messages: CounterVec,
+    pub bandwidth_usage: GaugeVec,
     pub message_latency: Histogram,
 }
 
 impl NetworkMetrics {
     fn new(registry: &Registry) -> MetricsResult<Self> {
         let connected_peers = Gauge::new("connected_peers", "Connected peers")?;
-        let network_messages = Counter::new("network_messages", "Total network messages")?;
+        let network_messages = CounterVec::new(
+            "network_messages",
+            "Total network messages",
+            &["type"],
+        )?;
+        let bandwidth_usage = GaugeVec::new(
+            "bandwidth_usage",
+            "Network bandwidth usage in bytes",
+            &["direction"],
+        )?;
         let message_latency = Histogram::new(
             "message_latency",
             "Network message latency",
@@ -276,37 +524,61 @@ impl NetworkMetrics {
             .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
         registry.register(Box::new(network_messages.inner.clone()))
             .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
+        registry.register(Box::new(bandwidth_usage.inner.clone()))
+            .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
         registry.register(Box::new(message_latency.inner.clone()))
             .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
 
         Ok(Self {
             connected_peers,
             network_messages,
+            bandwidth_usage,
             message_latency,
         })
     }
 }
 
-/// Storage metrics
+/// Storage metrics, labeled by operation and column family
 #[derive(Clone)]
 pub struct StorageMetrics {
     pub total_objects: Gauge,
-    pub storage_operations: Counter,
-    pub operation_latency: Histogram,
+    pub objects_by_type: GaugeVec,
+    pub storage_size: GaugeVec,
+    pub storage_operations: CounterVec,
+    pub operation_latency: HistogramVec,
 }
 
 impl StorageMetrics {
-    fn new(registry: &Registry) -> MetricsResult<Self> {
+    pub fn new(registry: &Registry) -> MetricsResult<Self> {
         let total_objects = Gauge::new("total_objects", "Total objects in storage")?;
-        let storage_operations = Counter::new("storage_operations", "Total storage operations")?;
-        let operation_latency = Histogram::new(
+        let objects_by_type = GaugeVec::new(
+            "objects_by_type",
+            "Live objects in storage by type tag",
+            &["type"],
+        )?;
+        let storage_size = GaugeVec::new(
+            "storage_size",
+            "Storage size in bytes",
+            &["type"],
+        )?;
+        let storage_operations = CounterVec::new(
+            "storage_operations",
+            "Total storage operations",
+            &["operation", "cf"],
+        )?;
+        let operation_latency = HistogramVec::new(
             "operation_latency",
             "Storage operation latency",
-            vec![0.001, 0.01, 0.1, 1.0],
+            &["operation", "cf"],
+            vec![0.0001, 0.001, 0.01, 0.1, 1.0],
         )?;
 
         registry.register(Box::new(total_objects.inner.clone()))
             .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
+        registry.register(Box::new(objects_by_type.inner.clone()))
+            .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
+        registry.register(Box::new(storage_size.inner.clone()))
+            .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
         registry.register(Box::new(storage_operations.inner.clone()))
             .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
         registry.register(Box::new(operation_latency.inner.clone()))
@@ -314,8 +586,132 @@ impl StorageMetrics {
 
         Ok(Self {
             total_objects,
+            objects_by_type,
+            storage_size,
             storage_operations,
             operation_latency,
         })
     }
-}
\ No newline at end of file
+}
+
+/// Mempool metrics
+#[derive(Clone)]
+pub struct MempoolMetrics {
+    pub expired_transactions: Counter,
+}
+
+impl MempoolMetrics {
+    pub fn new(registry: &Registry) -> MetricsResult<Self> {
+        let expired_transactions = Counter::new(
+            "mempool_expired_transactions",
+            "Transactions removed from the mempool after expiring",
+        )?;
+
+        registry.register(Box::new(expired_transactions.inner.clone()))
+            .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
+
+        Ok(Self {
+            expired_transactions,
+        })
+    }
+}
+
+/// Push client metrics
+#[derive(Clone)]
+pub struct PushMetrics {
+    pub last_success_timestamp: Gauge,
+    pub failures_total: Counter,
+}
+
+impl PushMetrics {
+    fn new(registry: &Registry) -> MetricsResult<Self> {
+        let last_success_timestamp = Gauge::new(
+            "metrics_push_last_success_timestamp",
+            "Unix timestamp of the last successful metrics push",
+        )?;
+        let failures_total = Counter::new(
+            "metrics_push_failures_total",
+            "Total failed metrics pushes",
+        )?;
+
+        registry.register(Box::new(last_success_timestamp.inner.clone()))
+            .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
+        registry.register(Box::new(failures_total.inner.clone()))
+            .map_err(|e| MetricsError::RegistrationError(e.to_string()))?;
+
+        Ok(Self {
+            last_success_timestamp,
+            failures_total,
+        })
+    }
+}
+
+impl fmt::Debug for StorageMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StorageMetrics").finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for MempoolMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MempoolMetrics").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_push_failure_counter() {
+        // Mock gateway that rejects every push
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let gateway = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 64 * 1024];
+                let _ = socket.read(&mut buf).await;
+                let _ = socket
+                    .write_all(b"HTTP/1.1 500 Internal Server Error\r\ncontent-length: 0\r\n\r\n")
+                    .await;
+            }
+        });
+
+        let registry = Registry::new();
+        let push = PushMetrics::new(&registry).unwrap();
+        let client = reqwest::Client::new();
+        let mut consecutive_failures = 0;
+
+        for _ in 0..3 {
+            push_once(&client, &gateway, &registry, &push, &mut consecutive_failures, 2).await;
+        }
+
+        assert_eq!(push.failures_total.get(), 3.0);
+        assert_eq!(push.last_success_timestamp.get(), 0.0);
+        assert_eq!(consecutive_failures, 3);
+    }
+
+    #[test]
+    fn test_unified_registry() {
+        let metrics = Metrics::new(MetricsConfig {
+            namespace: "swift".to_string(),
+            listen_address: "127.0.0.1:9184".to_string(),
+            push_gateway: None,
+            push_interval: 15,
+            push_failure_threshold: 3,
+            max_checkpoint_lag: 10,
+        }).unwrap();
+
+        metrics.record_tx_processed();
+        metrics.record_consensus_round();
+
+        let names: Vec<String> = metrics.gather()
+            .iter()
+            .map(|family| family.get_name().to_string())
+            .collect();
+        assert!(names.contains(&"total_transactions".to_string()));
+        assert!(names.contains(&"consensus_rounds".to_string()));
+    }
+}
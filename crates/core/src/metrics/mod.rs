@@ -1,8 +1,13 @@
 //! Metrics module for monitoring and observability.
 
+mod health;
 mod metrics;
 
-pub use metrics::{Metrics, MetricsConfig, Counter, Gauge, Histogram};
+pub use health::HealthState;
+pub use metrics::{
+    Metrics, MetricsConfig, Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramVec,
+    MempoolMetrics, StorageMetrics,
+};
 
 use crate::protocol::{ProtocolError, ProtocolResult};
 
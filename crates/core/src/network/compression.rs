@@ -0,0 +1,116 @@
+use super::{NetworkError, NetworkResult};
+
+/// Message compression codec. Every frame starts with the codec's header
+/// byte so peers can decode whatever codec the sender chose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Uncompressed
+    None,
+    /// LZ4
+    Lz4,
+    /// Zstandard
+    Zstd,
+}
+
+impl Compression {
+    /// Header byte identifying the codec
+    fn header(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Lz4 => 1,
+            Compression::Zstd => 2,
+        }
+    }
+
+    /// Codec for a header byte
+    fn from_header(header: u8) -> NetworkResult<Self> {
+        match header {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Lz4),
+            2 => Ok(Compression::Zstd),
+            other => Err(NetworkError::MessageError(format!("Unknown compression codec {}", other))),
+        }
+    }
+}
+
+/// Compression configuration
+#[derive(Debug, Clone)]
+pub struct CompressionConfig {
+    /// Codec used for outgoing messages
+    pub codec: Compression,
+    /// Messages smaller than this many bytes are sent uncompressed
+    pub threshold: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            codec: Compression::Lz4,
+            threshold: 4 * 1024,    // 4KB
+        }
+    }
+}
+
+/// Frame `data` with a codec header, compressing it if it is large enough
+pub fn compress(config: &CompressionConfig, data: &[u8]) -> NetworkResult<Vec<u8>> {
+    let codec = if data.len() < config.threshold { Compression::None } else { config.codec };
+
+    let mut frame = vec![codec.header()];
+    match codec {
+        Compression::None => frame.extend_from_slice(data),
+        Compression::Lz4 => frame.extend(lz4_flex::compress_prepend_size(data)),
+        Compression::Zstd => frame.extend(zstd::bulk::compress(data, 0)?),
+    }
+    Ok(frame)
+}
+
+/// Decode a frame produced by `compress`, refusing payloads that would
+/// expand past `max_size` bytes
+pub fn decompress(frame: &[u8], max_size: u64) -> NetworkResult<Vec<u8>> {
+    let (&header, payload) = frame.split_first()
+        .ok_or_else(|| NetworkError::MessageError("Empty message".into()))?;
+
+    let too_large = || NetworkError::MessageError(format!("Message exceeds {} bytes", max_size));
+    match Compression::from_header(header)? {
+        Compression::None => Ok(payload.to_vec()),
+        Compression::Lz4 => {
+            // The uncompressed size is prepended; check it before allocating
+            let size = payload.get(..4)
+                .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+                .ok_or_else(|| NetworkError::MessageError("Truncated LZ4 message".into()))?;
+            if size as u64 > max_size {
+                return Err(too_large());
+            }
+            lz4_flex::decompress_size_prepended(payload)
+                .map_err(|e| NetworkError::MessageError(e.to_string()))
+        }
+        Compression::Zstd => zstd::bulk::decompress(payload, max_size as usize)
+            .map_err(|_| too_large()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_messages_skip_compression() {
+        let config = CompressionConfig::default();
+        let frame = compress(&config, b"ping").unwrap();
+        assert_eq!(frame, [&[0u8][..], b"ping"].concat());
+        assert_eq!(decompress(&frame, 1024).unwrap(), b"ping");
+    }
+
+    #[test]
+    fn test_oversized_payload_refused() {
+        let config = CompressionConfig { codec: Compression::Lz4, threshold: 0 };
+        let frame = compress(&config, &vec![0; 1024 * 1024]).unwrap();
+        assert!(decompress(&frame, 1024).is_err());
+
+        let config = CompressionConfig { codec: Compression::Zstd, threshold: 0 };
+        let frame = compress(&config, &vec![0; 1024 * 1024]).unwrap();
+        assert!(decompress(&frame, 1024).is_err());
+
+        assert!(decompress(&[9, 1, 2, 3], 1024).is_err());
+    }
+}
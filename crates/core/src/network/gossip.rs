@@ -0,0 +1,130 @@
+use rand::seq::SliceRandom;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Gossip configuration
+#[derive(Debug, Clone)]
+pub struct GossipConfig {
+    /// Number of peers each message is forwarded to
+    pub fanout: usize,
+    /// How long a message digest is remembered
+    pub seen_ttl: Duration,
+}
+
+impl Default for GossipConfig {
+    fn default() -> Self {
+        Self {
+            fanout: 6,
+            seen_ttl: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Gossip state: forwards each message once to a random subset of peers
+#[derive(Debug)]
+pub struct Gossip {
+    /// Configuration
+    config: GossipConfig,
+    /// Digests already forwarded, with the time they were first seen
+    seen: HashMap<[u8; 32], Instant>,
+    /// Last time expired digests were pruned
+    last_prune: Instant,
+}
+
+impl Gossip {
+    pub fn new(config: GossipConfig) -> Self {
+        Self {
+            config,
+            seen: HashMap::new(),
+            last_prune: Instant::now(),
+        }
+    }
+
+    /// Digest used to deduplicate a message
+    pub fn digest(data: &[u8]) -> [u8; 32] {
+        Sha256::digest(data).into()
+    }
+
+    /// Record a message digest, returning true the first time it is seen
+    pub fn mark_seen(&mut self, digest: [u8; 32]) -> bool {
+        let now = Instant::now();
+        if now.duration_since(self.last_prune) >= self.config.seen_ttl {
+            let ttl = self.config.seen_ttl;
+            self.seen.retain(|_, seen_at| now.duration_since(*seen_at) < ttl);
+            self.last_prune = now;
+        }
+
+        if self.seen.contains_key(&digest) {
+            return false;
+        }
+        self.seen.insert(digest, now);
+        true
+    }
+
+    /// Pick up to `fanout` random peers, skipping the one we received from
+    pub fn select_peers<P: Clone + PartialEq>(&self, peers: &[P], from: Option<&P>) -> Vec<P> {
+        let candidates: Vec<&P> = peers.iter()
+            .filter(|peer| Some(*peer) != from)
+            .collect();
+        candidates
+            .choose_multiple(&mut rand::thread_rng(), self.config.fanout)
+            .map(|peer| (*peer).clone())
+            .collect()
+    }
+
+    /// Number of remembered digests
+    pub fn seen_count(&self) -> usize {
+        self.seen.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_gossip_reaches_all_nodes() {
+        // Ring lattice: each node links to its two neighbours on each side
+        let n = 32;
+        let peers: Vec<Vec<usize>> = (0..n)
+            .map(|i| vec![(i + 1) % n, (i + 2) % n, (i + n - 1) % n, (i + n - 2) % n])
+            .collect();
+        let mut nodes: Vec<Gossip> = (0..n)
+            .map(|_| Gossip::new(GossipConfig { fanout: 4, ..Default::default() }))
+            .collect();
+
+        let digest = Gossip::digest(b"transaction");
+        let mut queue = VecDeque::from([(0, None)]);
+        let mut messages = 0;
+
+        while let Some((node, from)) = queue.pop_front() {
+            if !nodes[node].mark_seen(digest) {
+                continue;
+            }
+            for peer in nodes[node].select_peers(&peers[node], from.as_ref()) {
+                messages += 1;
+                queue.push_back((peer, Some(node)));
+            }
+        }
+
+        assert!(nodes.iter().all(|node| node.seen_count() == 1));
+        assert!(messages < n * n / 4, "sent {} messages", messages);
+    }
+
+    #[test]
+    fn test_seen_expiry() {
+        let mut gossip = Gossip::new(GossipConfig {
+            fanout: 2,
+            seen_ttl: Duration::from_millis(10),
+        });
+        let digest = Gossip::digest(b"transaction");
+
+        assert!(gossip.mark_seen(digest));
+        assert!(!gossip.mark_seen(digest));
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(gossip.mark_seen(digest));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::storage::{Event, EventStore, EventType, TransactionEvent};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+/// Generated protobuf messages and service stubs
+pub mod proto {
+    tonic::include_proto!("swift.effects");
+}
+
+use proto::effects_service_server::{EffectsService, EffectsServiceServer};
+use proto::{SubscribeEffectsRequest, TransactionEffects};
+
+/// Stream of effects sent to one subscriber
+type EffectsStream = Pin<Box<dyn Stream<Item = Result<TransactionEffects, Status>> + Send>>;
+
+/// gRPC service streaming committed effects from the event feed
+pub struct EffectsStreamService {
+    /// Event store whose live feed is streamed
+    events: Arc<EventStore>,
+}
+
+impl EffectsStreamService {
+    /// Create new effects stream service
+    pub fn new(events: Arc<EventStore>) -> Self {
+        Self { events }
+    }
+
+    /// Wrap in a tonic server
+    pub fn into_server(self) -> EffectsServiceServer<Self> {
+        EffectsServiceServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl EffectsService for EffectsStreamService {
+    type SubscribeEffectsStream = EffectsStream;
+
+    async fn subscribe_effects(
+        &self,
+        request: Request<SubscribeEffectsRequest>,
+    ) -> Result<Response<Self::SubscribeEffectsStream>, Status> {
+        let filter = request.into_inner();
+        let stream = BroadcastStream::new(self.events.subscribe())
+            .filter_map(move |event| match event {
+                Ok(event) => effects_of(&event)
+                    .filter(|effects| matches_filter(&filter, effects))
+                    .map(Ok),
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                    log::warn!("Effects subscriber lagged, skipped {} events", skipped);
+                    None
+                }
+            });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Effects carried by `event`, if it's an effects commit
+fn effects_of(event: &Event) -> Option<TransactionEffects> {
+    match &event.type_ {
+        EventType::Transaction(TransactionEvent::EffectsCommitted {
+            tx_digest, sender, created, modified, deleted, gas_used, error,
+        }) => Some(TransactionEffects {
+            tx_digest: tx_digest.clone(),
+            sender: sender.clone(),
+            created: created.clone(),
+            modified: modified.clone(),
+            deleted: deleted.clone(),
+            gas_used: *gas_used,
+            error: error.clone(),
+        }),
+        _ => None,
+    }
+}
+
+/// Compare hex strings with or without a `0x` prefix
+fn same_hex(a: &str, b: &str) -> bool {
+    a.trim_start_matches("0x").eq_ignore_ascii_case(b.trim_start_matches("0x"))
+}
+
+/// Check `effects` against every filter field that is set
+fn matches_filter(filter: &SubscribeEffectsRequest, effects: &TransactionEffects) -> bool {
+    if let Some(sender) = &filter.sender {
+        if !same_hex(sender, &effects.sender) {
+            return false;
+        }
+    }
+
+    if let Some(object_id) = &filter.object_id {
+        let touched = effects.created.iter()
+            .chain(&effects.modified)
+            .chain(&effects.deleted)
+            .any(|id| same_hex(object_id, id));
+        if !touched {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_utils::temp_storage;
+    use crate::storage::StorageManager;
+    use chrono::Utc;
+    use proto::effects_service_client::EffectsServiceClient;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    fn effects_event(sender: &str, object_id: &str) -> Event {
+        Event {
+            id: String::new(),
+            type_: EventType::Transaction(TransactionEvent::EffectsCommitted {
+                tx_digest: hex::encode([7; 32]),
+                sender: sender.to_string(),
+                created: vec![],
+                modified: vec![object_id.to_string()],
+                deleted: vec![],
+                gas_used: 42,
+                error: None,
+            }),
+            timestamp: Utc::now(),
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_effects_streams_matching_effects() {
+        let (_temp_dir, config) = temp_storage();
+        let storage = StorageManager::new(config).unwrap();
+        let events = storage.event_store();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(EffectsStreamService::new(events.clone()).into_server())
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let mut client = EffectsServiceClient::connect(format!("http://{}", addr)).await.unwrap();
+        let mut stream = client
+            .subscribe_effects(SubscribeEffectsRequest {
+                sender: Some("0xaa".to_string()),
+                object_id: None,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        // Effects from another sender are filtered out
+        events.emit_event(effects_event("bb", "0x01")).unwrap();
+        events.emit_event(effects_event("aa", "0x02")).unwrap();
+
+        let effects = stream.message().await.unwrap().expect("streamed effects");
+        assert_eq!(effects.sender, "aa");
+        assert_eq!(effects.modified, vec!["0x02".to_string()]);
+        assert_eq!(effects.gas_used, 42);
+    }
+
+    #[test]
+    fn test_object_filter() {
+        let effects = effects_of(&effects_event("aa", "0xAB")).unwrap();
+        let by_object = |object_id: &str| SubscribeEffectsRequest {
+            sender: None,
+            object_id: Some(object_id.to_string()),
+        };
+        assert!(matches_filter(&by_object("ab"), &effects));
+        assert!(!matches_filter(&by_object("0xcd"), &effects));
+    }
+}
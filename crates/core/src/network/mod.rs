@@ -1,11 +1,22 @@
 //! Network module for P2P communication.
 
+mod compression;
+mod gossip;
+mod grpc;
 mod p2p;
 
-pub use p2p::{NetworkService, NetworkConfig, NetworkMessage, PeerInfo};
+pub use compression::{Compression, CompressionConfig};
+pub use gossip::{Gossip, GossipConfig};
+pub use grpc::{proto as effects_proto, EffectsStreamService};
+pub use p2p::{
+    peer_id_from_public_key, resolve_bootstrap_hosts,
+    CheckpointMessage, NetworkService, NetworkConfig, NetworkMessage, PeerInfo,
+};
 
 use crate::protocol::{ProtocolError, ProtocolResult};
+use libp2p::PeerId;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Network error types
 #[derive(Debug, thiserror::Error)]
@@ -21,6 +32,18 @@ pub enum NetworkError {
 
     #[error("Protocol error: {0}")]
     ProtocolError(String),
+
+    #[error("Peer not found: {0}")]
+    PeerNotFound(PeerId),
+
+    #[error("Timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] bincode::Error),
 }
 
 pub type NetworkResult<T> = Result<T, NetworkError>;
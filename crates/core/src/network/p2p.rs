@@ -1,13 +1,24 @@
-use super::{NetworkError, NetworkEvent, NetworkEventHandler, NetworkResult};
-use crate::protocol::{ProtocolError, ProtocolResult};
+use super::compression::{self, CompressionConfig};
+use super::{Gossip, GossipConfig, NetworkError, NetworkEvent, NetworkEventHandler, NetworkResult};
+use crate::authority::{Checkpoint, CheckpointStore};
+use crate::crypto::PublicKey;
+use crate::metrics::HealthState;
+use crate::protocol::{Committee, ProtocolError, ProtocolResult};
+use crate::utils::safe_decode;
 use libp2p::{
     core::{muxing::StreamMuxerBox, transport::Boxed},
-    identity, mplex, noise,
-    swarm::{NetworkBehaviour, SwarmBuilder, SwarmEvent},
+    identity, mplex, multiaddr::Protocol, noise,
+    swarm::{dial_opts::DialOpts, ConnectionId, NetworkBehaviour, SwarmBuilder, SwarmEvent},
     tcp, yamux, Multiaddr, PeerId, Swarm, Transport,
 };
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+
+/// Maximum size of a decoded network message
+const MAX_MESSAGE_SIZE: u64 = 16 * 1024 * 1024;
 
 /// Network configuration
 #[derive(Debug, Clone)]
@@ -16,12 +27,87 @@ pub struct NetworkConfig {
     pub listen_addresses: Vec<Multiaddr>,
     /// Bootstrap peers
     pub bootstrap_peers: Vec<Multiaddr>,
+    /// Bootstrap peers as `host:port`, resolved at startup
+    pub bootstrap_hosts: Vec<String>,
     /// Maximum peers
     pub max_peers: usize,
     /// Connection timeout
     pub connection_timeout: std::time::Duration,
     /// Protocol version
     pub protocol_version: String,
+    /// Node identity; generated if not set
+    pub identity: Option<identity::Keypair>,
+    /// Expected validator peers. When set, connections from any other
+    /// authenticated peer id are refused.
+    pub validator_peers: Option<HashSet<PeerId>>,
+    /// Transaction gossip configuration
+    pub gossip: GossipConfig,
+    /// Maximum outbound dials in flight; further dials wait in a queue
+    pub max_concurrent_dials: usize,
+    /// Outgoing message compression
+    pub compression: CompressionConfig,
+}
+
+impl NetworkConfig {
+    /// Only accept connections from the given committee members
+    pub fn with_validator_peers(mut self, committee: &[PublicKey]) -> NetworkResult<Self> {
+        let peers = committee.iter()
+            .map(peer_id_from_public_key)
+            .collect::<NetworkResult<_>>()?;
+        self.validator_peers = Some(peers);
+        Ok(self)
+    }
+
+    /// Check an authenticated peer against the validator allowlist
+    pub fn check_peer(&self, peer_id: &PeerId) -> NetworkResult<()> {
+        match &self.validator_peers {
+            Some(peers) if !peers.contains(peer_id) => Err(NetworkError::PeerError(
+                format!("Peer {} is not a committee member", peer_id)
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Listen on all IPv4 and IPv6 interfaces at `port`
+    pub fn with_dual_stack(mut self, port: u16) -> Self {
+        self.listen_addresses = vec![
+            Multiaddr::from(Ipv4Addr::UNSPECIFIED).with(Protocol::Tcp(port)),
+            Multiaddr::from(Ipv6Addr::UNSPECIFIED).with(Protocol::Tcp(port)),
+        ];
+        self
+    }
+}
+
+/// Derive the libp2p peer id of a validator public key
+pub fn peer_id_from_public_key(public_key: &PublicKey) -> NetworkResult<PeerId> {
+    match public_key {
+        PublicKey::Ed25519(pk) => {
+            let pk = identity::ed25519::PublicKey::decode(&pk.to_bytes())
+                .map_err(|e| NetworkError::PeerError(e.to_string()))?;
+            Ok(PeerId::from(identity::PublicKey::Ed25519(pk)))
+        }
+        _ => Err(NetworkError::PeerError(format!(
+            "Unsupported key scheme for peer id: {:?}",
+            public_key.scheme()
+        ))),
+    }
+}
+
+/// Resolve `host:port` bootstrap entries to addresses. Entries that fail to
+/// resolve are logged and skipped.
+pub async fn resolve_bootstrap_hosts(hosts: &[String]) -> Vec<Multiaddr> {
+    let mut addresses = Vec::new();
+    for host in hosts {
+        match tokio::net::lookup_host(host.as_str()).await {
+            Ok(resolved) => {
+                addresses.extend(resolved.map(|addr| {
+                    Multiaddr::from(addr.ip()).with(Protocol::Tcp(addr.port()))
+                }));
+            }
+            Err(e) => log::warn!("Failed to resolve bootstrap peer {}: {}", host, e),
+        }
+    }
+    addresses
 }
 
 /// Peer information
@@ -36,7 +122,7 @@ pub struct PeerInfo {
 }
 
 /// Network message
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NetworkMessage {
     /// Transaction message
     Transaction(TransactionMessage),
@@ -44,6 +130,118 @@ pub enum NetworkMessage {
     Consensus(ConsensusMessage),
     /// State sync message
     StateSync(StateSyncMessage),
+    /// Checkpoint sync message
+    Checkpoint(CheckpointMessage),
+}
+
+/// Checkpoint sync message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CheckpointMessage {
+    /// Request the checkpoint at `sequence`
+    GetCheckpoint { sequence: u64 },
+    /// Response to `GetCheckpoint`; `None` if the peer doesn't have it
+    CheckpointData {
+        sequence: u64,
+        checkpoint: Option<Checkpoint>,
+    },
+}
+
+/// Serialize and compress a message for the wire
+fn encode_message(config: &CompressionConfig, message: &NetworkMessage) -> NetworkResult<Vec<u8>> {
+    compression::compress(config, &bincode::serialize(message)?)
+}
+
+/// Decompress and deserialize a message received from the wire
+fn decode_message(data: &[u8]) -> NetworkResult<NetworkMessage> {
+    let data = compression::decompress(data, MAX_MESSAGE_SIZE)?;
+    safe_decode(&data, MAX_MESSAGE_SIZE)
+        .map_err(|e| NetworkError::MessageError(e.to_string()))
+}
+
+/// Record the sequence of a checkpoint a peer sent as the network's height,
+/// once `committee` has certified it, so a peer can't fake the height
+fn observe_peer_checkpoint(health: &HealthState, committee: &Committee, message: &NetworkMessage) {
+    if let NetworkMessage::Checkpoint(CheckpointMessage::CheckpointData {
+        checkpoint: Some(checkpoint),
+        ..
+    }) = message {
+        if checkpoint.verify() && checkpoint.is_certified(committee) {
+            health.observe_peer_checkpoint(checkpoint.sequence);
+        }
+    }
+}
+
+/// Answer a checkpoint request from the local store
+async fn serve_checkpoint(
+    store: &CheckpointStore,
+    sequence: u64,
+) -> NetworkResult<NetworkMessage> {
+    let checkpoint = store.get_checkpoint(sequence).await
+        .map_err(|e| NetworkError::ProtocolError(e.to_string()))?;
+    Ok(NetworkMessage::Checkpoint(CheckpointMessage::CheckpointData {
+        sequence,
+        checkpoint,
+    }))
+}
+
+/// Caps concurrent outbound dials. A slot is held from the dial until the
+/// connection is established or fails; dials past the cap are queued.
+/// Slots are keyed by connection id, so connections the limiter didn't
+/// dial never release one.
+struct DialLimiter {
+    /// Free dial slots
+    slots: Arc<Semaphore>,
+    /// Slots held by in-flight dials
+    in_flight: HashMap<ConnectionId, OwnedSemaphorePermit>,
+    /// Dials waiting for a slot
+    queued: VecDeque<Multiaddr>,
+}
+
+impl DialLimiter {
+    fn new(max_concurrent_dials: usize) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(max_concurrent_dials.max(1))),
+            in_flight: HashMap::new(),
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Take a slot for `addr`, returning the dial if it may start now;
+    /// otherwise it is queued
+    fn start(&mut self, addr: Multiaddr) -> Option<DialOpts> {
+        if !self.queued.is_empty() {
+            self.queued.push_back(addr);
+            return None;
+        }
+        match self.slots.clone().try_acquire_owned() {
+            Ok(permit) => Some(self.track(addr, permit)),
+            Err(_) => {
+                self.queued.push_back(addr);
+                None
+            }
+        }
+    }
+
+    /// Release the slot of the finished dial `id`, returning the next
+    /// queued dial, which takes over the slot. Ids the limiter didn't
+    /// dial are ignored.
+    fn finish(&mut self, id: ConnectionId) -> Option<DialOpts> {
+        let permit = self.in_flight.remove(&id)?;
+        let addr = self.queued.pop_front()?;
+        Some(self.track(addr, permit))
+    }
+
+    /// Hold `permit` for a new dial to `addr`
+    fn track(&mut self, addr: Multiaddr, permit: OwnedSemaphorePermit) -> DialOpts {
+        let opts = DialOpts::from(addr);
+        self.in_flight.insert(opts.connection_id(), permit);
+        opts
+    }
+
+    /// Number of dials in flight
+    fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
 }
 
 /// Network service
@@ -56,6 +254,15 @@ pub struct NetworkService {
     event_sender: mpsc::Sender<NetworkEvent>,
     /// Event handler
     event_handler: Arc<dyn NetworkEventHandler>,
+    /// Transaction gossip state
+    gossip: Gossip,
+    /// Checkpoint store serving checkpoint requests from peers
+    checkpoint_store: Option<Arc<CheckpointStore>>,
+    /// Health state told about checkpoints peers send, and the committee
+    /// that must have certified them
+    health: Option<(HealthState, Committee)>,
+    /// Outbound dial limiter
+    dials: DialLimiter,
 }
 
 impl NetworkService {
@@ -65,7 +272,8 @@ impl NetworkService {
         event_handler: Arc<dyn NetworkEventHandler>,
     ) -> NetworkResult<Self> {
         // Create identity
-        let identity = identity::Keypair::generate_ed25519();
+        let identity = config.identity.clone()
+            .unwrap_or_else(identity::Keypair::generate_ed25519);
         let peer_id = PeerId::from(identity.public());
 
         // Create transport
@@ -85,11 +293,17 @@ impl NetworkService {
         let (event_sender, mut event_receiver) = mpsc::channel(1000);
 
         // Create service
+        let gossip = Gossip::new(config.gossip.clone());
+        let dials = DialLimiter::new(config.max_concurrent_dials);
         let mut service = Self {
             config,
             swarm,
             event_sender,
             event_handler,
+            gossip,
+            checkpoint_store: None,
+            health: None,
+            dials,
         };
 
         // Start event loop
@@ -110,6 +324,19 @@ impl NetworkService {
         Ok(service)
     }
 
+    /// Serve checkpoint requests from peers out of `store`
+    pub fn with_checkpoint_store(mut self, store: Arc<CheckpointStore>) -> Self {
+        self.checkpoint_store = Some(store);
+        self
+    }
+
+    /// Report checkpoints `committee` certified received from peers to
+    /// `health`
+    pub fn with_health(mut self, health: HealthState, committee: Committee) -> Self {
+        self.health = Some((health, committee));
+        self
+    }
+
     /// Start listening
     async fn start_listening(&mut self) -> NetworkResult<()> {
         for addr in &self.config.listen_addresses {
@@ -121,19 +348,48 @@ impl NetworkService {
 
     /// Connect to bootstrap peers
     async fn connect_bootstrap_peers(&mut self) -> NetworkResult<()> {
-        for addr in &self.config.bootstrap_peers {
-            self.connect_peer(addr.clone()).await?;
+        let mut peers = self.config.bootstrap_peers.clone();
+        peers.extend(resolve_bootstrap_hosts(&self.config.bootstrap_hosts).await);
+
+        // An unreachable bootstrap peer shouldn't abort startup
+        for addr in peers {
+            if let Err(e) = self.connect_peer(addr.clone()).await {
+                log::warn!("Failed to connect to bootstrap peer {}: {}", addr, e);
+            }
         }
         Ok(())
     }
 
-    /// Connect to peer
+    /// Connect to peer. The dial is queued if `max_concurrent_dials` are
+    /// already in flight.
     pub async fn connect_peer(&mut self, addr: Multiaddr) -> NetworkResult<()> {
-        self.swarm.dial(addr.clone())
-            .map_err(|e| NetworkError::ConnectionError(e.to_string()))?;
+        let Some(opts) = self.dials.start(addr) else {
+            return Ok(());
+        };
+        let id = opts.connection_id();
+        if let Err(e) = self.swarm.dial(opts) {
+            self.dial_finished(id);
+            return Err(NetworkError::ConnectionError(e.to_string()));
+        }
         Ok(())
     }
 
+    /// Free the slot of the finished dial `id`, if the limiter started it,
+    /// and start the next queued dial
+    fn dial_finished(&mut self, id: ConnectionId) {
+        let mut next = self.dials.finish(id);
+        while let Some(opts) = next {
+            let id = opts.connection_id();
+            match self.swarm.dial(opts) {
+                Ok(()) => break,
+                Err(e) => {
+                    log::warn!("Failed to dial queued peer: {}", e);
+                    next = self.dials.finish(id);
+                }
+            }
+        }
+    }
+
     /// Broadcast message
     pub async fn broadcast(&mut self, message: NetworkMessage) -> NetworkResult<()> {
         // Get connected peers
@@ -147,15 +403,34 @@ impl NetworkService {
         Ok(())
     }
 
+    /// Gossip a transaction to a random subset of peers. Each transaction is
+    /// forwarded at most once; `from` is skipped when relaying.
+    pub async fn gossip_transaction(
+        &mut self,
+        message: NetworkMessage,
+        from: Option<PeerId>,
+    ) -> NetworkResult<()> {
+        let data = bincode::serialize(&message)?;
+        if !self.gossip.mark_seen(Gossip::digest(&data)) {
+            return Ok(());
+        }
+
+        let peers: Vec<_> = self.swarm.connected_peers().cloned().collect();
+        for peer_id in self.gossip.select_peers(&peers, from.as_ref()) {
+            self.send_message(peer_id, message.clone()).await?;
+        }
+
+        Ok(())
+    }
+
     /// Send message to peer
     pub async fn send_message(
         &mut self,
         peer_id: PeerId,
         message: NetworkMessage,
     ) -> NetworkResult<()> {
-        // Serialize message
-        let data = bincode::serialize(&message)
-            .map_err(|e| NetworkError::MessageError(e.to_string()))?;
+        // Serialize and compress message
+        let data = encode_message(&self.config.compression, &message)?;
 
         // Send message
         self.swarm.behaviour_mut().send_message(peer_id, data)
@@ -171,7 +446,16 @@ impl NetworkService {
                 SwarmEvent::NewListenAddr { address, .. } => {
                     log::info!("Listening on {}", address);
                 }
-                SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } => {
+                SwarmEvent::ConnectionEstablished { peer_id, connection_id, endpoint, .. } => {
+                    self.dial_finished(connection_id);
+
+                    // Noise has authenticated the peer id; refuse unexpected validators
+                    if let Err(e) = self.config.check_peer(&peer_id) {
+                        log::warn!("Refusing connection: {}", e);
+                        let _ = self.swarm.disconnect_peer_id(peer_id);
+                        continue;
+                    }
+
                     let peer_info = PeerInfo {
                         peer_id,
                         address: endpoint.get_remote_address().clone(),
@@ -189,6 +473,10 @@ impl NetworkService {
                     self.event_sender.send(NetworkEvent::PeerDisconnected(peer_info)).await
                         .map_err(|e| NetworkError::MessageError(e.to_string()))?;
                 }
+                SwarmEvent::OutgoingConnectionError { connection_id, error, .. } => {
+                    log::debug!("Outbound dial failed: {}", error);
+                    self.dial_finished(connection_id);
+                }
                 SwarmEvent::Behaviour(event) => {
                     self.handle_behaviour_event(event).await?;
                 }
@@ -201,18 +489,35 @@ impl NetworkService {
     async fn handle_behaviour_event(&mut self, event: BehaviourEvent) -> NetworkResult<()> {
         match event {
             BehaviourEvent::Message { peer_id, data } => {
-                // Deserialize message
-                let message: NetworkMessage = bincode::deserialize(&data)
-                    .map_err(|e| NetworkError::MessageError(e.to_string()))?;
+                // Decompress and deserialize message
+                let message = decode_message(&data)?;
 
                 // Create peer info
                 let peer_info = PeerInfo {
                     peer_id,
                     address: self.swarm.behaviour().get_peer_address(&peer_id)
-                        .ok_or_else(|| NetworkError::PeerError("Peer not found".into()))?.clone(),
+                        .ok_or(NetworkError::PeerNotFound(peer_id))?.clone(),
                     protocol_version: self.config.protocol_version.clone(),
                 };
 
+                // Relay transactions we haven't seen yet
+                if let NetworkMessage::Transaction(_) = &message {
+                    self.gossip_transaction(message.clone(), Some(peer_id)).await?;
+                }
+
+                // Track the network's height for the readiness probe
+                if let Some((health, committee)) = &self.health {
+                    observe_peer_checkpoint(health, committee, &message);
+                }
+
+                // Answer checkpoint requests directly from the store
+                if let NetworkMessage::Checkpoint(CheckpointMessage::GetCheckpoint { sequence }) = &message {
+                    if let Some(store) = self.checkpoint_store.clone() {
+                        let response = serve_checkpoint(&store, *sequence).await?;
+                        return self.send_message(peer_id, response).await;
+                    }
+                }
+
                 // Send event
                 self.event_sender.send(NetworkEvent::MessageReceived {
                     peer: peer_info,
@@ -241,4 +546,327 @@ fn build_transport(
 /// Build network behaviour
 fn build_behaviour(config: NetworkConfig) -> NetworkResult<NetworkBehaviour> {
     Ok(NetworkBehaviour::new(config))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn test_config() -> NetworkConfig {
+        NetworkConfig {
+            listen_addresses: vec![],
+            bootstrap_peers: vec![],
+            bootstrap_hosts: vec![],
+            max_peers: 50,
+            connection_timeout: Duration::from_secs(10),
+            protocol_version: "1.0".to_string(),
+            identity: None,
+            validator_peers: None,
+            gossip: GossipConfig::default(),
+            max_concurrent_dials: 8,
+            compression: CompressionConfig::default(),
+        }
+    }
+
+    /// Forwards every network event to a channel
+    struct ForwardEvents(mpsc::UnboundedSender<NetworkEvent>);
+
+    #[async_trait::async_trait]
+    impl NetworkEventHandler for ForwardEvents {
+        async fn handle_event(&self, event: NetworkEvent) -> NetworkResult<()> {
+            let _ = self.0.send(event);
+            Ok(())
+        }
+    }
+
+    /// Start a service, returning it with the events it reports
+    async fn start_service(
+        config: NetworkConfig,
+    ) -> (NetworkService, mpsc::UnboundedReceiver<NetworkEvent>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let service = NetworkService::new(config, Arc::new(ForwardEvents(sender))).await.unwrap();
+        (service, receiver)
+    }
+
+    /// Drive `service` until it reports an event `select` picks
+    async fn run_until<T>(
+        service: &mut NetworkService,
+        events: &mut mpsc::UnboundedReceiver<NetworkEvent>,
+        mut select: impl FnMut(NetworkEvent) -> Option<T>,
+    ) -> T {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                tokio::select! {
+                    result = service.run() => panic!("Network service stopped: {:?}", result),
+                    Some(event) = events.recv() => {
+                        if let Some(value) = select(event) {
+                            return value;
+                        }
+                    }
+                }
+            }
+        }).await.expect("Timed out waiting for a network event")
+    }
+
+    /// Loopback address on a free port
+    fn free_address() -> Multiaddr {
+        let port = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        Multiaddr::from(Ipv4Addr::LOCALHOST).with(Protocol::Tcp(port))
+    }
+
+    #[test]
+    fn test_dual_stack() {
+        let config = test_config().with_dual_stack(8080);
+        let addresses: Vec<String> = config.listen_addresses.iter().map(|a| a.to_string()).collect();
+        assert_eq!(addresses, vec!["/ip4/0.0.0.0/tcp/8080", "/ip6/::/tcp/8080"]);
+    }
+
+    #[tokio::test]
+    async fn test_unexpected_validator_refused() {
+        use crate::crypto::{KeyPair, SignatureScheme};
+
+        let secret = identity::ed25519::SecretKey::generate();
+        let member_key = KeyPair::from_private_key_bytes(SignatureScheme::Ed25519, secret.as_ref())
+            .unwrap()
+            .public();
+        let member = identity::Keypair::Ed25519(secret.into());
+        let member_id = PeerId::from(member.public());
+
+        let address = free_address();
+        let config = NetworkConfig {
+            listen_addresses: vec![address.clone()],
+            ..test_config()
+        }.with_validator_peers(&[member_key]).unwrap();
+        let (mut validator, mut validator_events) = start_service(config).await;
+        tokio::spawn(async move { validator.run().await });
+
+        // A peer outside the committee is dropped once noise names it
+        let (mut stranger, mut stranger_events) = start_service(NetworkConfig {
+            bootstrap_peers: vec![address.clone()],
+            ..test_config()
+        }).await;
+        run_until(&mut stranger, &mut stranger_events, |event| match event {
+            NetworkEvent::PeerDisconnected(_) => Some(()),
+            _ => None,
+        }).await;
+
+        let (mut committee_peer, mut committee_events) = start_service(NetworkConfig {
+            bootstrap_peers: vec![address],
+            identity: Some(member),
+            ..test_config()
+        }).await;
+        run_until(&mut committee_peer, &mut committee_events, |event| match event {
+            NetworkEvent::PeerConnected(_) => Some(()),
+            _ => None,
+        }).await;
+
+        // The validator only ever reported the committee member
+        let connected = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let Some(NetworkEvent::PeerConnected(peer)) = validator_events.recv().await {
+                    return peer.peer_id;
+                }
+            }
+        }).await.unwrap();
+        assert_eq!(connected, member_id);
+    }
+
+    #[tokio::test]
+    async fn test_unresolvable_bootstrap_skipped() {
+        let hosts = vec!["127.0.0.1:9000".to_string(), "not a host".to_string()];
+        let addresses = resolve_bootstrap_hosts(&hosts).await;
+        assert_eq!(addresses.len(), 1);
+        assert_eq!(addresses[0].to_string(), "/ip4/127.0.0.1/tcp/9000");
+    }
+
+    #[test]
+    fn test_io_error_converts() {
+        fn read_key() -> NetworkResult<Vec<u8>> {
+            Ok(std::fs::read("/nonexistent/swift-network/node.key")?)
+        }
+
+        assert!(matches!(read_key(), Err(NetworkError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_request_round_trip() {
+        use crate::authority::{AuthorityStore, StoreConfig};
+        use crate::crypto::{KeyPair, SignatureScheme};
+        use crate::storage::test_utils::temp_storage;
+
+        let (_temp_dir, storage) = temp_storage();
+        let store = AuthorityStore::new(StoreConfig {
+            storage,
+            cache_size: 128,
+        }).unwrap();
+        let checkpoints = CheckpointStore::new(Arc::new(store)).unwrap();
+        let validators: Vec<_> = (0..4)
+            .map(|_| KeyPair::generate(SignatureScheme::Ed25519).unwrap())
+            .collect();
+        let committee = Committee::new(validators.iter().map(|kp| (kp.public(), 1)).collect());
+        let mut checkpoint = Checkpoint::new(3, None, 100, vec![], vec![], [1; 32], 0, None);
+        let signatures = validators[..3].iter()
+            .map(|kp| (kp.public(), kp.sign(&checkpoint.digest)))
+            .collect();
+        checkpoint.certify(signatures, &committee).unwrap();
+        checkpoints.put_checkpoint(checkpoint.clone()).await.unwrap();
+        let uncertified = Checkpoint::new(5, None, 100, vec![], vec![], [2; 32], 0, None);
+        checkpoints.put_checkpoint(uncertified.clone()).await.unwrap();
+
+        let address = free_address();
+        let (server, _server_events) = start_service(NetworkConfig {
+            listen_addresses: vec![address.clone()],
+            ..test_config()
+        }).await;
+        let mut server = server.with_checkpoint_store(Arc::new(checkpoints));
+        tokio::spawn(async move { server.run().await });
+
+        let health = HealthState::new(0);
+        let (client, mut client_events) = start_service(NetworkConfig {
+            bootstrap_peers: vec![address],
+            ..test_config()
+        }).await;
+        let mut client = client.with_health(health.clone(), committee);
+        let server_id = run_until(&mut client, &mut client_events, |event| match event {
+            NetworkEvent::PeerConnected(peer) => Some(peer.peer_id),
+            _ => None,
+        }).await;
+
+        for (sequence, expected) in [(3, Some(checkpoint.digest)), (4, None), (5, Some(uncertified.digest))] {
+            client.send_message(
+                server_id,
+                NetworkMessage::Checkpoint(CheckpointMessage::GetCheckpoint { sequence }),
+            ).await.unwrap();
+
+            let (got, digest) = run_until(&mut client, &mut client_events, |event| match event {
+                NetworkEvent::MessageReceived {
+                    message: NetworkMessage::Checkpoint(CheckpointMessage::CheckpointData { sequence, checkpoint }),
+                    ..
+                } => Some((sequence, checkpoint.map(|c| c.digest))),
+                _ => None,
+            }).await;
+            assert_eq!(got, sequence);
+            assert_eq!(digest, expected);
+        }
+
+        // Only a certified checkpoint actually sent raises the network's height
+        assert_eq!(health.checkpoint_lag(), 3);
+    }
+
+    #[test]
+    fn test_dial_limit() {
+        let mut dials = DialLimiter::new(2);
+        let addrs: Vec<Multiaddr> = (0..5u16)
+            .map(|i| Multiaddr::from(Ipv4Addr::LOCALHOST).with(Protocol::Tcp(9000 + i)))
+            .collect();
+
+        let mut dialing: VecDeque<_> = addrs.iter()
+            .filter_map(|addr| dials.start(addr.clone()))
+            .map(|opts| opts.connection_id())
+            .collect();
+        assert_eq!(dialing.len(), 2);
+        assert_eq!(dials.in_flight(), 2);
+        assert_eq!(Vec::from(dials.queued.clone()), addrs[2..]);
+
+        // A connection the limiter didn't dial frees no slot
+        let foreign = DialOpts::from(addrs[0].clone()).connection_id();
+        assert!(dials.finish(foreign).is_none());
+        assert_eq!(dials.in_flight(), 2);
+
+        // Each finished dial hands its slot to the next queued one, in order
+        let mut started = 2;
+        while let Some(id) = dialing.pop_front() {
+            let next = dials.queued.front().cloned();
+            if let Some(opts) = dials.finish(id) {
+                assert_eq!(next.as_ref(), Some(&addrs[started]));
+                started += 1;
+                dialing.push_back(opts.connection_id());
+            }
+            assert!(dials.in_flight() <= 2);
+        }
+        assert_eq!(started, addrs.len());
+        assert_eq!(dials.in_flight(), 0);
+        assert!(dials.finish(foreign).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dial_limit_ignores_foreign_connections() {
+        // Three listening peers with known ids
+        let mut peers = Vec::new();
+        for _ in 0..3 {
+            let identity = identity::Keypair::generate_ed25519();
+            let peer_id = PeerId::from(identity.public());
+            let address = free_address();
+            let (mut peer, _events) = start_service(NetworkConfig {
+                listen_addresses: vec![address.clone()],
+                identity: Some(identity),
+                ..test_config()
+            }).await;
+            tokio::spawn(async move { peer.run().await });
+            peers.push((peer_id, address));
+        }
+
+        let (mut client, mut events) = start_service(NetworkConfig {
+            max_concurrent_dials: 1,
+            ..test_config()
+        }).await;
+
+        // One limited dial in flight, one queued behind it, and a dial made
+        // around the limiter
+        client.connect_peer(peers[0].1.clone()).await.unwrap();
+        client.connect_peer(peers[2].1.clone()).await.unwrap();
+        client.swarm.dial(peers[1].1.clone()).unwrap();
+        assert_eq!(client.dials.in_flight(), 1);
+        assert_eq!(client.dials.queued.len(), 1);
+
+        let mut connected = Vec::new();
+        run_until(&mut client, &mut events, |event| match event {
+            NetworkEvent::PeerConnected(peer) => {
+                connected.push(peer.peer_id);
+                (connected.len() == 3).then_some(())
+            }
+            _ => None,
+        }).await;
+
+        // The queued dial only started once the limited one finished, however
+        // early the foreign connection came up
+        let position = |peer_id| connected.iter().position(|id| *id == peer_id).unwrap();
+        assert!(position(peers[0].0) < position(peers[2].0));
+        assert_eq!(client.dials.in_flight(), 0);
+        assert!(client.dials.queued.is_empty());
+    }
+
+    #[test]
+    fn test_compressed_message_round_trip() {
+        use crate::network::Compression;
+        use crate::transaction::TransactionDigest;
+
+        let transactions = (0..2_000u32)
+            .map(|i| {
+                let mut bytes = [0u8; 32];
+                bytes[..4].copy_from_slice(&i.to_le_bytes());
+                TransactionDigest::from_bytes(bytes)
+            })
+            .collect();
+        let checkpoint = Checkpoint::new(1, None, 100, transactions, vec![], [0; 32], 0, None);
+        let message = NetworkMessage::Checkpoint(CheckpointMessage::CheckpointData {
+            sequence: 1,
+            checkpoint: Some(checkpoint),
+        });
+        let plain = bincode::serialize(&message).unwrap();
+
+        for codec in [Compression::Lz4, Compression::Zstd] {
+            let config = CompressionConfig { codec, threshold: 1024 };
+            let data = encode_message(&config, &message).unwrap();
+            assert!(data.len() < plain.len());
+
+            let decoded = decode_message(&data).unwrap();
+            assert_eq!(bincode::serialize(&decoded).unwrap(), plain);
+        }
+    }
+}
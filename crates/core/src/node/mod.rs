@@ -0,0 +1,230 @@
+//! Node services: the network-facing APIs served alongside the authority.
+
+use crate::authority::{admission_routes, AdmissionFilter};
+use crate::config::{ApiConfig, Config};
+use crate::execution::{ExecutionError, Executor};
+use crate::mempool::{gas_price_routes, Mempool};
+use crate::network::EffectsStreamService;
+use crate::runtime::RuntimeConfig;
+use crate::storage::{EventStore, Storage};
+use crate::transaction::{SignatureCache, TransactionManager, TransactionValidator};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use warp::filters::BoxedFilter;
+use warp::{Filter, Reply};
+
+/// Node error types
+#[derive(Debug, thiserror::Error)]
+pub enum NodeError {
+    #[error("Invalid address {address}: {reason}")]
+    InvalidAddress { address: String, reason: String },
+
+    #[error("Execution error: {0}")]
+    Execution(#[from] ExecutionError),
+}
+
+pub type NodeResult<T> = Result<T, NodeError>;
+
+/// HTTP routes with their reply type erased so optional services can be
+/// combined
+type Routes = BoxedFilter<(Box<dyn Reply>,)>;
+
+/// Box a filter into `Routes`
+fn boxed<F, R>(filter: F) -> Routes
+where
+    F: Filter<Extract = (R,), Error = warp::Rejection> + Clone + Send + Sync + 'static,
+    R: Reply + 'static,
+{
+    filter.map(|reply: R| Box::new(reply) as Box<dyn Reply>).boxed()
+}
+
+/// Build the transaction pipeline over `storage`. Privileged system
+/// transactions are accepted from the genesis system senders, and
+/// submissions are held to the reference gas price in storage. Submission
+/// and execution share one cache of verified signatures.
+pub fn transaction_manager(
+    config: &Config,
+    runtime_config: RuntimeConfig,
+    storage: Arc<dyn Storage>,
+) -> NodeResult<TransactionManager> {
+    let signature_cache = Arc::new(SignatureCache::default());
+    let mut executor = Executor::new(runtime_config, storage.clone())?;
+    executor.set_system_senders(config.genesis.system_senders.iter().copied().collect());
+    executor.set_signature_cache(signature_cache.clone());
+
+    let validator = TransactionValidator::new(config.chain_id.clone())
+        .with_storage(storage.clone())
+        .with_signature_cache(signature_cache);
+
+    Ok(TransactionManager::new(storage, Arc::new(executor), Arc::new(validator)))
+}
+
+/// Network-facing services of a node
+pub struct NodeServices {
+    /// API configuration
+    config: ApiConfig,
+    /// Transaction admission filter, reloadable through the admin API
+    admission: Arc<AdmissionFilter>,
+    /// Event store whose committed effects are streamed over gRPC
+    events: Option<Arc<EventStore>>,
+    /// Mempool whose suggested gas price is served over HTTP
+    mempool: Option<Arc<Mempool>>,
+}
+
+impl NodeServices {
+    /// Create node services
+    pub fn new(config: ApiConfig, admission: Arc<AdmissionFilter>) -> Self {
+        Self { config, admission, events: None, mempool: None }
+    }
+
+    /// Stream effects committed to `events` to gRPC subscribers
+    pub fn with_effects_stream(mut self, events: Arc<EventStore>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Serve the gas price `mempool` suggests
+    pub fn with_mempool(mut self, mempool: Arc<Mempool>) -> Self {
+        self.mempool = Some(mempool);
+        self
+    }
+
+    /// Routes of the HTTP API. The admin routes are only mounted when an
+    /// admin token is configured, the gas price route when a mempool is set.
+    pub fn routes(&self) -> Option<Routes> {
+        let mut routes: Vec<Routes> = Vec::new();
+        if let Some(mempool) = &self.mempool {
+            routes.push(boxed(gas_price_routes(mempool.clone())));
+        }
+        if let Some(token) = &self.config.admin_token {
+            routes.push(boxed(admission_routes(self.admission.clone(), token.clone())));
+        }
+
+        routes.into_iter().reduce(|all, next| all.or(next).unify().boxed())
+    }
+
+    /// Start serving the HTTP API, and the gRPC effects stream when both an
+    /// address and an event store are set
+    pub fn start(&self) -> NodeResult<()> {
+        let addr = parse_address(&self.config.listen_address)?;
+        if let Some(routes) = self.routes() {
+            log::info!("Serving node API on {}", addr);
+            tokio::spawn(warp::serve(routes).run(addr));
+        }
+
+        if let (Some(address), Some(events)) = (&self.config.grpc_listen_address, &self.events) {
+            let addr = parse_address(address)?;
+            let server = tonic::transport::Server::builder()
+                .add_service(EffectsStreamService::new(events.clone()).into_server())
+                .serve(addr);
+            log::info!("Serving effects stream on {}", addr);
+            tokio::spawn(async move {
+                if let Err(e) = server.await {
+                    log::error!("Effects stream server failed: {}", e);
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a listen address
+fn parse_address(address: &str) -> NodeResult<SocketAddr> {
+    address.parse().map_err(|e: std::net::AddrParseError| NodeError::InvalidAddress {
+        address: address.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::AdmissionConfig;
+
+    #[tokio::test]
+    async fn test_admin_routes_need_a_token() {
+        let admission = Arc::new(AdmissionFilter::new(AdmissionConfig::default()));
+
+        // No token, no admin API
+        let services = NodeServices::new(ApiConfig::default(), admission.clone());
+        assert!(services.routes().is_none());
+
+        let services = NodeServices::new(
+            ApiConfig { admin_token: Some("secret".to_string()), ..Default::default() },
+            admission,
+        );
+        let routes = services.routes().unwrap();
+        let response = warp::test::request()
+            .path("/admin/denylist")
+            .header("authorization", "Bearer secret")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_gas_price_route_mounted() {
+        use crate::mempool::MempoolConfig;
+
+        let admission = Arc::new(AdmissionFilter::new(AdmissionConfig::default()));
+        let mempool = Arc::new(Mempool::new(MempoolConfig {
+            gas_price_floor: 7,
+            ..Default::default()
+        }));
+        let services = NodeServices::new(ApiConfig::default(), admission).with_mempool(mempool);
+
+        let response = warp::test::request()
+            .path("/gas_price")
+            .reply(&services.routes().unwrap())
+            .await;
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["gas_price"], 7);
+    }
+
+    #[tokio::test]
+    async fn test_genesis_system_sender_sets_gas_price() {
+        use crate::core::Address;
+        use crate::crypto::{KeyPair, SignatureScheme};
+        use crate::framework::FrameworkConfig;
+        use crate::runtime::execution::ExecutionConfig;
+        use crate::storage::{self, MemStorage};
+        use crate::transaction::{SystemTransaction, Transaction, TransactionData};
+
+        let governance = Address::from_bytes([7; 20]);
+        let mut config = Config::default();
+        config.genesis.system_senders = vec![governance];
+        let storage = Arc::new(MemStorage::new());
+        let manager = transaction_manager(
+            &config,
+            RuntimeConfig {
+                execution: ExecutionConfig {
+                    max_gas_per_tx: 1_000_000,
+                    max_events: 128,
+                    execution_threads: 1,
+                    complexity_budget: 1_000_000,
+                },
+                framework: FrameworkConfig::default(),
+            },
+            storage.clone(),
+        ).unwrap();
+
+        let mut transaction = Transaction::new(
+            TransactionData::System(SystemTransaction::SetGasPrice(10)),
+            governance,
+            1_000,
+            1,
+            vec![],
+            0,
+            0,
+            config.chain_id.clone(),
+        );
+        transaction.sign(&KeyPair::generate(SignatureScheme::Ed25519).unwrap());
+
+        let digest = manager.submit_transaction(transaction).await.unwrap();
+        let effects = manager.execute_transaction(&digest).await.unwrap();
+        assert_eq!(effects.reference_gas_price, Some(10));
+        assert_eq!(storage::reference_gas_price(storage.as_ref()).unwrap(), Some(10));
+    }
+}
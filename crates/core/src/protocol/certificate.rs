@@ -1,11 +1,16 @@
 use super::{errors::*, types::*};
 use crate::crypto::{PublicKey, Signature};
-use std::collections::HashSet;
+use crate::utils::bcs;
+use std::collections::{HashMap, HashSet};
 
 pub struct CertificateBuilder {
     transaction: SignedTransaction,
     signatures: Vec<(PublicKey, Signature)>,
     signers: HashSet<PublicKey>,
+    // 已累计的权重
+    weight: u64,
+    // 委员会的法定人数阈值（添加第一个签名时记录）
+    quorum_threshold: Option<u64>,
 }
 
 impl CertificateBuilder {
@@ -14,6 +19,8 @@ impl CertificateBuilder {
             transaction,
             signatures: Vec::new(),
             signers: HashSet::new(),
+            weight: 0,
+            quorum_threshold: None,
         }
     }
 
@@ -21,6 +28,7 @@ impl CertificateBuilder {
         &mut self,
         authority: PublicKey,
         signature: Signature,
+        committee: &Committee,
     ) -> ProtocolResult<()> {
         // 检查是否已经有这个验证者的签名
         if self.signers.contains(&authority) {
@@ -29,24 +37,36 @@ impl CertificateBuilder {
             ));
         }
 
-        // 验证签名
-        if !signature.verify(&self.transaction.data, &authority) {
+        // 验证签名（BCS 编码的交易数据）
+        let message = bcs::to_bytes(&self.transaction.data)?;
+        if !signature.verify(&message, &authority) {
             return Err(ProtocolError::InvalidSignature(
                 "Invalid authority signature".into(),
             ));
         }
 
+        // 检查验证者是否在委员会中
+        let auth_weight = committee.weight(&authority)
+            .ok_or_else(|| ProtocolError::InvalidCertificate(
+                "Authority not in committee".into(),
+            ))?;
+
         self.signatures.push((authority, signature));
         self.signers.insert(authority);
+        self.weight += auth_weight;
+        self.quorum_threshold = Some(committee.quorum_threshold());
         Ok(())
     }
 
     pub fn build(self) -> ProtocolResult<TransactionCertificate> {
-        // 检查是否有足够的签名
-        if self.signatures.len() < 2 {  // 简化的示例，实际应该基于验证者权重
-            return Err(ProtocolError::InvalidCertificate(
-                "Insufficient signatures".into(),
-            ));
+        // 检查累计权重是否达到法定人数
+        match self.quorum_threshold {
+            Some(threshold) if self.weight >= threshold => {}
+            _ => {
+                return Err(ProtocolError::InvalidCertificate(
+                    "Insufficient quorum".into(),
+                ));
+            }
         }
 
         Ok(TransactionCertificate {
@@ -58,7 +78,8 @@ impl CertificateBuilder {
 
 impl TransactionCertificate {
     pub fn verify(&self, committee: &Committee) -> ProtocolResult<()> {
-        // 验证所有签名
+        // 验证所有签名（BCS 编码的交易数据）
+        let message = bcs::to_bytes(&self.transaction.data)?;
         let mut weight = 0;
         for (authority, signature) in &self.authority_signatures {
             // 检查验证者是否在委员会中
@@ -68,7 +89,7 @@ impl TransactionCertificate {
                 ))?;
 
             // 验证签名
-            if !signature.verify(&self.transaction.data, authority) {
+            if !signature.verify(&message, authority) {
                 return Err(ProtocolError::InvalidSignature(
                     "Invalid authority signature".into(),
                 ));
@@ -95,6 +116,14 @@ pub struct Committee {
 }
 
 impl Committee {
+    pub fn new(validators: HashMap<PublicKey, u64>) -> Self {
+        let total_weight = validators.values().sum();
+        Self {
+            validators,
+            total_weight,
+        }
+    }
+
     pub fn weight(&self, authority: &PublicKey) -> Option<u64> {
         self.validators.get(authority).copied()
     }
@@ -103,4 +132,58 @@ impl Committee {
         // 简化的 2/3 阈值计算
         (self.total_weight * 2) / 3 + 1
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, SignatureScheme};
+    use crate::transaction::test_utils::signed_transaction_by;
+
+    fn signed_transaction(sender: &KeyPair) -> SignedTransaction {
+        signed_transaction_by(sender, 1, 0, 0)
+    }
+
+    fn setup() -> (Vec<KeyPair>, Committee, SignedTransaction) {
+        let authorities: Vec<_> = (0..4)
+            .map(|_| KeyPair::generate(SignatureScheme::Ed25519).unwrap())
+            .collect();
+        let committee = Committee::new(
+            authorities.iter().map(|kp| (kp.public(), 1)).collect(),
+        );
+        let transaction = signed_transaction(&authorities[0]);
+        (authorities, committee, transaction)
+    }
+
+    #[test]
+    fn test_below_quorum() {
+        let (authorities, committee, transaction) = setup();
+        let message = bcs::to_bytes(&transaction.data).unwrap();
+        let mut builder = CertificateBuilder::new(transaction);
+
+        // 重复签名不应重复计入权重
+        for kp in &authorities[..2] {
+            builder.add_signature(kp.public(), kp.sign(&message), &committee).unwrap();
+        }
+        let kp = &authorities[1];
+        assert!(builder.add_signature(kp.public(), kp.sign(&message), &committee).is_err());
+
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn test_at_quorum() {
+        let (authorities, committee, transaction) = setup();
+        let message = bcs::to_bytes(&transaction.data).unwrap();
+        let mut builder = CertificateBuilder::new(transaction);
+
+        // 4 个验证者权重均为 1，阈值为 3
+        for kp in &authorities[..3] {
+            builder.add_signature(kp.public(), kp.sign(&message), &committee).unwrap();
+        }
+
+        let certificate = builder.build().unwrap();
+        assert_eq!(certificate.authority_signatures.len(), 3);
+        assert!(certificate.verify(&committee).is_ok());
+    }
+}
@@ -1,3 +1,8 @@
+use crate::execution::ExecutionError;
+use crate::storage::{ObjectWriteError, StorageError};
+use crate::transaction::TransactionDigest;
+use std::future::Future;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,6 +19,15 @@ pub enum ProtocolError {
     #[error("Object not found: {0}")]
     ObjectNotFound(String),
 
+    #[error("Transaction not found: {0:?}")]
+    TransactionNotFound(TransactionDigest),
+
+    #[error("Transaction already exists: {0:?}")]
+    TransactionExists(TransactionDigest),
+
+    #[error("Invalid transaction status")]
+    InvalidTransactionStatus,
+
     #[error("Version mismatch: expected {expected}, got {actual}")]
     VersionMismatch {
         expected: u64,
@@ -26,11 +40,313 @@ pub enum ProtocolError {
         available: u64,
     },
 
+    #[error("Gas budget {budget} below minimum {min}")]
+    GasBudgetTooLow {
+        budget: u64,
+        min: u64,
+    },
+
+    #[error("Gas price {price} below reference price {floor}")]
+    GasPriceTooLow {
+        price: u64,
+        floor: u64,
+    },
+
     #[error("Transaction expired")]
     TransactionExpired,
 
+    #[error("Too many dependencies: {count} exceeds limit {max}")]
+    TooManyDependencies {
+        count: usize,
+        max: usize,
+    },
+
+    #[error("Dependency cycle: {0}")]
+    DependencyCycle(String),
+
+    #[error("Backfill gap: range starts at {start}, next unindexed checkpoint is {next}")]
+    BackfillGap {
+        next: u64,
+        start: u64,
+    },
+
+    #[error("Chain id mismatch: expected {expected}, got {actual}")]
+    ChainIdMismatch {
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Execution error: {0}")]
+    ExecutionError(#[from] ExecutionError),
+
     #[error("System error: {0}")]
     SystemError(String),
+
+    #[error("Deserialization error: {0}")]
+    DeserializationError(String),
+
+    #[error("Timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] bincode::Error),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+}
+
+pub type ProtocolResult<T> = Result<T, ProtocolError>;
+
+impl From<rocksdb::Error> for ProtocolError {
+    fn from(error: rocksdb::Error) -> Self {
+        ProtocolError::Storage(error.into())
+    }
+}
+
+impl From<ObjectWriteError> for ProtocolError {
+    fn from(error: ObjectWriteError) -> Self {
+        ProtocolError::Storage(error.into())
+    }
+}
+
+/// Whether an operation that failed may succeed if retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Busy, timed out or interrupted; retrying may succeed
+    Transient,
+    /// Corruption, invalid input and the like; retrying won't help
+    Permanent,
+}
+
+impl ProtocolError {
+    /// Classify the error for retry decisions
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            ProtocolError::Storage(e) => e.class(),
+            ProtocolError::Timeout(_) => ErrorClass::Transient,
+            ProtocolError::Io(e) => match e.kind() {
+                std::io::ErrorKind::Interrupted
+                | std::io::ErrorKind::WouldBlock
+                | std::io::ErrorKind::TimedOut => ErrorClass::Transient,
+                _ => ErrorClass::Permanent,
+            },
+            _ => ErrorClass::Permanent,
+        }
+    }
+
+    /// Whether retrying may succeed
+    pub fn is_transient(&self) -> bool {
+        self.class() == ErrorClass::Transient
+    }
 }
 
-pub type ProtocolResult<T> = Result<T, ProtocolError>;
\ No newline at end of file
+/// Retry policy for transient errors, with exponential backoff
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts including the first; 1 disables retries
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between retries
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Run `op`, retrying transient errors until attempts run out.
+    /// Permanent errors are returned immediately.
+    pub async fn retry<T, F, Fut>(&self, mut op: F) -> ProtocolResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ProtocolResult<T>>,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match op().await {
+                Err(e) if e.is_transient() && attempt < self.max_attempts => {
+                    log::debug!("Attempt {} failed: {}; retrying in {:?}", attempt, e, backoff);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Like `retry`, for operations that aren't idempotent: before each
+    /// retry `completed` checks whether the failed attempt took effect
+    /// anyway, and its result is returned instead of running `op` again.
+    pub async fn retry_unless_completed<T, F, Fut, C, CFut>(
+        &self,
+        mut op: F,
+        mut completed: C,
+    ) -> ProtocolResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ProtocolResult<T>>,
+        C: FnMut() -> CFut,
+        CFut: Future<Output = ProtocolResult<Option<T>>>,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match op().await {
+                Err(e) if e.is_transient() && attempt < self.max_attempts => {
+                    log::debug!("Attempt {} failed: {}; retrying in {:?}", attempt, e, backoff);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                    attempt += 1;
+
+                    if let Some(result) = completed().await? {
+                        return Ok(result);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_missing() -> ProtocolResult<Vec<u8>> {
+        Ok(std::fs::read("/nonexistent/swift-network/file")?)
+    }
+
+    fn decode_garbage() -> ProtocolResult<u64> {
+        Ok(bincode::deserialize::<u64>(&[1, 2])?)
+    }
+
+    #[test]
+    fn test_io_error_converts() {
+        match read_missing() {
+            Err(ProtocolError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_serialization_error_converts() {
+        assert!(matches!(decode_garbage(), Err(ProtocolError::Serialization(_))));
+    }
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+        }
+    }
+
+    #[test]
+    fn test_error_classes() {
+        assert!(ProtocolError::Timeout(Duration::from_secs(1)).is_transient());
+        assert!(ProtocolError::Io(std::io::ErrorKind::Interrupted.into()).is_transient());
+        assert!(!ProtocolError::Io(std::io::ErrorKind::NotFound.into()).is_transient());
+    }
+
+    #[tokio::test]
+    async fn test_transient_error_retried() {
+        let mut calls = 0;
+        let result = fast_retry().retry(|| {
+            calls += 1;
+            let attempt = calls;
+            async move {
+                if attempt < 3 {
+                    Err(ProtocolError::Timeout(Duration::from_millis(10)))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        }).await;
+        assert_eq!(result.unwrap(), 3);
+
+        // Attempts run out
+        let mut calls = 0;
+        let result: ProtocolResult<()> = fast_retry().retry(|| {
+            calls += 1;
+            async { Err(ProtocolError::Timeout(Duration::from_millis(10))) }
+        }).await;
+        assert!(matches!(result, Err(ProtocolError::Timeout(_))));
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_error_propagates() {
+        let mut calls = 0;
+        let result: ProtocolResult<()> = fast_retry().retry(|| {
+            calls += 1;
+            async { Err(ProtocolError::DeserializationError("corrupt".into())) }
+        }).await;
+        assert!(matches!(result, Err(ProtocolError::DeserializationError(_))));
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_completed_attempt_not_repeated() {
+        use std::cell::Cell;
+
+        // The first attempt commits but reports a timeout
+        let executions = Cell::new(0);
+        let committed = Cell::new(None);
+        let result = fast_retry().retry_unless_completed(
+            || {
+                executions.set(executions.get() + 1);
+                committed.set(Some(7));
+                async { Err(ProtocolError::Timeout(Duration::from_millis(10))) }
+            },
+            || {
+                let stored = committed.get();
+                async move { Ok(stored) }
+            },
+        ).await;
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(executions.get(), 1);
+
+        // Nothing committed: retried until it succeeds
+        let executions = Cell::new(0);
+        let result = fast_retry().retry_unless_completed(
+            || {
+                executions.set(executions.get() + 1);
+                let attempt = executions.get();
+                async move {
+                    if attempt < 2 {
+                        Err(ProtocolError::Timeout(Duration::from_millis(10)))
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+            || async { Ok(None) },
+        ).await;
+        assert_eq!(result.unwrap(), 2);
+
+        // Permanent errors aren't retried
+        let executions = Cell::new(0);
+        let result: ProtocolResult<u32> = fast_retry().retry_unless_completed(
+            || {
+                executions.set(executions.get() + 1);
+                async { Err(ProtocolError::DeserializationError("corrupt".into())) }
+            },
+            || async { Ok(None) },
+        ).await;
+        assert!(matches!(result, Err(ProtocolError::DeserializationError(_))));
+        assert_eq!(executions.get(), 1);
+    }
+}
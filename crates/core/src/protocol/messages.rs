@@ -117,6 +117,10 @@ pub struct TransactionEffects {
     pub modified: Vec<ObjectID>,
     /// 删除的对象
     pub deleted: Vec<ObjectID>,
+    /// 被包装进其他对象的对象（仍存在，但不能再独立寻址）
+    pub wrapped: Vec<ObjectID>,
+    /// 从包装中取出、重新成为顶层对象的对象
+    pub unwrapped: Vec<ObjectID>,
     /// Gas 使用情况
     pub gas_used: u64,
     /// 状态变更
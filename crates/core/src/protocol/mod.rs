@@ -5,11 +5,11 @@ mod errors;
 mod messages;
 mod types;
 
-pub use certificate::{CertificateBuilder, TransactionCertificate};
-pub use errors::{ProtocolError, ProtocolResult};
+pub use certificate::{CertificateBuilder, Committee, TransactionCertificate};
+pub use errors::{ErrorClass, ProtocolError, ProtocolResult, RetryPolicy};
 pub use messages::{
-    ConsensusMessage, NetworkMessage, RequestMessage, ResponseMessage,
-    TransactionInfoRequest, TransactionInfoResponse,
+    ConsensusMessage, ExecutionStatus, NetworkMessage, RequestMessage, ResponseMessage,
+    TransactionEffects, TransactionInfoRequest, TransactionInfoResponse,
 };
 pub use types::{
     CallArg, SignedTransaction, StructTag, TransactionData,
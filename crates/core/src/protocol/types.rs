@@ -1,11 +1,24 @@
 use serde::{Deserialize, Serialize};
-use crate::core::{ObjectID, SequenceNumber};
+use crate::core::{ObjectID, ObjectRef, SequenceNumber};
 use crate::crypto::{PublicKey, Signature};
+use std::fmt;
 
 /// 交易摘要
-#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct TransactionDigest([u8; 32]);
 
+impl TransactionDigest {
+    /// 从字节创建
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// 获取字节
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
 /// 交易数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionData {
@@ -17,6 +30,8 @@ pub struct TransactionData {
     pub gas_budget: u64,
     /// Gas 价格
     pub gas_price: u64,
+    /// 给出块验证者的小费
+    pub tip: u64,
     /// 交易过期时间
     pub expiration: u64,
 }
@@ -79,10 +94,39 @@ pub struct StructTag {
     pub type_args: Vec<TypeTag>,
 }
 
+impl fmt::Display for TypeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeTag::Bool => write!(f, "bool"),
+            TypeTag::U8 => write!(f, "u8"),
+            TypeTag::U64 => write!(f, "u64"),
+            TypeTag::U128 => write!(f, "u128"),
+            TypeTag::Address => write!(f, "address"),
+            TypeTag::Vector(inner) => write!(f, "vector<{}>", inner),
+            TypeTag::Struct(tag) => write!(f, "{}", tag),
+        }
+    }
+}
+
+/// 规范格式：`address::module::name<T1, T2>`，泛型参数不同则字符串不同
+impl fmt::Display for StructTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}::{}", self.address, self.module, self.name)?;
+        if !self.type_args.is_empty() {
+            let args: Vec<String> = self.type_args.iter().map(|t| t.to_string()).collect();
+            write!(f, "<{}>", args.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
 /// 调用参数
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CallArg {
+    /// 纯值参数（BCS 编码）
     Pure(Vec<u8>),
-    Object(ObjectID),
-    ObjVec(Vec<ObjectID>),
+    /// 对象参数（指定版本）
+    Object(ObjectRef),
+    /// 对象数组参数
+    ObjVec(Vec<ObjectRef>),
 }
\ No newline at end of file
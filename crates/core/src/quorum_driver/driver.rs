@@ -1,11 +1,19 @@
-use super::{DriverError, DriverResult, DriverStatus};
+use super::{DriverError, DriverResult, DriverStatus, RateLimiter};
+use crate::authority::{AuthorityError, AuthorityStore, ObjectLockGuard};
 use crate::consensus::{ConsensusState, Certificate};
+use crate::core::ObjectRef;
 use crate::network::{NetworkService, NetworkMessage};
-use crate::protocol::{Transaction, TransactionDigest, TransactionEffects};
-use crate::storage::Storage;
+use crate::protocol::{RetryPolicy, Transaction, TransactionDigest, TransactionEffects};
+use crate::storage::{Event, EventType, Storage, TransactionEvent};
+use crate::transaction::TransactionManager;
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, RwLock, Semaphore};
 use tokio::time::timeout;
 
 /// Driver configuration
@@ -17,8 +25,14 @@ pub struct DriverConfig {
     pub timeout: Duration,
     /// Maximum pending transactions
     pub max_pending_transactions: usize,
-    /// Maximum concurrent tasks
+    /// Maximum transactions processed concurrently; the rest wait in the queue
     pub max_concurrent_tasks: usize,
+    /// Submissions per second allowed per sender
+    pub sender_rate_limit: f64,
+    /// Submission burst allowed per sender
+    pub sender_burst: u32,
+    /// Retry policy for transient storage errors
+    pub storage_retry: RetryPolicy,
 }
 
 impl Default for DriverConfig {
@@ -28,6 +42,9 @@ impl Default for DriverConfig {
             timeout: Duration::from_secs(30),
             max_pending_transactions: 10000,
             max_concurrent_tasks: 100,
+            sender_rate_limit: 10.0,
+            sender_burst: 50,
+            storage_retry: RetryPolicy::default(),
         }
     }
 }
@@ -56,6 +73,12 @@ pub struct QuorumDriver {
     pending_transactions: RwLock<HashMap<TransactionDigest, PendingTransaction>>,
     /// Transaction sender
     tx_sender: mpsc::Sender<(Transaction, mpsc::Sender<DriverResult<TransactionEffects>>)>,
+    /// Per-sender submission rate limiter
+    rate_limiter: RateLimiter<String>,
+    /// Store whose owned object locks cover transactions in flight
+    authority_store: Option<Arc<AuthorityStore>>,
+    /// Node pipeline told when its submitted transactions are certified
+    transaction_manager: Option<Arc<TransactionManager>>,
 }
 
 impl QuorumDriver {
@@ -66,6 +89,7 @@ impl QuorumDriver {
         storage: Arc<dyn Storage>,
     ) -> Self {
         let (tx_sender, tx_receiver) = mpsc::channel(config.max_pending_transactions);
+        let rate_limiter = RateLimiter::new(config.sender_rate_limit, config.sender_burst);
         
         let driver = Self {
             config,
@@ -74,6 +98,9 @@ impl QuorumDriver {
             status: RwLock::new(DriverStatus::Active),
             pending_transactions: RwLock::new(HashMap::new()),
             tx_sender,
+            rate_limiter,
+            authority_store: None,
+            transaction_manager: None,
         };
 
         // Start transaction processor
@@ -82,23 +109,33 @@ impl QuorumDriver {
         driver
     }
 
+    /// Lock owned inputs in `store` while their transaction is in flight
+    pub fn with_authority_store(mut self, store: Arc<AuthorityStore>) -> Self {
+        self.authority_store = Some(store);
+        self
+    }
+
+    /// Mark transactions certified in `manager` once their certificate
+    /// verifies
+    pub fn with_transaction_manager(mut self, manager: Arc<TransactionManager>) -> Self {
+        self.transaction_manager = Some(manager);
+        self
+    }
+
     /// Start transaction processor
     fn start_transaction_processor(
         &self,
-        mut tx_receiver: mpsc::Receiver<(Transaction, mpsc::Sender<DriverResult<TransactionEffects>>)>,
+        tx_receiver: mpsc::Receiver<(Transaction, mpsc::Sender<DriverResult<TransactionEffects>>)>,
     ) {
         let driver = Arc::new(self.clone());
-        
-        tokio::spawn(async move {
-            while let Some((transaction, response_sender)) = tx_receiver.recv().await {
-                let driver = driver.clone();
-                
-                tokio::spawn(async move {
-                    let result = driver.process_transaction(transaction, response_sender.clone()).await;
-                    if let Err(e) = result {
-                        let _ = response_sender.send(Err(e)).await;
-                    }
-                });
+
+        spawn_limited(tx_receiver, self.config.max_concurrent_tasks, move |(transaction, response_sender)| {
+            let driver = driver.clone();
+            async move {
+                let result = driver.process_transaction(transaction, response_sender.clone()).await;
+                if let Err(e) = result {
+                    let _ = response_sender.send(Err(e)).await;
+                }
             }
         });
     }
@@ -117,6 +154,33 @@ impl QuorumDriver {
         // Get transaction digest
         let digest = transaction.digest();
 
+        // Hold the owned inputs until quorum is reached or the attempt ends;
+        // the guard releases them on failure, timeout or cancellation
+        let result = async {
+            let _locks = match &self.authority_store {
+                Some(store) => Some(lock_owned_inputs(store, &transaction.input_object_refs(), digest).await?),
+                None => None,
+            };
+            self.drive_transaction(digest, transaction, response_sender).await
+        }.await;
+
+        if let Err(e) = &result {
+            self.pending_transactions.write().await.remove(&digest);
+            if let Err(emit_error) = self.storage.emit_event(failed_event(&digest, e)) {
+                log::warn!("Failed to record failure of {:?}: {}", digest, emit_error);
+            }
+        }
+
+        result
+    }
+
+    /// Broadcast a transaction and wait for quorum
+    async fn drive_transaction(
+        &self,
+        digest: TransactionDigest,
+        transaction: Transaction,
+        response_sender: mpsc::Sender<DriverResult<TransactionEffects>>,
+    ) -> DriverResult<()> {
         // Create pending transaction
         let pending = PendingTransaction {
             transaction: transaction.clone(),
@@ -128,17 +192,11 @@ impl QuorumDriver {
         self.pending_transactions.write().await.insert(digest, pending);
 
         // Broadcast transaction
-        self.network.broadcast(NetworkMessage::Transaction(transaction)).await
-            .map_err(|e| DriverError::NetworkError(e.to_string()))?;
+        self.network.broadcast(NetworkMessage::Transaction(transaction)).await?;
 
         // Wait for quorum with timeout
-        if let Err(_) = timeout(self.config.timeout, self.wait_for_quorum(digest)).await {
-            // Remove pending transaction
-            self.pending_transactions.write().await.remove(&digest);
-            return Err(DriverError::TimeoutError("Quorum timeout".into()));
-        }
-
-        Ok(())
+        timeout(self.config.timeout, self.wait_for_quorum(digest)).await
+            .map_err(|_| DriverError::Timeout(self.config.timeout))?
     }
 
     /// Wait for quorum
@@ -147,7 +205,7 @@ impl QuorumDriver {
             // Get pending transaction
             let pending = self.pending_transactions.read().await
                 .get(&digest)
-                .ok_or_else(|| DriverError::ConsensusError("Transaction not found".into()))?
+                .ok_or_else(|| DriverError::TransactionNotFound(digest.clone()))?
                 .clone();
 
             // Check if we have quorum
@@ -182,12 +240,22 @@ impl QuorumDriver {
         certificate.verify()
             .map_err(|e| DriverError::CertificateError(e.to_string()))?;
 
-        // Store certificate
-        self.storage.put_certificate(&certificate).await
+        let digest = certificate.transaction.digest();
+        if let Some(manager) = &self.transaction_manager {
+            note_certified(manager, &digest).await;
+        }
+
+        // Store certificate, retrying transient storage errors
+        let retry = &self.config.storage_retry;
+        retry.retry(|| self.storage.put_certificate(&certificate)).await
             .map_err(|e| DriverError::ConsensusError(e.to_string()))?;
 
-        // Execute transaction
-        let effects = self.storage.execute_certificate(&certificate).await
+        // Execution isn't idempotent: before retrying a transient error,
+        // return the effects if the failed attempt committed anyway
+        let effects = retry.retry_unless_completed(
+            || self.storage.execute_certificate(&certificate),
+            || self.storage.get_effects(&digest),
+        ).await
             .map_err(|e| DriverError::ConsensusError(e.to_string()))?;
 
         Ok(effects)
@@ -198,18 +266,37 @@ impl QuorumDriver {
         &self,
         transaction: Transaction,
     ) -> DriverResult<TransactionEffects> {
+        // Check sender quota
+        self.rate_limiter.check(&transaction.sender().to_string()).await?;
+
         // Create response channel
         let (response_sender, mut response_receiver) = mpsc::channel(1);
 
-        // Send transaction to processor
-        self.tx_sender.send((transaction, response_sender)).await
-            .map_err(|e| DriverError::ConsensusError(e.to_string()))?;
+        // Hand off to processor, shedding load if the queue is full
+        try_enqueue(&self.tx_sender, (transaction, response_sender))?;
 
         // Wait for response
         response_receiver.recv().await
             .ok_or_else(|| DriverError::ConsensusError("Response channel closed".into()))?
     }
 
+    /// Submit a batch of transactions concurrently, at most
+    /// `max_concurrent_tasks` at a time. Results are returned in input order;
+    /// a failed transaction doesn't affect the rest of the batch.
+    pub async fn submit_transactions(
+        &self,
+        transactions: Vec<Transaction>,
+    ) -> Vec<DriverResult<TransactionEffects>> {
+        submit_ordered(transactions, self.config.max_concurrent_tasks, |transaction| {
+            self.submit_transaction(transaction)
+        }).await
+    }
+
+    /// Number of submitted transactions waiting for the processor
+    pub fn pending_count(&self) -> usize {
+        queued(&self.tx_sender)
+    }
+
     /// Handle signature
     pub async fn handle_signature(
         &self,
@@ -220,7 +307,7 @@ impl QuorumDriver {
         // Get pending transaction
         let mut pending_transactions = self.pending_transactions.write().await;
         let pending = pending_transactions.get_mut(&digest)
-            .ok_or_else(|| DriverError::ConsensusError("Transaction not found".into()))?;
+            .ok_or_else(|| DriverError::TransactionNotFound(digest.clone()))?;
 
         // Add signature
         pending.signatures.push((public_key, signature));
@@ -237,4 +324,287 @@ impl QuorumDriver {
     pub async fn set_status(&self, status: DriverStatus) {
         *self.status.write().await = status;
     }
-}
\ No newline at end of file
+}
+
+/// Run `handle` on each received item with at most `limit` running at once.
+/// Items beyond the limit stay queued in the channel until a task finishes.
+fn spawn_limited<T, F, Fut>(
+    mut receiver: mpsc::Receiver<T>,
+    limit: usize,
+    handle: F,
+) -> tokio::task::JoinHandle<()>
+where
+    T: Send + 'static,
+    F: Fn(T) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(limit.max(1)));
+
+    tokio::spawn(async move {
+        loop {
+            // Wait for a free slot before taking the next item off the queue
+            let permit = match semaphore.clone().acquire_owned().await {
+                Ok(permit) => permit,
+                Err(_) => break,
+            };
+            let Some(item) = receiver.recv().await else { break };
+
+            let task = handle(item);
+            tokio::spawn(async move {
+                task.await;
+                drop(permit);
+            });
+        }
+    })
+}
+
+/// Run `submit` on every item with at most `limit` in flight, collecting
+/// the results in input order
+async fn submit_ordered<T, R, F, Fut>(items: Vec<T>, limit: usize, submit: F) -> Vec<R>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = R>,
+{
+    stream::iter(items).map(submit).buffered(limit.max(1)).collect().await
+}
+
+/// Lock the owned objects among `refs` for `digest`; shared and immutable
+/// inputs stay available to other transactions
+async fn lock_owned_inputs(
+    store: &Arc<AuthorityStore>,
+    refs: &[ObjectRef],
+    digest: TransactionDigest,
+) -> DriverResult<ObjectLockGuard> {
+    store.acquire_owned_locks(refs, digest).await.map_err(|e| match e {
+        AuthorityError::ObjectLocked { object, .. } => DriverError::ObjectLocked(object),
+        e => DriverError::ConsensusError(e.to_string()),
+    })
+}
+
+/// Event recording that the driver gave up on a transaction
+fn failed_event(digest: &TransactionDigest, error: &DriverError) -> Event {
+    Event {
+        id: String::new(),
+        type_: EventType::Transaction(TransactionEvent::Failed {
+            tx_digest: hex::encode(digest.as_bytes()),
+            reason: error.to_string(),
+        }),
+        timestamp: Utc::now(),
+        metadata: None,
+    }
+}
+
+/// Advance `digest` to `Certified` in `manager`. Transactions not
+/// submitted through the manager have no status to advance.
+async fn note_certified(manager: &TransactionManager, digest: &TransactionDigest) {
+    if let Err(e) = manager.mark_certified(digest).await {
+        log::debug!("Not marking {:?} certified: {}", digest, e);
+    }
+}
+
+/// Number of items queued in a bounded channel
+fn queued<T>(sender: &mpsc::Sender<T>) -> usize {
+    sender.max_capacity() - sender.capacity()
+}
+
+/// Queue without waiting, returning `Overloaded` when the channel is full
+fn try_enqueue<T>(sender: &mpsc::Sender<T>, item: T) -> DriverResult<()> {
+    sender.try_send(item).map_err(|e| match e {
+        TrySendError::Full(_) => DriverError::Overloaded(queued(sender)),
+        TrySendError::Closed(_) => DriverError::ConsensusError("Processor stopped".into()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_overloaded_when_full() {
+        let (sender, _receiver) = mpsc::channel(2);
+
+        try_enqueue(&sender, 1).unwrap();
+        try_enqueue(&sender, 2).unwrap();
+        assert_eq!(queued(&sender), 2);
+
+        assert!(matches!(try_enqueue(&sender, 3), Err(DriverError::Overloaded(2))));
+    }
+
+    #[test]
+    fn test_network_error_converts() {
+        fn broadcast() -> DriverResult<()> {
+            let io: crate::network::NetworkResult<()> =
+                Err(std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into());
+            Ok(io?)
+        }
+
+        assert!(matches!(
+            broadcast(),
+            Err(DriverError::Network(crate::network::NetworkError::Io(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let limit = 3;
+        let total = 20;
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicUsize::new(0));
+
+        let (sender, receiver) = mpsc::channel(total);
+        for i in 0..total {
+            sender.try_send(i).unwrap();
+        }
+        drop(sender);
+
+        let (gauge, max, finished) = (in_flight.clone(), peak.clone(), done.clone());
+        spawn_limited(receiver, limit, move |_| {
+            let (gauge, max, finished) = (gauge.clone(), max.clone(), finished.clone());
+            async move {
+                let current = gauge.fetch_add(1, Ordering::SeqCst) + 1;
+                max.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                gauge.fetch_sub(1, Ordering::SeqCst);
+                finished.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        timeout(Duration::from_secs(5), async {
+            while done.load(Ordering::SeqCst) < total {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await.unwrap();
+
+        assert_eq!(peak.load(Ordering::SeqCst), limit);
+        assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_batch_results_are_positional() {
+        let results = submit_ordered(vec![1u64, 0, 3], 2, |fee| async move {
+            // Later items finish first
+            tokio::time::sleep(Duration::from_millis(10 / (fee + 1))).await;
+            if fee == 0 {
+                Err(DriverError::ConsensusError("invalid transaction".into()))
+            } else {
+                Ok(fee * 10)
+            }
+        }).await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &10);
+        assert!(matches!(results[1], Err(DriverError::ConsensusError(_))));
+        assert_eq!(results[2].as_ref().unwrap(), &30);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_releases_object_locks() {
+        use crate::authority::StoreConfig;
+        use crate::core::{Address, Object, ObjectID, Owner, SequenceNumber, TypeTag};
+        use crate::storage::test_utils::temp_storage;
+
+        let (_temp_dir, storage) = temp_storage();
+        let store = Arc::new(AuthorityStore::new(StoreConfig {
+            storage,
+            cache_size: 128,
+        }).unwrap());
+
+        let object = |owner: Owner| {
+            let mut object = Object::new(ObjectID::random(), owner, TypeTag::U64, vec![]);
+            object.set_version(SequenceNumber::new(1));
+            object
+        };
+        let owned = object(Owner::AddressOwner(Address::from_bytes([1; 20])));
+        let shared = object(Owner::Shared { initial_shared_version: SequenceNumber::new(1) });
+        store.put_object(owned.clone()).await.unwrap();
+        store.put_object(shared.clone()).await.unwrap();
+        let owned = ObjectRef::new(owned.id(), owned.version());
+        let shared = ObjectRef::new(shared.id(), shared.version());
+
+        let stalled = TransactionDigest::from_bytes([1; 32]);
+        let next = TransactionDigest::from_bytes([2; 32]);
+
+        let result: DriverResult<()> = async {
+            let _locks = lock_owned_inputs(&store, &[owned, shared], stalled).await?;
+
+            // The conflicting transaction can't start while the first holds
+            // the owned input, but the shared one stays available
+            assert!(matches!(
+                lock_owned_inputs(&store, &[owned], next).await,
+                Err(DriverError::ObjectLocked(_))
+            ));
+            drop(lock_owned_inputs(&store, &[shared], next).await?);
+
+            let quorum = std::future::pending::<DriverResult<()>>();
+            timeout(Duration::from_millis(10), quorum).await
+                .map_err(|_| DriverError::Timeout(Duration::from_millis(10)))?
+        }.await;
+        assert!(matches!(result, Err(DriverError::Timeout(_))));
+
+        let event = failed_event(&stalled, result.as_ref().unwrap_err());
+        assert!(matches!(event.type_, EventType::Transaction(TransactionEvent::Failed { .. })));
+
+        // Once the first transaction times out the object is free
+        drop(lock_owned_inputs(&store, &[owned], next).await.unwrap());
+
+        // A cancelled attempt releases its locks too
+        let attempt = async {
+            let _locks = lock_owned_inputs(&store, &[owned], stalled).await?;
+            std::future::pending::<DriverResult<()>>().await
+        };
+        assert!(timeout(Duration::from_millis(10), attempt).await.is_err());
+        lock_owned_inputs(&store, &[owned], next).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_certificate_marks_submitted_transaction_certified() {
+        use crate::config::Config;
+        use crate::core::Address;
+        use crate::crypto::{KeyPair, SignatureScheme};
+        use crate::framework::FrameworkConfig;
+        use crate::node::transaction_manager;
+        use crate::runtime::execution::ExecutionConfig;
+        use crate::runtime::RuntimeConfig;
+        use crate::storage::MemStorage;
+        use crate::transaction::{SystemTransaction, Transaction, TransactionData, TransactionStatus};
+
+        let config = Config::default();
+        let manager = transaction_manager(
+            &config,
+            RuntimeConfig {
+                execution: ExecutionConfig {
+                    max_gas_per_tx: 1_000_000,
+                    max_events: 128,
+                    execution_threads: 1,
+                    complexity_budget: 1_000_000,
+                },
+                framework: FrameworkConfig::default(),
+            },
+            Arc::new(MemStorage::new()),
+        ).unwrap();
+
+        let mut transaction = Transaction::new(
+            TransactionData::System(SystemTransaction::SetGasPrice(10)),
+            Address::from_bytes([1; 20]),
+            1_000,
+            1,
+            vec![],
+            0,
+            0,
+            config.chain_id.clone(),
+        );
+        transaction.sign(&KeyPair::generate(SignatureScheme::Ed25519).unwrap());
+        let digest = manager.submit_transaction(transaction).await.unwrap();
+
+        note_certified(&manager, &digest).await;
+        assert_eq!(manager.status(&digest).await, TransactionStatus::Certified);
+
+        // Certificates for transactions the manager never saw are ignored
+        let unknown = TransactionDigest::from_bytes([9; 32]);
+        note_certified(&manager, &unknown).await;
+        assert_eq!(manager.status(&unknown).await, TransactionStatus::NotFound);
+    }
+}
@@ -1,10 +1,14 @@
 //! Quorum driver module for consensus.
 
 mod driver;
+mod rate_limiter;
 
 pub use driver::{QuorumDriver, DriverConfig};
+pub use rate_limiter::RateLimiter;
 
-use crate::protocol::{ProtocolError, ProtocolResult};
+use crate::network::NetworkError;
+use crate::protocol::{ProtocolError, ProtocolResult, TransactionDigest};
+use std::time::Duration;
 
 /// Quorum driver error types
 #[derive(Debug, thiserror::Error)]
@@ -15,11 +19,29 @@ pub enum DriverError {
     #[error("Certificate error: {0}")]
     CertificateError(String),
 
-    #[error("Timeout error: {0}")]
-    TimeoutError(String),
+    #[error("Quorum not reached after {0:?}")]
+    Timeout(Duration),
+
+    #[error("Transaction not found: {0:?}")]
+    TransactionNotFound(TransactionDigest),
 
     #[error("Network error: {0}")]
-    NetworkError(String),
+    Network(#[from] NetworkError),
+
+    #[error("Protocol error: {0}")]
+    Protocol(#[from] ProtocolError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Driver overloaded: {0} transactions pending")]
+    Overloaded(usize),
+
+    #[error("Object {0} is locked by another transaction")]
+    ObjectLocked(String),
 }
 
 pub type DriverResult<T> = Result<T, DriverError>;
@@ -0,0 +1,124 @@
+use super::{DriverError, DriverResult};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Instant;
+use tokio::sync::RwLock;
+
+/// Token bucket state
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    /// Available tokens
+    tokens: f64,
+    /// Last refill time
+    last_refill: Instant,
+}
+
+/// Buckets by key, with the time idle ones were last evicted
+struct Buckets<K> {
+    /// Buckets by key
+    by_key: HashMap<K, TokenBucket>,
+    /// Last eviction time
+    last_eviction: Instant,
+}
+
+/// Per-key token bucket rate limiter
+pub struct RateLimiter<K> {
+    /// Tokens added per second
+    rate: f64,
+    /// Bucket capacity
+    burst: f64,
+    /// Buckets by key
+    buckets: RwLock<Buckets<K>>,
+}
+
+impl<K: Hash + Eq + Clone + std::fmt::Debug> RateLimiter<K> {
+    /// Create new rate limiter
+    pub fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            rate,
+            burst: burst as f64,
+            buckets: RwLock::new(Buckets {
+                by_key: HashMap::new(),
+                last_eviction: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take one token for key, failing if its bucket is empty
+    pub async fn check(&self, key: &K) -> DriverResult<()> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.write().await;
+
+        // A bucket that has refilled completely is no different from the
+        // full one an unseen key starts with, so drop it. Any idle bucket
+        // refills within `burst / rate`, so sweeping once per that period
+        // is enough.
+        if now.duration_since(buckets.last_eviction).as_secs_f64() * self.rate >= self.burst {
+            let (rate, burst) = (self.rate, self.burst);
+            buckets.by_key.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.last_refill).as_secs_f64() * rate < burst
+            });
+            buckets.last_eviction = now;
+        }
+
+        // Unseen keys start with a full bucket
+        let bucket = buckets.by_key.entry(key.clone()).or_insert_with(|| TokenBucket {
+            tokens: self.burst,
+            last_refill: now,
+        });
+
+        // Refill
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens < 1.0 {
+            return Err(DriverError::RateLimited(format!("{:?}", key)));
+        }
+        bucket.tokens -= 1.0;
+
+        Ok(())
+    }
+
+    /// Number of keys with a bucket
+    pub async fn tracked_keys(&self) -> usize {
+        self.buckets.read().await.by_key.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rate_limiter_burst() {
+        let limiter = RateLimiter::new(0.001, 3);
+        let flooder = "flooder".to_string();
+        let honest = "honest".to_string();
+
+        for _ in 0..3 {
+            assert!(limiter.check(&flooder).await.is_ok());
+        }
+        assert!(matches!(
+            limiter.check(&flooder).await,
+            Err(DriverError::RateLimited(_))
+        ));
+
+        // Other senders have their own bucket
+        assert!(limiter.check(&honest).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_idle_buckets_evicted() {
+        // Buckets refill within 10ms
+        let limiter = RateLimiter::new(100.0, 1);
+        for i in 0..100 {
+            limiter.check(&format!("sender-{}", i)).await.unwrap();
+        }
+        assert_eq!(limiter.tracked_keys().await, 100);
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        limiter.check(&"active".to_string()).await.unwrap();
+        assert_eq!(limiter.tracked_keys().await, 1);
+    }
+}
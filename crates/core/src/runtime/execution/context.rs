@@ -1,9 +1,11 @@
+use super::TransactionRng;
+use crate::execution::ExecutionError;
 use crate::protocol::{ProtocolError, ProtocolResult};
 use move_core_types::{
     account_address::AccountAddress,
     effects::Event,
     identifier::Identifier,
-    language_storage::{ModuleId, StructTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
     value::MoveValue,
 };
 use std::collections::HashMap;
@@ -14,8 +16,17 @@ pub struct ExecutionContext {
     state: StateView,
     /// Events
     events: Vec<Event>,
+    /// Maximum events the transaction may emit
+    max_events: Option<usize>,
+    /// Set once the event limit has been exceeded
+    events_exceeded: bool,
+    /// Deterministic random source for natives
+    rng: Option<TransactionRng>,
     /// Published modules
     modules: HashMap<ModuleId, Vec<u8>>,
+    /// Modules published at each address, including those loaded from
+    /// storage
+    module_counts: HashMap<AccountAddress, usize>,
     /// Resources
     resources: HashMap<(AccountAddress, StructTag), Vec<u8>>,
 }
@@ -25,7 +36,11 @@ impl ExecutionContext {
         Self {
             state,
             events: Vec::new(),
+            max_events: None,
+            events_exceeded: false,
+            rng: None,
             modules: HashMap::new(),
+            module_counts: HashMap::new(),
             resources: HashMap::new(),
         }
     }
@@ -45,9 +60,45 @@ impl ExecutionContext {
         &self.events
     }
 
-    /// Add event
-    pub fn add_event(&mut self, event: Event) {
+    /// Limit the number of events the transaction may emit
+    pub fn set_max_events(&mut self, max_events: usize) {
+        self.max_events = Some(max_events);
+    }
+
+    /// Add event, failing once the event limit is exceeded. Events emitted
+    /// so far are discarded along with the aborted transaction.
+    pub fn add_event(&mut self, event: Event) -> ProtocolResult<()> {
+        if let Some(max) = self.max_events {
+            if self.events_exceeded || self.events.len() >= max {
+                self.events_exceeded = true;
+                self.events.clear();
+                return Err(ProtocolError::ExecutionError(ExecutionError::TooManyEvents { max }));
+            }
+        }
         self.events.push(event);
+        Ok(())
+    }
+
+    /// Fail if the event limit was exceeded during execution
+    pub fn check_event_limit(&self) -> ProtocolResult<()> {
+        match self.max_events {
+            Some(max) if self.events_exceeded => {
+                Err(ProtocolError::ExecutionError(ExecutionError::TooManyEvents { max }))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Seed the transaction's random source
+    pub fn seed_rng(&mut self, tx_digest: &[u8], beacon: &[u8; 32]) {
+        self.rng = Some(TransactionRng::new(tx_digest, beacon));
+    }
+
+    /// Random source for natives; fails if the transaction wasn't seeded
+    pub fn rng(&mut self) -> ProtocolResult<&mut TransactionRng> {
+        self.rng.as_mut().ok_or_else(|| ProtocolError::ExecutionError(
+            ExecutionError::ExecutionError("Random source not seeded".into()),
+        ))
     }
 
     /// Get module
@@ -57,7 +108,41 @@ impl ExecutionContext {
 
     /// Add module
     pub fn add_module(&mut self, id: ModuleId, module: Vec<u8>) {
-        self.modules.insert(id, module);
+        let address = *id.address();
+        if self.modules.insert(id, module).is_none() {
+            *self.module_counts.entry(address).or_default() += 1;
+        }
+    }
+
+    /// Number of modules published at `address`
+    pub fn module_count(&self, address: &AccountAddress) -> usize {
+        self.module_counts.get(address).copied().unwrap_or_default()
+    }
+
+    /// Record `count` modules already published at `address` in storage.
+    /// Only the first load of an address counts.
+    pub fn load_module_count(&mut self, address: AccountAddress, count: usize) {
+        self.module_counts.entry(address).or_insert(count);
+    }
+
+    /// Reject publishing a module at each of `addresses` if any address
+    /// would end up with more than `max` modules, counting those already
+    /// published
+    pub fn check_module_count(&self, addresses: &[AccountAddress], max: usize) -> ProtocolResult<()> {
+        let mut new_modules: HashMap<AccountAddress, usize> = HashMap::new();
+        for address in addresses {
+            *new_modules.entry(*address).or_default() += 1;
+        }
+
+        for (address, count) in new_modules {
+            let published = self.module_count(&address);
+            if published + count > max {
+                return Err(ProtocolError::ExecutionError(
+                    ExecutionError::TooManyModules { address, max },
+                ));
+            }
+        }
+        Ok(())
     }
 
     /// Get resource
@@ -89,6 +174,15 @@ impl ExecutionContext {
     }
 }
 
+/// Number of nodes in a type tag, the complexity of instantiating it
+pub fn type_complexity(tag: &TypeTag) -> u64 {
+    match tag {
+        TypeTag::Vector(inner) => 1 + type_complexity(inner),
+        TypeTag::Struct(tag) => 1 + tag.type_params.iter().map(type_complexity).sum::<u64>(),
+        _ => 1,
+    }
+}
+
 /// Execution result
 #[derive(Debug)]
 pub struct ExecutionResult {
@@ -96,6 +190,8 @@ pub struct ExecutionResult {
     pub return_values: Vec<MoveValue>,
     /// Events
     pub events: Vec<Event>,
+    /// Tip charged to the sender, owed to the proposer
+    pub tip: u64,
 }
 
 impl ExecutionResult {
@@ -103,6 +199,71 @@ impl ExecutionResult {
         Self {
             return_values,
             events,
+            tip: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::language_storage::TypeTag;
+
+    #[test]
+    fn test_type_complexity_counts_nodes() {
+        let nested = TypeTag::Vector(Box::new(TypeTag::Vector(Box::new(TypeTag::U8))));
+        assert_eq!(type_complexity(&nested), 3);
+        assert_eq!(type_complexity(&TypeTag::U64), 1);
+    }
+
+    #[test]
+    fn test_deterministic_rng() {
+        use crate::runtime::execution::epoch_beacon;
+
+        let draws = |digest: &[u8], epoch: u64| {
+            let mut context = ExecutionContext::new(StateView::default());
+            assert!(context.rng().is_err());
+            context.seed_rng(digest, &epoch_beacon(epoch, b"committee output"));
+            let rng = context.rng().unwrap();
+            let mut bytes = [0u8; 40];
+            rng.fill_bytes(&mut bytes);
+            (rng.next_u64(), rng.next_u64(), bytes)
+        };
+
+        // Every execution of a transaction draws the same values
+        assert_eq!(draws(&[1; 32], 7), draws(&[1; 32], 7));
+        assert_ne!(draws(&[1; 32], 7), draws(&[2; 32], 7));
+        assert_ne!(draws(&[1; 32], 7), draws(&[1; 32], 8));
+
+        // The beacon depends on the committee output, not just the epoch
+        assert_ne!(epoch_beacon(7, b"committee output"), epoch_beacon(7, b"other output"));
+    }
+
+    #[test]
+    fn test_module_count_limit() {
+        let mut context = ExecutionContext::new(StateView::default());
+        for name in ["coin", "pay"] {
+            let id = ModuleId::new(AccountAddress::TWO, Identifier::new(name).unwrap());
+            context.add_module(id, vec![]);
         }
+
+        // Two published plus two new reaches the limit
+        let addresses = [AccountAddress::TWO; 2];
+        assert!(context.check_module_count(&addresses, 4).is_ok());
+
+        // One more is rejected
+        let addresses = [AccountAddress::TWO; 3];
+        assert!(matches!(
+            context.check_module_count(&addresses, 4),
+            Err(ProtocolError::ExecutionError(ExecutionError::TooManyModules { max: 4, .. }))
+        ));
+
+        // Other addresses have their own quota
+        assert!(context.check_module_count(&[AccountAddress::ONE; 4], 4).is_ok());
+
+        // Modules published in earlier transactions count too
+        context.load_module_count(AccountAddress::ONE, 3);
+        assert!(context.check_module_count(&[AccountAddress::ONE], 4).is_ok());
+        assert!(context.check_module_count(&[AccountAddress::ONE; 2], 4).is_err());
     }
-}
\ No newline at end of file
+}
@@ -1,11 +1,25 @@
 //! Execution module for transaction processing.
 
 mod context;
+mod natives;
+mod random;
+mod workers;
 
-pub use context::{ExecutionContext, ExecutionResult};
+pub use context::{type_complexity, ExecutionContext, ExecutionResult};
+pub use natives::{
+    call_event_native, call_random_native, event_module, native_functions, random_module,
+};
+pub use random::{epoch_beacon, TransactionRng};
+pub use workers::WorkerPool;
 
+use crate::execution::{GasSchedule, GasStatus, GasUnit};
+use crate::framework::FrameworkConfig;
 use crate::protocol::{ProtocolError, ProtocolResult};
+use crate::storage::{self, Storage};
+use move_binary_format::CompiledModule;
+use move_core_types::{account_address::AccountAddress, language_storage::TypeTag};
 use move_vm_runtime::session::Session;
+use std::sync::RwLock;
 
 /// Execution engine configuration
 #[derive(Debug, Clone)]
@@ -14,6 +28,11 @@ pub struct ExecutionConfig {
     pub max_gas_per_tx: u64,
     /// Maximum number of events
     pub max_events: usize,
+    /// Worker threads for bytecode verification
+    pub execution_threads: usize,
+    /// Complexity units a transaction may use, charged for type
+    /// instantiations and argument sizes independently of gas
+    pub complexity_budget: u64,
 }
 
 /// Execution engine
@@ -22,11 +41,45 @@ pub struct ExecutionEngine {
     config: ExecutionConfig,
     /// Move VM
     vm: Arc<MoveVM>,
+    /// Verification workers
+    workers: WorkerPool,
+    /// Randomness beacon of the current epoch
+    beacon: RwLock<[u8; 32]>,
+    /// Maximum modules published at one address
+    max_modules_per_address: usize,
+    /// Storage holding the number of modules published at each address
+    storage: Option<Arc<dyn Storage>>,
 }
 
 impl ExecutionEngine {
     pub fn new(config: ExecutionConfig, vm: Arc<MoveVM>) -> Self {
-        Self { config, vm }
+        let workers = WorkerPool::new(config.execution_threads);
+        Self {
+            config,
+            vm,
+            workers,
+            beacon: RwLock::new(epoch_beacon(0, &[])),
+            max_modules_per_address: FrameworkConfig::default().max_modules_per_address as usize,
+            storage: None,
+        }
+    }
+
+    /// Apply the framework's publishing limits
+    pub fn with_framework_config(mut self, framework: &FrameworkConfig) -> Self {
+        self.max_modules_per_address = framework.max_modules_per_address as usize;
+        self
+    }
+
+    /// Count modules published in earlier transactions through `storage`
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Switch the randomness beacon to `epoch`, seeded from the previous
+    /// committee's certified output
+    pub fn set_epoch(&self, epoch: u64, committee_output: &[u8]) {
+        *self.beacon.write().unwrap() = epoch_beacon(epoch, committee_output);
     }
 
     /// Execute transaction
@@ -35,22 +88,40 @@ impl ExecutionEngine {
         tx: SignedTransaction,
         context: &mut ExecutionContext,
     ) -> ProtocolResult<ExecutionResult> {
+        // Bound the events the transaction may emit
+        context.set_max_events(self.config.max_events);
+        let beacon = *self.beacon.read().unwrap();
+        context.seed_rng(tx.digest().as_ref(), &beacon);
+
+        // One meter for the whole transaction: the tip is charged up front,
+        // so execution only gets what is left of the budget
+        let mut gas_status = GasStatus::new(
+            GasSchedule::default(),
+            GasUnit::new(tx.data.gas_budget.min(self.config.max_gas_per_tx)),
+        ).with_complexity_budget(self.config.complexity_budget);
+        gas_status.charge_tip(tx.data.tip).map_err(ProtocolError::ExecutionError)?;
+
         // Create new session
         let session = self.vm.new_session(context);
 
         // Execute transaction
-        let result = match tx.payload {
+        let mut result = match tx.payload {
             TransactionPayload::Script(script) => {
-                self.execute_script(script, session, context).await
+                self.execute_script(script, session, context, &mut gas_status).await
             }
             TransactionPayload::ModuleBundle(modules) => {
-                self.publish_modules(modules, session, context).await
+                self.publish_modules(modules, session, context, &mut gas_status).await
             }
             TransactionPayload::Function(function) => {
-                self.execute_function(function, session, context).await
+                self.execute_function(function, session, context, &mut gas_status).await
             }
         }?;
 
+        // Abort if the VM swallowed an event limit or complexity error
+        context.check_event_limit()?;
+        gas_status.check_complexity().map_err(ProtocolError::ExecutionError)?;
+
+        result.tip = gas_status.tip_charged().value();
         Ok(result)
     }
 
@@ -60,16 +131,21 @@ impl ExecutionEngine {
         script: Script,
         session: Session<ExecutionContext>,
         context: &mut ExecutionContext,
+        gas_status: &mut GasStatus,
     ) -> ProtocolResult<ExecutionResult> {
-        // Verify script
-        self.vm.verify_script(&script)?;
+        // Verify script off the async runtime
+        let vm = self.vm.clone();
+        let script = self.workers
+            .run(move || vm.verify_script(&script).map(|_| script))
+            .await??;
+        charge_call(gas_status, &script.ty_args, &script.args)?;
 
         // Execute script
         let result = session.execute_script(
             script.code,
             script.ty_args,
             script.args,
-            &mut GasStatus::new(self.config.max_gas_per_tx),
+            gas_status,
         )?;
 
         Ok(ExecutionResult::new(result, context.events().to_vec()))
@@ -81,10 +157,31 @@ impl ExecutionEngine {
         modules: Vec<Module>,
         session: Session<ExecutionContext>,
         context: &mut ExecutionContext,
+        gas_status: &mut GasStatus,
     ) -> ProtocolResult<ExecutionResult> {
-        // Verify modules
-        for module in &modules {
-            self.vm.verify_module(module)?;
+        // Reject oversized bundles before spending time on verification
+        let addresses = modules.iter()
+            .map(module_address)
+            .collect::<ProtocolResult<Vec<_>>>()?;
+        if let Some(storage) = &self.storage {
+            for address in &addresses {
+                context.load_module_count(*address, storage::module_count(storage.as_ref(), address)?);
+            }
+        }
+        context.check_module_count(&addresses, self.max_modules_per_address)?;
+
+        // Verify modules concurrently on the worker pool
+        let verifications: Vec<_> = modules.iter()
+            .map(|module| {
+                let vm = self.vm.clone();
+                let module = module.clone();
+                let workers = self.workers.clone();
+                tokio::spawn(async move { workers.run(move || vm.verify_module(&module)).await })
+            })
+            .collect();
+        for verification in verifications {
+            verification.await
+                .map_err(|e| ProtocolError::SystemError(e.to_string()))???;
         }
 
         // Publish modules
@@ -92,10 +189,18 @@ impl ExecutionEngine {
             session.publish_module(
                 module.code,
                 module.sender,
-                &mut GasStatus::new(self.config.max_gas_per_tx),
+                gas_status,
             )?;
         }
 
+        // Count the new modules against their addresses' quota
+        if let Some(storage) = &self.storage {
+            for address in &addresses {
+                let count = storage::module_count(storage.as_ref(), address)?;
+                storage::set_module_count(storage.as_ref(), address, count + 1)?;
+            }
+        }
+
         Ok(ExecutionResult::new(vec![], context.events().to_vec()))
     }
 
@@ -105,15 +210,129 @@ impl ExecutionEngine {
         function: Function,
         session: Session<ExecutionContext>,
         context: &mut ExecutionContext,
+        gas_status: &mut GasStatus,
     ) -> ProtocolResult<ExecutionResult> {
+        charge_call(gas_status, &function.ty_args, &function.args)?;
+
+        // Random natives draw from the transaction's seeded source
+        if function.module == random_module() {
+            let value = call_random_native(context, function.function.as_str(), &function.args)?;
+            return Ok(ExecutionResult::new(vec![value], context.events().to_vec()));
+        }
+        if function.module == event_module() {
+            call_event_native(context, function.function.as_str(), &function.ty_args, &function.args)?;
+            return Ok(ExecutionResult::new(vec![], context.events().to_vec()));
+        }
+
         let result = session.execute_function(
             &function.module,
             &function.function,
             function.ty_args,
             function.args,
-            &mut GasStatus::new(self.config.max_gas_per_tx),
+            gas_status,
         )?;
 
         Ok(ExecutionResult::new(result, context.events().to_vec()))
     }
-}
\ No newline at end of file
+}
+
+/// Charge a call's type instantiations and argument bytes through the
+/// meter's allocation hooks
+fn charge_call(gas_status: &mut GasStatus, ty_args: &[TypeTag], args: &[Vec<u8>]) -> ProtocolResult<()> {
+    for ty in ty_args {
+        gas_status.charge_instantiation(type_complexity(ty)).map_err(ProtocolError::ExecutionError)?;
+    }
+    for arg in args {
+        gas_status.charge_allocation(arg.len() as u64).map_err(ProtocolError::ExecutionError)?;
+    }
+    Ok(())
+}
+
+/// Address a module is published at, taken from its own ID
+fn module_address(module: &Module) -> ProtocolResult<AccountAddress> {
+    let compiled = CompiledModule::deserialize(&module.code)
+        .map_err(|e| ProtocolError::DeserializationError(e.to_string()))?;
+    Ok(*compiled.self_id().address())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::ExecutionError;
+    use crate::transaction::test_utils::signed_transaction;
+    use move_core_types::identifier::Identifier;
+
+    fn test_engine(max_events: usize) -> ExecutionEngine {
+        test_engine_with_budget(max_events, 1_000_000)
+    }
+
+    fn test_engine_with_budget(max_events: usize, complexity_budget: u64) -> ExecutionEngine {
+        ExecutionEngine::new(
+            ExecutionConfig {
+                max_gas_per_tx: 1_000_000,
+                max_events,
+                execution_threads: 1,
+                complexity_budget,
+            },
+            Arc::new(MoveVM::new(native_functions()).unwrap()),
+        )
+    }
+
+    fn emit_transaction(value: u64) -> SignedTransaction {
+        SignedTransaction {
+            payload: TransactionPayload::Function(Function {
+                module: event_module(),
+                function: Identifier::new("emit").unwrap(),
+                ty_args: vec![TypeTag::U64],
+                args: vec![bcs::to_bytes(&value).unwrap()],
+            }),
+            ..signed_transaction(1, 0, 0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_limit_aborts() {
+        let mut context = ExecutionContext::new(StateView::default());
+        let result = test_engine(1).execute_transaction(emit_transaction(7), &mut context).await.unwrap();
+        assert_eq!(result.events.len(), 1);
+
+        // Past the limit the transaction aborts and its events are discarded
+        let mut context = ExecutionContext::new(StateView::default());
+        assert!(matches!(
+            test_engine(0).execute_transaction(emit_transaction(7), &mut context).await,
+            Err(ProtocolError::ExecutionError(ExecutionError::TooManyEvents { max: 0 }))
+        ));
+        assert!(context.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tip_charged_against_execution_meter() {
+        let mut transaction = emit_transaction(7);
+        transaction.data.gas_budget = 2_000_000;
+        transaction.data.tip = 1_500_000;
+
+        // The budget covers the tip, but the meter execution runs under doesn't
+        let mut context = ExecutionContext::new(StateView::default());
+        assert!(test_engine(1).execute_transaction(transaction.clone(), &mut context).await.is_err());
+
+        transaction.data.tip = 500;
+        let mut context = ExecutionContext::new(StateView::default());
+        let result = test_engine(1).execute_transaction(transaction, &mut context).await.unwrap();
+        assert_eq!(result.tip, 500);
+    }
+
+    #[tokio::test]
+    async fn test_complexity_budget_aborts_transaction() {
+        // A one-node type and an 8 byte argument, well within the gas limit
+        let mut context = ExecutionContext::new(StateView::default());
+        let result = test_engine_with_budget(1, 9).execute_transaction(emit_transaction(7), &mut context).await;
+        assert!(result.is_ok());
+
+        let mut context = ExecutionContext::new(StateView::default());
+        assert!(matches!(
+            test_engine_with_budget(1, 8).execute_transaction(emit_transaction(7), &mut context).await,
+            Err(ProtocolError::ExecutionError(ExecutionError::ComplexityBudgetExceeded { budget: 8 }))
+        ));
+        assert!(context.events().is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+use super::ExecutionContext;
+use crate::execution::ExecutionError;
+use crate::protocol::{ProtocolError, ProtocolResult};
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::InternalGas,
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+    value::MoveValue,
+};
+use move_vm_runtime::native_functions::{NativeContext, NativeFunction};
+use move_vm_types::{
+    loaded_data::runtime_types::Type,
+    natives::function::NativeResult,
+    pop_arg,
+    values::Value,
+};
+use smallvec::smallvec;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Most bytes a single `random::bytes` call may draw
+pub const MAX_RANDOM_BYTES: u64 = 4_096;
+
+/// Abort code of a random native called in an unseeded transaction or
+/// asking for too many bytes
+const E_RANDOM_UNAVAILABLE: u64 = 1;
+
+/// `0x2::random`, the module whose natives draw from the transaction's
+/// random source
+pub fn random_module() -> ModuleId {
+    ModuleId::new(AccountAddress::TWO, Identifier::new("random").unwrap())
+}
+
+/// Call native `function` of `0x2::random` with BCS encoded `args`
+pub fn call_random_native(
+    context: &mut ExecutionContext,
+    function: &str,
+    args: &[Vec<u8>],
+) -> ProtocolResult<MoveValue> {
+    match (function, args) {
+        ("next_u64", []) => Ok(MoveValue::U64(random_u64(context)?)),
+        ("bytes", [len]) => {
+            let len: u64 = bcs::from_bytes(len)
+                .map_err(|e| ProtocolError::DeserializationError(e.to_string()))?;
+            let bytes = random_bytes(context, len)?;
+            Ok(MoveValue::Vector(bytes.into_iter().map(MoveValue::U8).collect()))
+        }
+        _ => Err(native_error(format!("Unknown random native {}", function))),
+    }
+}
+
+fn random_u64(context: &mut ExecutionContext) -> ProtocolResult<u64> {
+    Ok(context.rng()?.next_u64())
+}
+
+fn random_bytes(context: &mut ExecutionContext, len: u64) -> ProtocolResult<Vec<u8>> {
+    if len > MAX_RANDOM_BYTES {
+        return Err(native_error(format!(
+            "Requested {} random bytes, at most {} allowed", len, MAX_RANDOM_BYTES
+        )));
+    }
+    let mut bytes = vec![0u8; len as usize];
+    context.rng()?.fill_bytes(&mut bytes);
+    Ok(bytes)
+}
+
+/// Natives the VM links into Move code, keyed by address, module and
+/// function name
+pub fn native_functions() -> Vec<(AccountAddress, Identifier, Identifier, NativeFunction)> {
+    let random = random_module();
+    let natives: [(&str, NativeFunction); 2] = [
+        ("next_u64", Arc::new(native_next_u64)),
+        ("bytes", Arc::new(native_bytes)),
+    ];
+    natives.into_iter()
+        .map(|(name, native)| {
+            (*random.address(), random.name().to_owned(), Identifier::new(name).unwrap(), native)
+        })
+        .collect()
+}
+
+/// `random::next_u64(): u64`
+fn native_next_u64(
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    _args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    let execution = context.extensions_mut().get_mut::<ExecutionContext>();
+    Ok(match random_u64(execution) {
+        Ok(value) => NativeResult::ok(InternalGas::zero(), smallvec![Value::u64(value)]),
+        Err(_) => NativeResult::err(InternalGas::zero(), E_RANDOM_UNAVAILABLE),
+    })
+}
+
+/// `random::bytes(len: u64): vector<u8>`
+fn native_bytes(
+    context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    let len = pop_arg!(args, u64);
+    let execution = context.extensions_mut().get_mut::<ExecutionContext>();
+    Ok(match random_bytes(execution, len) {
+        Ok(bytes) => NativeResult::ok(InternalGas::zero(), smallvec![Value::vector_u8(bytes)]),
+        Err(_) => NativeResult::err(InternalGas::zero(), E_RANDOM_UNAVAILABLE),
+    })
+}
+
+/// `0x2::event`, the module whose `emit` native records transaction events
+pub fn event_module() -> ModuleId {
+    ModuleId::new(AccountAddress::TWO, Identifier::new("event").unwrap())
+}
+
+/// Call native `function` of `0x2::event`, emitting the BCS encoded
+/// `args` as an event of type `ty_args`
+pub fn call_event_native(
+    context: &mut ExecutionContext,
+    function: &str,
+    ty_args: &[TypeTag],
+    args: &[Vec<u8>],
+) -> ProtocolResult<()> {
+    match (function, ty_args, args) {
+        ("emit", [type_tag], [data]) => {
+            let sequence = context.events().len() as u64;
+            context.add_event((vec![], sequence, type_tag.clone(), data.clone()))
+        }
+        _ => Err(native_error(format!("Unknown event native {}", function))),
+    }
+}
+
+fn native_error(message: String) -> ProtocolError {
+    ProtocolError::ExecutionError(ExecutionError::ExecutionError(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::execution::epoch_beacon;
+
+    #[test]
+    fn test_random_natives_use_seeded_source() {
+        let mut context = ExecutionContext::new(StateView::default());
+        assert!(call_random_native(&mut context, "next_u64", &[]).is_err());
+
+        let beacon = epoch_beacon(1, b"committee output");
+        context.seed_rng(&[1; 32], &beacon);
+        let first = call_random_native(&mut context, "next_u64", &[]).unwrap();
+
+        // Re-executing the transaction draws the same value
+        let mut replay = ExecutionContext::new(StateView::default());
+        replay.seed_rng(&[1; 32], &beacon);
+        assert_eq!(call_random_native(&mut replay, "next_u64", &[]).unwrap(), first);
+
+        let len = bcs::to_bytes(&16u64).unwrap();
+        assert!(matches!(
+            call_random_native(&mut context, "bytes", &[len]).unwrap(),
+            MoveValue::Vector(bytes) if bytes.len() == 16
+        ));
+
+        let too_many = bcs::to_bytes(&(MAX_RANDOM_BYTES + 1)).unwrap();
+        assert!(call_random_native(&mut context, "bytes", &[too_many]).is_err());
+    }
+
+    #[test]
+    fn test_random_natives_registered() {
+        let natives = native_functions();
+        for name in ["next_u64", "bytes"] {
+            assert!(natives.iter().any(|(address, module, function, _)| {
+                *address == AccountAddress::TWO
+                    && module.as_str() == "random"
+                    && function.as_str() == name
+            }));
+        }
+    }
+}
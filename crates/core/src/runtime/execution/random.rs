@@ -0,0 +1,59 @@
+use sha2::{Digest, Sha256};
+
+/// Deterministic random source for Move natives. Seeded only from the
+/// transaction digest and the epoch beacon, so every validator draws the
+/// same values for a transaction; it has no access to system entropy.
+#[derive(Debug, Clone)]
+pub struct TransactionRng {
+    /// Per-transaction seed
+    seed: [u8; 32],
+    /// Blocks drawn so far
+    counter: u64,
+}
+
+impl TransactionRng {
+    pub fn new(tx_digest: &[u8], beacon: &[u8; 32]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(b"swift-tx-rng");
+        hasher.update(beacon);
+        hasher.update(tx_digest);
+        Self {
+            seed: hasher.finalize().into(),
+            counter: 0,
+        }
+    }
+
+    /// Next 32-byte block: SHA-256 of the seed and a block counter
+    fn next_block(&mut self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed);
+        hasher.update(self.counter.to_le_bytes());
+        self.counter += 1;
+        hasher.finalize().into()
+    }
+
+    /// Draw a random u64
+    pub fn next_u64(&mut self) -> u64 {
+        let block = self.next_block();
+        u64::from_le_bytes(block[..8].try_into().unwrap())
+    }
+
+    /// Fill `dest` with random bytes
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(32) {
+            let block = self.next_block();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+    }
+}
+
+/// Randomness beacon for an epoch, shared by all validators. Derived from
+/// `committee_output`, the certified output of the previous committee, so
+/// it can't be known before a quorum has signed.
+pub fn epoch_beacon(epoch: u64, committee_output: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"swift-epoch-beacon");
+    hasher.update(epoch.to_le_bytes());
+    hasher.update(committee_output);
+    hasher.finalize().into()
+}
@@ -0,0 +1,70 @@
+use crate::protocol::{ProtocolError, ProtocolResult};
+use std::sync::Arc;
+use tokio::sync::{oneshot, Semaphore};
+
+/// Bounded pool running CPU-heavy work (bytecode verification) off the
+/// async runtime
+#[derive(Debug, Clone)]
+pub struct WorkerPool {
+    /// Permits, one per worker
+    permits: Arc<Semaphore>,
+}
+
+impl WorkerPool {
+    pub fn new(threads: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(threads.max(1))),
+        }
+    }
+
+    /// Run `job` on a blocking thread once a worker is free
+    pub async fn run<F, T>(&self, job: F) -> ProtocolResult<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = self.permits.clone().acquire_owned().await
+            .map_err(|e| ProtocolError::SystemError(e.to_string()))?;
+        let (sender, receiver) = oneshot::channel();
+
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let _ = sender.send(job());
+        });
+
+        receiver.await
+            .map_err(|_| ProtocolError::SystemError("Worker dropped job".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    async fn run_jobs(pool: WorkerPool, jobs: usize) -> Duration {
+        let start = Instant::now();
+        let handles: Vec<_> = (0..jobs)
+            .map(|_| {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    // Stand-in for verifying one module
+                    pool.run(|| std::thread::sleep(Duration::from_millis(50))).await
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+        start.elapsed()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_more_workers_finish_faster() {
+        let single = run_jobs(WorkerPool::new(1), 8).await;
+        let pooled = run_jobs(WorkerPool::new(4), 8).await;
+
+        assert!(single >= Duration::from_millis(400));
+        assert!(pooled < single / 2, "pooled {:?} vs single {:?}", pooled, single);
+    }
+}
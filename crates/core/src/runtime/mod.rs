@@ -2,7 +2,9 @@
 
 pub mod execution;
 
+use crate::framework::FrameworkConfig;
 use crate::protocol::{ProtocolError, ProtocolResult};
+use crate::storage::Storage;
 use move_vm_runtime::move_vm::MoveVM;
 use std::sync::Arc;
 
@@ -11,6 +13,8 @@ use std::sync::Arc;
 pub struct RuntimeConfig {
     /// Execution configuration
     pub execution: execution::ExecutionConfig,
+    /// Framework limits enforced when publishing modules
+    pub framework: FrameworkConfig,
 }
 
 /// Runtime manager
@@ -25,11 +29,11 @@ pub struct Runtime {
 
 impl Runtime {
     pub fn new(config: RuntimeConfig) -> ProtocolResult<Self> {
-        let vm = Arc::new(MoveVM::new()?);
+        let vm = Arc::new(MoveVM::new(execution::native_functions())?);
         let execution = execution::ExecutionEngine::new(
             config.execution.clone(),
             vm.clone(),
-        );
+        ).with_framework_config(&config.framework);
 
         Ok(Self {
             config,
@@ -38,6 +42,17 @@ impl Runtime {
         })
     }
 
+    /// Count published modules against the limits through `storage`
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.execution = self.execution.with_storage(storage);
+        self
+    }
+
+    /// Reseed the randomness beacon for `epoch`
+    pub fn set_epoch(&self, epoch: u64, committee_output: &[u8]) {
+        self.execution.set_epoch(epoch, committee_output);
+    }
+
     pub fn execution_engine(&self) -> &execution::ExecutionEngine {
         &self.execution
     }
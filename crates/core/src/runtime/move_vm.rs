@@ -11,6 +11,7 @@ use move_vm_runtime::{
     session::Session,
 };
 use std::sync::Arc;
+use crate::execution::ExecutionError;
 use crate::protocol::{ProtocolError, ProtocolResult};
 
 /// VM configuration
@@ -49,6 +50,36 @@ impl Default for VMConfig {
     }
 }
 
+/// Verify a type argument's depth, and that its whole tree, counted in
+/// `nodes`, stays within `max_type_nodes` however shallow it is
+fn verify_type_argument(
+    config: &VMConfig,
+    ty: &TypeTag,
+    depth: u8,
+    nodes: &mut usize,
+) -> ProtocolResult<()> {
+    if depth > config.max_type_argument_depth {
+        return Err(ProtocolError::TypeArgumentTooDeep);
+    }
+
+    *nodes += 1;
+    let max = config.max_type_nodes as usize;
+    if *nodes > max {
+        return Err(ExecutionError::TooManyTypeNodes { max }.into());
+    }
+
+    match ty {
+        TypeTag::Vector(inner) => verify_type_argument(config, inner, depth + 1, nodes),
+        TypeTag::Struct(struct_tag) => {
+            for ty_arg in &struct_tag.type_params {
+                verify_type_argument(config, ty_arg, depth + 1, nodes)?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Move VM implementation
 pub struct MoveVM {
     /// Inner VM
@@ -100,10 +131,10 @@ impl MoveVM {
             ty_args,
             args,
             &mut gas_status,
-        ).map_err(|e| ProtocolError::VMError(e))?;
+        ).map_err(ProtocolError::from)?;
 
         // Get events
-        let events = session.finish().map_err(|e| ProtocolError::VMError(e))?.1;
+        let events = session.finish().map_err(ProtocolError::from)?.1;
 
         Ok(ExecutionResult {
             return_values: result,
@@ -133,10 +164,10 @@ impl MoveVM {
             module.into_inner(),
             AccountAddress::ZERO,
             &mut GasStatus::new(self.config.gas_schedule.clone()),
-        ).map_err(|e| ProtocolError::VMError(e))?;
+        ).map_err(ProtocolError::from)?;
 
         // Finish session
-        session.finish().map_err(|e| ProtocolError::VMError(e))?;
+        session.finish().map_err(ProtocolError::from)?;
 
         Ok(())
     }
@@ -176,30 +207,12 @@ impl MoveVM {
         }
 
         for ty in ty_args {
-            self.verify_type_argument(ty, 0)?;
+            verify_type_argument(&self.config, ty, 0, &mut 0)?;
         }
 
         Ok(())
     }
 
-    /// Verify single type argument
-    fn verify_type_argument(&self, ty: &TypeTag, depth: u8) -> ProtocolResult<()> {
-        if depth > self.config.max_type_argument_depth {
-            return Err(ProtocolError::TypeArgumentTooDeep);
-        }
-
-        match ty {
-            TypeTag::Vector(inner) => self.verify_type_argument(inner, depth + 1),
-            TypeTag::Struct(struct_tag) => {
-                for ty_arg in &struct_tag.type_params {
-                    self.verify_type_argument(ty_arg, depth + 1)?;
-                }
-                Ok(())
-            }
-            _ => Ok(()),
-        }
-    }
-
     /// Verify module dependencies
     fn verify_dependencies(&self, module: &CompiledModule) -> ProtocolResult<()> {
         for dep in module.immediate_dependencies() {
@@ -209,4 +222,92 @@ impl MoveVM {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::language_storage::StructTag;
+
+    fn struct_with(type_params: Vec<TypeTag>) -> TypeTag {
+        TypeTag::Struct(Box::new(StructTag {
+            address: AccountAddress::ONE,
+            module: Identifier::new("wide").unwrap(),
+            name: Identifier::new("Wide").unwrap(),
+            type_params,
+        }))
+    }
+
+    #[test]
+    fn test_wide_type_exceeds_node_budget() {
+        let config = VMConfig { max_type_nodes: 16, ..Default::default() };
+
+        // One level deep, well within the depth limit, but 31 nodes wide
+        let wide = struct_with(vec![TypeTag::U64; 30]);
+        assert!(matches!(
+            verify_type_argument(&config, &wide, 0, &mut 0),
+            Err(ProtocolError::ExecutionError(ExecutionError::TooManyTypeNodes { max: 16 }))
+        ));
+
+        let narrow = struct_with(vec![TypeTag::U64; 15]);
+        assert!(verify_type_argument(&config, &narrow, 0, &mut 0).is_ok());
+    }
+
+    /// Module with one entry function, `fail`, that aborts with `code`
+    fn aborting_module(code: u64) -> CompiledModule {
+        use move_binary_format::file_format::{
+            empty_module, Bytecode, CodeUnit, FunctionDefinition, FunctionHandle,
+            FunctionHandleIndex, IdentifierIndex, ModuleHandleIndex, SignatureIndex, Visibility,
+        };
+
+        let mut module = empty_module();
+        module.identifiers.push(Identifier::new("fail").unwrap());
+        module.function_handles.push(FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex((module.identifiers.len() - 1) as u16),
+            parameters: SignatureIndex(0),
+            return_: SignatureIndex(0),
+            type_parameters: vec![],
+        });
+        module.function_defs.push(FunctionDefinition {
+            function: FunctionHandleIndex(0),
+            visibility: Visibility::Public,
+            is_entry: true,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code: vec![Bytecode::LdU64(code), Bytecode::Abort],
+            }),
+        });
+        module
+    }
+
+    #[tokio::test]
+    async fn test_move_abort_keeps_code() {
+        use crate::execution::ExecutionError;
+        use crate::runtime::execution::ExecutionContext;
+
+        let vm = MoveVM::new(VMConfig::default()).unwrap();
+        let mut context = ExecutionContext::new(StateView::default());
+        let module = aborting_module(42);
+        let id = module.self_id();
+        vm.publish_module(module, &mut context).await.unwrap();
+
+        let result = vm.execute_function(
+            &id,
+            &Identifier::new("fail").unwrap(),
+            vec![],
+            vec![],
+            &mut context,
+        ).await;
+        assert!(matches!(
+            result,
+            Err(ProtocolError::ExecutionError(ExecutionError::MoveAbort { ref module, function: 0, code: 42 }))
+                if *module == id
+        ));
+
+        // The executor sees the same abort, not its text
+        let error: ExecutionError = result.unwrap_err().into();
+        assert!(matches!(error, ExecutionError::MoveAbort { code: 42, .. }));
+    }
+}
@@ -1,6 +1,9 @@
 use super::{StateError, StateResult};
+use crate::core::ObjectID;
+use crate::storage::{ObjectKey, ObjectStore, ObjectValue};
 use sha2::{Sha256, Digest};
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
 
 /// Accumulator node
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +97,22 @@ impl StateAccumulator {
         Ok(())
     }
 
+    /// Compute the Merkle root over the latest version of every live
+    /// object, ordered by ObjectID so every validator gets the same root
+    pub fn compute_root(object_store: &ObjectStore) -> StateResult<[u8; 32]> {
+        let objects = object_store.list_live()
+            .map_err(|e| StateError::StorageError(e.to_string()))?;
+        Self::root_of(&live_objects(objects))
+    }
+
+    /// Merkle root over live objects already in canonical order
+    pub fn root_of(objects: &[(ObjectKey, ObjectValue)]) -> StateResult<[u8; 32]> {
+        let level = objects.iter()
+            .map(|(key, value)| leaf_hash(key, value))
+            .collect::<StateResult<Vec<_>>>()?;
+        Ok(merkle_root(level))
+    }
+
     /// Get root hash
     pub fn root_hash(&self) -> Option<[u8; 32]> {
         self.root.as_ref().map(|node| node.hash)
@@ -149,4 +168,136 @@ impl StateAccumulator {
 
         current_hash == root_hash
     }
-}
\ No newline at end of file
+}
+
+/// Latest version of each object, ordered by ObjectID
+pub(crate) fn live_objects(
+    objects: Vec<(ObjectKey, ObjectValue)>,
+) -> Vec<(ObjectKey, ObjectValue)> {
+    let mut latest: HashMap<ObjectID, (ObjectKey, ObjectValue)> = HashMap::new();
+    for (key, value) in objects {
+        if latest.get(&key.id).is_some_and(|(k, _)| k.version >= key.version) {
+            continue;
+        }
+        latest.insert(key.id, (key, value));
+    }
+
+    let mut live: Vec<_> = latest.into_values().collect();
+    live.sort_by(|(a, _), (b, _)| a.id.as_bytes().cmp(b.id.as_bytes()));
+    live
+}
+
+/// Hash of one object's leaf in the state root
+pub(crate) fn leaf_hash(key: &ObjectKey, value: &ObjectValue) -> StateResult<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    hasher.update(&value.data);
+    hasher.update(value.owner.as_bytes());
+    hasher.update(value.type_.as_bytes());
+    let value_hash: [u8; 32] = hasher.finalize().into();
+
+    let mut data = bincode::serialize(key)
+        .map_err(|e| StateError::SerializationError(e.to_string()))?;
+    data.extend_from_slice(&value_hash);
+    Ok(AccumulatorNode::leaf(&data).hash)
+}
+
+/// Fold leaf hashes pairwise into a root; an odd node is carried up as is
+pub(crate) fn merkle_root(mut level: Vec<[u8; 32]>) -> [u8; 32] {
+    if level.is_empty() {
+        return [0; 32];
+    }
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut hasher = Sha256::new();
+                    hasher.update([1u8]); // Internal prefix
+                    hasher.update(left);
+                    hasher.update(right);
+                    hasher.finalize().into()
+                }
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    level[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::SequenceNumber;
+    use crate::storage::{ObjectValue, RocksConfig, RocksStore};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn value(data: Vec<u8>) -> ObjectValue {
+        ObjectValue {
+            data,
+            owner: "owner".to_string(),
+            type_: "0x2::coin::Coin".to_string(),
+            created_at: 100,
+            modified_at: 100,
+        }
+    }
+
+    #[test]
+    fn test_compute_root_tracks_object_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        }).unwrap());
+        let store = ObjectStore::new(rocks);
+
+        let keys: Vec<_> = (0..3u8)
+            .map(|i| ObjectKey {
+                id: ObjectID::from_bytes([i; 32]),
+                version: SequenceNumber::new(1),
+            })
+            .collect();
+        for (i, key) in keys.iter().enumerate() {
+            store.put(key.clone(), value(vec![i as u8])).unwrap();
+        }
+        let original = StateAccumulator::compute_root(&store).unwrap();
+
+        // Modify one object
+        store.put(keys[1].clone(), value(vec![42])).unwrap();
+        let modified = StateAccumulator::compute_root(&store).unwrap();
+        assert_ne!(original, modified);
+
+        // Change it back
+        store.put(keys[1].clone(), value(vec![1])).unwrap();
+        assert_eq!(StateAccumulator::compute_root(&store).unwrap(), original);
+    }
+
+    #[test]
+    fn test_compute_root_skips_deleted_objects() {
+        let temp_dir = TempDir::new().unwrap();
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        }).unwrap());
+        let store = ObjectStore::new(rocks);
+
+        let kept = ObjectKey { id: ObjectID::from_bytes([1; 32]), version: SequenceNumber::new(1) };
+        store.put(kept.clone(), value(vec![1])).unwrap();
+        let expected = StateAccumulator::root_of(&[(kept, value(vec![1]))]).unwrap();
+
+        // An object deleted at its latest version doesn't fall back to an
+        // older one
+        let id = ObjectID::from_bytes([2; 32]);
+        let older = ObjectKey { id, version: SequenceNumber::new(1) };
+        let latest = ObjectKey { id, version: SequenceNumber::new(2) };
+        store.put(older, value(vec![2])).unwrap();
+        store.put(latest.clone(), value(vec![3])).unwrap();
+        assert_ne!(StateAccumulator::compute_root(&store).unwrap(), expected);
+
+        store.delete(&latest).unwrap();
+        assert_eq!(StateAccumulator::compute_root(&store).unwrap(), expected);
+    }
+}
@@ -1,6 +1,8 @@
-use super::{StateError, StateResult, StateStore};
+use super::{StateAccumulator, StateError, StateResult, StateStore};
 use crate::core::{Object, ObjectID};
 use crate::protocol::TransactionDigest;
+use crate::storage::ObjectStore;
+use crate::utils::{Hasher, Sha256Hasher};
 use serde::{Serialize, Deserialize};
 use std::sync::Arc;
 
@@ -24,15 +26,17 @@ pub struct Checkpoint {
 }
 
 impl Checkpoint {
-    /// Create new checkpoint
+    /// Create new checkpoint. Transactions are put in digest order, so
+    /// validators holding the same transaction set produce identical digests.
     pub fn new(
         sequence: u64,
         previous_digest: Option<[u8; 32]>,
         timestamp: u64,
-        transactions: Vec<TransactionDigest>,
+        mut transactions: Vec<TransactionDigest>,
         state_root: [u8; 32],
         epoch: u64,
     ) -> Self {
+        transactions.sort();
         let mut checkpoint = Self {
             sequence,
             digest: [0; 32],
@@ -46,21 +50,40 @@ impl Checkpoint {
         checkpoint
     }
 
+    /// Create new checkpoint with the state root computed from the object
+    /// store
+    pub fn from_store(
+        sequence: u64,
+        previous_digest: Option<[u8; 32]>,
+        timestamp: u64,
+        transactions: Vec<TransactionDigest>,
+        object_store: &ObjectStore,
+        epoch: u64,
+    ) -> StateResult<Self> {
+        let state_root = StateAccumulator::compute_root(object_store)?;
+        Ok(Self::new(sequence, previous_digest, timestamp, transactions, state_root, epoch))
+    }
+
     /// Compute checkpoint digest
-    fn compute_digest(&self) -> [u8; 32] {
-        use sha2::{Sha256, Digest};
-        let mut hasher = Sha256::new();
-        hasher.update(self.sequence.to_le_bytes());
+    pub(crate) fn compute_digest(&self) -> [u8; 32] {
+        self.compute_digest_with::<Sha256Hasher>()
+    }
+
+    /// Compute checkpoint digest using hasher `H`; only SHA-256 digests are
+    /// valid on chain
+    pub fn compute_digest_with<H: Hasher>(&self) -> [u8; 32] {
+        let mut hasher = H::default();
+        hasher.update(&self.sequence.to_le_bytes());
         if let Some(prev) = self.previous_digest {
-            hasher.update(prev);
+            hasher.update(&prev);
         }
-        hasher.update(self.timestamp.to_le_bytes());
+        hasher.update(&self.timestamp.to_le_bytes());
         for tx in &self.transactions {
             hasher.update(tx.as_bytes());
         }
-        hasher.update(self.state_root);
-        hasher.update(self.epoch.to_le_bytes());
-        hasher.finalize().into()
+        hasher.update(&self.state_root);
+        hasher.update(&self.epoch.to_le_bytes());
+        hasher.finalize()
     }
 }
 
@@ -110,4 +133,24 @@ impl CheckpointStore {
     ) -> StateResult<HashMap<ObjectID, Object>> {
         self.store.get_state_at_checkpoint(sequence).await
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_order_is_deterministic() {
+        let txs: Vec<_> = [3u8, 1, 2, 4].iter()
+            .map(|i| TransactionDigest::new([*i; 32]))
+            .collect();
+
+        let a = Checkpoint::new(1, None, 100, txs.clone(), [0; 32], 0);
+        let b = Checkpoint::new(1, None, 100, txs.into_iter().rev().collect(), [0; 32], 0);
+
+        assert_eq!(a.digest, b.digest);
+        assert_eq!(a.transactions, b.transactions);
+        assert_eq!(a.transactions[0], TransactionDigest::new([1; 32]));
+        assert_eq!(a.transactions[3], TransactionDigest::new([4; 32]));
+    }
+}
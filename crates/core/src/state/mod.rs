@@ -3,12 +3,16 @@
 mod accumulator;
 mod checkpoint;
 mod pruner;
+mod snapshot;
 mod store;
+mod sync;
 
 pub use accumulator::{StateAccumulator, AccumulatorNode};
 pub use checkpoint::{Checkpoint, CheckpointStore};
 pub use pruner::{StatePruner, PruneConfig};
+pub use snapshot::{read_snapshot, write_snapshot};
 pub use store::{StateStore, StateVersion};
+pub use sync::{CheckpointSyncWindow, StateSyncConfig};
 
 use crate::protocol::{ProtocolError, ProtocolResult};
 
@@ -26,6 +30,9 @@ pub enum StateError {
 
     #[error("Checkpoint error: {0}")]
     CheckpointError(String),
+
+    #[error("Snapshot error: {0}")]
+    SnapshotError(String),
 }
 
 pub type StateResult<T> = Result<T, StateError>;
\ No newline at end of file
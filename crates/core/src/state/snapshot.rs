@@ -0,0 +1,279 @@
+use super::accumulator::{leaf_hash, live_objects, merkle_root};
+use super::{Checkpoint, StateError, StateResult};
+use crate::core::ObjectID;
+use crate::storage::{ObjectKey, ObjectStore, ObjectValue};
+use crate::utils::safe_decode;
+use serde::{Serialize, Deserialize};
+use std::io::{Read, Write};
+
+/// Snapshot format version
+const SNAPSHOT_VERSION: u32 = 2;
+
+/// Maximum size of a single frame
+const MAX_FRAME_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Frame in a snapshot file, each written as a little-endian u32 length
+/// followed by its bincode encoding
+#[derive(Debug, Serialize, Deserialize)]
+enum SnapshotFrame {
+    /// First frame
+    Header {
+        version: u32,
+        object_count: u64,
+    },
+    /// Second frame: checkpoint the objects belong to, so it can be
+    /// trusted before any object is read
+    Checkpoint(Checkpoint),
+    /// Live object, in ObjectID order
+    Object(ObjectKey, ObjectValue),
+}
+
+/// Write every live object in `object_store` followed by `checkpoint`
+pub fn write_snapshot<W: Write>(
+    writer: &mut W,
+    object_store: &ObjectStore,
+    checkpoint: &Checkpoint,
+) -> StateResult<()> {
+    let objects = object_store.list_live()
+        .map_err(|e| StateError::StorageError(e.to_string()))?;
+    let objects = live_objects(objects);
+
+    write_frame(writer, &SnapshotFrame::Header {
+        version: SNAPSHOT_VERSION,
+        object_count: objects.len() as u64,
+    })?;
+    write_frame(writer, &SnapshotFrame::Checkpoint(checkpoint.clone()))?;
+    for (key, value) in objects {
+        write_frame(writer, &SnapshotFrame::Object(key, value))?;
+    }
+
+    writer.flush()
+        .map_err(|e| StateError::SnapshotError(e.to_string()))
+}
+
+/// Load a snapshot into an empty `object_store`. The snapshot's checkpoint
+/// must have digest `trusted_digest`, obtained from a source other than the
+/// snapshot itself. Objects are written as they're read; if they don't hash
+/// to the checkpoint's state root, everything written is removed again.
+pub fn read_snapshot<R: Read>(
+    reader: &mut R,
+    object_store: &ObjectStore,
+    trusted_digest: &[u8; 32],
+) -> StateResult<Checkpoint> {
+    let existing = object_store.list()
+        .map_err(|e| StateError::StorageError(e.to_string()))?;
+    if !existing.is_empty() {
+        return Err(StateError::SnapshotError("Target store is not empty".into()));
+    }
+
+    let object_count = match read_frame(reader)? {
+        SnapshotFrame::Header { version, object_count } if version == SNAPSHOT_VERSION => {
+            object_count
+        }
+        SnapshotFrame::Header { version, .. } => {
+            return Err(StateError::SnapshotError(format!(
+                "Unsupported snapshot version {}",
+                version
+            )));
+        }
+        _ => return Err(StateError::SnapshotError("Missing snapshot header".into())),
+    };
+
+    // Trust the checkpoint before touching the store
+    let checkpoint = match read_frame(reader)? {
+        SnapshotFrame::Checkpoint(checkpoint) => checkpoint,
+        _ => return Err(StateError::SnapshotError("Expected checkpoint frame".into())),
+    };
+    if checkpoint.digest != *trusted_digest || checkpoint.compute_digest() != checkpoint.digest {
+        return Err(StateError::SnapshotError(format!(
+            "Checkpoint {} is not the trusted checkpoint {}",
+            hex::encode(checkpoint.digest),
+            hex::encode(trusted_digest)
+        )));
+    }
+
+    let mut written = Vec::new();
+    let result = import_objects(reader, object_store, object_count, &mut written)
+        .and_then(|root| match root == checkpoint.state_root {
+            true => Ok(()),
+            false => Err(StateError::SnapshotError(format!(
+                "State root mismatch: snapshot {}, checkpoint {}",
+                hex::encode(root),
+                hex::encode(checkpoint.state_root)
+            ))),
+        });
+    if let Err(e) = result {
+        for key in &written {
+            if let Err(e) = object_store.delete(key) {
+                log::error!("Failed to remove {:?} of a rejected snapshot: {}", key, e);
+            }
+        }
+        return Err(e);
+    }
+
+    Ok(checkpoint)
+}
+
+/// Stream `object_count` object frames into `object_store`, recording each
+/// key in `written`. Returns the state root of the objects.
+fn import_objects<R: Read>(
+    reader: &mut R,
+    object_store: &ObjectStore,
+    object_count: u64,
+    written: &mut Vec<ObjectKey>,
+) -> StateResult<[u8; 32]> {
+    let mut leaves = Vec::new();
+    let mut previous: Option<ObjectID> = None;
+    for _ in 0..object_count {
+        let (key, value) = match read_frame(reader)? {
+            SnapshotFrame::Object(key, value) => (key, value),
+            _ => return Err(StateError::SnapshotError("Expected object frame".into())),
+        };
+
+        // One version per object in canonical order, as the root expects
+        if previous.is_some_and(|prev| prev.as_bytes() >= key.id.as_bytes()) {
+            return Err(StateError::SnapshotError(format!(
+                "Object {} out of order",
+                key.id.to_hex()
+            )));
+        }
+        previous = Some(key.id);
+
+        leaves.push(leaf_hash(&key, &value)?);
+        object_store.put(key.clone(), value)
+            .map_err(|e| StateError::StorageError(e.to_string()))?;
+        written.push(key);
+    }
+
+    Ok(merkle_root(leaves))
+}
+
+fn write_frame<W: Write>(writer: &mut W, frame: &SnapshotFrame) -> StateResult<()> {
+    let bytes = bincode::serialize(frame)
+        .map_err(|e| StateError::SerializationError(e.to_string()))?;
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())
+        .and_then(|_| writer.write_all(&bytes))
+        .map_err(|e| StateError::SnapshotError(e.to_string()))
+}
+
+fn read_frame<R: Read>(reader: &mut R) -> StateResult<SnapshotFrame> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)
+        .map_err(|e| StateError::SnapshotError(e.to_string()))?;
+    let len = u32::from_le_bytes(len) as u64;
+    if len > MAX_FRAME_SIZE {
+        return Err(StateError::SnapshotError(format!("Frame of {} bytes too large", len)));
+    }
+
+    let mut bytes = vec![0u8; len as usize];
+    reader.read_exact(&mut bytes)
+        .map_err(|e| StateError::SnapshotError(e.to_string()))?;
+    safe_decode(&bytes, MAX_FRAME_SIZE)
+        .map_err(|e| StateError::SerializationError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::SequenceNumber;
+    use crate::state::StateAccumulator;
+    use crate::storage::{RocksConfig, RocksStore};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn object_store(temp_dir: &TempDir) -> ObjectStore {
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        }).unwrap());
+        ObjectStore::new(rocks)
+    }
+
+    fn populated_store(temp_dir: &TempDir) -> ObjectStore {
+        let store = object_store(temp_dir);
+        for i in 0..3u8 {
+            for version in 1..=2 {
+                store.put(
+                    ObjectKey {
+                        id: ObjectID::from_bytes([i; 32]),
+                        version: SequenceNumber::new(version),
+                    },
+                    ObjectValue {
+                        data: vec![i, version as u8],
+                        owner: "owner".to_string(),
+                        type_: "0x2::coin::Coin".to_string(),
+                        created_at: 100,
+                        modified_at: 100,
+                    },
+                ).unwrap();
+            }
+        }
+        store
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let source_dir = TempDir::new().unwrap();
+        let source = populated_store(&source_dir);
+        let checkpoint = Checkpoint::from_store(7, None, 100, vec![], &source, 1).unwrap();
+
+        let mut file = Vec::new();
+        write_snapshot(&mut file, &source, &checkpoint).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        let target = object_store(&target_dir);
+        let imported = read_snapshot(&mut file.as_slice(), &target, &checkpoint.digest).unwrap();
+
+        assert_eq!(imported.digest, checkpoint.digest);
+        assert_eq!(StateAccumulator::compute_root(&target).unwrap(), checkpoint.state_root);
+        // Only the latest version of each object is carried over
+        assert_eq!(target.list().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_snapshot_root_mismatch_aborts() {
+        let source_dir = TempDir::new().unwrap();
+        let source = populated_store(&source_dir);
+        let checkpoint = Checkpoint::new(7, None, 100, vec![], [9; 32], 1);
+
+        let mut file = Vec::new();
+        write_snapshot(&mut file, &source, &checkpoint).unwrap();
+
+        let target_dir = TempDir::new().unwrap();
+        let target = object_store(&target_dir);
+        assert!(matches!(
+            read_snapshot(&mut file.as_slice(), &target, &checkpoint.digest),
+            Err(StateError::SnapshotError(_))
+        ));
+        // Objects streamed in before the mismatch was found are removed
+        assert!(target.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_requires_trusted_checkpoint() {
+        let source_dir = TempDir::new().unwrap();
+        let source = populated_store(&source_dir);
+        let checkpoint = Checkpoint::from_store(7, None, 100, vec![], &source, 1).unwrap();
+
+        let mut file = Vec::new();
+        write_snapshot(&mut file, &source, &checkpoint).unwrap();
+
+        // A self-consistent snapshot is still rejected without the digest
+        // learned out of band
+        let target_dir = TempDir::new().unwrap();
+        let target = object_store(&target_dir);
+        assert!(matches!(
+            read_snapshot(&mut file.as_slice(), &target, &[1; 32]),
+            Err(StateError::SnapshotError(_))
+        ));
+        assert!(target.list().unwrap().is_empty());
+
+        // So is a checkpoint whose fields don't match its digest
+        let mut forged = checkpoint.clone();
+        forged.state_root = [9; 32];
+        let mut file = Vec::new();
+        write_snapshot(&mut file, &source, &forged).unwrap();
+        assert!(read_snapshot(&mut file.as_slice(), &target, &checkpoint.digest).is_err());
+        assert!(target.list().unwrap().is_empty());
+    }
+}
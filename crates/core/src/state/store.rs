@@ -1,6 +1,8 @@
-use super::{Checkpoint, StateError, StateResult};
+use super::{read_snapshot, write_snapshot, Checkpoint, StateError, StateResult};
 use crate::core::{Object, ObjectID};
-use crate::storage::Storage;
+use crate::protocol::RetryPolicy;
+use crate::storage::{ObjectStore, Storage};
+use std::io::{Read, Write};
 use std::sync::Arc;
 
 /// State version
@@ -11,12 +13,53 @@ pub struct StateVersion(pub u64);
 pub struct StateStore {
     /// Storage
     storage: Arc<dyn Storage>,
+    /// Object store backing snapshots
+    object_store: Option<Arc<ObjectStore>>,
+    /// Retry policy for transient errors on checkpoint reads and writes
+    retry: RetryPolicy,
 }
 
 impl StateStore {
     /// Create new state store
     pub fn new(storage: Arc<dyn Storage>) -> Self {
-        Self { storage }
+        Self { storage, object_store: None, retry: RetryPolicy::default() }
+    }
+
+    /// Use `object_store` for snapshot export and import
+    pub fn with_object_store(mut self, object_store: Arc<ObjectStore>) -> Self {
+        self.object_store = Some(object_store);
+        self
+    }
+
+    /// Retry transient checkpoint storage errors with `retry`
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    fn snapshot_store(&self) -> StateResult<&ObjectStore> {
+        self.object_store.as_deref()
+            .ok_or_else(|| StateError::SnapshotError("No object store configured".into()))
+    }
+
+    /// Write all live objects and the latest checkpoint to `writer`
+    pub async fn export_snapshot<W: Write>(&self, writer: &mut W) -> StateResult<()> {
+        let checkpoint = self.get_latest_checkpoint().await?
+            .ok_or_else(|| StateError::SnapshotError("No checkpoint to export".into()))?;
+        write_snapshot(writer, self.snapshot_store()?, &checkpoint)
+    }
+
+    /// Load a snapshot of the checkpoint with digest `trusted_digest` into
+    /// this (empty) store, aborting if the objects don't match the
+    /// checkpoint's state root
+    pub async fn import_snapshot<R: Read>(
+        &self,
+        reader: &mut R,
+        trusted_digest: &[u8; 32],
+    ) -> StateResult<Checkpoint> {
+        let checkpoint = read_snapshot(reader, self.snapshot_store()?, trusted_digest)?;
+        self.put_checkpoint(checkpoint.clone()).await?;
+        Ok(checkpoint)
     }
 
     /// Get object
@@ -57,7 +100,7 @@ impl StateStore {
         &self,
         sequence: u64,
     ) -> StateResult<Option<Checkpoint>> {
-        self.storage.get_checkpoint(sequence)
+        self.retry.retry(|| self.storage.get_checkpoint(sequence))
             .await
             .map_err(|e| StateError::StorageError(e.to_string()))
     }
@@ -67,7 +110,7 @@ impl StateStore {
         &self,
         checkpoint: Checkpoint,
     ) -> StateResult<()> {
-        self.storage.put_checkpoint(&checkpoint)
+        self.retry.retry(|| self.storage.put_checkpoint(&checkpoint))
             .await
             .map_err(|e| StateError::StorageError(e.to_string()))
     }
@@ -0,0 +1,129 @@
+use super::{StateError, StateResult};
+use crate::protocol::RetryPolicy;
+use std::collections::BTreeMap;
+
+/// State sync configuration
+#[derive(Debug, Clone)]
+pub struct StateSyncConfig {
+    /// Maximum checkpoints requested but not yet applied
+    pub max_in_flight_checkpoints: usize,
+    /// Retry policy for transient storage errors while applying checkpoints;
+    /// install it with `StateStore::with_retry_policy`
+    pub storage_retry: RetryPolicy,
+}
+
+impl Default for StateSyncConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight_checkpoints: 16,
+            storage_retry: RetryPolicy::default(),
+        }
+    }
+}
+
+/// Sliding window over the checkpoints being synced. At most
+/// `max_in_flight_checkpoints` are outstanding at once; checkpoints that
+/// arrive early are buffered and released strictly in sequence order.
+pub struct CheckpointSyncWindow<T> {
+    /// Maximum outstanding checkpoints
+    max_in_flight: u64,
+    /// Next sequence to hand out for applying
+    next_to_apply: u64,
+    /// Next sequence to request
+    next_to_request: u64,
+    /// Last sequence to sync
+    target: u64,
+    /// Checkpoints received ahead of `next_to_apply`
+    buffered: BTreeMap<u64, T>,
+}
+
+impl<T> CheckpointSyncWindow<T> {
+    /// Sync checkpoints `start..=target`
+    pub fn new(config: &StateSyncConfig, start: u64, target: u64) -> Self {
+        Self {
+            max_in_flight: config.max_in_flight_checkpoints.max(1) as u64,
+            next_to_apply: start,
+            next_to_request: start,
+            target,
+            buffered: BTreeMap::new(),
+        }
+    }
+
+    /// Checkpoints requested or buffered but not yet applied
+    pub fn in_flight(&self) -> u64 {
+        self.next_to_request - self.next_to_apply
+    }
+
+    /// Sequences to request now, filling the window
+    pub fn next_requests(&mut self) -> Vec<u64> {
+        let mut requests = Vec::new();
+        while self.next_to_request <= self.target && self.in_flight() < self.max_in_flight {
+            requests.push(self.next_to_request);
+            self.next_to_request += 1;
+        }
+        requests
+    }
+
+    /// Accept a downloaded checkpoint, returning those now ready to apply
+    /// in order. Late duplicates are dropped; unrequested sequences are
+    /// rejected.
+    pub fn receive(&mut self, sequence: u64, checkpoint: T) -> StateResult<Vec<T>> {
+        if sequence < self.next_to_apply {
+            return Ok(Vec::new());
+        }
+        if sequence >= self.next_to_request {
+            return Err(StateError::CheckpointError(
+                format!("Checkpoint {} was not requested", sequence)
+            ));
+        }
+        self.buffered.insert(sequence, checkpoint);
+
+        let mut ready = Vec::new();
+        while let Some(checkpoint) = self.buffered.remove(&self.next_to_apply) {
+            ready.push(checkpoint);
+            self.next_to_apply += 1;
+        }
+        Ok(ready)
+    }
+
+    /// Whether every checkpoint up to the target has been released
+    pub fn is_complete(&self) -> bool {
+        self.next_to_apply > self.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_window_applies_in_order() {
+        let config = StateSyncConfig { max_in_flight_checkpoints: 3, ..Default::default() };
+        let mut window = CheckpointSyncWindow::new(&config, 0, 9);
+        let mut applied = Vec::new();
+        let mut pending = Vec::new();
+
+        while !window.is_complete() {
+            pending.extend(window.next_requests());
+            assert!(window.in_flight() <= 3);
+
+            // Responses arrive newest first
+            let sequence = pending.pop().unwrap();
+            applied.extend(window.receive(sequence, sequence).unwrap());
+            assert!(applied.iter().copied().eq(0..applied.len() as u64));
+        }
+
+        assert_eq!(applied, (0..10).collect::<Vec<_>>());
+        assert!(window.next_requests().is_empty());
+    }
+
+    #[test]
+    fn test_unrequested_checkpoint_rejected() {
+        let mut window = CheckpointSyncWindow::new(&StateSyncConfig::default(), 5, 10);
+        assert_eq!(window.next_requests().len(), 6);
+
+        assert!(matches!(window.receive(11, ()), Err(StateError::CheckpointError(_))));
+        assert_eq!(window.receive(5, ()).unwrap().len(), 1);
+        assert!(window.receive(5, ()).unwrap().is_empty());
+    }
+}
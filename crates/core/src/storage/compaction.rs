@@ -0,0 +1,126 @@
+// storage/compaction.rs
+use super::rocks_store::RocksStore;
+use crate::protocol::{ProtocolError, ProtocolResult};
+use chrono::{Timelike, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Compaction scheduler configuration
+#[derive(Debug, Clone)]
+pub struct CompactionConfig {
+    /// Time between compaction checks
+    pub interval: Duration,
+    /// UTC hour of day compaction is restricted to; any hour if unset
+    pub window_hour: Option<u32>,
+    /// Column families to compact
+    pub column_families: Vec<String>,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(6 * 3600),    // 6 hours
+            window_hour: None,
+            column_families: vec!["objects".to_string(), "events".to_string()],
+        }
+    }
+}
+
+/// Periodically compacts column families so deleted and overwritten
+/// versions are reclaimed
+pub struct CompactionScheduler {
+    /// RocksDB store
+    rocks: Arc<RocksStore>,
+    /// Configuration
+    config: CompactionConfig,
+}
+
+impl CompactionScheduler {
+    pub fn new(rocks: Arc<RocksStore>, config: CompactionConfig) -> ProtocolResult<Self> {
+        let column_families: Vec<&str> = config.column_families.iter().map(|cf| cf.as_str()).collect();
+        rocks.require_column_families(&column_families)?;
+        Ok(Self { rocks, config })
+    }
+
+    /// Compact every configured column family over its full key range.
+    /// RocksDB compacts in the background of reads, so the store keeps
+    /// serving while this runs.
+    pub fn compact_now(&self) -> ProtocolResult<()> {
+        for cf in &self.config.column_families {
+            self.rocks.compact_range(cf, None, None)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `hour` falls in the configured compaction window
+    fn in_window(&self, hour: u32) -> bool {
+        self.config.window_hour.is_none_or(|window| window == hour)
+    }
+
+    /// Start compacting every interval, skipping ticks outside the window
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.interval);
+            // The first tick fires immediately; don't compact on startup
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                if !self.in_window(Utc::now().hour()) {
+                    continue;
+                }
+
+                let scheduler = self.clone();
+                let result = tokio::task::spawn_blocking(move || scheduler.compact_now()).await
+                    .map_err(|e| ProtocolError::SystemError(e.to_string()));
+                match result {
+                    Ok(Ok(())) => log::info!("Compacted {:?}", self.config.column_families),
+                    Ok(Err(e)) | Err(e) => log::warn!("Compaction failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::RocksConfig;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compact_now() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        })?);
+
+        for i in 0..100u32 {
+            rocks.put("objects", &i.to_be_bytes(), b"old")?;
+            rocks.put("objects", &i.to_be_bytes(), b"new")?;
+            rocks.put("events", &i.to_be_bytes(), b"event")?;
+        }
+        for i in 0..50u32 {
+            rocks.delete("events", &i.to_be_bytes())?;
+        }
+        rocks.flush()?;
+
+        let scheduler = CompactionScheduler::new(rocks.clone(), CompactionConfig {
+            window_hour: Some(3),
+            ..Default::default()
+        })?;
+        assert!(scheduler.in_window(3));
+        assert!(!scheduler.in_window(4));
+        scheduler.compact_now()?;
+
+        for i in 0..100u32 {
+            assert_eq!(rocks.get("objects", &i.to_be_bytes())?, Some(b"new".to_vec()));
+            let expected = (i >= 50).then(|| b"event".to_vec());
+            assert_eq!(rocks.get("events", &i.to_be_bytes())?, expected);
+        }
+
+        Ok(())
+    }
+}
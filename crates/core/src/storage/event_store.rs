@@ -4,6 +4,10 @@ use crate::protocol::{ProtocolError, ProtocolResult};
 use serde::{Serialize, Deserialize};
 use std::sync::Arc;
 use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+
+/// Events buffered for each live subscriber before it starts lagging
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
 
 /// Event type
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +41,21 @@ pub enum TransactionEvent {
         tx_digest: String,
         certificate: String,
     },
+    /// Transaction abandoned before execution, e.g. on a quorum timeout
+    Failed {
+        tx_digest: String,
+        reason: String,
+    },
+    /// Effects committed, with the objects they touched
+    EffectsCommitted {
+        tx_digest: String,
+        sender: String,
+        created: Vec<String>,
+        modified: Vec<String>,
+        deleted: Vec<String>,
+        gas_used: u64,
+        error: Option<String>,
+    },
 }
 
 /// Object event
@@ -107,6 +126,33 @@ pub struct EventFilter {
     pub limit: Option<usize>,
 }
 
+impl EventFilter {
+    /// Check if event matches filter
+    pub fn matches(&self, event: &Event) -> bool {
+        // Check type
+        if let Some(types) = &self.types {
+            if !types.iter().any(|t| std::mem::discriminant(t) == std::mem::discriminant(&event.type_)) {
+                return false;
+            }
+        }
+
+        // Check time range
+        if let Some(start) = self.start_time {
+            if event.timestamp < start {
+                return false;
+            }
+        }
+
+        if let Some(end) = self.end_time {
+            if event.timestamp > end {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
 /// Event store implementation
 pub struct EventStore {
     /// RocksDB store
@@ -115,6 +161,8 @@ pub struct EventStore {
     events_cf: String,
     /// Column family for indexes
     indexes_cf: String,
+    /// Live feed of emitted events
+    sender: broadcast::Sender<Event>,
 }
 
 impl EventStore {
@@ -123,9 +171,20 @@ impl EventStore {
             rocks,
             events_cf: "events".to_string(),
             indexes_cf: "event_indexes".to_string(),
+            sender: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
         }
     }
 
+    /// Receive every event emitted from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+
+    /// Column families used by this store
+    pub fn column_families(&self) -> Vec<&str> {
+        vec![&self.events_cf, &self.indexes_cf]
+    }
+
     /// Emit new event
     pub fn emit_event(&self, event: Event) -> ProtocolResult<()> {
         // Generate event ID if not present
@@ -154,6 +213,9 @@ impl EventStore {
         // Commit batch
         batch.write()?;
 
+        // Publish to live subscribers; having none is fine
+        let _ = self.sender.send(event);
+
         Ok(())
     }
 
@@ -167,7 +229,7 @@ impl EventStore {
             let event: Event = bincode::deserialize(&value_bytes)?;
 
             // Apply filters
-            if self.matches_filter(&event, filter) {
+            if filter.matches(&event) {
                 events.push(event);
             }
 
@@ -185,6 +247,58 @@ impl EventStore {
         Ok(events)
     }
 
+    /// Get up to `limit` matching events in timestamp order, starting after
+    /// `cursor`. Walks the timestamp index instead of loading and sorting the
+    /// whole events column family. The returned cursor resumes the stream and
+    /// is `None` once no matching events remain.
+    pub fn get_events_after(
+        &self,
+        cursor: Option<String>,
+        filter: &EventFilter,
+        limit: usize,
+    ) -> ProtocolResult<(Vec<Event>, Option<String>)> {
+        let start = match (&cursor, filter.start_time) {
+            (Some(cursor), _) => cursor.clone(),
+            (None, Some(start)) => time_index_prefix(&start),
+            (None, None) => TIME_INDEX_PREFIX.to_string(),
+        };
+
+        let mut events = Vec::new();
+        let mut last_key = None;
+        for item in self.rocks.iter_from(&self.indexes_cf, start.as_bytes())? {
+            let (key_bytes, _) = item?;
+            let key = String::from_utf8_lossy(&key_bytes).into_owned();
+            if !key.starts_with(TIME_INDEX_PREFIX) {
+                break;
+            }
+            if cursor.as_deref() == Some(key.as_str()) {
+                continue;
+            }
+
+            // Index entries may outlive pruned events
+            let id = key.splitn(3, ':').nth(2).unwrap_or_default();
+            let event = match self.get_event(id)? {
+                Some(event) => event,
+                None => continue,
+            };
+            if filter.end_time.is_some_and(|end| event.timestamp > end) {
+                break;
+            }
+            if !filter.matches(&event) {
+                continue;
+            }
+
+            // One more match past a full page means the stream continues
+            if events.len() == limit {
+                return Ok((events, last_key));
+            }
+            events.push(event);
+            last_key = Some(key);
+        }
+
+        Ok((events, None))
+    }
+
     /// Get event by ID
     pub fn get_event(&self, id: &str) -> ProtocolResult<Option<Event>> {
         let key = id.as_bytes();
@@ -206,12 +320,46 @@ impl EventStore {
         batch.put(&self.indexes_cf, type_key.as_bytes(), &[])?;
 
         // Index by timestamp
-        let time_key = format!("time:{}:{}", event.timestamp.timestamp(), event.id);
+        let time_key = format!("{}{}", time_index_prefix(&event.timestamp), event.id);
         batch.put(&self.indexes_cf, time_key.as_bytes(), &[])?;
 
         Ok(())
     }
 
+    /// Rewrite timestamp index keys from before they held zero-padded
+    /// microseconds. Those sort out of time order and would be skipped or
+    /// misplaced by `get_events_after`. Returns the number of keys rewritten.
+    pub fn migrate_time_index(&self) -> ProtocolResult<u64> {
+        let mut count = 0;
+        let batch = self.rocks.batch();
+
+        for item in self.rocks.iter_from(&self.indexes_cf, TIME_INDEX_PREFIX.as_bytes())? {
+            let (key_bytes, _) = item?;
+            let key = String::from_utf8_lossy(&key_bytes).into_owned();
+            if !key.starts_with(TIME_INDEX_PREFIX) {
+                break;
+            }
+            let mut parts = key.splitn(3, ':').skip(1);
+            let (Some(timestamp), Some(id)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            if timestamp.len() == TIME_INDEX_DIGITS {
+                continue;
+            }
+
+            // Re-index the event under the new key, if it wasn't pruned
+            batch.delete(&self.indexes_cf, &key_bytes)?;
+            if let Some(event) = self.get_event(id)? {
+                let time_key = format!("{}{}", time_index_prefix(&event.timestamp), event.id);
+                batch.put(&self.indexes_cf, time_key.as_bytes(), &[])?;
+            }
+            count += 1;
+        }
+
+        batch.write()?;
+        Ok(count)
+    }
+
     /// Get type key for indexing
     fn get_type_key(&self, type_: &EventType) -> String {
         match type_ {
@@ -222,31 +370,6 @@ impl EventStore {
         }.to_string()
     }
 
-    /// Check if event matches filter
-    fn matches_filter(&self, event: &Event, filter: &EventFilter) -> bool {
-        // Check type
-        if let Some(types) = &filter.types {
-            if !types.iter().any(|t| std::mem::discriminant(t) == std::mem::discriminant(&event.type_)) {
-                return false;
-            }
-        }
-
-        // Check time range
-        if let Some(start) = filter.start_time {
-            if event.timestamp < start {
-                return false;
-            }
-        }
-
-        if let Some(end) = filter.end_time {
-            if event.timestamp > end {
-                return false;
-            }
-        }
-
-        true
-    }
-
     /// Prune old events
     pub fn prune_events(&self, before: DateTime<Utc>) -> ProtocolResult<u64> {
         let mut count = 0;
@@ -268,6 +391,23 @@ impl EventStore {
     }
 }
 
+/// Prefix of the timestamp index keys
+const TIME_INDEX_PREFIX: &str = "time:";
+
+/// Digits of the timestamp in a timestamp index key
+const TIME_INDEX_DIGITS: usize = 20;
+
+/// Timestamp index key prefix for `timestamp`, zero-padded so keys sort in
+/// time order
+fn time_index_prefix(timestamp: &DateTime<Utc>) -> String {
+    format!(
+        "{}{:0width$}:",
+        TIME_INDEX_PREFIX,
+        timestamp.timestamp_micros(),
+        width = TIME_INDEX_DIGITS
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -312,4 +452,94 @@ mod tests {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_resumable_event_pages() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        })?);
+        let store = EventStore::new(rocks);
+
+        // Emit out of order so the index, not insertion order, decides
+        let start = Utc::now();
+        for i in (0..30).rev() {
+            store.emit_event(Event {
+                id: format!("evt_{:02}", i),
+                type_: EventType::Custom(i.to_string()),
+                timestamp: start + chrono::Duration::seconds(i),
+                metadata: None,
+            })?;
+        }
+
+        let filter = EventFilter {
+            types: None,
+            start_time: None,
+            end_time: None,
+            limit: None,
+        };
+        let mut ids = Vec::new();
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let (events, next) = store.get_events_after(cursor, &filter, 10)?;
+            assert_eq!(events.len(), 10);
+            ids.extend(events.into_iter().map(|e| e.id));
+            pages += 1;
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 3);
+        let expected: Vec<_> = (0..30).map(|i| format!("evt_{:02}", i)).collect();
+        assert_eq!(ids, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_time_index_migrated() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        })?);
+        let store = EventStore::new(rocks.clone());
+
+        // Events indexed under the old `time:{seconds}:{id}` keys, whose
+        // string order puts 100s after 1000s
+        let start = DateTime::<Utc>::from_timestamp(100, 0).unwrap();
+        for (i, seconds) in [(0, 100), (1, 1_000)] {
+            let event = Event {
+                id: format!("evt_{}", i),
+                type_: EventType::Custom(i.to_string()),
+                timestamp: start + chrono::Duration::seconds(seconds - 100),
+                metadata: None,
+            };
+            store.emit_event(event.clone())?;
+            let new_key = format!("{}{}", time_index_prefix(&event.timestamp), event.id);
+            rocks.delete("event_indexes", new_key.as_bytes())?;
+            let old_key = format!("time:{}:{}", event.timestamp.timestamp(), event.id);
+            rocks.put("event_indexes", old_key.as_bytes(), &[])?;
+        }
+
+        assert_eq!(store.migrate_time_index()?, 2);
+        assert_eq!(store.migrate_time_index()?, 0);
+
+        let filter = EventFilter {
+            types: None,
+            start_time: None,
+            end_time: None,
+            limit: None,
+        };
+        let (events, next) = store.get_events_after(None, &filter, 10)?;
+        let ids: Vec<_> = events.into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec!["evt_0", "evt_1"]);
+        assert!(next.is_none());
+
+        Ok(())
+    }
+}
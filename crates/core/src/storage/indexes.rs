@@ -1,6 +1,8 @@
 // storage/indexes.rs
 use super::rocks_store::RocksStore;
-use crate::protocol::{ProtocolError, ProtocolResult};
+use super::StorageError;
+use crate::protocol::ProtocolResult;
+use rocksdb::WriteBatch;
 use serde::{Serialize, Deserialize};
 use std::sync::Arc;
 
@@ -29,6 +31,19 @@ pub enum IndexKey {
     },
 }
 
+impl IndexKey {
+    /// Encoded prefix shared by every custom index key named `name`
+    pub fn custom_prefix(name: &str) -> ProtocolResult<Vec<u8>> {
+        let mut prefix = bincode::serialize(&IndexKey::Custom {
+            name: name.to_string(),
+            key: Vec::new(),
+        })?;
+        // Drop the length of the empty key
+        prefix.truncate(prefix.len() - std::mem::size_of::<u64>());
+        Ok(prefix)
+    }
+}
+
 /// Index value types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum IndexValue {
@@ -58,6 +73,11 @@ impl IndexStore {
         }
     }
 
+    /// Column families used by this store
+    pub fn column_families(&self) -> Vec<&str> {
+        vec![&self.indexes_cf]
+    }
+
     /// Get index value
     pub fn get(&self, key: &IndexKey) -> ProtocolResult<Option<IndexValue>> {
         let key_bytes = bincode::serialize(key)?;
@@ -81,6 +101,27 @@ impl IndexStore {
         Ok(())
     }
 
+    /// Stage an index update into `batch`
+    pub(crate) fn stage_update(
+        &self,
+        batch: &mut WriteBatch,
+        key: &IndexKey,
+        value: &IndexValue,
+    ) -> ProtocolResult<()> {
+        let key_bytes = bincode::serialize(key)?;
+        let value_bytes = bincode::serialize(value)?;
+
+        batch.put_cf(self.rocks.get_cf(&self.indexes_cf)?, &key_bytes, &value_bytes);
+        Ok(())
+    }
+
+    /// Stage an index deletion into `batch`
+    pub(crate) fn stage_delete(&self, batch: &mut WriteBatch, key: &IndexKey) -> ProtocolResult<()> {
+        let key_bytes = bincode::serialize(key)?;
+        batch.delete_cf(self.rocks.get_cf(&self.indexes_cf)?, &key_bytes);
+        Ok(())
+    }
+
     /// Delete index
     pub fn delete(&self, key: &IndexKey) -> ProtocolResult<()> {
         let key_bytes = bincode::serialize(key)?;
@@ -110,18 +151,18 @@ impl IndexStore {
                 IndexValue::EventIds(ids)
             }
             Some(IndexValue::Custom(_)) => {
-                return Err(ProtocolError::Storage(
+                return Err(StorageError::InvalidIndex(
                     "Cannot add to custom index".into()
-                ))
+                ).into())
             }
             None => match key {
                 IndexKey::Object { .. } => IndexValue::ObjectIds(vec![id]),
                 IndexKey::Transaction { .. } => IndexValue::TransactionDigests(vec![id]),
                 IndexKey::Event { .. } => IndexValue::EventIds(vec![id]),
                 IndexKey::Custom { .. } => {
-                    return Err(ProtocolError::Storage(
+                    return Err(StorageError::InvalidIndex(
                         "Cannot add to custom index".into()
-                    ))
+                    ).into())
                 }
             },
         };
@@ -146,9 +187,9 @@ impl IndexStore {
                     IndexValue::EventIds(ids)
                 }
                 IndexValue::Custom(_) => {
-                    return Err(ProtocolError::Storage(
+                    return Err(StorageError::InvalidIndex(
                         "Cannot remove from custom index".into()
-                    ))
+                    ).into())
                 }
             };
 
@@ -158,17 +199,15 @@ impl IndexStore {
         Ok(())
     }
 
-    /// Create index iterator
-    pub fn iter_prefix(&self, prefix: &[u8]) -> ProtocolResult<impl Iterator<Item = (IndexKey, IndexValue)>> {
-        let iter = self.rocks.iter(&self.indexes_cf)?;
+    /// Iterate over the indexes whose encoded key starts with `prefix`,
+    /// seeking to it rather than scanning the column family
+    pub fn iter_prefix<'a>(&'a self, prefix: &'a [u8]) -> ProtocolResult<impl Iterator<Item = (IndexKey, IndexValue)> + 'a> {
+        let iter = self.rocks.iter_from(&self.indexes_cf, prefix)?;
         
         Ok(iter
-            .filter(move |result| {
-                if let Ok((key, _)) = result {
-                    key.starts_with(prefix)
-                } else {
-                    false
-                }
+            .take_while(move |result| match result {
+                Ok((key, _)) => key.starts_with(prefix),
+                Err(_) => false,
             })
             .filter_map(|result| {
                 result.ok().and_then(|(key, value)| {
@@ -233,4 +272,31 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_custom_prefix_iteration() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        })?);
+        let store = IndexStore::new(rocks);
+
+        let custom = |name: &str, key: u8| IndexKey::Custom { name: name.to_string(), key: vec![key] };
+        for (name, key) in [("stakes", 1), ("stakes", 2), ("stakes_old", 3), ("votes", 4)] {
+            store.update(custom(name, key), IndexValue::Custom(vec![key]))?;
+        }
+
+        // A table whose name extends another's isn't part of it
+        let prefix = IndexKey::custom_prefix("stakes")?;
+        let keys: Vec<_> = store.iter_prefix(&prefix)?
+            .map(|(key, _)| match key {
+                IndexKey::Custom { key, .. } => key,
+                other => panic!("unexpected key {:?}", other),
+            })
+            .collect();
+        assert_eq!(keys, vec![vec![1], vec![2]]);
+
+        Ok(())
+    }
 }
\ No newline at end of file
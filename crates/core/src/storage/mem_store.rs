@@ -0,0 +1,166 @@
+// storage/mem_store.rs
+use super::{Event, EventFilter, IndexKey, IndexValue, ObjectKey, ObjectValue, Storage, StorageWrite};
+use crate::core::{ObjectID, SequenceNumber};
+use crate::protocol::ProtocolResult;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// In-memory storage for tests
+#[derive(Default)]
+pub struct MemStorage {
+    /// Objects
+    objects: RwLock<HashMap<ObjectKey, ObjectValue>>,
+    /// Events in emission order
+    events: RwLock<Vec<Event>>,
+    /// Indexes keyed by serialized index key
+    indexes: RwLock<HashMap<Vec<u8>, IndexValue>>,
+}
+
+impl MemStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemStorage {
+    fn get_object(&self, key: &ObjectKey) -> ProtocolResult<Option<ObjectValue>> {
+        Ok(self.objects.read().unwrap().get(key).cloned())
+    }
+
+    fn put_object(&self, key: ObjectKey, value: ObjectValue) -> ProtocolResult<()> {
+        self.objects.write().unwrap().insert(key, value);
+        Ok(())
+    }
+
+    fn delete_object(&self, key: &ObjectKey) -> ProtocolResult<()> {
+        self.objects.write().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn latest_version(&self, id: &ObjectID) -> ProtocolResult<Option<SequenceNumber>> {
+        Ok(self.objects.read().unwrap()
+            .keys()
+            .filter(|key| key.id == *id)
+            .map(|key| key.version)
+            .max())
+    }
+
+    fn get_events(&self, filter: &EventFilter) -> ProtocolResult<Vec<Event>> {
+        let mut events: Vec<Event> = self.events.read().unwrap()
+            .iter()
+            .filter(|event| filter.matches(event))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+
+        // Sort by timestamp
+        events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+        Ok(events)
+    }
+
+    fn emit_event(&self, event: Event) -> ProtocolResult<()> {
+        // Generate event ID if not present
+        let event = if event.id.is_empty() {
+            Event {
+                id: format!("evt_{}", uuid::Uuid::new_v4()),
+                ..event
+            }
+        } else {
+            event
+        };
+
+        self.events.write().unwrap().push(event);
+        Ok(())
+    }
+
+    fn get_index(&self, key: &IndexKey) -> ProtocolResult<Option<IndexValue>> {
+        let key_bytes = bincode::serialize(key)?;
+        Ok(self.indexes.read().unwrap().get(&key_bytes).cloned())
+    }
+
+    fn update_index(&self, key: IndexKey, value: IndexValue) -> ProtocolResult<()> {
+        let key_bytes = bincode::serialize(&key)?;
+        self.indexes.write().unwrap().insert(key_bytes, value);
+        Ok(())
+    }
+
+    fn write_atomic(&self, writes: Vec<StorageWrite>) -> ProtocolResult<()> {
+        // Encode index keys first so nothing is applied if one fails
+        let index_keys = writes.iter()
+            .map(|write| match write {
+                StorageWrite::Index(key, _) | StorageWrite::DeleteIndex(key) => {
+                    bincode::serialize(key).map(Some)
+                }
+                _ => Ok(None),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Hold both locks so readers never see part of the batch
+        let mut objects = self.objects.write().unwrap();
+        let mut indexes = self.indexes.write().unwrap();
+        for (write, index_key) in writes.into_iter().zip(index_keys) {
+            match write {
+                StorageWrite::PutObject(key, value) => {
+                    objects.insert(key, value);
+                }
+                StorageWrite::DeleteObject(key) if key.version == SequenceNumber::MAX => {
+                    let latest = objects.keys()
+                        .filter(|stored| stored.id == key.id)
+                        .max_by_key(|stored| stored.version)
+                        .cloned();
+                    if let Some(latest) = latest {
+                        objects.remove(&latest);
+                    }
+                }
+                StorageWrite::DeleteObject(key) => {
+                    objects.remove(&key);
+                }
+                StorageWrite::Index(_, value) => {
+                    indexes.extend(index_key.map(|key| (key, value)));
+                }
+                StorageWrite::DeleteIndex(_) => {
+                    if let Some(key) = index_key {
+                        indexes.remove(&key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ObjectID, SequenceNumber};
+
+    #[test]
+    fn test_mem_storage_objects() -> ProtocolResult<()> {
+        let storage = MemStorage::new();
+
+        let key = ObjectKey {
+            id: ObjectID::random(),
+            version: SequenceNumber::new(1),
+        };
+
+        let value = ObjectValue {
+            data: vec![1, 2, 3],
+            owner: "test".to_string(),
+            type_: "TestObject".to_string(),
+            created_at: 100,
+            modified_at: 100,
+        };
+
+        assert!(storage.get_object(&key)?.is_none());
+
+        storage.put_object(key.clone(), value.clone())?;
+        let retrieved = storage.get_object(&key)?.unwrap();
+        assert_eq!(retrieved.data, value.data);
+
+        storage.delete_object(&key)?;
+        assert!(storage.get_object(&key)?.is_none());
+
+        Ok(())
+    }
+}
@@ -5,15 +5,30 @@ mod event_store;
 mod rocks_store;
 mod indexes;
 mod cache;
+mod compaction;
+#[cfg(any(test, feature = "test-utils"))]
+mod mem_store;
+#[cfg(test)]
+pub(crate) mod test_utils;
 
-pub use object_store::{ObjectStore, ObjectKey, ObjectValue};
-pub use event_store::{EventStore, Event, EventFilter};
-pub use rocks_store::{RocksStore, RocksConfig};
+pub use object_store::{ObjectStore, ObjectKey, ObjectValue, ObjectWriteError};
+use object_store::StagedObjects;
+pub use event_store::{
+    EventStore, Event, EventFilter, EventType, ObjectEvent, SystemEvent, TransactionEvent,
+    EVENT_CHANNEL_CAPACITY,
+};
+pub use rocks_store::{RocksStore, RocksConfig, COLUMN_FAMILIES, SCHEMA_VERSION};
 pub use indexes::{IndexStore, IndexKey, IndexValue};
 pub use cache::{CacheStore, CacheConfig};
+pub use compaction::{CompactionScheduler, CompactionConfig};
+#[cfg(any(test, feature = "test-utils"))]
+pub use mem_store::MemStorage;
 
-use crate::protocol::{ProtocolError, ProtocolResult};
+use crate::core::{ObjectID, SequenceNumber};
+use crate::protocol::{ErrorClass, ProtocolError, ProtocolResult};
+use move_core_types::account_address::AccountAddress;
 use std::sync::Arc;
+use tokio::task::JoinHandle;
 
 /// Storage configuration
 #[derive(Debug, Clone)]
@@ -26,16 +41,94 @@ pub struct StorageConfig {
     pub rocks_config: RocksConfig,
 }
 
+/// Storage error types
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("RocksDB error: {0}")]
+    Rocks(#[from] rocksdb::Error),
+
+    #[error("Column family not found: {0} (add it to rocks_store::COLUMN_FAMILIES)")]
+    ColumnFamilyNotFound(String),
+
+    #[error("Database schema version {found} is newer than supported version {supported}")]
+    SchemaVersionTooNew { found: u32, supported: u32 },
+
+    #[error("Invalid storage configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Invalid index operation: {0}")]
+    InvalidIndex(String),
+
+    #[error(transparent)]
+    ObjectWrite(Box<ObjectWriteError>),
+}
+
+impl From<ObjectWriteError> for StorageError {
+    fn from(error: ObjectWriteError) -> Self {
+        StorageError::ObjectWrite(Box::new(error))
+    }
+}
+
+impl StorageError {
+    /// Classify the error for retry decisions
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            StorageError::Rocks(e) => rocksdb_error_class(e.kind()),
+            StorageError::ObjectWrite(e) => e.source_error().class(),
+            _ => ErrorClass::Permanent,
+        }
+    }
+}
+
+/// Class of a RocksDB status code
+fn rocksdb_error_class(kind: rocksdb::ErrorKind) -> ErrorClass {
+    use rocksdb::ErrorKind;
+    match kind {
+        ErrorKind::Busy
+        | ErrorKind::TryAgain
+        | ErrorKind::TimedOut
+        | ErrorKind::Incomplete
+        | ErrorKind::MergeInProgress => ErrorClass::Transient,
+        _ => ErrorClass::Permanent,
+    }
+}
+
+/// One write of an atomic batch
+#[derive(Debug, Clone)]
+pub enum StorageWrite {
+    /// Put an object version
+    PutObject(ObjectKey, ObjectValue),
+    /// Delete an object version; `SequenceNumber::MAX` deletes the latest
+    DeleteObject(ObjectKey),
+    /// Set an index entry
+    Index(IndexKey, IndexValue),
+    /// Remove an index entry
+    DeleteIndex(IndexKey),
+}
+
 /// Main storage interface
 pub trait Storage: Send + Sync {
     /// Get object by key
     fn get_object(&self, key: &ObjectKey) -> ProtocolResult<Option<ObjectValue>>;
     
+    /// Get several objects at once
+    fn multi_get_objects(&self, keys: &[ObjectKey]) -> ProtocolResult<Vec<Option<ObjectValue>>> {
+        keys.iter().map(|key| self.get_object(key)).collect()
+    }
+
     /// Put object
     fn put_object(&self, key: ObjectKey, value: ObjectValue) -> ProtocolResult<()>;
     
     /// Delete object
     fn delete_object(&self, key: &ObjectKey) -> ProtocolResult<()>;
+
+    /// Latest stored version of an object
+    fn latest_version(&self, id: &ObjectID) -> ProtocolResult<Option<SequenceNumber>>;
+
+    /// Latest stored versions of several objects
+    fn multi_latest_versions(&self, ids: &[ObjectID]) -> ProtocolResult<Vec<Option<SequenceNumber>>> {
+        ids.iter().map(|id| self.latest_version(id)).collect()
+    }
     
     /// Get events by filter
     fn get_events(&self, filter: &EventFilter) -> ProtocolResult<Vec<Event>>;
@@ -48,10 +141,71 @@ pub trait Storage: Send + Sync {
     
     /// Update index
     fn update_index(&self, key: IndexKey, value: IndexValue) -> ProtocolResult<()>;
+
+    /// Apply all of `writes` or none of them
+    fn write_atomic(&self, writes: Vec<StorageWrite>) -> ProtocolResult<()>;
+}
+
+/// Index key of the reference gas price
+fn reference_gas_price_key() -> IndexKey {
+    IndexKey::Custom {
+        name: "system".to_string(),
+        key: b"reference_gas_price".to_vec(),
+    }
+}
+
+/// Get the persisted reference gas price, if one has been set
+pub fn reference_gas_price(storage: &dyn Storage) -> ProtocolResult<Option<u64>> {
+    match storage.get_index(&reference_gas_price_key())? {
+        Some(IndexValue::Custom(bytes)) => {
+            let bytes: [u8; 8] = bytes.try_into()
+                .map_err(|_| ProtocolError::DeserializationError("Invalid reference gas price".into()))?;
+            Ok(Some(u64::from_le_bytes(bytes)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Persist the reference gas price
+pub fn set_reference_gas_price(storage: &dyn Storage, price: u64) -> ProtocolResult<()> {
+    storage.update_index(
+        reference_gas_price_key(),
+        IndexValue::Custom(price.to_le_bytes().to_vec()),
+    )
+}
+
+/// Index key of the number of modules published at `address`
+fn module_count_key(address: &AccountAddress) -> IndexKey {
+    IndexKey::Custom {
+        name: "module_count".to_string(),
+        key: address.to_vec(),
+    }
+}
+
+/// Get the number of modules published at `address`
+pub fn module_count(storage: &dyn Storage, address: &AccountAddress) -> ProtocolResult<usize> {
+    match storage.get_index(&module_count_key(address))? {
+        Some(IndexValue::Custom(bytes)) => {
+            let bytes: [u8; 8] = bytes.try_into()
+                .map_err(|_| ProtocolError::DeserializationError("Invalid module count".into()))?;
+            Ok(u64::from_le_bytes(bytes) as usize)
+        }
+        _ => Ok(0),
+    }
+}
+
+/// Persist the number of modules published at `address`
+pub fn set_module_count(storage: &dyn Storage, address: &AccountAddress, count: usize) -> ProtocolResult<()> {
+    storage.update_index(
+        module_count_key(address),
+        IndexValue::Custom((count as u64).to_le_bytes().to_vec()),
+    )
 }
 
 /// Storage manager
 pub struct StorageManager {
+    /// RocksDB store shared by the stores below
+    rocks: Arc<RocksStore>,
     /// Object store
     object_store: Arc<ObjectStore>,
     /// Event store
@@ -60,9 +214,13 @@ pub struct StorageManager {
     index_store: Arc<IndexStore>,
     /// Cache store
     cache_store: Arc<CacheStore>,
+    /// Background compaction task, running when opened inside a runtime
+    compaction: Option<JoinHandle<()>>,
 }
 
 impl StorageManager {
+    /// Open storage. Inside a tokio runtime this also starts the default
+    /// compaction schedule, stopped when the manager is dropped.
     pub fn new(config: StorageConfig) -> ProtocolResult<Self> {
         // Initialize RocksDB
         let rocks = RocksStore::new(&config.rocks_config)?;
@@ -72,12 +230,33 @@ impl StorageManager {
         let event_store = Arc::new(EventStore::new(rocks.clone()));
         let index_store = Arc::new(IndexStore::new(rocks.clone()));
         let cache_store = Arc::new(CacheStore::new(config.cache_config));
+
+        // Fail fast if any store relies on an undeclared column family
+        rocks.require_column_families(&object_store.column_families())?;
+        rocks.require_column_families(&event_store.column_families())?;
+        rocks.require_column_families(&index_store.column_families())?;
+
+        // Bring timestamp index keys written by older versions up to date
+        let migrated = event_store.migrate_time_index()?;
+        if migrated > 0 {
+            log::info!("Migrated {} event timestamp index keys", migrated);
+        }
+
+        let compaction = match tokio::runtime::Handle::try_current() {
+            Ok(_) => {
+                let scheduler = CompactionScheduler::new(rocks.clone(), CompactionConfig::default())?;
+                Some(Arc::new(scheduler).start())
+            }
+            Err(_) => None,
+        };
         
         Ok(Self {
+            rocks,
             object_store,
             event_store,
             index_store,
             cache_store,
+            compaction,
         })
     }
     
@@ -96,10 +275,38 @@ impl StorageManager {
     pub fn cache_store(&self) -> Arc<CacheStore> {
         self.cache_store.clone()
     }
+
+    /// Get the latest version of an object. The version comes from the
+    /// object metadata, so the cache only answers for that exact version
+    /// and a newer write is never shadowed by an older cached one.
+    pub fn get_latest_object(&self, id: &ObjectID) -> ProtocolResult<Option<ObjectValue>> {
+        match self.object_store.get_latest_version(id)? {
+            Some(version) => self.get_object(&ObjectKey { id: *id, version }),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether the background compaction schedule is running
+    pub fn is_compacting(&self) -> bool {
+        self.compaction.as_ref().is_some_and(|handle| !handle.is_finished())
+    }
+}
+
+impl Drop for StorageManager {
+    fn drop(&mut self) {
+        if let Some(handle) = self.compaction.take() {
+            handle.abort();
+        }
+    }
 }
 
 impl Storage for StorageManager {
     fn get_object(&self, key: &ObjectKey) -> ProtocolResult<Option<ObjectValue>> {
+        // Resolve "latest" reads to a concrete version before the cache
+        if key.version == SequenceNumber::MAX {
+            return self.get_latest_object(&key.id);
+        }
+
         // Try cache first
         if let Some(value) = self.cache_store.get(key)? {
             return Ok(Some(value));
@@ -116,6 +323,42 @@ impl Storage for StorageManager {
         Ok(value)
     }
     
+    fn multi_get_objects(&self, keys: &[ObjectKey]) -> ProtocolResult<Vec<Option<ObjectValue>>> {
+        // Resolve "latest" reads to concrete versions before the cache, with
+        // one batched metadata read
+        let latest: Vec<usize> = (0..keys.len())
+            .filter(|&i| keys[i].version == SequenceNumber::MAX)
+            .collect();
+        let latest_ids: Vec<ObjectID> = latest.iter().map(|&i| keys[i].id).collect();
+        let mut resolved: Vec<Option<ObjectKey>> = keys.iter().cloned().map(Some).collect();
+        for (i, version) in latest.into_iter().zip(self.object_store.multi_get_latest_versions(&latest_ids)?) {
+            resolved[i] = version.map(|version| ObjectKey { id: keys[i].id, version });
+        }
+
+        let mut values = resolved.iter()
+            .map(|key| match key {
+                Some(key) => self.cache_store.get(key),
+                None => Ok(None),
+            })
+            .collect::<ProtocolResult<Vec<_>>>()?;
+
+        // Read every cache miss in one batch
+        let missing: Vec<usize> = (0..keys.len())
+            .filter(|&i| values[i].is_none() && resolved[i].is_some())
+            .collect();
+        let missing_keys: Vec<ObjectKey> = missing.iter().filter_map(|&i| resolved[i].clone()).collect();
+        let fetched = self.object_store.multi_get(&missing_keys)?;
+
+        for ((i, key), value) in missing.into_iter().zip(missing_keys).zip(fetched) {
+            if let Some(value) = &value {
+                self.cache_store.put(key, value.clone())?;
+            }
+            values[i] = value;
+        }
+
+        Ok(values)
+    }
+
     fn put_object(&self, key: ObjectKey, value: ObjectValue) -> ProtocolResult<()> {
         // Update object store
         self.object_store.put(key.clone(), value.clone())?;
@@ -135,6 +378,14 @@ impl Storage for StorageManager {
         
         Ok(())
     }
+
+    fn latest_version(&self, id: &ObjectID) -> ProtocolResult<Option<SequenceNumber>> {
+        self.object_store.get_latest_version(id)
+    }
+
+    fn multi_latest_versions(&self, ids: &[ObjectID]) -> ProtocolResult<Vec<Option<SequenceNumber>>> {
+        self.object_store.multi_get_latest_versions(ids)
+    }
     
     fn get_events(&self, filter: &EventFilter) -> ProtocolResult<Vec<Event>> {
         self.event_store.get_events(filter)
@@ -151,4 +402,170 @@ impl Storage for StorageManager {
     fn update_index(&self, key: IndexKey, value: IndexValue) -> ProtocolResult<()> {
         self.index_store.update(key, value)
     }
-}
\ No newline at end of file
+
+    fn write_atomic(&self, writes: Vec<StorageWrite>) -> ProtocolResult<()> {
+        let mut batch = self.rocks.batch();
+        let mut staged = StagedObjects::default();
+        let mut removed = Vec::new();
+        for write in &writes {
+            match write {
+                StorageWrite::PutObject(key, value) => {
+                    self.object_store.stage_put(&mut batch, &mut staged, key, value)?;
+                }
+                StorageWrite::DeleteObject(key) => {
+                    removed.extend(self.object_store.stage_delete(&mut batch, &mut staged, key)?);
+                }
+                StorageWrite::Index(key, value) => {
+                    self.index_store.stage_update(&mut batch, key, value)?;
+                }
+                StorageWrite::DeleteIndex(key) => {
+                    self.index_store.stage_delete(&mut batch, key)?;
+                }
+            }
+        }
+        self.rocks.write_batch(batch)?;
+        self.object_store.record_staged(&staged);
+
+        // The cache only changes once the batch is durable
+        for write in writes {
+            if let StorageWrite::PutObject(key, value) = write {
+                self.cache_store.put(key, value)?;
+            }
+        }
+        for key in &removed {
+            self.cache_store.delete(key)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use test_utils::temp_storage;
+
+    #[test]
+    fn test_storage_error_classes() {
+        assert_eq!(rocksdb_error_class(rocksdb::ErrorKind::Busy), ErrorClass::Transient);
+        assert_eq!(rocksdb_error_class(rocksdb::ErrorKind::Corruption), ErrorClass::Permanent);
+        assert_eq!(StorageError::InvalidConfig("bad".into()).class(), ErrorClass::Permanent);
+
+        let error: ProtocolError = StorageError::ColumnFamilyNotFound("missing".into()).into();
+        assert!(matches!(error, ProtocolError::Storage(StorageError::ColumnFamilyNotFound(_))));
+    }
+
+    #[test]
+    fn test_latest_read_sees_new_version() {
+        let (_temp_dir, config) = temp_storage();
+        let storage = StorageManager::new(config).unwrap();
+
+        let id = ObjectID::random();
+        let value = |data: u8| ObjectValue {
+            data: vec![data],
+            owner: "alice".to_string(),
+            type_: "0x2::counter::Counter".to_string(),
+            created_at: 0,
+            modified_at: 0,
+        };
+        let latest = ObjectKey { id, version: SequenceNumber::MAX };
+
+        storage.put_object(ObjectKey { id, version: SequenceNumber::new(1) }, value(1)).unwrap();
+        assert_eq!(storage.get_object(&latest).unwrap().unwrap().data, vec![1]);
+
+        // Version 1 is still cached, but the latest read follows the bump
+        storage.put_object(ObjectKey { id, version: SequenceNumber::new(2) }, value(2)).unwrap();
+        assert_eq!(storage.get_latest_object(&id).unwrap().unwrap().data, vec![2]);
+        assert_eq!(storage.get_object(&latest).unwrap().unwrap().data, vec![2]);
+        assert_eq!(
+            storage.get_object(&ObjectKey { id, version: SequenceNumber::new(1) }).unwrap().unwrap().data,
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_multi_get_latest_on_cold_cache() {
+        let (_temp_dir, config) = temp_storage();
+        let value = |data: u8| ObjectValue {
+            data: vec![data],
+            owner: "alice".to_string(),
+            type_: "0x2::counter::Counter".to_string(),
+            created_at: 0,
+            modified_at: 0,
+        };
+
+        let (a, b) = (ObjectID::random(), ObjectID::random());
+        {
+            let storage = StorageManager::new(config.clone()).unwrap();
+            storage.put_object(ObjectKey { id: a, version: SequenceNumber::new(1) }, value(1)).unwrap();
+            storage.put_object(ObjectKey { id: a, version: SequenceNumber::new(2) }, value(2)).unwrap();
+            storage.put_object(ObjectKey { id: b, version: SequenceNumber::new(5) }, value(5)).unwrap();
+        }
+
+        // Reopen so nothing is cached
+        let storage = StorageManager::new(config).unwrap();
+        let values = storage.multi_get_objects(&[
+            ObjectKey::latest(&a),
+            ObjectKey::latest(&b),
+            ObjectKey::latest(&ObjectID::random()),
+            ObjectKey { id: a, version: SequenceNumber::new(1) },
+        ]).unwrap();
+
+        let data: Vec<_> = values.into_iter().map(|v| v.map(|v| v.data)).collect();
+        assert_eq!(data, vec![Some(vec![2]), Some(vec![5]), None, Some(vec![1])]);
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let (_temp_dir, config) = temp_storage();
+        let storage = StorageManager::new(config).unwrap();
+        let value = |data: u8| ObjectValue {
+            data: vec![data],
+            owner: "alice".to_string(),
+            type_: "0x2::counter::Counter".to_string(),
+            created_at: 0,
+            modified_at: 0,
+        };
+
+        let (a, b) = (ObjectID::random(), ObjectID::random());
+        storage.put_object(ObjectKey { id: a, version: SequenceNumber::new(1) }, value(1)).unwrap();
+        assert!(storage.get_object(&ObjectKey::latest(&a)).unwrap().is_some());
+
+        let index = IndexKey::Custom { name: "test".to_string(), key: vec![1] };
+        storage.write_atomic(vec![
+            StorageWrite::DeleteObject(ObjectKey::latest(&a)),
+            StorageWrite::PutObject(ObjectKey { id: b, version: SequenceNumber::new(1) }, value(2)),
+            StorageWrite::Index(index.clone(), IndexValue::Custom(vec![3])),
+        ]).unwrap();
+
+        // The cached copy of `a` is gone along with the stored one
+        assert!(storage.get_object(&ObjectKey::latest(&a)).unwrap().is_none());
+        assert_eq!(storage.get_object(&ObjectKey::latest(&b)).unwrap().unwrap().data, vec![2]);
+        assert!(matches!(storage.get_index(&index).unwrap(), Some(IndexValue::Custom(v)) if v == vec![3]));
+    }
+
+    #[tokio::test]
+    async fn test_compaction_started_with_runtime() {
+        let config = |temp_dir: &TempDir| {
+            let path = temp_dir.path().to_str().unwrap().to_string();
+            StorageConfig {
+                data_dir: path.clone(),
+                cache_config: CacheConfig::default(),
+                rocks_config: RocksConfig { path, ..Default::default() },
+            }
+        };
+
+        let temp_dir = TempDir::new().unwrap();
+        let storage = StorageManager::new(config(&temp_dir)).unwrap();
+        assert!(storage.is_compacting());
+
+        // Outside a runtime there is nothing to spawn onto
+        let other_dir = TempDir::new().unwrap();
+        let other_config = config(&other_dir);
+        let other = std::thread::spawn(move || StorageManager::new(other_config).unwrap())
+            .join()
+            .unwrap();
+        assert!(!other.is_compacting());
+    }
+}
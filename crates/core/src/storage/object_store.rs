@@ -2,8 +2,16 @@
 use super::rocks_store::RocksStore;
 use crate::protocol::{ProtocolError, ProtocolResult};
 use crate::core::{ObjectID, SequenceNumber};
+use crate::metrics::StorageMetrics;
+use crate::utils::safe_decode;
+use rocksdb::WriteBatch;
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use thiserror::Error;
+
+/// Maximum size of a decoded object record
+const MAX_OBJECT_SIZE: u64 = 64 * 1024 * 1024;
 
 /// Object key for storage
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
@@ -14,6 +22,13 @@ pub struct ObjectKey {
     pub version: SequenceNumber,
 }
 
+impl ObjectKey {
+    /// Key reading whatever version of `id` is latest
+    pub fn latest(id: &ObjectID) -> Self {
+        Self { id: *id, version: SequenceNumber::MAX }
+    }
+}
+
 /// Object value in storage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectValue {
@@ -40,6 +55,61 @@ pub struct ObjectMetadata {
     pub ref_count: u64,
 }
 
+/// Object writes staged into a batch that the caller commits
+#[derive(Default)]
+pub(crate) struct StagedObjects {
+    /// Metadata as it will be once the batch commits
+    metadata: HashMap<ObjectID, ObjectMetadata>,
+    /// Types of objects the batch makes live
+    created: Vec<String>,
+    /// Types of objects the batch removes
+    deleted: Vec<String>,
+}
+
+/// Failed object write. Object and metadata are committed in one atomic
+/// batch, so none of the write is persisted and it can be retried as is.
+#[derive(Error, Debug)]
+pub enum ObjectWriteError {
+    #[error("Object write failed for {key:?}: {source}")]
+    Object {
+        key: ObjectKey,
+        #[source]
+        source: ProtocolError,
+    },
+
+    #[error("Metadata write failed for {key:?}: {source}")]
+    Metadata {
+        key: ObjectKey,
+        #[source]
+        source: ProtocolError,
+    },
+
+    #[error("Batch commit failed for {key:?}: {source}")]
+    Commit {
+        key: ObjectKey,
+        #[source]
+        source: ProtocolError,
+    },
+}
+
+impl ObjectWriteError {
+    /// Key of the object that wasn't written
+    pub fn key(&self) -> &ObjectKey {
+        match self {
+            Self::Object { key, .. } | Self::Metadata { key, .. } | Self::Commit { key, .. } => key,
+        }
+    }
+
+    /// Underlying storage error
+    pub fn source_error(&self) -> &ProtocolError {
+        match self {
+            Self::Object { source, .. }
+            | Self::Metadata { source, .. }
+            | Self::Commit { source, .. } => source,
+        }
+    }
+}
+
 /// Object store implementation
 pub struct ObjectStore {
     /// RocksDB store
@@ -48,6 +118,8 @@ pub struct ObjectStore {
     objects_cf: String,
     /// Column family for metadata
     metadata_cf: String,
+    /// Live object counts (optional)
+    metrics: Option<StorageMetrics>,
 }
 
 impl ObjectStore {
@@ -56,9 +128,21 @@ impl ObjectStore {
             rocks,
             objects_cf: "objects".to_string(),
             metadata_cf: "object_metadata".to_string(),
+            metrics: None,
         }
     }
 
+    /// Count live objects, in total and per type tag, in `metrics`
+    pub fn with_metrics(mut self, metrics: StorageMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Column families used by this store
+    pub fn column_families(&self) -> Vec<&str> {
+        vec![&self.objects_cf, &self.metadata_cf]
+    }
+
     /// Get object by key
     pub fn get(&self, key: &ObjectKey) -> ProtocolResult<Option<ObjectValue>> {
         // Check metadata first
@@ -75,17 +159,71 @@ impl ObjectStore {
 
         match value_bytes {
             Some(bytes) => {
-                let value: ObjectValue = bincode::deserialize(&bytes)?;
+                let value: ObjectValue = safe_decode(&bytes, MAX_OBJECT_SIZE)?;
                 Ok(Some(value))
             }
             None => Ok(None),
         }
     }
 
-    /// Put object
-    pub fn put(&self, key: ObjectKey, value: ObjectValue) -> ProtocolResult<()> {
+    /// Get several objects with one batched read of the metadata column
+    /// family and one of the objects column family. Keys at
+    /// `SequenceNumber::MAX` read the latest version.
+    pub fn multi_get(&self, keys: &[ObjectKey]) -> ProtocolResult<Vec<Option<ObjectValue>>> {
+        let ids: Vec<ObjectID> = keys.iter().map(|key| key.id).collect();
+        let metadata = self.multi_get_metadata(&ids)?;
+
+        // Resolve latest reads; deleted or unknown objects read nothing
+        let resolved: Vec<Option<ObjectKey>> = keys.iter()
+            .zip(&metadata)
+            .map(|(key, meta)| match meta {
+                Some(meta) if meta.deleted => None,
+                Some(meta) if key.version == SequenceNumber::MAX => {
+                    Some(ObjectKey { id: key.id, version: meta.latest_version })
+                }
+                None if key.version == SequenceNumber::MAX => None,
+                _ => Some(key.clone()),
+            })
+            .collect();
+
+        let key_bytes = resolved.iter()
+            .flatten()
+            .map(bincode::serialize)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut values = self.rocks.multi_get(&self.objects_cf, &key_bytes)?.into_iter();
+
+        resolved.iter()
+            .map(|key| match key.as_ref().and_then(|_| values.next().flatten()) {
+                Some(bytes) => Ok(Some(safe_decode(&bytes, MAX_OBJECT_SIZE)?)),
+                None => Ok(None),
+            })
+            .collect()
+    }
+
+    /// Latest versions of several objects with one batched metadata read
+    pub fn multi_get_latest_versions(
+        &self,
+        ids: &[ObjectID],
+    ) -> ProtocolResult<Vec<Option<SequenceNumber>>> {
+        Ok(self.multi_get_metadata(ids)?
+            .into_iter()
+            .map(|meta| meta.map(|m| m.latest_version))
+            .collect())
+    }
+
+    /// Put object. The object and its metadata are written in one batch,
+    /// so a failure leaves the store unchanged.
+    pub fn put(&self, key: ObjectKey, value: ObjectValue) -> Result<(), ObjectWriteError> {
+        let object_error = |source: ProtocolError| ObjectWriteError::Object { key: key.clone(), source };
+        let metadata_error = |source: ProtocolError| ObjectWriteError::Metadata { key: key.clone(), source };
+
+        let key_bytes = bincode::serialize(&key).map_err(|e| object_error(e.into()))?;
+        let value_bytes = bincode::serialize(&value).map_err(|e| object_error(e.into()))?;
+
         // Update metadata
-        let mut metadata = self.get_metadata(&key.id)?.unwrap_or_else(|| ObjectMetadata {
+        let existing = self.get_metadata(&key.id).map_err(metadata_error)?;
+        let created = existing.as_ref().is_none_or(|meta| meta.deleted);
+        let mut metadata = existing.unwrap_or_else(|| ObjectMetadata {
             latest_version: key.version,
             deleted: false,
             ref_count: 0,
@@ -94,48 +232,172 @@ impl ObjectStore {
         if key.version > metadata.latest_version {
             metadata.latest_version = key.version;
         }
-        metadata.ref_count += 1;
+        // Only a new version adds a live reference; overwrites don't
+        if self.rocks.get(&self.objects_cf, &key_bytes).map_err(object_error)?.is_none() {
+            metadata.ref_count += 1;
+        }
+        metadata.deleted = false;
 
         // Write object
-        let key_bytes = bincode::serialize(&key)?;
-        let value_bytes = bincode::serialize(&value)?;
-        
-        let batch = self.rocks.batch();
-        batch.put(&self.objects_cf, &key_bytes, &value_bytes)?;
-        
+        let mut batch = self.rocks.batch();
+        let objects_cf = self.rocks.get_cf(&self.objects_cf).map_err(object_error)?;
+        batch.put_cf(objects_cf, &key_bytes, &value_bytes);
+
         // Write metadata
-        let metadata_key = bincode::serialize(&key.id)?;
-        let metadata_value = bincode::serialize(&metadata)?;
-        batch.put(&self.metadata_cf, &metadata_key, &metadata_value)?;
-        
-        batch.write()?;
+        let metadata_key = bincode::serialize(&key.id).map_err(|e| metadata_error(e.into()))?;
+        let metadata_value = bincode::serialize(&metadata).map_err(|e| metadata_error(e.into()))?;
+        let metadata_cf = self.rocks.get_cf(&self.metadata_cf).map_err(metadata_error)?;
+        batch.put_cf(metadata_cf, &metadata_key, &metadata_value);
+
+        self.rocks.write_batch(batch)
+            .map_err(|source| ObjectWriteError::Commit { key: key.clone(), source })?;
+
+        if created {
+            if let Some(metrics) = &self.metrics {
+                metrics.total_objects.inc();
+                metrics.objects_by_type.inc(&[&value.type_]);
+            }
+        }
         
         Ok(())
     }
 
-    /// Delete object
+    /// Delete object version
     pub fn delete(&self, key: &ObjectKey) -> ProtocolResult<()> {
+        let key_bytes = bincode::serialize(key)?;
+
+        // Nothing to do if this version isn't stored
+        let Some(value_bytes) = self.rocks.get(&self.objects_cf, &key_bytes)? else {
+            return Ok(());
+        };
+
         // Update metadata
         if let Some(mut metadata) = self.get_metadata(&key.id)? {
-            metadata.deleted = true;
             metadata.ref_count = metadata.ref_count.saturating_sub(1);
+            metadata.deleted = metadata.ref_count == 0;
 
             let metadata_key = bincode::serialize(&key.id)?;
             let metadata_value = bincode::serialize(&metadata)?;
 
-            let batch = self.rocks.batch();
-            batch.put(&self.metadata_cf, &metadata_key, &metadata_value)?;
+            let mut batch = self.rocks.batch();
+            batch.put_cf(self.rocks.get_cf(&self.metadata_cf)?, &metadata_key, &metadata_value);
             
             // Delete object
-            let key_bytes = bincode::serialize(key)?;
-            batch.delete(&self.objects_cf, &key_bytes)?;
+            batch.delete_cf(self.rocks.get_cf(&self.objects_cf)?, &key_bytes);
             
-            batch.write()?;
+            self.rocks.write_batch(batch)?;
+
+            if let (true, Some(metrics)) = (metadata.deleted, &self.metrics) {
+                let value: ObjectValue = safe_decode(&value_bytes, MAX_OBJECT_SIZE)?;
+                metrics.total_objects.dec();
+                metrics.objects_by_type.dec(&[&value.type_]);
+            }
         }
 
         Ok(())
     }
 
+    /// Stage an object put into `batch`. Metadata is tracked in `staged`
+    /// so several writes to one object in the batch stay consistent.
+    pub(crate) fn stage_put(
+        &self,
+        batch: &mut WriteBatch,
+        staged: &mut StagedObjects,
+        key: &ObjectKey,
+        value: &ObjectValue,
+    ) -> ProtocolResult<()> {
+        let key_bytes = bincode::serialize(key)?;
+        let value_bytes = bincode::serialize(value)?;
+
+        let existing = self.staged_metadata(staged, &key.id)?;
+        let created = existing.as_ref().is_none_or(|meta| meta.deleted);
+        let mut metadata = existing.unwrap_or(ObjectMetadata {
+            latest_version: key.version,
+            deleted: false,
+            ref_count: 0,
+        });
+        if key.version > metadata.latest_version {
+            metadata.latest_version = key.version;
+        }
+        if self.rocks.get(&self.objects_cf, &key_bytes)?.is_none() {
+            metadata.ref_count += 1;
+        }
+        metadata.deleted = false;
+
+        let metadata_key = bincode::serialize(&key.id)?;
+        let metadata_value = bincode::serialize(&metadata)?;
+        batch.put_cf(self.rocks.get_cf(&self.objects_cf)?, &key_bytes, &value_bytes);
+        batch.put_cf(self.rocks.get_cf(&self.metadata_cf)?, &metadata_key, &metadata_value);
+
+        if created {
+            staged.created.push(value.type_.clone());
+        }
+        staged.metadata.insert(key.id, metadata);
+        Ok(())
+    }
+
+    /// Stage deleting an object version into `batch`; `SequenceNumber::MAX`
+    /// deletes the latest version. Returns the key deleted, if it was stored.
+    pub(crate) fn stage_delete(
+        &self,
+        batch: &mut WriteBatch,
+        staged: &mut StagedObjects,
+        key: &ObjectKey,
+    ) -> ProtocolResult<Option<ObjectKey>> {
+        let Some(mut metadata) = self.staged_metadata(staged, &key.id)? else {
+            return Ok(None);
+        };
+        let key = if key.version == SequenceNumber::MAX {
+            ObjectKey { id: key.id, version: metadata.latest_version }
+        } else {
+            key.clone()
+        };
+        let key_bytes = bincode::serialize(&key)?;
+        let Some(value_bytes) = self.rocks.get(&self.objects_cf, &key_bytes)? else {
+            return Ok(None);
+        };
+
+        metadata.ref_count = metadata.ref_count.saturating_sub(1);
+        metadata.deleted = metadata.ref_count == 0;
+
+        let metadata_key = bincode::serialize(&key.id)?;
+        let metadata_value = bincode::serialize(&metadata)?;
+        batch.put_cf(self.rocks.get_cf(&self.metadata_cf)?, &metadata_key, &metadata_value);
+        batch.delete_cf(self.rocks.get_cf(&self.objects_cf)?, &key_bytes);
+
+        if metadata.deleted {
+            let value: ObjectValue = safe_decode(&value_bytes, MAX_OBJECT_SIZE)?;
+            staged.deleted.push(value.type_);
+        }
+        staged.metadata.insert(key.id, metadata);
+        Ok(Some(key))
+    }
+
+    /// Update live object counts once a staged batch has committed
+    pub(crate) fn record_staged(&self, staged: &StagedObjects) {
+        let Some(metrics) = &self.metrics else { return };
+        for type_ in &staged.created {
+            metrics.total_objects.inc();
+            metrics.objects_by_type.inc(&[type_]);
+        }
+        for type_ in &staged.deleted {
+            metrics.total_objects.dec();
+            metrics.objects_by_type.dec(&[type_]);
+        }
+    }
+
+    /// Metadata of `id`, including writes already staged in `staged`
+    fn staged_metadata(
+        &self,
+        staged: &StagedObjects,
+        id: &ObjectID,
+    ) -> ProtocolResult<Option<ObjectMetadata>> {
+        match staged.metadata.get(id) {
+            Some(metadata) => Ok(Some(metadata.clone())),
+            None => self.get_metadata(id),
+        }
+    }
+
     /// Get object metadata
     fn get_metadata(&self, id: &ObjectID) -> ProtocolResult<Option<ObjectMetadata>> {
         let key = bincode::serialize(id)?;
@@ -143,13 +405,41 @@ impl ObjectStore {
 
         match value {
             Some(bytes) => {
-                let metadata: ObjectMetadata = bincode::deserialize(&bytes)?;
+                let metadata: ObjectMetadata = safe_decode(&bytes, MAX_OBJECT_SIZE)?;
                 Ok(Some(metadata))
             }
             None => Ok(None),
         }
     }
 
+    /// Get metadata of several objects with one batched read
+    fn multi_get_metadata(&self, ids: &[ObjectID]) -> ProtocolResult<Vec<Option<ObjectMetadata>>> {
+        let keys = ids.iter()
+            .map(bincode::serialize)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.rocks.multi_get(&self.metadata_cf, &keys)?
+            .into_iter()
+            .map(|value| value.map(|bytes| safe_decode(&bytes, MAX_OBJECT_SIZE)).transpose())
+            .collect()
+    }
+
+    /// List the latest version of every object not deleted. Older versions
+    /// and objects whose latest version was deleted are left out.
+    pub fn list_live(&self) -> ProtocolResult<Vec<(ObjectKey, ObjectValue)>> {
+        let objects = self.list()?;
+        let ids: Vec<ObjectID> = objects.iter().map(|(key, _)| key.id).collect();
+        let metadata = self.multi_get_metadata(&ids)?;
+
+        Ok(objects.into_iter()
+            .zip(metadata)
+            .filter(|((key, _), metadata)| metadata.as_ref().is_some_and(|metadata| {
+                !metadata.deleted && metadata.latest_version == key.version
+            }))
+            .map(|(object, _)| object)
+            .collect())
+    }
+
     /// List all objects
     pub fn list(&self) -> ProtocolResult<Vec<(ObjectKey, ObjectValue)>> {
         let mut objects = Vec::new();
@@ -157,14 +447,47 @@ impl ObjectStore {
 
         for item in iter {
             let (key_bytes, value_bytes) = item?;
-            let key: ObjectKey = bincode::deserialize(&key_bytes)?;
-            let value: ObjectValue = bincode::deserialize(&value_bytes)?;
+            let key: ObjectKey = safe_decode(&key_bytes, MAX_OBJECT_SIZE)?;
+            let value: ObjectValue = safe_decode(&value_bytes, MAX_OBJECT_SIZE)?;
             objects.push((key, value));
         }
 
         Ok(objects)
     }
 
+    /// List objects a page at a time, starting at `cursor` (inclusive).
+    /// Returns the page and the cursor for the next page, if any.
+    pub fn list_paginated(
+        &self,
+        cursor: Option<ObjectKey>,
+        limit: usize,
+    ) -> ProtocolResult<(Vec<(ObjectKey, ObjectValue)>, Option<ObjectKey>)> {
+        let iter = match &cursor {
+            Some(key) => self.rocks.iter_from(&self.objects_cf, &bincode::serialize(key)?)?,
+            None => self.rocks.iter(&self.objects_cf)?,
+        };
+
+        let mut objects = Vec::new();
+        for item in iter {
+            let (key_bytes, value_bytes) = item?;
+            let key: ObjectKey = safe_decode(&key_bytes, MAX_OBJECT_SIZE)?;
+
+            // Skip deleted objects
+            if self.get_metadata(&key.id)?.is_some_and(|m| m.deleted) {
+                continue;
+            }
+
+            if objects.len() >= limit {
+                return Ok((objects, Some(key)));
+            }
+
+            let value: ObjectValue = safe_decode(&value_bytes, MAX_OBJECT_SIZE)?;
+            objects.push((key, value));
+        }
+
+        Ok((objects, None))
+    }
+
     /// Get latest version of object
     pub fn get_latest_version(&self, id: &ObjectID) -> ProtocolResult<Option<SequenceNumber>> {
         Ok(self.get_metadata(id)?.map(|m| m.latest_version))
@@ -220,4 +543,186 @@ mod tests {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_list_paginated() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        })?);
+
+        let store = ObjectStore::new(rocks);
+        for i in 0..25 {
+            let key = ObjectKey {
+                id: ObjectID::random(),
+                version: SequenceNumber::new(1),
+            };
+            let value = ObjectValue {
+                data: vec![i],
+                owner: "test".to_string(),
+                type_: "TestObject".to_string(),
+                created_at: 100,
+                modified_at: 100,
+            };
+            store.put(key, value)?;
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let mut page_sizes = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = store.list_paginated(cursor, 10)?;
+            page_sizes.push(page.len());
+            for (key, _) in page {
+                assert!(seen.insert(key));
+            }
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(page_sizes, vec![10, 10, 5]);
+        assert_eq!(seen.len(), 25);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_ref_count() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        })?);
+
+        let store = ObjectStore::new(rocks);
+        let id = ObjectID::random();
+        let value = ObjectValue {
+            data: vec![1, 2, 3],
+            owner: "test".to_string(),
+            type_: "TestObject".to_string(),
+            created_at: 100,
+            modified_at: 100,
+        };
+
+        let v1 = ObjectKey { id, version: SequenceNumber::new(1) };
+        let v2 = ObjectKey { id, version: SequenceNumber::new(2) };
+
+        store.put(v1.clone(), value.clone())?;
+        // Overwriting the same version must not add a reference
+        store.put(v1.clone(), value.clone())?;
+        store.put(v2.clone(), value.clone())?;
+        assert_eq!(store.get_ref_count(&id)?, 2);
+
+        store.delete(&v1)?;
+        assert_eq!(store.get_ref_count(&id)?, 1);
+        assert!(store.get(&v2)?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_objects_by_type_metrics() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        })?);
+        let metrics = StorageMetrics::new(&prometheus::Registry::new()).unwrap();
+        let store = ObjectStore::new(rocks).with_metrics(metrics.clone());
+
+        let put = |type_: &str, version: u64| -> ProtocolResult<ObjectKey> {
+            let key = ObjectKey { id: ObjectID::random(), version: SequenceNumber::new(version) };
+            store.put(key.clone(), ObjectValue {
+                data: vec![],
+                owner: "test".to_string(),
+                type_: type_.to_string(),
+                created_at: 0,
+                modified_at: 0,
+            })?;
+            Ok(key)
+        };
+        let coin = "0x2::coin::Coin<0x2::sui::SUI>";
+        let nft = "0x2::devnet_nft::DevNetNFT";
+
+        let coins = (0..3).map(|_| put(coin, 1)).collect::<ProtocolResult<Vec<_>>>()?;
+        let nft_key = put(nft, 1)?;
+        assert_eq!(metrics.objects_by_type.get(&[coin]), 3.0);
+        assert_eq!(metrics.objects_by_type.get(&[nft]), 1.0);
+
+        // A new version of an existing object isn't a new object
+        let mut next = store.get(&coins[0])?.unwrap();
+        next.modified_at = 1;
+        store.put(ObjectKey { id: coins[0].id, version: SequenceNumber::new(2) }, next)?;
+        assert_eq!(metrics.objects_by_type.get(&[coin]), 3.0);
+
+        store.delete(&nft_key)?;
+        store.delete(&coins[1])?;
+        assert_eq!(metrics.objects_by_type.get(&[coin]), 2.0);
+        assert_eq!(metrics.objects_by_type.get(&[nft]), 0.0);
+        assert_eq!(metrics.total_objects.get(), 2.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_put_leaves_store_unchanged() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let rocks = Arc::new(RocksStore::new(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        })?);
+
+        let store = ObjectStore::new(rocks.clone());
+        let id = ObjectID::random();
+        let value = ObjectValue {
+            data: vec![1, 2, 3],
+            owner: "test".to_string(),
+            type_: "TestObject".to_string(),
+            created_at: 100,
+            modified_at: 100,
+        };
+        let v1 = ObjectKey { id, version: SequenceNumber::new(1) };
+        let v2 = ObjectKey { id, version: SequenceNumber::new(2) };
+        store.put(v1.clone(), value.clone())?;
+
+        // Force failures by pointing one side of the write at a missing
+        // column family
+        let no_metadata = ObjectStore {
+            rocks: rocks.clone(),
+            objects_cf: "objects".to_string(),
+            metadata_cf: "missing".to_string(),
+            metrics: None,
+        };
+        let err = no_metadata.put(v2.clone(), value.clone()).unwrap_err();
+        assert!(matches!(err, ObjectWriteError::Metadata { ref key, .. } if *key == v2));
+
+        let no_objects = ObjectStore {
+            rocks,
+            objects_cf: "missing".to_string(),
+            metadata_cf: "object_metadata".to_string(),
+            metrics: None,
+        };
+        let err = no_objects.put(v2.clone(), value.clone()).unwrap_err();
+        assert!(matches!(err, ObjectWriteError::Object { ref key, .. } if *key == v2));
+        assert!(!ProtocolError::from(err).is_transient());
+
+        // A commit failure: reads succeed, but the read-only store rejects
+        // the batch
+        let read_only = ObjectStore::new(Arc::new(RocksStore::open_read_only(&RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        })?));
+        let err = read_only.put(v2.clone(), value.clone()).unwrap_err();
+        assert!(matches!(err, ObjectWriteError::Commit { ref key, .. } if *key == v2));
+
+        // Neither the object nor its metadata moved
+        assert!(store.get(&v2)?.is_none());
+        assert_eq!(store.get_latest_version(&id)?, Some(SequenceNumber::new(1)));
+        assert_eq!(store.get_ref_count(&id)?, 1);
+
+        Ok(())
+    }
+}
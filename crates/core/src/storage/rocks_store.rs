@@ -1,11 +1,39 @@
 // storage/rocks_store.rs
+use crate::metrics::StorageMetrics;
 use crate::protocol::{ProtocolError, ProtocolResult};
+use super::StorageError;
 use rocksdb::{
     ColumnFamily, ColumnFamilyDescriptor, DBCompactionStyle, DBCompressionType,
-    Options, WriteBatch, DB,
+    Options, ReadOptions, SliceTransform, WriteBatch, WriteOptions, DB,
 };
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
+
+/// Column families opened by every store
+pub const COLUMN_FAMILIES: &[&str] = &[
+    "default",
+    "objects",
+    "object_metadata",
+    "events",
+    "event_indexes",
+    "indexes",
+    "transactions",
+    "state",
+    "checkpoints",
+    "consensus",
+    "meta",
+];
+
+/// Current database schema version, bumped whenever column families are
+/// added so older databases are migrated on open
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Column family holding database metadata
+const META_CF: &str = "meta";
+
+/// Key of the schema version in the meta column family
+const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
 
 /// RocksDB configuration
 #[derive(Debug, Clone)]
@@ -24,6 +52,19 @@ pub struct RocksConfig {
     pub compression_type: DBCompressionType,
     /// Compaction style
     pub compaction_style: DBCompactionStyle,
+    /// Bloom filter bits per key (0 disables the filter)
+    pub bloom_filter_bits: f64,
+    /// Fixed key prefix length for the objects column family; object keys
+    /// start with the 32-byte ObjectID
+    pub object_prefix_len: Option<usize>,
+    /// Fsync the WAL on every write, so acknowledged writes survive a
+    /// machine crash
+    pub sync_writes: bool,
+    /// Skip the WAL entirely; unflushed writes are lost on any crash. Only
+    /// for throwaway stores.
+    pub disable_wal: bool,
+    /// Operation metrics (optional)
+    pub metrics: Option<StorageMetrics>,
 }
 
 impl Default for RocksConfig {
@@ -36,6 +77,11 @@ impl Default for RocksConfig {
             block_cache_size: 512 * 1024 * 1024,    // 512MB
             compression_type: DBCompressionType::Lz4,
             compaction_style: DBCompactionStyle::Level,
+            bloom_filter_bits: 10.0,
+            object_prefix_len: Some(32),
+            sync_writes: false,
+            disable_wal: false,
+            metrics: None,
         }
     }
 }
@@ -46,11 +92,35 @@ pub struct RocksStore {
     db: Arc<DB>,
     /// Column families
     column_families: Vec<String>,
+    /// Fsync the WAL on every write
+    sync_writes: bool,
+    /// Skip the WAL
+    disable_wal: bool,
+    /// Operation metrics
+    metrics: Option<StorageMetrics>,
 }
 
 impl RocksStore {
     /// Create new RocksDB store
     pub fn new(config: &RocksConfig) -> ProtocolResult<Self> {
+        Self::open(config, false)
+    }
+
+    /// Open an existing database without write access, e.g. to inspect a
+    /// node's state. Every write fails.
+    pub fn open_read_only(config: &RocksConfig) -> ProtocolResult<Self> {
+        Self::open(config, true)
+    }
+
+    /// Open the database, migrating its schema unless `read_only`
+    fn open(config: &RocksConfig, read_only: bool) -> ProtocolResult<Self> {
+        // Syncing a WAL that is never written gives no durability
+        if config.sync_writes && config.disable_wal {
+            return Err(StorageError::InvalidConfig(
+                "sync_writes requires the WAL, but disable_wal is set".into()
+            ).into());
+        }
+
         // Create options
         let mut opts = Options::default();
         opts.create_if_missing(true);
@@ -65,51 +135,127 @@ impl RocksStore {
         let cache = rocksdb::Cache::new_lru_cache(config.block_cache_size)?;
         let mut block_opts = rocksdb::BlockBasedOptions::default();
         block_opts.set_block_cache(&cache);
+        if config.bloom_filter_bits > 0.0 {
+            block_opts.set_bloom_filter(config.bloom_filter_bits, false);
+        }
         opts.set_block_based_table_factory(&block_opts);
 
-        // Default column families
-        let cf_names = vec![
-            "default",
-            "objects",
-            "object_metadata",
-            "events",
-            "event_indexes",
-            "transactions",
-            "state",
-        ];
+        // Open every required column family, creating missing ones, plus any
+        // extra ones already on disk, which RocksDB refuses to open without
+        let mut column_families: Vec<String> = COLUMN_FAMILIES.iter().map(|cf| cf.to_string()).collect();
+        if Path::new(&config.path).exists() {
+            for cf in DB::list_cf(&opts, &config.path).unwrap_or_default() {
+                if !column_families.contains(&cf) {
+                    column_families.push(cf);
+                }
+            }
+        }
 
         // Create column family descriptors
-        let cf_descriptors: Vec<_> = cf_names
+        let cf_descriptors: Vec<_> = column_families
             .iter()
-            .map(|name| ColumnFamilyDescriptor::new(*name, opts.clone()))
+            .map(|name| {
+                let mut cf_opts = opts.clone();
+                if let ("objects", Some(len)) = (name.as_str(), config.object_prefix_len) {
+                    // Lets the bloom filter rule out every version of a
+                    // missing object at once
+                    cf_opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(len));
+                }
+                ColumnFamilyDescriptor::new(name, cf_opts)
+            })
             .collect();
 
         // Open database
-        let db = DB::open_cf_descriptors(&opts, &config.path, cf_descriptors)?;
+        let db = if read_only {
+            DB::open_cf_descriptors_read_only(&opts, &config.path, cf_descriptors, false)?
+        } else {
+            DB::open_cf_descriptors(&opts, &config.path, cf_descriptors)?
+        };
         let db = Arc::new(db);
 
-        Ok(Self {
+        let store = Self {
             db,
-            column_families: cf_names.into_iter().map(String::from).collect(),
-        })
+            column_families,
+            sync_writes: config.sync_writes,
+            disable_wal: config.disable_wal,
+            metrics: config.metrics.clone(),
+        };
+        if !read_only {
+            store.migrate()?;
+        }
+
+        Ok(store)
+    }
+
+    /// Schema version recorded in the database, if any
+    pub fn schema_version(&self) -> ProtocolResult<Option<u32>> {
+        match self.get(META_CF, SCHEMA_VERSION_KEY)? {
+            Some(bytes) => {
+                let bytes: [u8; 4] = bytes.try_into()
+                    .map_err(|_| ProtocolError::DeserializationError("Invalid schema version".into()))?;
+                Ok(Some(u32::from_le_bytes(bytes)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Bring an older database up to `SCHEMA_VERSION`. Missing column
+    /// families were already created on open, so only the recorded version
+    /// needs updating; databases written by newer code are rejected.
+    fn migrate(&self) -> ProtocolResult<()> {
+        match self.schema_version()? {
+            Some(found) if found > SCHEMA_VERSION => Err(StorageError::SchemaVersionTooNew {
+                found,
+                supported: SCHEMA_VERSION,
+            }.into()),
+            Some(found) if found == SCHEMA_VERSION => Ok(()),
+            found => {
+                log::info!(
+                    "Migrating database schema from version {} to {}",
+                    found.unwrap_or(0),
+                    SCHEMA_VERSION
+                );
+                self.put(META_CF, SCHEMA_VERSION_KEY, &SCHEMA_VERSION.to_le_bytes())
+            }
+        }
     }
 
     /// Get value by key
     pub fn get(&self, cf: &str, key: &[u8]) -> ProtocolResult<Option<Vec<u8>>> {
-        let cf = self.get_cf(cf)?;
-        Ok(self.db.get_cf(cf, key)?)
+        let start = Instant::now();
+        let handle = self.get_cf(cf)?;
+        let value = self.db.get_cf(handle, key)?;
+        self.record_operation("get", cf, start);
+        Ok(value)
+    }
+
+    /// Get values for several keys in one batched read
+    pub fn multi_get(&self, cf: &str, keys: &[Vec<u8>]) -> ProtocolResult<Vec<Option<Vec<u8>>>> {
+        let start = Instant::now();
+        let handle = self.get_cf(cf)?;
+        let values = self.db.multi_get_cf(keys.iter().map(|key| (handle, key)))
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        self.record_operation("multi_get", cf, start);
+        Ok(values)
     }
 
     /// Put key-value pair
     pub fn put(&self, cf: &str, key: &[u8], value: &[u8]) -> ProtocolResult<()> {
-        let cf = self.get_cf(cf)?;
-        Ok(self.db.put_cf(cf, key, value)?)
+        let start = Instant::now();
+        let handle = self.get_cf(cf)?;
+        self.db.put_cf_opt(handle, key, value, &self.write_options())?;
+        self.record_operation("put", cf, start);
+        Ok(())
     }
 
     /// Delete key
     pub fn delete(&self, cf: &str, key: &[u8]) -> ProtocolResult<()> {
-        let cf = self.get_cf(cf)?;
-        Ok(self.db.delete_cf(cf, key)?)
+        let start = Instant::now();
+        let handle = self.get_cf(cf)?;
+        self.db.delete_cf_opt(handle, key, &self.write_options())?;
+        self.record_operation("delete", cf, start);
+        Ok(())
     }
 
     /// Create write batch
@@ -119,20 +265,58 @@ impl RocksStore {
 
     /// Write batch
     pub fn write_batch(&self, batch: WriteBatch) -> ProtocolResult<()> {
-        Ok(self.db.write(batch)?)
+        let start = Instant::now();
+        self.db.write_opt(batch, &self.write_options())?;
+        // A batch may span several column families
+        self.record_operation("write_batch", "batch", start);
+        Ok(())
+    }
+
+    /// Write options for the configured durability policy
+    fn write_options(&self) -> WriteOptions {
+        let mut opts = WriteOptions::default();
+        opts.set_sync(self.sync_writes);
+        opts.disable_wal(self.disable_wal);
+        opts
+    }
+
+    /// Record operation count and latency
+    fn record_operation(&self, operation: &str, cf: &str, start: Instant) {
+        if let Some(metrics) = &self.metrics {
+            metrics.storage_operations.inc(&[operation, cf]);
+            metrics.operation_latency.observe(&[operation, cf], start.elapsed().as_secs_f64());
+        }
     }
 
     /// Create iterator
     pub fn iter(&self, cf: &str) -> ProtocolResult<rocksdb::DBIterator> {
         let cf = self.get_cf(cf)?;
-        Ok(self.db.iterator_cf(cf, rocksdb::IteratorMode::Start))
+        Ok(self.db.iterator_cf_opt(cf, total_order(), rocksdb::IteratorMode::Start))
+    }
+
+    /// Create iterator starting at key
+    pub fn iter_from(&self, cf: &str, start: &[u8]) -> ProtocolResult<rocksdb::DBIterator> {
+        let cf = self.get_cf(cf)?;
+        Ok(self.db.iterator_cf_opt(
+            cf,
+            total_order(),
+            rocksdb::IteratorMode::From(start, rocksdb::Direction::Forward),
+        ))
     }
 
     /// Get column family handle
-    fn get_cf(&self, name: &str) -> ProtocolResult<&ColumnFamily> {
+    pub(crate) fn get_cf(&self, name: &str) -> ProtocolResult<&ColumnFamily> {
         self.db
             .cf_handle(name)
-            .ok_or_else(|| ProtocolError::Storage(format!("Column family not found: {}", name)))
+            .ok_or_else(|| StorageError::ColumnFamilyNotFound(name.to_string()).into())
+    }
+
+    /// Ensure every required column family is open
+    pub fn require_column_families(&self, required: &[&str]) -> ProtocolResult<()> {
+        for name in required {
+            self.get_cf(name)?;
+        }
+        Ok(())
     }
 
     /// Create new column family
@@ -193,6 +377,13 @@ impl RocksStore {
     }
 }
 
+/// Read options for scans that cross key prefixes
+fn total_order() -> ReadOptions {
+    let mut opts = ReadOptions::default();
+    opts.set_total_order_seek(true);
+    opts
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,4 +422,160 @@ mod tests {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_rocks_store_metrics() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let registry = prometheus::Registry::new();
+        let metrics = StorageMetrics::new(&registry).unwrap();
+        let config = RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            metrics: Some(metrics.clone()),
+            ..Default::default()
+        };
+
+        let store = RocksStore::new(&config)?;
+
+        let n = 5;
+        for i in 0..n {
+            store.put("objects", format!("key{}", i).as_bytes(), b"value")?;
+        }
+
+        assert_eq!(metrics.storage_operations.get(&["put", "objects"]), n as f64);
+        assert_eq!(metrics.operation_latency.sample_count(&["put", "objects"]), n);
+        assert_eq!(metrics.storage_operations.get(&["get", "objects"]), 0.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_column_families() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let config = RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            ..Default::default()
+        };
+
+        let store = RocksStore::new(&config)?;
+        store.require_column_families(COLUMN_FAMILIES)?;
+
+        for cf in COLUMN_FAMILIES {
+            store.put(cf, b"key", cf.as_bytes())?;
+            assert_eq!(store.get(cf, b"key")?, Some(cf.as_bytes().to_vec()));
+        }
+
+        assert!(matches!(
+            store.require_column_families(&["missing"]),
+            Err(ProtocolError::Storage(StorageError::ColumnFamilyNotFound(name))) if name == "missing"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bloom_filter_and_prefix_extractor() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let config = RocksConfig {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+            bloom_filter_bits: 10.0,
+            object_prefix_len: Some(32),
+            ..Default::default()
+        };
+
+        let store = RocksStore::new(&config)?;
+
+        // Object keys: 32-byte id followed by an 8-byte version
+        let key = |id: u8, version: u64| {
+            let mut key = vec![id; 32];
+            key.extend_from_slice(&version.to_le_bytes());
+            key
+        };
+        for id in 0..4u8 {
+            for version in 1..=2 {
+                store.put("objects", &key(id, version), &[id])?;
+            }
+        }
+        store.flush()?;
+
+        // Hits and misses round-trip
+        assert_eq!(store.get("objects", &key(2, 1))?, Some(vec![2]));
+        assert_eq!(store.get("objects", &key(2, 3))?, None);
+        assert_eq!(store.get("objects", &key(9, 1))?, None);
+
+        // Scans still cross prefixes
+        assert_eq!(store.iter("objects")?.count(), 8);
+        assert_eq!(store.iter_from("objects", &key(2, 0))?.count(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_migration() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().to_str().unwrap().to_string();
+
+        // A database from before the meta and consensus column families
+        {
+            let mut opts = Options::default();
+            opts.create_if_missing(true);
+            opts.create_missing_column_families(true);
+            let db = DB::open_cf(&opts, &path, ["default", "objects", "legacy"])?;
+            db.put_cf(db.cf_handle("objects").unwrap(), b"key", b"value")?;
+        }
+
+        let config = RocksConfig {
+            path,
+            ..Default::default()
+        };
+        let store = RocksStore::new(&config)?;
+        store.require_column_families(COLUMN_FAMILIES)?;
+        store.require_column_families(&["legacy"])?;
+        assert_eq!(store.get("objects", b"key")?, Some(b"value".to_vec()));
+        assert_eq!(store.schema_version()?, Some(SCHEMA_VERSION));
+
+        // A database written by newer code is refused
+        store.put(META_CF, SCHEMA_VERSION_KEY, &(SCHEMA_VERSION + 1).to_le_bytes())?;
+        drop(store);
+        assert!(matches!(
+            RocksStore::new(&config),
+            Err(ProtocolError::Storage(StorageError::SchemaVersionTooNew { found, supported }))
+                if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_durability_settings() -> ProtocolResult<()> {
+        for (sync_writes, disable_wal) in [(false, false), (true, false), (false, true)] {
+            let temp_dir = TempDir::new()?;
+            let store = RocksStore::new(&RocksConfig {
+                path: temp_dir.path().to_str().unwrap().to_string(),
+                sync_writes,
+                disable_wal,
+                ..Default::default()
+            })?;
+
+            store.put("default", b"key1", b"value1")?;
+            let mut batch = store.batch();
+            batch.put_cf(store.get_cf("default")?, b"key2", b"value2");
+            store.write_batch(batch)?;
+
+            assert_eq!(store.get("default", b"key1")?, Some(b"value1".to_vec()));
+            assert_eq!(store.get("default", b"key2")?, Some(b"value2".to_vec()));
+        }
+
+        let temp_dir = TempDir::new()?;
+        assert!(matches!(
+            RocksStore::new(&RocksConfig {
+                path: temp_dir.path().to_str().unwrap().to_string(),
+                sync_writes: true,
+                disable_wal: true,
+                ..Default::default()
+            }),
+            Err(ProtocolError::Storage(StorageError::InvalidConfig(_)))
+        ));
+
+        Ok(())
+    }
+}
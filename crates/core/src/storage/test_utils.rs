@@ -0,0 +1,12 @@
+//! Fixtures shared by tests that need on-disk storage.
+
+use super::{CacheConfig, RocksConfig, StorageConfig};
+use tempfile::TempDir;
+
+/// Storage configuration rooted in a fresh temporary directory. The
+/// directory is removed when the returned `TempDir` drops, so keep it alive
+/// while the storage is open.
+pub(crate) fn temp_storage() -> (TempDir, StorageConfig) {
+    let (_temp_dir, config) = temp_storage();;
+    (temp_dir, config)
+}
@@ -1,7 +1,11 @@
-use super::{SystemError, SystemResult};
+use super::validators::{ValidatorInfo, ValidatorPerformance, ValidatorStatus};
+use super::{AsyncStorage, SystemError, SystemResult};
 use crate::core::{Object, ObjectID};
-use crate::storage::Storage;
+use crate::crypto::PublicKey;
+use crate::transaction::TransactionDigest;
 use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::sync::Arc;
 
 /// Genesis configuration
@@ -12,11 +16,60 @@ pub struct GenesisConfig {
     /// Genesis timestamp
     pub timestamp: u64,
     /// Initial validators
-    pub validators: Vec<ValidatorConfig>,
+    pub validators: Vec<GenesisValidator>,
     /// Framework objects
     pub framework_objects: Vec<FrameworkObject>,
 }
 
+impl GenesisConfig {
+    /// Total stake of the initial validators
+    pub fn total_stake(&self) -> u64 {
+        self.validators.iter()
+            .map(|v| v.stake_amount)
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Digest identifying this genesis. Objects created at genesis derive
+    /// their IDs from it, so every validator assigns the same ones.
+    pub fn digest(&self) -> SystemResult<TransactionDigest> {
+        let bytes = bincode::serialize(self)
+            .map_err(|e| SystemError::GenesisError(e.to_string()))?;
+        Ok(TransactionDigest::from_bytes(Sha256::digest(&bytes).into()))
+    }
+
+    /// Check the initial validator set
+    pub fn validate(&self) -> SystemResult<()> {
+        if self.total_stake() == 0 {
+            return Err(SystemError::GenesisError("Total genesis stake is zero".into()));
+        }
+
+        let mut public_keys = HashSet::new();
+        for validator in &self.validators {
+            if !public_keys.insert(&validator.public_key) {
+                return Err(SystemError::GenesisError(format!(
+                    "Duplicate genesis validator {:?}",
+                    validator.public_key
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Initial validator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenesisValidator {
+    /// Public key
+    pub public_key: PublicKey,
+    /// Network address
+    pub network_address: String,
+    /// Stake amount
+    pub stake_amount: u64,
+    /// Commission rate
+    pub commission_rate: f64,
+}
+
 /// Framework object
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrameworkObject {
@@ -33,7 +86,7 @@ pub struct Genesis {
     /// Configuration
     config: GenesisConfig,
     /// Storage
-    storage: Arc<dyn Storage>,
+    storage: Arc<dyn AsyncStorage>,
     /// Initialized flag
     initialized: bool,
 }
@@ -42,8 +95,10 @@ impl Genesis {
     /// Create new genesis
     pub fn new(
         config: GenesisConfig,
-        storage: Arc<dyn Storage>,
+        storage: Arc<dyn AsyncStorage>,
     ) -> SystemResult<Self> {
+        config.validate()?;
+
         Ok(Self {
             config,
             storage,
@@ -69,26 +124,34 @@ impl Genesis {
         }
 
         // Initialize validators
-        for validator in &self.config.validators {
-            self.initialize_validator(validator).await?;
+        let digest = self.config.digest()?;
+        for (index, validator) in self.config.validators.iter().enumerate() {
+            self.initialize_validator(validator, ObjectID::derive(&digest, index as u64)).await?;
         }
 
         self.initialized = true;
         Ok(())
     }
 
-    /// Initialize validator
-    async fn initialize_validator(&self, config: &ValidatorConfig) -> SystemResult<()> {
-        // Create validator object
-        let validator = Object::new_validator(
-            ObjectID::random(),
-            config.public_key.clone(),
-            config.network_address.clone(),
-            config.stake_amount,
-        );
+    /// Initialize validator, adding it to the active validator set
+    async fn initialize_validator(&self, config: &GenesisValidator, id: ObjectID) -> SystemResult<()> {
+        let validator = ValidatorInfo {
+            id,
+            public_key: config.public_key.clone(),
+            network_address: config.network_address.clone(),
+            stake_amount: config.stake_amount,
+            commission_rate: config.commission_rate,
+            performance: ValidatorPerformance {
+                blocks_proposed: 0,
+                blocks_signed: 0,
+                response_time: 0,
+                uptime: 100.0,
+            },
+            status: ValidatorStatus::Active,
+        };
 
         // Store validator
-        self.storage.put_object(&validator, None).await
+        self.storage.put_validator(&validator).await
             .map_err(|e| SystemError::GenesisError(e.to_string()))?;
 
         Ok(())
@@ -105,7 +168,7 @@ impl Genesis {
     }
 
     /// Get validators
-    pub fn validators(&self) -> &[ValidatorConfig] {
+    pub fn validators(&self) -> &[GenesisValidator] {
         &self.config.validators
     }
 
@@ -118,4 +181,124 @@ impl Genesis {
     pub fn is_initialized(&self) -> bool {
         self.initialized
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, SignatureScheme};
+    use crate::storage::{CacheConfig, RocksConfig, StorageConfig, StorageManager};
+    use crate::swift_system::validators::{ValidatorConfig, ValidatorSet};
+    use tempfile::TempDir;
+
+    fn test_storage(temp_dir: &TempDir) -> Arc<StorageManager> {
+        let path = temp_dir.path().to_str().unwrap().to_string();
+        Arc::new(StorageManager::new(StorageConfig {
+            data_dir: path.clone(),
+            cache_config: CacheConfig::default(),
+            rocks_config: RocksConfig {
+                path,
+                ..Default::default()
+            },
+        }).unwrap())
+    }
+
+    fn genesis_validator(public_key: PublicKey, stake_amount: u64) -> GenesisValidator {
+        GenesisValidator {
+            public_key,
+            network_address: "127.0.0.1:9000".to_string(),
+            stake_amount,
+            commission_rate: 0.05,
+        }
+    }
+
+    fn genesis_config(validators: Vec<GenesisValidator>) -> GenesisConfig {
+        GenesisConfig {
+            chain_id: "swift-test".to_string(),
+            timestamp: 0,
+            validators,
+            framework_objects: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_genesis_validator_set() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = test_storage(&temp_dir);
+        let validators = (1..=4)
+            .map(|i| genesis_validator(KeyPair::generate(SignatureScheme::Ed25519).unwrap().public(), i * 1_000))
+            .collect();
+
+        let mut genesis = Genesis::new(genesis_config(validators), storage.clone())?;
+        genesis.initialize().await?;
+        assert_eq!(genesis.config.total_stake(), 10_000);
+
+        let mut validator_set = ValidatorSet::new(
+            ValidatorConfig {
+                min_stake_amount: 0,
+                max_validator_count: 10,
+                performance_window: 100,
+                min_performance_threshold: 0.5,
+            },
+            storage,
+        );
+        validator_set.initialize().await?;
+        assert_eq!(validator_set.get_active_validators().await?.len(), 4);
+        assert_eq!(validator_set.get_total_stake().await, 10_000);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_genesis_ids_are_deterministic() -> SystemResult<()> {
+        let config = genesis_config((1..=3)
+            .map(|i| genesis_validator(KeyPair::generate(SignatureScheme::Ed25519).unwrap().public(), i * 1_000))
+            .collect());
+
+        // Two validators building the same genesis agree on every ID
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let temp_dir = TempDir::new().unwrap();
+            let storage = test_storage(&temp_dir);
+            Genesis::new(config.clone(), storage.clone())?.initialize().await?;
+
+            let mut validator_set = ValidatorSet::new(
+                ValidatorConfig {
+                    min_stake_amount: 0,
+                    max_validator_count: 10,
+                    performance_window: 100,
+                    min_performance_threshold: 0.5,
+                },
+                storage,
+            );
+            validator_set.initialize().await?;
+            let mut validators: Vec<_> = validator_set.get_active_validators().await?
+                .into_iter()
+                .map(|v| (v.stake_amount, v.id))
+                .collect();
+            validators.sort_by_key(|(stake, _)| *stake);
+            ids.push(validators);
+        }
+        assert_eq!(ids[0], ids[1]);
+
+        let digest = config.digest()?;
+        let expected: Vec<_> = (0..3).map(|i| ((i + 1) * 1_000, ObjectID::derive(&digest, i))).collect();
+        assert_eq!(ids[0], expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_genesis_validators() {
+        let public_key = KeyPair::generate(SignatureScheme::Ed25519).unwrap().public();
+
+        let zero_stake = genesis_config(vec![genesis_validator(public_key.clone(), 0)]);
+        assert!(zero_stake.validate().is_err());
+
+        let duplicate = genesis_config(vec![
+            genesis_validator(public_key.clone(), 1_000),
+            genesis_validator(public_key, 1_000),
+        ]);
+        assert!(duplicate.validate().is_err());
+    }
+}
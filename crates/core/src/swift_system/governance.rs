@@ -1,6 +1,11 @@
-use super::{SystemError, SystemResult};
+use super::{AsyncStorage, StakeSystem, SystemError, SystemResult, TxContext};
+use crate::authority::{AuthorityError, AuthorityResult, EpochChangeListener, EpochInfo};
 use crate::core::{Address, ObjectID};
-use crate::storage::Storage;
+use crate::framework::check_upgrade_compat;
+use crate::utils::{Clock, SystemClock};
+use move_binary_format::file_format::CompiledModule;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -15,10 +20,32 @@ pub struct GovernanceConfig {
     pub min_participation_rate: f64,
     /// Required approval rate
     pub required_approval_rate: f64,
+    /// Vote weighting strategy
+    pub voting_strategy: VotingStrategy,
+}
+
+/// Vote weighting strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VotingStrategy {
+    /// Weight equals voting power
+    #[default]
+    Linear,
+    /// Weight equals square root of voting power
+    Quadratic,
+}
+
+impl VotingStrategy {
+    /// Get vote weight for voting power
+    pub fn weight(&self, power: u64) -> u64 {
+        match self {
+            VotingStrategy::Linear => power,
+            VotingStrategy::Quadratic => (power as f64).sqrt() as u64,
+        }
+    }
 }
 
 /// Proposal type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ProposalType {
     /// Parameter update
     ParameterUpdate {
@@ -43,7 +70,7 @@ pub enum ProposalType {
 }
 
 /// Proposal status
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProposalStatus {
     /// Pending
     Pending,
@@ -58,7 +85,7 @@ pub enum ProposalStatus {
 }
 
 /// Proposal
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Proposal {
     /// Proposal ID
     pub id: ObjectID,
@@ -82,6 +109,8 @@ pub struct Proposal {
     pub no_votes: u64,
     /// Voters
     pub voters: Vec<Address>,
+    /// Voting powers frozen at creation
+    pub voting_power_snapshot: HashMap<Address, u64>,
 }
 
 /// Voting power
@@ -98,29 +127,38 @@ pub struct Governance {
     /// Configuration
     config: GovernanceConfig,
     /// Storage
-    storage: Arc<dyn Storage>,
+    storage: Arc<dyn AsyncStorage>,
     /// Proposals
     proposals: RwLock<HashMap<ObjectID, Proposal>>,
     /// Voting powers
     voting_powers: RwLock<HashMap<Address, u64>>,
+    /// Time source
+    clock: Arc<dyn Clock>,
 }
 
 impl Governance {
     /// Create new governance system
     pub fn new(
         config: GovernanceConfig,
-        storage: Arc<dyn Storage>,
+        storage: Arc<dyn AsyncStorage>,
     ) -> Self {
         Self {
             config,
             storage,
             proposals: RwLock::new(HashMap::new()),
             voting_powers: RwLock::new(HashMap::new()),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Read time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Initialize governance system
-    pub async fn initialize(&mut self) -> SystemResult<()> {
+    pub async fn initialize(&self) -> SystemResult<()> {
         // Load proposals
         let proposals = self.storage.get_proposals().await
             .map_err(|e| SystemError::GovernanceError(e.to_string()))?;
@@ -137,6 +175,7 @@ impl Governance {
     /// Create proposal
     pub async fn create_proposal(
         &self,
+        ctx: &mut TxContext,
         proposer: Address,
         type_: ProposalType,
         description: String,
@@ -148,18 +187,20 @@ impl Governance {
         }
 
         // Create proposal
+        let now = self.clock.now();
         let proposal = Proposal {
-            id: ObjectID::random(),
+            id: ctx.fresh_object_id(),
             proposer,
             type_,
             description,
-            start_time: crate::utils::current_timestamp(),
-            end_time: crate::utils::current_timestamp() + self.config.voting_period,
+            start_time: now,
+            end_time: now + self.config.voting_period,
             status: ProposalStatus::Active,
             deposit,
             yes_votes: 0,
             no_votes: 0,
             voters: Vec::new(),
+            voting_power_snapshot: self.voting_powers.read().await.clone(),
         };
 
         // Store proposal
@@ -192,16 +233,17 @@ impl Governance {
             return Err(SystemError::GovernanceError("Already voted".into()));
         }
 
-        // Get voting power
-        let voting_power = self.voting_powers.read().await.get(&voter)
+        // Get voting power from the proposal's snapshot
+        let voting_power = proposal.voting_power_snapshot.get(&voter)
             .copied()
             .unwrap_or(0);
+        let weight = self.config.voting_strategy.weight(voting_power);
 
         // Update votes
         if approve {
-            proposal.yes_votes += voting_power;
+            proposal.yes_votes += weight;
         } else {
-            proposal.no_votes += voting_power;
+            proposal.no_votes += weight;
         }
         proposal.voters.push(voter);
 
@@ -212,6 +254,45 @@ impl Governance {
         Ok(())
     }
 
+    /// Cancel proposal before any vote is cast, refunding the deposit
+    pub async fn cancel_proposal(
+        &self,
+        proposal_id: ObjectID,
+        caller: Address,
+    ) -> SystemResult<()> {
+        // Get proposal
+        let mut proposals = self.proposals.write().await;
+        let proposal = proposals.get_mut(&proposal_id)
+            .ok_or_else(|| SystemError::GovernanceError("Proposal not found".into()))?;
+
+        // Check caller
+        if proposal.proposer != caller {
+            return Err(SystemError::GovernanceError("Only the proposer can cancel".into()));
+        }
+
+        // Check status
+        if proposal.status != ProposalStatus::Active {
+            return Err(SystemError::GovernanceError("Proposal not active".into()));
+        }
+
+        // Check no votes cast
+        if !proposal.voters.is_empty() {
+            return Err(SystemError::GovernanceError("Proposal already has votes".into()));
+        }
+
+        // Refund deposit
+        self.storage.refund_deposit(&proposal.proposer, proposal.deposit).await
+            .map_err(|e| SystemError::GovernanceError(e.to_string()))?;
+        proposal.deposit = 0;
+        proposal.status = ProposalStatus::Failed;
+
+        // Store updated proposal
+        self.storage.put_proposal(proposal).await
+            .map_err(|e| SystemError::GovernanceError(e.to_string()))?;
+
+        Ok(())
+    }
+
     /// Execute proposal
     pub async fn execute_proposal(&self, proposal_id: ObjectID) -> SystemResult<()> {
         // Get proposal
@@ -225,20 +306,25 @@ impl Governance {
         }
 
         // Check if voting period ended
-        if crate::utils::current_timestamp() < proposal.end_time {
+        if self.clock.now() < proposal.end_time {
             return Err(SystemError::GovernanceError("Voting period not ended".into()));
         }
 
-        // Calculate participation and approval rates
+        // Calculate vote totals, weighing the snapshot the same way as votes
         let total_votes = proposal.yes_votes + proposal.no_votes;
-        let total_power: u64 = self.voting_powers.read().await.values().sum();
-        let participation_rate = total_votes as f64 / total_power as f64;
-        let approval_rate = proposal.yes_votes as f64 / total_votes as f64;
+        let total_power: u64 = proposal.voting_power_snapshot.values()
+            .map(|power| self.config.voting_strategy.weight(*power))
+            .sum();
+        if total_power == 0 {
+            return Err(SystemError::GovernanceError("No registered voting power".into()));
+        }
 
-        // Check rates
-        if participation_rate < self.config.min_participation_rate {
+        // Check participation and approval rates
+        if total_votes == 0 {
             proposal.status = ProposalStatus::Failed;
-        } else if approval_rate < self.config.required_approval_rate {
+        } else if total_votes as f64 / total_power as f64 < self.config.min_participation_rate {
+            proposal.status = ProposalStatus::Failed;
+        } else if proposal.yes_votes as f64 / total_votes as f64 < self.config.required_approval_rate {
             proposal.status = ProposalStatus::Failed;
         } else {
             proposal.status = ProposalStatus::Passed;
@@ -257,6 +343,38 @@ impl Governance {
         Ok(())
     }
 
+    /// Rebuild voting powers from active stakes
+    pub async fn rebuild_voting_powers(&self, stake_system: &StakeSystem) -> SystemResult<()> {
+        let mut voting_powers = HashMap::new();
+        for stake in stake_system.get_active_stakes().await {
+            *voting_powers.entry(stake.staker).or_insert(0) += stake.amount;
+        }
+
+        self.storage.put_voting_powers(&voting_powers).await
+            .map_err(|e| SystemError::GovernanceError(e.to_string()))?;
+        *self.voting_powers.write().await = voting_powers;
+
+        Ok(())
+    }
+
+    /// Reject upgrades that break already published system modules
+    async fn check_upgrade(&self, modules: &[Vec<u8>]) -> SystemResult<()> {
+        for bytes in modules {
+            let new = CompiledModule::deserialize(bytes)
+                .map_err(|e| SystemError::GovernanceError(e.to_string()))?;
+            let existing = self.storage.get_module(&new.self_id()).await
+                .map_err(|e| SystemError::GovernanceError(e.to_string()))?;
+
+            if let Some(existing) = existing {
+                let old = CompiledModule::deserialize(&existing)
+                    .map_err(|e| SystemError::GovernanceError(e.to_string()))?;
+                check_upgrade_compat(&old, &new)
+                    .map_err(|e| SystemError::GovernanceError(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
     /// Execute proposal type
     async fn execute_proposal_type(&self, type_: &ProposalType) -> SystemResult<()> {
         match type_ {
@@ -265,6 +383,7 @@ impl Governance {
                     .map_err(|e| SystemError::GovernanceError(e.to_string()))?;
             }
             ProposalType::SystemUpgrade { version, modules } => {
+                self.check_upgrade(modules).await?;
                 self.storage.upgrade_system(version, modules).await
                     .map_err(|e| SystemError::GovernanceError(e.to_string()))?;
             }
@@ -279,4 +398,330 @@ impl Governance {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Recomputes governance voting power at each epoch boundary
+pub struct GovernanceEpochHook {
+    /// Governance system
+    governance: Arc<Governance>,
+    /// Stake system
+    stake_system: Arc<StakeSystem>,
+}
+
+impl GovernanceEpochHook {
+    /// Create new hook
+    pub fn new(governance: Arc<Governance>, stake_system: Arc<StakeSystem>) -> Self {
+        Self {
+            governance,
+            stake_system,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EpochChangeListener for GovernanceEpochHook {
+    async fn on_epoch_change(&self, _epoch: &EpochInfo) -> AuthorityResult<()> {
+        self.governance.rebuild_voting_powers(&self.stake_system).await
+            .map_err(|e| AuthorityError::EpochChangeError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swift_system::stake::StakeConfig;
+    use crate::storage::{CacheConfig, RocksConfig, StorageConfig, StorageManager};
+    use crate::transaction::TransactionDigest;
+    use tempfile::TempDir;
+
+    fn test_governance(temp_dir: &TempDir) -> Governance {
+        test_governance_with_period(temp_dir, 3600)
+    }
+
+    fn test_governance_with_period(temp_dir: &TempDir, voting_period: u64) -> Governance {
+        test_governance_with(temp_dir, voting_period, VotingStrategy::Linear)
+    }
+
+    fn test_governance_with(
+        temp_dir: &TempDir,
+        voting_period: u64,
+        voting_strategy: VotingStrategy,
+    ) -> Governance {
+        let path = temp_dir.path().to_str().unwrap().to_string();
+        let storage = StorageManager::new(StorageConfig {
+            data_dir: path.clone(),
+            cache_config: CacheConfig::default(),
+            rocks_config: RocksConfig {
+                path,
+                ..Default::default()
+            },
+        }).unwrap();
+
+        Governance::new(
+            GovernanceConfig {
+                min_proposal_deposit: 100,
+                voting_period,
+                min_participation_rate: 0.5,
+                required_approval_rate: 0.5,
+                voting_strategy,
+            },
+            Arc::new(storage),
+        )
+    }
+
+    fn tx_context() -> TxContext {
+        TxContext::new(TransactionDigest::from_bytes([1; 32]))
+    }
+
+    fn custom_proposal() -> ProposalType {
+        ProposalType::Custom {
+            type_: "test".to_string(),
+            data: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_proposal() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let governance = test_governance(&temp_dir);
+        let proposer = Address::from_bytes([1; 20]);
+
+        let id = governance.create_proposal(&mut tx_context(), proposer, custom_proposal(), "test".into(), 100).await?;
+        governance.cancel_proposal(id, proposer).await?;
+
+        let proposals = governance.proposals.read().await;
+        let proposal = proposals.get(&id).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Failed);
+        assert_eq!(proposal.deposit, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_proposal_after_vote() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let governance = test_governance(&temp_dir);
+        let proposer = Address::from_bytes([1; 20]);
+
+        let id = governance.create_proposal(&mut tx_context(), proposer, custom_proposal(), "test".into(), 100).await?;
+        governance.vote(id, Address::from_bytes([2; 20]), true).await?;
+
+        assert!(governance.cancel_proposal(id, proposer).await.is_err());
+        assert_eq!(
+            governance.proposals.read().await.get(&id).unwrap().status,
+            ProposalStatus::Active
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_proposal_by_non_proposer() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let governance = test_governance(&temp_dir);
+        let proposer = Address::from_bytes([1; 20]);
+
+        let id = governance.create_proposal(&mut tx_context(), proposer, custom_proposal(), "test".into(), 100).await?;
+
+        assert!(governance.cancel_proposal(id, Address::from_bytes([2; 20])).await.is_err());
+        assert_eq!(
+            governance.proposals.read().await.get(&id).unwrap().status,
+            ProposalStatus::Active
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_proposal_without_votes() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let governance = test_governance_with_period(&temp_dir, 0);
+        let proposer = Address::from_bytes([1; 20]);
+        governance.voting_powers.write().await.insert(proposer, 100);
+
+        let id = governance.create_proposal(&mut tx_context(), proposer, custom_proposal(), "test".into(), 100).await?;
+        governance.execute_proposal(id).await?;
+
+        assert_eq!(
+            governance.proposals.read().await.get(&id).unwrap().status,
+            ProposalStatus::Failed
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_proposal_without_voting_power() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let governance = test_governance_with_period(&temp_dir, 0);
+        let proposer = Address::from_bytes([1; 20]);
+
+        let id = governance.create_proposal(&mut tx_context(), proposer, custom_proposal(), "test".into(), 100).await?;
+
+        assert!(governance.execute_proposal(id).await.is_err());
+        assert_eq!(
+            governance.proposals.read().await.get(&id).unwrap().status,
+            ProposalStatus::Active
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_quadratic_voting_limits_whale() -> SystemResult<()> {
+        let whale = Address::from_bytes([1; 20]);
+        let small = [
+            Address::from_bytes([2; 20]),
+            Address::from_bytes([3; 20]),
+            Address::from_bytes([4; 20]),
+        ];
+
+        let mut tallies = Vec::new();
+        for strategy in [VotingStrategy::Linear, VotingStrategy::Quadratic] {
+            let temp_dir = TempDir::new().unwrap();
+            let governance = test_governance_with(&temp_dir, 3600, strategy);
+            {
+                let mut powers = governance.voting_powers.write().await;
+                powers.insert(whale, 10_000);
+                for voter in small.iter() {
+                    powers.insert(*voter, 100);
+                }
+            }
+
+            let id = governance.create_proposal(&mut tx_context(), whale, custom_proposal(), "test".into(), 100).await?;
+            governance.vote(id, whale, true).await?;
+            for voter in small.iter() {
+                governance.vote(id, *voter, false).await?;
+            }
+
+            let proposals = governance.proposals.read().await;
+            let proposal = proposals.get(&id).unwrap();
+            tallies.push((proposal.yes_votes, proposal.no_votes));
+        }
+
+        assert_eq!(tallies[0], (10_000, 300));
+        assert_eq!(tallies[1], (100, 30));
+
+        // Whale's share of the vote shrinks under quadratic weighting
+        let linear_share = tallies[0].0 as f64 / (tallies[0].0 + tallies[0].1) as f64;
+        let quadratic_share = tallies[1].0 as f64 / (tallies[1].0 + tallies[1].1) as f64;
+        assert!(quadratic_share < linear_share);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_quadratic_participation_uses_weighted_power() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let governance = test_governance_with(&temp_dir, 0, VotingStrategy::Quadratic);
+        let whale = Address::from_bytes([1; 20]);
+        {
+            let mut powers = governance.voting_powers.write().await;
+            powers.insert(whale, 10_000);
+            for i in 2..5 {
+                powers.insert(Address::from_bytes([i; 20]), 100);
+            }
+        }
+
+        // The whale alone casts 100 of 130 weighted votes, enough to take
+        // part, though only 1% of the raw power
+        let id = governance.create_proposal(&mut tx_context(), whale, custom_proposal(), "test".into(), 100).await?;
+        governance.vote(id, whale, true).await?;
+        governance.execute_proposal(id).await?;
+
+        assert_eq!(
+            governance.proposals.read().await.get(&id).unwrap().status,
+            ProposalStatus::Executed
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_epoch_hook_keeps_proposal_snapshot() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let governance = Arc::new(test_governance(&temp_dir));
+        let stakes = Arc::new(StakeSystem::new(
+            StakeConfig {
+                min_stake_amount: 100,
+                max_stake_amount: 1_000_000,
+                min_stake_duration: 0,
+                max_stake_duration: 3600,
+                unstake_delay: 0,
+            },
+            governance.storage.clone(),
+        ));
+        let hook = GovernanceEpochHook::new(governance.clone(), stakes.clone());
+        let alice = Address::from_bytes([1; 20]);
+        let bob = Address::from_bytes([2; 20]);
+
+        let mut ctx = tx_context();
+        stakes.create_stake(&mut ctx, alice, 1_000, 60).await?;
+        hook.on_epoch_change(&EpochInfo::genesis()).await.unwrap();
+        assert_eq!(governance.voting_powers.read().await.get(&alice), Some(&1_000));
+
+        let id = governance.create_proposal(&mut ctx, alice, custom_proposal(), "test".into(), 100).await?;
+
+        // Bob stakes after the proposal opened
+        stakes.create_stake(&mut ctx, bob, 500, 60).await?;
+        hook.on_epoch_change(&EpochInfo::genesis()).await.unwrap();
+        assert_eq!(governance.voting_powers.read().await.get(&bob), Some(&500));
+
+        governance.vote(id, alice, true).await?;
+        governance.vote(id, bob, false).await?;
+
+        let proposals = governance.proposals.read().await;
+        let proposal = proposals.get(&id).unwrap();
+        assert_eq!(proposal.yes_votes, 1_000);
+        assert_eq!(proposal.no_votes, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_epoch_change_rebuilds_voting_power() -> SystemResult<()> {
+        use crate::authority::{AuthorityStore, EpochConfig, EpochManager, StoreConfig};
+        use crate::crypto::{KeyPair, SignatureScheme};
+        use crate::storage::test_utils::temp_storage;
+
+        let temp_dir = TempDir::new().unwrap();
+        let governance = Arc::new(test_governance(&temp_dir));
+        let stakes = Arc::new(StakeSystem::new(
+            StakeConfig {
+                min_stake_amount: 100,
+                max_stake_amount: 1_000_000,
+                min_stake_duration: 0,
+                max_stake_duration: 3600,
+                unstake_delay: 0,
+            },
+            governance.storage.clone(),
+        ));
+
+        let (_store_dir, storage) = temp_storage();
+        let store = Arc::new(AuthorityStore::new(StoreConfig {
+            storage,
+            cache_size: 128,
+        }).unwrap());
+        let epochs = EpochManager::new(
+            EpochConfig {
+                epoch_duration_ms: 1_000,
+                min_validator_stake: 100,
+                max_validator_count: 2,
+            },
+            store,
+        ).unwrap();
+        epochs.register_listener(Arc::new(GovernanceEpochHook::new(governance.clone(), stakes.clone()))).await;
+
+        let alice = Address::from_bytes([1; 20]);
+        stakes.create_stake(&mut tx_context(), alice, 1_000, 60).await?;
+        assert!(governance.voting_powers.read().await.get(&alice).is_none());
+
+        // Stake counts once the epoch turns over
+        let public_key = KeyPair::generate(SignatureScheme::Ed25519).unwrap().public();
+        epochs.prepare_next_epoch(vec![(public_key, 100)]).await.unwrap();
+        epochs.start_new_epoch(1_000).await.unwrap();
+        assert_eq!(governance.voting_powers.read().await.get(&alice), Some(&1_000));
+
+        Ok(())
+    }
+}
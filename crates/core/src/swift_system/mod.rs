@@ -4,15 +4,23 @@ mod genesis;
 mod governance;
 mod rewards;
 mod stake;
+mod store;
 mod validators;
 
-pub use genesis::{Genesis, GenesisConfig};
-pub use governance::{Governance, ProposalType, VotingPower};
-pub use rewards::{RewardSystem, RewardType};
-pub use stake::{StakeSystem, StakeInfo};
-pub use validators::{ValidatorSet, ValidatorInfo};
-
+pub use genesis::{Genesis, GenesisConfig, GenesisValidator};
+pub use governance::{
+    Governance, GovernanceConfig, GovernanceEpochHook, ProposalType, VotingPower, VotingStrategy,
+};
+pub use rewards::{RewardConfig, RewardSystem, RewardType};
+pub use stake::{StakeConfig, StakeSystem, StakeInfo};
+pub use store::AsyncStorage;
+pub use validators::{ValidatorConfig, ValidatorSet, ValidatorInfo};
+
+use crate::authority::EpochManager;
+use crate::core::ObjectID;
 use crate::protocol::{ProtocolError, ProtocolResult};
+use crate::transaction::TransactionDigest;
+use std::sync::Arc;
 
 /// System error types
 #[derive(Debug, thiserror::Error)]
@@ -35,6 +43,33 @@ pub enum SystemError {
 
 pub type SystemResult<T> = Result<T, SystemError>;
 
+/// Transaction driving a system call. Objects it creates get IDs derived
+/// from its digest, so every validator assigns the same ones.
+#[derive(Debug, Clone)]
+pub struct TxContext {
+    /// Digest of the transaction
+    tx_digest: TransactionDigest,
+    /// Number of objects created so far by the transaction
+    creation_index: u64,
+}
+
+impl TxContext {
+    /// Create context for transaction `tx_digest`
+    pub fn new(tx_digest: TransactionDigest) -> Self {
+        Self {
+            tx_digest,
+            creation_index: 0,
+        }
+    }
+
+    /// Next deterministic object ID for this transaction
+    pub fn fresh_object_id(&mut self) -> ObjectID {
+        let id = ObjectID::derive(&self.tx_digest, self.creation_index);
+        self.creation_index += 1;
+        id
+    }
+}
+
 /// System configuration
 #[derive(Debug, Clone)]
 pub struct SystemConfig {
@@ -55,29 +90,30 @@ pub struct SystemState {
     /// Genesis state
     pub genesis: Genesis,
     /// Governance system
-    pub governance: Governance,
+    pub governance: Arc<Governance>,
     /// Reward system
     pub rewards: RewardSystem,
     /// Stake system
-    pub stake: StakeSystem,
+    pub stake: Arc<StakeSystem>,
     /// Validator set
     pub validators: ValidatorSet,
 }
 
 impl SystemState {
-    /// Create new system state
-    pub fn new(config: SystemConfig) -> SystemResult<Self> {
+    /// Create new system state, every subsystem persisting to `storage`
+    pub fn new(config: SystemConfig, storage: Arc<dyn AsyncStorage>) -> SystemResult<Self> {
         Ok(Self {
-            genesis: Genesis::new(config.genesis)?,
-            governance: Governance::new(config.governance),
-            rewards: RewardSystem::new(config.rewards),
-            stake: StakeSystem::new(config.stake),
-            validators: ValidatorSet::new(config.validator),
+            genesis: Genesis::new(config.genesis, storage.clone())?,
+            governance: Arc::new(Governance::new(config.governance, storage.clone())),
+            rewards: RewardSystem::new(config.rewards, storage.clone()),
+            stake: Arc::new(StakeSystem::new(config.stake, storage.clone())),
+            validators: ValidatorSet::new(config.validator, storage),
         })
     }
 
-    /// Initialize system state
-    pub async fn initialize(&mut self) -> SystemResult<()> {
+    /// Initialize system state, recomputing voting power at each epoch
+    /// change of `epoch_manager`
+    pub async fn initialize(&mut self, epoch_manager: &EpochManager) -> SystemResult<()> {
         // Initialize genesis
         self.genesis.initialize().await?;
 
@@ -93,6 +129,12 @@ impl SystemState {
         // Initialize validators
         self.validators.initialize().await?;
 
+        // Keep voting power in step with stake
+        epoch_manager.register_listener(Arc::new(GovernanceEpochHook::new(
+            self.governance.clone(),
+            self.stake.clone(),
+        ))).await;
+
         Ok(())
     }
 }
\ No newline at end of file
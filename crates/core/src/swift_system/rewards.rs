@@ -1,6 +1,10 @@
-use super::{SystemError, SystemResult};
+use super::stake::StakeStatus;
+use super::validators::ValidatorPerformance;
+use super::{AsyncStorage, StakeSystem, SystemError, SystemResult, ValidatorInfo};
 use crate::core::{Address, ObjectID};
-use crate::storage::Storage;
+use crate::utils::{Clock, SystemClock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -15,10 +19,14 @@ pub struct RewardConfig {
     pub max_reward_per_epoch: u64,
     /// Reward distribution interval
     pub distribution_interval: u64,
+    /// Blocks a validator is expected to sign per epoch (zero disables the check)
+    pub expected_blocks_signed: u64,
+    /// Lowest performance multiplier applied to validator rewards
+    pub min_performance_multiplier: f64,
 }
 
 /// Reward type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RewardType {
     /// Staking reward
     Staking {
@@ -34,6 +42,11 @@ pub enum RewardType {
         /// Transactions processed
         transactions_processed: u64,
     },
+    /// Tips from transactions in a proposed block, paid in full
+    Tip {
+        /// Tip amount
+        amount: u64,
+    },
     /// Governance reward
     Governance {
         /// Proposals created
@@ -44,7 +57,7 @@ pub enum RewardType {
 }
 
 /// Reward distribution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RewardDistribution {
     /// Epoch number
     pub epoch: u64,
@@ -61,27 +74,39 @@ pub struct RewardSystem {
     /// Configuration
     config: RewardConfig,
     /// Storage
-    storage: Arc<dyn Storage>,
+    storage: Arc<dyn AsyncStorage>,
     /// Current epoch
     current_epoch: RwLock<u64>,
     /// Pending rewards
     pending_rewards: RwLock<HashMap<Address, Vec<(RewardType, u64)>>>,
+    /// Distributed rewards not yet claimed
+    claimable_rewards: RwLock<HashMap<Address, u64>>,
+    /// Time source
+    clock: Arc<dyn Clock>,
 }
 
 impl RewardSystem {
     /// Create new reward system
     pub fn new(
         config: RewardConfig,
-        storage: Arc<dyn Storage>,
+        storage: Arc<dyn AsyncStorage>,
     ) -> Self {
         Self {
             config,
             storage,
             current_epoch: RwLock::new(0),
             pending_rewards: RwLock::new(HashMap::new()),
+            claimable_rewards: RwLock::new(HashMap::new()),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Read time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Initialize reward system
     pub async fn initialize(&mut self) -> SystemResult<()> {
         // Load current epoch
@@ -94,17 +119,29 @@ impl RewardSystem {
             .map_err(|e| SystemError::RewardError(e.to_string()))?;
         *self.pending_rewards.write().await = pending_rewards;
 
+        // Load claimable rewards
+        let claimable_rewards = self.storage.get_claimable_rewards().await
+            .map_err(|e| SystemError::RewardError(e.to_string()))?;
+        *self.claimable_rewards.write().await = claimable_rewards;
+
         Ok(())
     }
 
-    /// Add reward
+    /// Add reward. Validator rewards owe commission and depend on
+    /// performance, so they must go through `add_validator_reward`.
     pub async fn add_reward(
         &self,
         address: Address,
         reward_type: RewardType,
     ) -> SystemResult<()> {
+        if matches!(reward_type, RewardType::Validator { .. }) {
+            return Err(SystemError::RewardError(
+                "Validator rewards must be added with add_validator_reward".into()
+            ));
+        }
+
         // Calculate reward amount
-        let amount = self.calculate_reward_amount(&reward_type)?;
+        let amount = self.calculate_reward_amount(&reward_type, None)?;
 
         // Add to pending rewards
         let mut pending_rewards = self.pending_rewards.write().await;
@@ -119,8 +156,61 @@ impl RewardSystem {
         Ok(())
     }
 
-    /// Calculate reward amount
-    fn calculate_reward_amount(&self, reward_type: &RewardType) -> SystemResult<u64> {
+    /// Add validator reward, keeping the validator's commission and
+    /// splitting the remainder across delegators by delegated stake
+    pub async fn add_validator_reward(
+        &self,
+        validator_address: Address,
+        validator: &ValidatorInfo,
+        delegations: &[(Address, u64)],
+        reward_type: RewardType,
+    ) -> SystemResult<()> {
+        // Calculate reward amount
+        let amount = self.calculate_reward_amount(&reward_type, Some(&validator.performance))?;
+        let (commission, shares) = split_validator_reward(
+            amount,
+            validator.commission_rate,
+            delegations,
+        )?;
+
+        // Add to pending rewards
+        let mut pending_rewards = self.pending_rewards.write().await;
+        pending_rewards.entry(validator_address)
+            .or_insert_with(Vec::new)
+            .push((reward_type.clone(), commission));
+        for (delegator, share) in shares {
+            pending_rewards.entry(delegator)
+                .or_insert_with(Vec::new)
+                .push((reward_type.clone(), share));
+        }
+
+        // Store updated pending rewards
+        self.storage.put_pending_rewards(&*pending_rewards).await
+            .map_err(|e| SystemError::RewardError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Reward multiplier for a validator's performance: uptime times the
+    /// share of expected blocks signed, within `[min_performance_multiplier, 1.0]`
+    pub fn performance_multiplier(&self, performance: &ValidatorPerformance) -> f64 {
+        let uptime = performance.uptime / 100.0;
+        let signed = if self.config.expected_blocks_signed == 0 {
+            1.0
+        } else {
+            performance.blocks_signed as f64 / self.config.expected_blocks_signed as f64
+        };
+
+        (uptime * signed).clamp(self.config.min_performance_multiplier.min(1.0), 1.0)
+    }
+
+    /// Calculate reward amount, scaling validator rewards by `performance`
+    /// when known
+    fn calculate_reward_amount(
+        &self,
+        reward_type: &RewardType,
+        performance: Option<&ValidatorPerformance>,
+    ) -> SystemResult<u64> {
         let amount = match reward_type {
             RewardType::Staking { stake_amount, stake_duration } => {
                 if *stake_amount < self.config.min_stake_for_rewards {
@@ -133,13 +223,16 @@ impl RewardSystem {
             RewardType::Validator { blocks_proposed, transactions_processed } => {
                 let block_reward = *blocks_proposed * 100;
                 let tx_reward = *transactions_processed * 1;
-                block_reward + tx_reward
+                let multiplier = performance.map_or(1.0, |p| self.performance_multiplier(p));
+                ((block_reward + tx_reward) as f64 * multiplier) as u64
             }
             RewardType::Governance { proposals_created, votes_cast } => {
                 let proposal_reward = *proposals_created * 1000;
                 let vote_reward = *votes_cast * 10;
                 proposal_reward + vote_reward
             }
+            // Tips were paid by senders and are not subject to the epoch cap
+            RewardType::Tip { amount } => return Ok(*amount),
         };
 
         Ok(amount.min(self.config.max_reward_per_epoch))
@@ -174,7 +267,7 @@ impl RewardSystem {
         // Create distribution
         let distribution = RewardDistribution {
             epoch: current_epoch,
-            timestamp: crate::utils::current_timestamp(),
+            timestamp: self.clock.now(),
             total_reward,
             distributions,
         };
@@ -183,7 +276,13 @@ impl RewardSystem {
         self.storage.put_reward_distribution(&distribution).await
             .map_err(|e| SystemError::RewardError(e.to_string()))?;
 
+        // Credit claimable rewards
+        for (address, amount) in distribution.distributions.iter() {
+            self.credit_claimable(*address, *amount).await?;
+        }
+
         // Clear pending rewards
+        drop(pending_rewards);
         self.pending_rewards.write().await.clear();
         self.storage.clear_pending_rewards().await
             .map_err(|e| SystemError::RewardError(e.to_string()))?;
@@ -199,6 +298,63 @@ impl RewardSystem {
             .unwrap_or_default())
     }
 
+    /// Get claimable rewards
+    pub async fn get_claimable_rewards(&self, address: &Address) -> u64 {
+        self.claimable_rewards.read().await
+            .get(address)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Claim distributed rewards
+    pub async fn claim(&self, address: Address) -> SystemResult<u64> {
+        let mut claimable_rewards = self.claimable_rewards.write().await;
+        let amount = claimable_rewards.get(&address).copied().unwrap_or(0);
+        if amount > 0 {
+            self.storage.put_claimable_reward(&address, 0).await
+                .map_err(|e| SystemError::RewardError(e.to_string()))?;
+            claimable_rewards.remove(&address);
+        }
+        Ok(amount)
+    }
+
+    /// Add `amount` to the rewards `address` can claim, storing the new total
+    async fn credit_claimable(&self, address: Address, amount: u64) -> SystemResult<()> {
+        let mut claimable_rewards = self.claimable_rewards.write().await;
+        let total = claimable_rewards.get(&address).copied().unwrap_or(0) + amount;
+        self.storage.put_claimable_reward(&address, total).await
+            .map_err(|e| SystemError::RewardError(e.to_string()))?;
+        claimable_rewards.insert(address, total);
+        Ok(())
+    }
+
+    /// Compound claimable rewards into the address's active stake
+    pub async fn compound(
+        &self,
+        address: Address,
+        stake_system: &StakeSystem,
+    ) -> SystemResult<u64> {
+        // Find active stake
+        let stake = stake_system.get_stakes_by_staker(&address).await?
+            .into_iter()
+            .find(|stake| stake.status == StakeStatus::Active)
+            .ok_or_else(|| SystemError::RewardError("No active stake".into()))?;
+
+        // Claim rewards
+        let amount = self.claim(address).await?;
+        if amount == 0 {
+            return Ok(0);
+        }
+
+        // Add to stake, restoring claimable rewards on failure
+        if let Err(e) = stake_system.add_to_stake(stake.id, amount).await {
+            self.credit_claimable(address, amount).await?;
+            return Err(e);
+        }
+
+        Ok(amount)
+    }
+
     /// Get reward distributions
     pub async fn get_reward_distributions(
         &self,
@@ -208,4 +364,277 @@ impl RewardSystem {
         self.storage.get_reward_distributions(start_epoch, end_epoch).await
             .map_err(|e| SystemError::RewardError(e.to_string()))
     }
-}
\ No newline at end of file
+}
+
+/// Split a validator reward into the validator's commission and delegator
+/// shares proportional to delegated stake. Rounding remainders stay with
+/// the validator so the parts always sum to `amount`.
+fn split_validator_reward(
+    amount: u64,
+    commission_rate: f64,
+    delegations: &[(Address, u64)],
+) -> SystemResult<(u64, Vec<(Address, u64)>)> {
+    if !(0.0..=1.0).contains(&commission_rate) {
+        return Err(SystemError::RewardError("Invalid commission rate".into()));
+    }
+
+    let total_delegated: u128 = delegations.iter().map(|(_, stake)| *stake as u128).sum();
+    if total_delegated == 0 {
+        return Ok((amount, Vec::new()));
+    }
+
+    let pool = amount - (amount as f64 * commission_rate) as u64;
+    let shares: Vec<_> = delegations.iter()
+        .map(|(delegator, stake)| {
+            (*delegator, (pool as u128 * *stake as u128 / total_delegated) as u64)
+        })
+        .collect();
+    let distributed: u64 = shares.iter().map(|(_, share)| share).sum();
+
+    Ok((amount - distributed, shares))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, SignatureScheme};
+    use crate::swift_system::stake::StakeConfig;
+    use crate::swift_system::validators::{ValidatorPerformance, ValidatorStatus};
+    use crate::swift_system::TxContext;
+    use crate::storage::{CacheConfig, RocksConfig, StorageConfig, StorageManager};
+    use tempfile::TempDir;
+
+    fn test_storage(temp_dir: &TempDir) -> Arc<dyn AsyncStorage> {
+        let path = temp_dir.path().to_str().unwrap().to_string();
+        Arc::new(StorageManager::new(StorageConfig {
+            data_dir: path.clone(),
+            cache_config: CacheConfig::default(),
+            rocks_config: RocksConfig {
+                path,
+                ..Default::default()
+            },
+        }).unwrap())
+    }
+
+    fn test_reward_system(storage: Arc<dyn AsyncStorage>) -> RewardSystem {
+        RewardSystem::new(
+            RewardConfig {
+                base_reward_rate: 0.01,
+                min_stake_for_rewards: 0,
+                max_reward_per_epoch: 1_000_000,
+                distribution_interval: 1,
+                expected_blocks_signed: 0,
+                min_performance_multiplier: 0.0,
+            },
+            storage,
+        )
+    }
+
+    fn test_validator(uptime: f64) -> ValidatorInfo {
+        ValidatorInfo {
+            id: ObjectID::random(),
+            public_key: KeyPair::generate(SignatureScheme::Ed25519).unwrap().public(),
+            network_address: "127.0.0.1:8080".into(),
+            stake_amount: 1_000,
+            commission_rate: 0.1,
+            performance: ValidatorPerformance {
+                blocks_proposed: 0,
+                blocks_signed: 0,
+                response_time: 0,
+                uptime,
+            },
+            status: ValidatorStatus::Active,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tips_credited_in_full() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let rewards = test_reward_system(test_storage(&temp_dir));
+        let proposer = Address::from_bytes([1; 20]);
+
+        // Tips aren't capped by the per-epoch reward limit
+        rewards.add_reward(proposer, RewardType::Tip { amount: 2_000_000 }).await?;
+        assert!(matches!(
+            rewards.get_pending_rewards(&proposer).await?.as_slice(),
+            [(RewardType::Tip { amount: 2_000_000 }, 2_000_000)]
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_claim_rewards_once() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let rewards = test_reward_system(test_storage(&temp_dir));
+        let validator = Address::from_bytes([1; 20]);
+
+        rewards.add_validator_reward(validator, &test_validator(100.0), &[], RewardType::Validator {
+            blocks_proposed: 2,
+            transactions_processed: 50,
+        }).await?;
+
+        // Nothing claimable until distributed
+        assert_eq!(rewards.claim(validator).await?, 0);
+
+        rewards.distribute_rewards().await?;
+        assert!(rewards.get_pending_rewards(&validator).await?.is_empty());
+
+        assert_eq!(rewards.claim(validator).await?, 250);
+        assert_eq!(rewards.claim(validator).await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_claimable_rewards_persisted() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = test_storage(&temp_dir);
+        let rewards = test_reward_system(storage.clone());
+        let validator = Address::from_bytes([1; 20]);
+
+        rewards.add_validator_reward(validator, &test_validator(100.0), &[], RewardType::Validator {
+            blocks_proposed: 2,
+            transactions_processed: 50,
+        }).await?;
+        rewards.distribute_rewards().await?;
+
+        // A restarted node still owes the distributed rewards, once
+        let mut restarted = test_reward_system(storage.clone());
+        restarted.initialize().await?;
+        assert_eq!(restarted.get_claimable_rewards(&validator).await, 250);
+        assert_eq!(restarted.claim(validator).await?, 250);
+
+        let mut restarted = test_reward_system(storage);
+        restarted.initialize().await?;
+        assert_eq!(restarted.claim(validator).await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compound_rewards() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = test_storage(&temp_dir);
+        let rewards = test_reward_system(storage.clone());
+        let stakes = StakeSystem::new(
+            StakeConfig {
+                min_stake_amount: 100,
+                max_stake_amount: 1_000_000,
+                min_stake_duration: 0,
+                max_stake_duration: 3600,
+                unstake_delay: 0,
+            },
+            storage,
+        );
+        let staker = Address::from_bytes([1; 20]);
+        let tx_digest = crate::transaction::TransactionDigest::from_bytes([1; 32]);
+        let stake_id = stakes.create_stake(&mut TxContext::new(tx_digest), staker, 1_000, 60).await?;
+
+        rewards.add_validator_reward(staker, &test_validator(100.0), &[], RewardType::Validator {
+            blocks_proposed: 1,
+            transactions_processed: 0,
+        }).await?;
+        rewards.distribute_rewards().await?;
+
+        assert_eq!(rewards.compound(staker, &stakes).await?, 100);
+        assert_eq!(stakes.get_stake(&stake_id).await?.unwrap().amount, 1_100);
+        assert_eq!(stakes.get_total_staked().await, 1_100);
+        assert_eq!(rewards.claim(staker).await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validator_commission_split() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let rewards = test_reward_system(test_storage(&temp_dir));
+        let validator_address = Address::from_bytes([1; 20]);
+        let delegator_a = Address::from_bytes([2; 20]);
+        let delegator_b = Address::from_bytes([3; 20]);
+
+        rewards.add_validator_reward(
+            validator_address,
+            &test_validator(100.0),
+            &[(delegator_a, 300), (delegator_b, 700)],
+            RewardType::Validator {
+                blocks_proposed: 10,
+                transactions_processed: 0,
+            },
+        ).await?;
+
+        let distribution = rewards.distribute_rewards().await?;
+        assert_eq!(distribution.total_reward, 1_000);
+        assert_eq!(rewards.claim(validator_address).await?, 100);
+        assert_eq!(rewards.claim(delegator_a).await?, 270);
+        assert_eq!(rewards.claim(delegator_b).await?, 630);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validator_reward_not_credited_gross() -> SystemResult<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let rewards = test_reward_system(test_storage(&temp_dir));
+        let validator_address = Address::from_bytes([1; 20]);
+        let delegator = Address::from_bytes([2; 20]);
+        let reward = RewardType::Validator {
+            blocks_proposed: 10,
+            transactions_processed: 0,
+        };
+
+        assert!(matches!(
+            rewards.add_reward(validator_address, reward.clone()).await,
+            Err(SystemError::RewardError(_))
+        ));
+
+        // Half the uptime halves the reward before commission is taken
+        rewards.add_validator_reward(
+            validator_address,
+            &test_validator(50.0),
+            &[(delegator, 1_000)],
+            reward,
+        ).await?;
+        rewards.distribute_rewards().await?;
+        assert_eq!(rewards.claim(validator_address).await?, 50);
+        assert_eq!(rewards.claim(delegator).await?, 450);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_performance_multiplier() {
+        let temp_dir = TempDir::new().unwrap();
+        let rewards = RewardSystem::new(
+            RewardConfig {
+                base_reward_rate: 0.01,
+                min_stake_for_rewards: 0,
+                max_reward_per_epoch: 1_000_000,
+                distribution_interval: 1,
+                expected_blocks_signed: 10,
+                min_performance_multiplier: 0.2,
+            },
+            test_storage(&temp_dir),
+        );
+        let performance = |uptime: f64, blocks_signed: u64| ValidatorPerformance {
+            blocks_proposed: 10,
+            blocks_signed,
+            response_time: 0,
+            uptime,
+        };
+        let reward = RewardType::Validator {
+            blocks_proposed: 10,
+            transactions_processed: 0,
+        };
+
+        let full = performance(100.0, 10);
+        let half = performance(50.0, 10);
+        assert_eq!(rewards.calculate_reward_amount(&reward, Some(&full)).unwrap(), 1_000);
+        assert_eq!(rewards.calculate_reward_amount(&reward, Some(&half)).unwrap(), 500);
+
+        // Signing beyond expectations does not raise the multiplier above 1.0
+        assert_eq!(rewards.performance_multiplier(&performance(100.0, 20)), 1.0);
+        // And poor performance is floored at the configured minimum
+        assert_eq!(rewards.performance_multiplier(&performance(10.0, 1)), 0.2);
+    }
+}
@@ -1,6 +1,8 @@
-use super::{SystemError, SystemResult};
+use super::{AsyncStorage, SystemError, SystemResult, TxContext};
 use crate::core::{Address, ObjectID};
-use crate::storage::Storage;
+use crate::utils::{Clock, SystemClock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -20,7 +22,7 @@ pub struct StakeConfig {
 }
 
 /// Stake info
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StakeInfo {
     /// Stake ID
     pub id: ObjectID,
@@ -37,7 +39,7 @@ pub struct StakeInfo {
 }
 
 /// Stake status
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StakeStatus {
     /// Active
     Active,
@@ -55,29 +57,38 @@ pub struct StakeSystem {
     /// Configuration
     config: StakeConfig,
     /// Storage
-    storage: Arc<dyn Storage>,
+    storage: Arc<dyn AsyncStorage>,
     /// Stakes
     stakes: RwLock<HashMap<ObjectID, StakeInfo>>,
     /// Total staked amount
     total_staked: RwLock<u64>,
+    /// Time source
+    clock: Arc<dyn Clock>,
 }
 
 impl StakeSystem {
     /// Create new stake system
     pub fn new(
         config: StakeConfig,
-        storage: Arc<dyn Storage>,
+        storage: Arc<dyn AsyncStorage>,
     ) -> Self {
         Self {
             config,
             storage,
             stakes: RwLock::new(HashMap::new()),
             total_staked: RwLock::new(0),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Read time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Initialize stake system
-    pub async fn initialize(&mut self) -> SystemResult<()> {
+    pub async fn initialize(&self) -> SystemResult<()> {
         // Load stakes
         let stakes = self.storage.get_stakes().await
             .map_err(|e| SystemError::StakeError(e.to_string()))?;
@@ -96,6 +107,7 @@ impl StakeSystem {
     /// Create stake
     pub async fn create_stake(
         &self,
+        ctx: &mut TxContext,
         staker: Address,
         amount: u64,
         duration: u64,
@@ -112,10 +124,10 @@ impl StakeSystem {
 
         // Create stake info
         let stake = StakeInfo {
-            id: ObjectID::random(),
+            id: ctx.fresh_object_id(),
             staker,
             amount,
-            start_time: crate::utils::current_timestamp(),
+            start_time: self.clock.now(),
             duration,
             status: StakeStatus::Active,
         };
@@ -144,14 +156,15 @@ impl StakeSystem {
         }
 
         // Check minimum duration
-        let elapsed = crate::utils::current_timestamp() - stake.start_time;
+        let now = self.clock.now();
+        let elapsed = now - stake.start_time;
         if elapsed < stake.duration {
             return Err(SystemError::StakeError("Minimum duration not met".into()));
         }
 
         // Update status
         stake.status = StakeStatus::Unstaking {
-            unstake_time: crate::utils::current_timestamp(),
+            unstake_time: now,
         };
 
         // Store updated stake
@@ -175,7 +188,7 @@ impl StakeSystem {
         };
 
         // Check delay
-        let elapsed = crate::utils::current_timestamp() - unstake_time;
+        let elapsed = self.clock.now() - unstake_time;
         if elapsed < self.config.unstake_delay {
             return Err(SystemError::StakeError("Unstake delay not met".into()));
         }
@@ -193,6 +206,37 @@ impl StakeSystem {
         Ok(stake.amount)
     }
 
+    /// Add amount to an active stake
+    pub async fn add_to_stake(&self, stake_id: ObjectID, amount: u64) -> SystemResult<()> {
+        // Get stake
+        let mut stakes = self.stakes.write().await;
+        let stake = stakes.get_mut(&stake_id)
+            .ok_or_else(|| SystemError::StakeError("Stake not found".into()))?;
+
+        // Check status
+        if stake.status != StakeStatus::Active {
+            return Err(SystemError::StakeError("Stake not active".into()));
+        }
+
+        // Validate amount
+        let new_amount = stake.amount.saturating_add(amount);
+        if new_amount > self.config.max_stake_amount {
+            return Err(SystemError::StakeError("Invalid stake amount".into()));
+        }
+
+        // Update amount
+        stake.amount = new_amount;
+
+        // Store updated stake
+        self.storage.put_stake(stake).await
+            .map_err(|e| SystemError::StakeError(e.to_string()))?;
+
+        // Update total staked
+        *self.total_staked.write().await += amount;
+
+        Ok(())
+    }
+
     /// Get stake info
     pub async fn get_stake(&self, stake_id: &ObjectID) -> SystemResult<Option<StakeInfo>> {
         Ok(self.stakes.read().await.get(stake_id).cloned())
@@ -207,6 +251,15 @@ impl StakeSystem {
             .collect())
     }
 
+    /// Get active stakes
+    pub async fn get_active_stakes(&self) -> Vec<StakeInfo> {
+        self.stakes.read().await
+            .values()
+            .filter(|stake| stake.status == StakeStatus::Active)
+            .cloned()
+            .collect()
+    }
+
     /// Get total staked amount
     pub async fn get_total_staked(&self) -> u64 {
         *self.total_staked.read().await
@@ -215,7 +268,7 @@ impl StakeSystem {
     /// Process expired stakes
     pub async fn process_expired_stakes(&self) -> SystemResult<()> {
         let mut stakes = self.stakes.write().await;
-        let current_time = crate::utils::current_timestamp();
+        let current_time = self.clock.now();
 
         for stake in stakes.values_mut() {
             if stake.status == StakeStatus::Active {
@@ -232,4 +285,53 @@ impl StakeSystem {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_utils::temp_storage;
+    use crate::storage::StorageManager;
+    use crate::transaction::TransactionDigest;
+    use crate::utils::MockClock;
+
+    #[tokio::test]
+    async fn test_unstake_delay_with_mock_clock() -> SystemResult<()> {
+        let (_temp_dir, config) = temp_storage();
+        let storage = Arc::new(StorageManager::new(config).unwrap());
+        let clock = Arc::new(MockClock::new(1_000));
+        let stakes = StakeSystem::new(
+            StakeConfig {
+                min_stake_amount: 100,
+                max_stake_amount: 1_000_000,
+                min_stake_duration: 0,
+                max_stake_duration: 3600,
+                unstake_delay: 600,
+            },
+            storage,
+        ).with_clock(clock.clone());
+
+        let stake_id = stakes.create_stake(
+            &mut TxContext::new(TransactionDigest::from_bytes([1; 32])),
+            Address::from_bytes([1; 20]),
+            1_000,
+            60,
+        ).await?;
+        assert_eq!(stake_id, ObjectID::derive(&TransactionDigest::from_bytes([1; 32]), 0));
+        assert!(stakes.start_unstake(stake_id).await.is_err());
+
+        clock.advance(60);
+        stakes.start_unstake(stake_id).await?;
+        assert_eq!(
+            stakes.get_stake(&stake_id).await?.unwrap().status,
+            StakeStatus::Unstaking { unstake_time: 1_060 }
+        );
+
+        clock.advance(599);
+        assert!(stakes.withdraw_stake(stake_id).await.is_err());
+
+        clock.advance(1);
+        assert_eq!(stakes.withdraw_stake(stake_id).await?, 1_000);
+
+        Ok(())
+    }
+}
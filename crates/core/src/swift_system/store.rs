@@ -0,0 +1,461 @@
+// swift_system/store.rs
+use super::governance::Proposal;
+use super::rewards::{RewardDistribution, RewardType};
+use super::stake::StakeInfo;
+use super::validators::ValidatorInfo;
+use crate::core::{Address, ObjectID};
+use crate::protocol::ProtocolResult;
+use crate::storage::{IndexKey, IndexValue, Storage, StorageManager, StorageWrite};
+use move_core_types::language_storage::ModuleId;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+
+/// Tables holding system state, stored as custom indexes
+const VALIDATORS: &str = "system_validators";
+const PROPOSALS: &str = "system_proposals";
+const VOTING_POWERS: &str = "system_voting_powers";
+const STAKES: &str = "system_stakes";
+const PENDING_REWARDS: &str = "system_pending_rewards";
+const REWARD_DISTRIBUTIONS: &str = "system_reward_distributions";
+const CLAIMABLE_REWARDS: &str = "system_claimable_rewards";
+const DEPOSIT_REFUNDS: &str = "system_deposit_refunds";
+const PARAMETERS: &str = "system_parameters";
+const MODULES: &str = "system_modules";
+const CUSTOM_PROPOSALS: &str = "system_custom_proposals";
+const REMOVED_VALIDATORS: &str = "system_removed_validators";
+const SYSTEM: &str = "system";
+
+/// Async storage of system state used by governance, staking, rewards
+/// and the validator set
+#[async_trait::async_trait]
+pub trait AsyncStorage: Storage {
+    /// Get current epoch
+    async fn get_current_epoch(&self) -> ProtocolResult<u64>;
+
+    /// Set current epoch
+    async fn set_current_epoch(&self, epoch: u64) -> ProtocolResult<()>;
+
+    /// Get all validators
+    async fn get_validators(&self) -> ProtocolResult<HashMap<ObjectID, ValidatorInfo>>;
+
+    /// Put validator
+    async fn put_validator(&self, validator: &ValidatorInfo) -> ProtocolResult<()>;
+
+    /// Apply a validator set update, adding `added` and recording `removed`
+    async fn update_validator_set(
+        &self,
+        added: &[ValidatorInfo],
+        removed: &[Address],
+    ) -> ProtocolResult<()>;
+
+    /// Get all proposals
+    async fn get_proposals(&self) -> ProtocolResult<HashMap<ObjectID, Proposal>>;
+
+    /// Put proposal
+    async fn put_proposal(&self, proposal: &Proposal) -> ProtocolResult<()>;
+
+    /// Get voting powers
+    async fn get_voting_powers(&self) -> ProtocolResult<HashMap<Address, u64>>;
+
+    /// Replace voting powers
+    async fn put_voting_powers(&self, voting_powers: &HashMap<Address, u64>) -> ProtocolResult<()>;
+
+    /// Credit a refunded proposal deposit to `address`
+    async fn refund_deposit(&self, address: &Address, amount: u64) -> ProtocolResult<()>;
+
+    /// Get total deposits refunded to `address`
+    async fn get_refunded_deposit(&self, address: &Address) -> ProtocolResult<u64>;
+
+    /// Update system parameter
+    async fn update_parameter(&self, parameter: &str, value: &str) -> ProtocolResult<()>;
+
+    /// Get system parameter
+    async fn get_parameter(&self, parameter: &str) -> ProtocolResult<Option<String>>;
+
+    /// Get bytes of a published system module
+    async fn get_module(&self, id: &ModuleId) -> ProtocolResult<Option<Vec<u8>>>;
+
+    /// Publish system modules under `version`
+    async fn upgrade_system(&self, version: &str, modules: &[Vec<u8>]) -> ProtocolResult<()>;
+
+    /// Record an executed custom proposal
+    async fn execute_custom_proposal(&self, type_: &str, data: &[u8]) -> ProtocolResult<()>;
+
+    /// Get all stakes
+    async fn get_stakes(&self) -> ProtocolResult<HashMap<ObjectID, StakeInfo>>;
+
+    /// Put stake
+    async fn put_stake(&self, stake: &StakeInfo) -> ProtocolResult<()>;
+
+    /// Get pending rewards
+    async fn get_pending_rewards(&self) -> ProtocolResult<HashMap<Address, Vec<(RewardType, u64)>>>;
+
+    /// Replace pending rewards
+    async fn put_pending_rewards(
+        &self,
+        pending_rewards: &HashMap<Address, Vec<(RewardType, u64)>>,
+    ) -> ProtocolResult<()>;
+
+    /// Clear pending rewards
+    async fn clear_pending_rewards(&self) -> ProtocolResult<()>;
+
+    /// Put reward distribution
+    async fn put_reward_distribution(&self, distribution: &RewardDistribution) -> ProtocolResult<()>;
+
+    /// Get distributed rewards not yet claimed, by address
+    async fn get_claimable_rewards(&self) -> ProtocolResult<HashMap<Address, u64>>;
+
+    /// Set the distributed rewards `address` has not yet claimed
+    async fn put_claimable_reward(&self, address: &Address, amount: u64) -> ProtocolResult<()>;
+
+    /// Get reward distributions of epochs `start_epoch..=end_epoch`
+    async fn get_reward_distributions(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> ProtocolResult<Vec<RewardDistribution>>;
+}
+
+/// Index key of an entry in a system table
+fn table_key(table: &str, key: &[u8]) -> IndexKey {
+    IndexKey::Custom {
+        name: table.to_string(),
+        key: key.to_vec(),
+    }
+}
+
+impl StorageManager {
+    /// Get entry of a system table
+    fn get_entry<T: DeserializeOwned>(&self, table: &str, key: &[u8]) -> ProtocolResult<Option<T>> {
+        match self.get_index(&table_key(table, key))? {
+            Some(IndexValue::Custom(bytes)) => Ok(Some(bincode::deserialize(&bytes)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Put entry of a system table
+    fn put_entry<T: Serialize>(&self, table: &str, key: &[u8], value: &T) -> ProtocolResult<()> {
+        self.update_index(
+            table_key(table, key),
+            IndexValue::Custom(bincode::serialize(value)?),
+        )
+    }
+
+    /// Raw keys and values of a system table
+    fn raw_entries(&self, table: &str) -> ProtocolResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let prefix = IndexKey::custom_prefix(table)?;
+        Ok(self.index_store().iter_prefix(&prefix)?
+            .filter_map(|entry| match entry {
+                (IndexKey::Custom { name, key }, IndexValue::Custom(bytes)) if name == table => {
+                    Some((key, bytes))
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// All values of a system table
+    fn entries<T: DeserializeOwned>(&self, table: &str) -> ProtocolResult<Vec<T>> {
+        self.raw_entries(table)?
+            .iter()
+            .map(|(_, bytes)| Ok(bincode::deserialize(bytes)?))
+            .collect()
+    }
+
+    /// Replace every entry of a system table with `entries` in one atomic
+    /// write, so readers never see the table half rebuilt
+    fn replace_table<T: Serialize>(
+        &self,
+        table: &str,
+        entries: impl IntoIterator<Item = (Vec<u8>, T)>,
+    ) -> ProtocolResult<()> {
+        let mut writes: Vec<StorageWrite> = self.raw_entries(table)?
+            .into_iter()
+            .map(|(key, _)| StorageWrite::DeleteIndex(table_key(table, &key)))
+            .collect();
+        for (key, value) in entries {
+            writes.push(StorageWrite::Index(
+                table_key(table, &key),
+                IndexValue::Custom(bincode::serialize(&value)?),
+            ));
+        }
+        self.write_atomic(writes)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncStorage for StorageManager {
+    async fn get_current_epoch(&self) -> ProtocolResult<u64> {
+        Ok(self.get_entry(SYSTEM, b"current_epoch")?.unwrap_or(0))
+    }
+
+    async fn set_current_epoch(&self, epoch: u64) -> ProtocolResult<()> {
+        self.put_entry(SYSTEM, b"current_epoch", &epoch)
+    }
+
+    async fn get_validators(&self) -> ProtocolResult<HashMap<ObjectID, ValidatorInfo>> {
+        Ok(self.entries::<ValidatorInfo>(VALIDATORS)?
+            .into_iter()
+            .map(|validator| (validator.id, validator))
+            .collect())
+    }
+
+    async fn put_validator(&self, validator: &ValidatorInfo) -> ProtocolResult<()> {
+        self.put_entry(VALIDATORS, validator.id.as_bytes(), validator)
+    }
+
+    async fn update_validator_set(
+        &self,
+        added: &[ValidatorInfo],
+        removed: &[Address],
+    ) -> ProtocolResult<()> {
+        for validator in added {
+            self.put_validator(validator).await?;
+        }
+        for address in removed {
+            self.put_entry(REMOVED_VALIDATORS, address.as_bytes(), address)?;
+        }
+        Ok(())
+    }
+
+    async fn get_proposals(&self) -> ProtocolResult<HashMap<ObjectID, Proposal>> {
+        Ok(self.entries::<Proposal>(PROPOSALS)?
+            .into_iter()
+            .map(|proposal| (proposal.id, proposal))
+            .collect())
+    }
+
+    async fn put_proposal(&self, proposal: &Proposal) -> ProtocolResult<()> {
+        self.put_entry(PROPOSALS, proposal.id.as_bytes(), proposal)
+    }
+
+    async fn get_voting_powers(&self) -> ProtocolResult<HashMap<Address, u64>> {
+        Ok(self.entries::<(Address, u64)>(VOTING_POWERS)?.into_iter().collect())
+    }
+
+    async fn put_voting_powers(&self, voting_powers: &HashMap<Address, u64>) -> ProtocolResult<()> {
+        self.replace_table(
+            VOTING_POWERS,
+            voting_powers.iter().map(|(address, power)| (address.as_bytes().to_vec(), (*address, *power))),
+        )
+    }
+
+    async fn refund_deposit(&self, address: &Address, amount: u64) -> ProtocolResult<()> {
+        let refunded = self.get_refunded_deposit(address).await?;
+        self.put_entry(DEPOSIT_REFUNDS, address.as_bytes(), &refunded.saturating_add(amount))
+    }
+
+    async fn get_refunded_deposit(&self, address: &Address) -> ProtocolResult<u64> {
+        Ok(self.get_entry(DEPOSIT_REFUNDS, address.as_bytes())?.unwrap_or(0))
+    }
+
+    async fn update_parameter(&self, parameter: &str, value: &str) -> ProtocolResult<()> {
+        self.put_entry(PARAMETERS, parameter.as_bytes(), &value.to_string())
+    }
+
+    async fn get_parameter(&self, parameter: &str) -> ProtocolResult<Option<String>> {
+        self.get_entry(PARAMETERS, parameter.as_bytes())
+    }
+
+    async fn get_module(&self, id: &ModuleId) -> ProtocolResult<Option<Vec<u8>>> {
+        self.get_entry(MODULES, id.to_string().as_bytes())
+    }
+
+    async fn upgrade_system(&self, version: &str, modules: &[Vec<u8>]) -> ProtocolResult<()> {
+        for bytes in modules {
+            let module = move_binary_format::file_format::CompiledModule::deserialize(bytes)
+                .map_err(|e| crate::protocol::ProtocolError::DeserializationError(e.to_string()))?;
+            self.put_entry(MODULES, module.self_id().to_string().as_bytes(), bytes)?;
+        }
+        self.put_entry(SYSTEM, b"system_version", &version.to_string())
+    }
+
+    async fn execute_custom_proposal(&self, type_: &str, data: &[u8]) -> ProtocolResult<()> {
+        self.put_entry(CUSTOM_PROPOSALS, type_.as_bytes(), &data.to_vec())
+    }
+
+    async fn get_stakes(&self) -> ProtocolResult<HashMap<ObjectID, StakeInfo>> {
+        Ok(self.entries::<StakeInfo>(STAKES)?
+            .into_iter()
+            .map(|stake| (stake.id, stake))
+            .collect())
+    }
+
+    async fn put_stake(&self, stake: &StakeInfo) -> ProtocolResult<()> {
+        self.put_entry(STAKES, stake.id.as_bytes(), stake)
+    }
+
+    async fn get_pending_rewards(&self) -> ProtocolResult<HashMap<Address, Vec<(RewardType, u64)>>> {
+        Ok(self.entries::<(Address, Vec<(RewardType, u64)>)>(PENDING_REWARDS)?.into_iter().collect())
+    }
+
+    async fn put_pending_rewards(
+        &self,
+        pending_rewards: &HashMap<Address, Vec<(RewardType, u64)>>,
+    ) -> ProtocolResult<()> {
+        self.replace_table(
+            PENDING_REWARDS,
+            pending_rewards.iter().map(|(address, rewards)| (address.as_bytes().to_vec(), (*address, rewards))),
+        )
+    }
+
+    async fn clear_pending_rewards(&self) -> ProtocolResult<()> {
+        self.replace_table::<()>(PENDING_REWARDS, [])
+    }
+
+    async fn put_reward_distribution(&self, distribution: &RewardDistribution) -> ProtocolResult<()> {
+        self.put_entry(REWARD_DISTRIBUTIONS, &distribution.epoch.to_be_bytes(), distribution)
+    }
+
+    async fn get_reward_distributions(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> ProtocolResult<Vec<RewardDistribution>> {
+        let mut distributions: Vec<RewardDistribution> = self.entries(REWARD_DISTRIBUTIONS)?;
+        distributions.retain(|d| (start_epoch..=end_epoch).contains(&d.epoch));
+        distributions.sort_by_key(|d| d.epoch);
+        Ok(distributions)
+    }
+
+    async fn get_claimable_rewards(&self) -> ProtocolResult<HashMap<Address, u64>> {
+        Ok(self.entries::<(Address, u64)>(CLAIMABLE_REWARDS)?.into_iter().collect())
+    }
+
+    async fn put_claimable_reward(&self, address: &Address, amount: u64) -> ProtocolResult<()> {
+        self.put_entry(CLAIMABLE_REWARDS, address.as_bytes(), &(*address, amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, SignatureScheme};
+    use crate::storage::{CacheConfig, RocksConfig, StorageConfig};
+    use crate::swift_system::governance::{Governance, GovernanceConfig, ProposalType};
+    use crate::swift_system::TxContext;
+    use crate::swift_system::validators::{
+        ValidatorConfig, ValidatorPerformance, ValidatorSet, ValidatorStatus,
+    };
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn test_storage(temp_dir: &TempDir) -> Arc<StorageManager> {
+        let path = temp_dir.path().to_str().unwrap().to_string();
+        Arc::new(StorageManager::new(StorageConfig {
+            data_dir: path.clone(),
+            cache_config: CacheConfig::default(),
+            rocks_config: RocksConfig {
+                path,
+                ..Default::default()
+            },
+        }).unwrap())
+    }
+
+    fn test_validator(stake_amount: u64) -> ValidatorInfo {
+        ValidatorInfo {
+            id: ObjectID::random(),
+            public_key: KeyPair::generate(SignatureScheme::Ed25519).unwrap().public(),
+            network_address: "127.0.0.1:9000".to_string(),
+            stake_amount,
+            commission_rate: 0.1,
+            performance: ValidatorPerformance {
+                blocks_proposed: 0,
+                blocks_signed: 0,
+                response_time: 0,
+                uptime: 100.0,
+            },
+            status: ValidatorStatus::Active,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validator_persistence() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let storage = test_storage(&temp_dir);
+
+        let mut validator = test_validator(1_000);
+        storage.put_validator(&validator).await?;
+        storage.put_validator(&test_validator(2_000)).await?;
+
+        // Updates overwrite the stored entry
+        validator.stake_amount = 1_500;
+        storage.put_validator(&validator).await?;
+
+        let validators = storage.get_validators().await?;
+        assert_eq!(validators.len(), 2);
+        assert_eq!(validators[&validator.id].stake_amount, 1_500);
+
+        // A fresh validator set loads them through the trait
+        let mut set = ValidatorSet::new(
+            ValidatorConfig {
+                min_stake_amount: 0,
+                max_validator_count: 10,
+                performance_window: 100,
+                min_performance_threshold: 0.5,
+            },
+            storage,
+        );
+        set.initialize().await.unwrap();
+        assert_eq!(set.get_active_validators().await.unwrap().len(), 2);
+        assert_eq!(set.get_total_stake().await, 3_500);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_proposal_persistence() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let storage = test_storage(&temp_dir);
+        let config = GovernanceConfig {
+            min_proposal_deposit: 100,
+            voting_period: 3600,
+            min_participation_rate: 0.5,
+            required_approval_rate: 0.5,
+            voting_strategy: Default::default(),
+        };
+        let proposer = Address::from_bytes([1; 20]);
+
+        let governance = Governance::new(config.clone(), storage.clone());
+        let id = governance.create_proposal(
+            &mut TxContext::new(crate::transaction::TransactionDigest::from_bytes([1; 32])),
+            proposer,
+            ProposalType::Custom { type_: "test".to_string(), data: vec![1, 2, 3] },
+            "test".into(),
+            100,
+        ).await.unwrap();
+        governance.cancel_proposal(id, proposer).await.unwrap();
+
+        let proposals = storage.get_proposals().await?;
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[&id].proposer, proposer);
+        assert_eq!(proposals[&id].deposit, 0);
+        assert_eq!(storage.get_refunded_deposit(&proposer).await?, 100);
+
+        // A fresh governance instance sees the cancelled proposal
+        let mut reloaded = Governance::new(config, storage);
+        reloaded.initialize().await.unwrap();
+        assert!(reloaded.cancel_proposal(id, proposer).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replace_table_drops_stale_entries() -> ProtocolResult<()> {
+        let temp_dir = TempDir::new()?;
+        let storage = test_storage(&temp_dir);
+        let (a, b) = (Address::from_bytes([1; 20]), Address::from_bytes([2; 20]));
+
+        storage.put_voting_powers(&HashMap::from([(a, 10), (b, 20)])).await?;
+        storage.put_voting_powers(&HashMap::from([(b, 30)])).await?;
+        assert_eq!(storage.get_voting_powers().await?, HashMap::from([(b, 30)]));
+
+        // Other tables are left alone
+        storage.put_pending_rewards(&HashMap::from([(a, vec![])])).await?;
+        storage.clear_pending_rewards().await?;
+        assert!(storage.get_pending_rewards().await?.is_empty());
+        assert_eq!(storage.get_voting_powers().await?, HashMap::from([(b, 30)]));
+
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
-use super::{SystemError, SystemResult};
+use super::{AsyncStorage, SystemError, SystemResult, TxContext};
 use crate::core::{Address, ObjectID};
 use crate::crypto::PublicKey;
-use crate::storage::Storage;
+use crate::utils::{Clock, SystemClock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -19,7 +21,7 @@ pub struct ValidatorConfig {
 }
 
 /// Validator info
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidatorInfo {
     /// Validator ID
     pub id: ObjectID,
@@ -38,7 +40,7 @@ pub struct ValidatorInfo {
 }
 
 /// Validator performance
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidatorPerformance {
     /// Blocks proposed
     pub blocks_proposed: u64,
@@ -51,7 +53,7 @@ pub struct ValidatorPerformance {
 }
 
 /// Validator status
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ValidatorStatus {
     /// Active
     Active,
@@ -71,39 +73,48 @@ pub struct ValidatorSet {
     /// Configuration
     config: ValidatorConfig,
     /// Storage
-    storage: Arc<dyn Storage>,
+    storage: Arc<dyn AsyncStorage>,
     /// Validators
     validators: RwLock<HashMap<ObjectID, ValidatorInfo>>,
     /// Active set
     active_set: RwLock<Vec<ObjectID>>,
+    /// Time source
+    clock: Arc<dyn Clock>,
 }
 
 impl ValidatorSet {
     /// Create new validator set
     pub fn new(
         config: ValidatorConfig,
-        storage: Arc<dyn Storage>,
+        storage: Arc<dyn AsyncStorage>,
     ) -> Self {
         Self {
             config,
             storage,
             validators: RwLock::new(HashMap::new()),
             active_set: RwLock::new(Vec::new()),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Read time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Initialize validator set
     pub async fn initialize(&mut self) -> SystemResult<()> {
         // Load validators
         let validators = self.storage.get_validators().await
             .map_err(|e| SystemError::ValidatorError(e.to_string()))?;
-        *self.validators.write().await = validators;
 
         // Build active set
         let active_set: Vec<_> = validators.iter()
             .filter(|(_, v)| v.status == ValidatorStatus::Active)
             .map(|(id, _)| *id)
             .collect();
+        *self.validators.write().await = validators;
         *self.active_set.write().await = active_set;
 
         Ok(())
@@ -112,6 +123,7 @@ impl ValidatorSet {
     /// Register validator
     pub async fn register_validator(
         &self,
+        ctx: &mut TxContext,
         public_key: PublicKey,
         network_address: String,
         stake_amount: u64,
@@ -122,6 +134,11 @@ impl ValidatorSet {
             return Err(SystemError::ValidatorError("Insufficient stake".into()));
         }
 
+        // Validate commission rate
+        if !(0.0..=1.0).contains(&commission_rate) {
+            return Err(SystemError::ValidatorError("Commission rate must be within [0, 1]".into()));
+        }
+
         // Check validator count
         if self.validators.read().await.len() >= self.config.max_validator_count {
             return Err(SystemError::ValidatorError("Maximum validator count reached".into()));
@@ -129,7 +146,7 @@ impl ValidatorSet {
 
         // Create validator info
         let validator = ValidatorInfo {
-            id: ObjectID::random(),
+            id: ctx.fresh_object_id(),
             public_key,
             network_address,
             stake_amount,
@@ -180,6 +197,27 @@ impl ValidatorSet {
         Ok(())
     }
 
+    /// Replace a validator's public key, taking effect when the next
+    /// committee is selected
+    pub async fn rotate_key(
+        &self,
+        old_key: &PublicKey,
+        new_key: PublicKey,
+    ) -> SystemResult<()> {
+        let mut validators = self.validators.write().await;
+        let validator = validators.values_mut()
+            .find(|v| v.public_key == *old_key)
+            .ok_or_else(|| SystemError::ValidatorError("Validator not found".into()))?;
+
+        validator.public_key = new_key;
+
+        // Store updated validator
+        self.storage.put_validator(validator).await
+            .map_err(|e| SystemError::ValidatorError(e.to_string()))?;
+
+        Ok(())
+    }
+
     /// Update validator performance
     pub async fn update_performance(
         &self,
@@ -205,7 +243,7 @@ impl ValidatorSet {
         // Check performance threshold
         if uptime < self.config.min_performance_threshold {
             validator.status = ValidatorStatus::Jailed {
-                jail_time: crate::utils::current_timestamp(),
+                jail_time: self.clock.now(),
                 reason: "Poor performance".into(),
             };
             self.active_set.write().await.retain(|id| *id != validator_id);
@@ -231,7 +269,7 @@ impl ValidatorSet {
 
         // Update status
         validator.status = ValidatorStatus::Jailed {
-            jail_time: crate::utils::current_timestamp(),
+            jail_time: self.clock.now(),
             reason,
         };
 
@@ -284,6 +322,22 @@ impl ValidatorSet {
             .collect())
     }
 
+    /// Active validators for the next committee: the highest staked, up to
+    /// the maximum validator count
+    pub async fn select_committee(&self) -> Vec<(PublicKey, u64)> {
+        let mut candidates: Vec<_> = self.get_active_validators().await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|v| v.stake_amount >= self.config.min_stake_amount)
+            .map(|v| (v.public_key, v.stake_amount))
+            .collect();
+
+        // Stable sort keeps registration order among equal stakes
+        candidates.sort_by(|a, b| b.1.cmp(&a.1));
+        candidates.truncate(self.config.max_validator_count);
+        candidates
+    }
+
     /// Get total stake
     pub async fn get_total_stake(&self) -> u64 {
         self.validators.read().await
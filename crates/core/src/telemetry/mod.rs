@@ -2,12 +2,12 @@
 
 mod logging;
 mod tracing;
-mod metrics;
 
 pub use logging::{Logger, LogLevel};
 pub use tracing::{Tracer, Span, SpanContext};
-pub use metrics::{Metrics, Counter, Gauge, Histogram};
+pub use crate::metrics::{Metrics, MetricsConfig, Counter, Gauge, Histogram};
 
+use crate::metrics::MetricsResult;
 use std::sync::Arc;
 
 /// Telemetry configuration
@@ -17,8 +17,8 @@ pub struct TelemetryConfig {
     pub log_level: LogLevel,
     /// Enable tracing
     pub enable_tracing: bool,
-    /// Metrics endpoint
-    pub metrics_endpoint: String,
+    /// Metrics configuration
+    pub metrics: MetricsConfig,
 }
 
 /// Telemetry system
@@ -30,17 +30,17 @@ pub struct Telemetry {
 }
 
 impl Telemetry {
-    pub fn new(config: TelemetryConfig) -> Self {
+    pub fn new(config: TelemetryConfig) -> MetricsResult<Self> {
         let logger = Arc::new(Logger::new(config.log_level));
         let tracer = Arc::new(Tracer::new(config.enable_tracing));
-        let metrics = Arc::new(Metrics::new(&config.metrics_endpoint));
+        let metrics = Arc::new(Metrics::new(config.metrics.clone())?);
 
-        Self {
+        Ok(Self {
             config,
             logger,
             tracer,
             metrics,
-        }
+        })
     }
 
     pub fn logger(&self) -> Arc<Logger> {
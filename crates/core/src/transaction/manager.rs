@@ -1,10 +1,14 @@
-use super::{Transaction, TransactionDigest, ValidationResult};
+use super::{Transaction, TransactionDigest, TransactionValidator, ValidationResult};
 use crate::core::ObjectID;
-use crate::execution::{ExecutionEffects, Executor};
-use crate::storage::Storage;
+use crate::execution::{ExecutionEffects, ExecutionStatus, Executor};
+use crate::storage::{self, Storage};
 use crate::protocol::{ProtocolError, ProtocolResult};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+
+/// Status changes buffered for each subscriber before it starts lagging
+pub const STATUS_CHANNEL_CAPACITY: usize = 1024;
 
 /// Transaction information
 #[derive(Debug, Clone)]
@@ -24,12 +28,16 @@ pub struct TransactionInfo {
 pub enum TransactionStatus {
     /// Pending in mempool
     Pending,
+    /// Certified by a quorum, awaiting execution
+    Certified,
     /// Being processed
     Processing,
     /// Executed successfully
     Executed,
     /// Failed execution
     Failed(String),
+    /// Not known to this node
+    NotFound,
 }
 
 /// Transaction manager
@@ -42,6 +50,8 @@ pub struct TransactionManager {
     validator: Arc<TransactionValidator>,
     /// Processing transactions
     processing: RwLock<HashMap<TransactionDigest, TransactionInfo>>,
+    /// Live feed of status changes
+    status_sender: broadcast::Sender<(TransactionDigest, TransactionStatus)>,
 }
 
 impl TransactionManager {
@@ -56,9 +66,15 @@ impl TransactionManager {
             executor,
             validator,
             processing: RwLock::new(HashMap::new()),
+            status_sender: broadcast::channel(STATUS_CHANNEL_CAPACITY).0,
         }
     }
 
+    /// Receive every status change from now on
+    pub fn subscribe_status(&self) -> broadcast::Receiver<(TransactionDigest, TransactionStatus)> {
+        self.status_sender.subscribe()
+    }
+
     /// Submit transaction
     pub async fn submit_transaction(
         &self,
@@ -75,6 +91,17 @@ impl TransactionManager {
             return Err(ProtocolError::TransactionExists(digest));
         }
 
+        // Check dependencies don't form a cycle with pending transactions
+        {
+            let processing = self.processing.read().await;
+            let pending: HashMap<_, _> = processing.iter()
+                .map(|(digest, info)| (*digest, info.transaction.dependencies.clone()))
+                .collect();
+            if has_dependency_cycle(&digest, &transaction.dependencies, &pending) {
+                return Err(ProtocolError::DependencyCycle(format!("{:?}", digest)));
+            }
+        }
+
         // Create transaction info
         let info = TransactionInfo {
             transaction: transaction.clone(),
@@ -88,10 +115,22 @@ impl TransactionManager {
 
         // Add to processing queue
         self.processing.write().await.insert(digest, info);
+        let _ = self.status_sender.send((digest, TransactionStatus::Pending));
 
         Ok(digest)
     }
 
+    /// Record that a quorum certified a pending transaction
+    pub async fn mark_certified(&self, digest: &TransactionDigest) -> ProtocolResult<()> {
+        let mut info = self.get_transaction_info(digest).await?;
+        if info.status != TransactionStatus::Pending {
+            return Err(ProtocolError::InvalidTransactionStatus);
+        }
+
+        info.status = TransactionStatus::Certified;
+        self.update_transaction_info(&info).await
+    }
+
     /// Execute transaction
     pub async fn execute_transaction(
         &self,
@@ -101,7 +140,7 @@ impl TransactionManager {
         let mut info = self.get_transaction_info(digest).await?;
 
         // Check status
-        if info.status != TransactionStatus::Pending {
+        if !matches!(info.status, TransactionStatus::Pending | TransactionStatus::Certified) {
             return Err(ProtocolError::InvalidTransactionStatus);
         }
 
@@ -112,12 +151,19 @@ impl TransactionManager {
         // Execute transaction
         let effects = match self.executor.execute_transaction(&info.transaction).await {
             Ok(effects) => {
+                // Commit system state changes carried in the effects
+                if let (ExecutionStatus::Success, Some(price)) = (&effects.status, effects.reference_gas_price) {
+                    storage::set_reference_gas_price(self.storage.as_ref(), price)?;
+                }
+
                 info.status = TransactionStatus::Executed;
                 info.effects = Some(effects.clone());
                 effects
             }
             Err(e) => {
                 info.status = TransactionStatus::Failed(e.to_string());
+                self.update_transaction_info(&info).await?;
+                self.processing.write().await.remove(digest);
                 return Err(ProtocolError::ExecutionError(e));
             }
         };
@@ -146,6 +192,18 @@ impl TransactionManager {
             .ok_or_else(|| ProtocolError::TransactionNotFound(*digest))
     }
 
+    /// Current lifecycle status of a transaction; `NotFound` if unknown
+    pub async fn status(&self, digest: &TransactionDigest) -> TransactionStatus {
+        match self.get_transaction_info(digest).await {
+            Ok(info) => info.status,
+            Err(ProtocolError::TransactionNotFound(_)) => TransactionStatus::NotFound,
+            Err(e) => {
+                log::warn!("Failed to load status of {:?}: {}", digest, e);
+                TransactionStatus::NotFound
+            }
+        }
+    }
+
     /// Update transaction info
     async fn update_transaction_info(&self, info: &TransactionInfo) -> ProtocolResult<()> {
         // Update storage
@@ -156,6 +214,9 @@ impl TransactionManager {
             *processing_info = info.clone();
         }
 
+        // No subscribers is fine
+        let _ = self.status_sender.send((info.transaction.digest(), info.status.clone()));
+
         Ok(())
     }
 
@@ -171,4 +232,124 @@ impl TransactionManager {
     pub async fn get_processing_transactions(&self) -> Vec<TransactionInfo> {
         self.processing.read().await.values().cloned().collect()
     }
-}
\ No newline at end of file
+}
+
+/// Check whether adding a transaction with `dependencies` would close a
+/// cycle through the pending dependency graph
+fn has_dependency_cycle(
+    digest: &TransactionDigest,
+    dependencies: &[TransactionDigest],
+    pending: &HashMap<TransactionDigest, Vec<TransactionDigest>>,
+) -> bool {
+    let mut visited = HashSet::new();
+    let mut stack: Vec<TransactionDigest> = dependencies.to_vec();
+
+    while let Some(current) = stack.pop() {
+        if current == *digest {
+            return true;
+        }
+        if !visited.insert(current) {
+            continue;
+        }
+        if let Some(next) = pending.get(&current) {
+            stack.extend(next.iter().copied());
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependency_cycle() {
+        let a = TransactionDigest::from_bytes([1; 32]);
+        let b = TransactionDigest::from_bytes([2; 32]);
+        let c = TransactionDigest::from_bytes([3; 32]);
+
+        // B is pending and depends on A; A now arrives depending on B
+        let mut pending = HashMap::new();
+        pending.insert(b, vec![a]);
+        assert!(has_dependency_cycle(&a, &[b], &pending));
+
+        // C depending on B does not close a cycle
+        assert!(!has_dependency_cycle(&c, &[b], &pending));
+    }
+
+    #[tokio::test]
+    async fn test_status_transitions() {
+        use crate::core::Address;
+        use crate::crypto::{KeyPair, SignatureScheme};
+        use crate::framework::FrameworkConfig;
+        use crate::runtime::execution::ExecutionConfig;
+        use crate::runtime::RuntimeConfig;
+        use crate::storage::MemStorage;
+        use crate::transaction::{SystemTransaction, TransactionData};
+
+        let sender = Address::from_bytes([1; 20]);
+        let storage = Arc::new(MemStorage::new());
+        let mut executor = Executor::new(
+            RuntimeConfig {
+                execution: ExecutionConfig {
+                    max_gas_per_tx: 1_000_000,
+                    max_events: 128,
+                    execution_threads: 1,
+                    complexity_budget: 1_000_000,
+                },
+                framework: FrameworkConfig::default(),
+            },
+            storage.clone(),
+        ).unwrap();
+        executor.set_system_senders(HashSet::from([sender]));
+        let manager = TransactionManager::new(
+            storage,
+            Arc::new(executor),
+            Arc::new(TransactionValidator::new("sui-local".to_string())),
+        );
+
+        let mut transaction = Transaction::new(
+            TransactionData::System(SystemTransaction::SetGasPrice(10)),
+            sender,
+            1_000,
+            1,
+            vec![],
+            0,
+            0,
+            "sui-local".to_string(),
+        );
+        transaction.sign(&KeyPair::generate(SignatureScheme::Ed25519).unwrap());
+
+        let digest = transaction.digest();
+        assert_eq!(manager.status(&digest).await, TransactionStatus::NotFound);
+        let mut updates = manager.subscribe_status();
+
+        manager.submit_transaction(transaction).await.unwrap();
+        assert_eq!(manager.status(&digest).await, TransactionStatus::Pending);
+
+        manager.mark_certified(&digest).await.unwrap();
+        assert_eq!(manager.status(&digest).await, TransactionStatus::Certified);
+        assert!(manager.mark_certified(&digest).await.is_err());
+
+        // An authorized gas price change always succeeds
+        let effects = manager.execute_transaction(&digest).await.unwrap();
+        assert!(matches!(effects.status, ExecutionStatus::Success));
+        assert_eq!(manager.status(&digest).await, TransactionStatus::Executed);
+        assert!(manager.get_processing_transactions().await.is_empty());
+
+        // Processing was published before the executor ran
+        let mut seen = Vec::new();
+        while let Ok((updated, status)) = updates.try_recv() {
+            assert_eq!(updated, digest);
+            seen.push(status);
+        }
+        assert_eq!(seen, vec![
+            TransactionStatus::Pending,
+            TransactionStatus::Certified,
+            TransactionStatus::Processing,
+            TransactionStatus::Executed,
+        ]);
+        assert!(manager.execute_transaction(&digest).await.is_err());
+    }
+}
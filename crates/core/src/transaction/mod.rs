@@ -1,13 +1,20 @@
 //! Transaction module for processing and managing transactions.
 
 mod manager;
+mod signature_cache;
+#[cfg(test)]
+pub(crate) mod test_utils;
 mod validator;
 
-pub use manager::{TransactionManager, TransactionInfo};
+pub use manager::{TransactionManager, TransactionInfo, TransactionStatus};
+pub use signature_cache::{SignatureCache, DEFAULT_SIGNATURE_CACHE_SIZE};
 pub use validator::{TransactionValidator, ValidationResult};
 
-use crate::core::{Address, ObjectID};
-use crate::crypto::{PublicKey, Signature};
+use crate::core::{Address, ObjectID, ObjectRef};
+use crate::protocol::CallArg;
+use crate::crypto::{CryptoResult, KeyPair, MultiSigPublicKey, PublicKey, Signature};
+use crate::protocol::{ProtocolError, ProtocolResult};
+use crate::utils::{Hasher, Sha256Hasher};
 use serde::{Serialize, Deserialize};
 
 /// Transaction digest (32 bytes)
@@ -45,7 +52,7 @@ pub struct MoveTransaction {
     /// Type arguments
     pub type_arguments: Vec<TypeTag>,
     /// Arguments
-    pub arguments: Vec<Vec<u8>>,
+    pub arguments: Vec<CallArg>,
 }
 
 /// Move module
@@ -73,6 +80,29 @@ pub enum SystemTransaction {
     ChangeEpoch(EpochChange),
     /// Genesis
     Genesis(Genesis),
+    /// Set the reference gas price floor
+    SetGasPrice(u64),
+    /// Announce a validator's signing key for the next epoch
+    RotateKey {
+        /// Key used until the epoch boundary
+        old_key: PublicKey,
+        /// Key used from the next epoch
+        new_key: PublicKey,
+        /// Signature by `new_key` over `old_key`, proving possession
+        proof: Signature,
+    },
+}
+
+impl SystemTransaction {
+    /// Announce that `next` replaces `old_key` at the next epoch boundary
+    pub fn rotate_key(old_key: PublicKey, next: &KeyPair) -> Self {
+        let proof = next.sign(&old_key.to_bytes());
+        Self::RotateKey {
+            old_key,
+            new_key: next.public(),
+            proof,
+        }
+    }
 }
 
 /// Transaction
@@ -92,6 +122,8 @@ pub struct Transaction {
     pub epoch: u64,
     /// Expiration timestamp
     pub expiration: u64,
+    /// Chain the transaction is valid on
+    pub chain_id: String,
     /// Signature
     pub signature: Option<Signature>,
     /// Public key
@@ -108,6 +140,7 @@ impl Transaction {
         dependencies: Vec<TransactionDigest>,
         epoch: u64,
         expiration: u64,
+        chain_id: String,
     ) -> Self {
         Self {
             data,
@@ -117,17 +150,29 @@ impl Transaction {
             dependencies,
             epoch,
             expiration,
+            chain_id,
             signature: None,
             public_key: None,
         }
     }
 
-    /// Get transaction digest
+    /// Get transaction digest (SHA-256 over the BCS encoding, excluding
+    /// signature fields so signing doesn't change the digest)
     pub fn digest(&self) -> TransactionDigest {
-        use sha2::{Sha256, Digest};
-        let mut hasher = Sha256::new();
-        hasher.update(bincode::serialize(self).unwrap());
-        TransactionDigest(hasher.finalize().into())
+        self.digest_with::<Sha256Hasher>()
+    }
+
+    /// Get transaction digest using hasher `H`; only SHA-256 digests are
+    /// valid on chain
+    pub fn digest_with<H: Hasher>(&self) -> TransactionDigest {
+        let unsigned = Self {
+            signature: None,
+            public_key: None,
+            ..self.clone()
+        };
+        TransactionDigest(
+            crate::utils::bcs::hash_with::<H, _>(&unsigned).expect("Transaction is BCS serializable"),
+        )
     }
 
     /// Sign transaction
@@ -137,7 +182,46 @@ impl Transaction {
         self.public_key = Some(keypair.public());
     }
 
-    /// Verify signature
+    /// Sign transaction with `signers`, members of the multi-sig `public_key`
+    pub fn sign_multisig(
+        &mut self,
+        public_key: MultiSigPublicKey,
+        signers: &[KeyPair],
+    ) -> CryptoResult<()> {
+        let multisig = public_key.sign(self.digest().as_bytes(), signers)?;
+        self.signature = Some(Signature::MultiSig(multisig));
+        self.public_key = Some(PublicKey::MultiSig(public_key));
+        Ok(())
+    }
+
+    /// Check that a key rotation is signed by the key it replaces and
+    /// proves possession of the new key. Other transactions pass.
+    pub fn verify_key_rotation(&self) -> ProtocolResult<()> {
+        let TransactionData::System(SystemTransaction::RotateKey { old_key, new_key, proof }) = &self.data else {
+            return Ok(());
+        };
+
+        if old_key == new_key {
+            return Err(ProtocolError::InvalidTransaction(
+                "Rotated key must differ from the current key".into()
+            ));
+        }
+        if self.public_key.as_ref() != Some(old_key) || !self.verify_signature() {
+            return Err(ProtocolError::InvalidSignature(
+                "Key rotation must be signed by the key being rotated".into()
+            ));
+        }
+        if !proof.verify(&old_key.to_bytes(), new_key) {
+            return Err(ProtocolError::InvalidSignature(
+                "Missing proof of possession of the new key".into()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Verify signature; a multi-sig needs member signatures reaching its
+    /// threshold
     pub fn verify_signature(&self) -> bool {
         if let (Some(signature), Some(public_key)) = (&self.signature, &self.public_key) {
             public_key.verify(self.digest().as_bytes(), signature)
@@ -163,10 +247,20 @@ impl Transaction {
 
     /// Get input objects
     pub fn input_objects(&self) -> Vec<ObjectID> {
+        self.input_object_refs().into_iter().map(|r| r.id).collect()
+    }
+
+    /// Get input object references, with the versions the sender expects
+    pub fn input_object_refs(&self) -> Vec<ObjectRef> {
         match &self.data {
             TransactionData::Move(move_tx) => {
-                // Extract object references from Move transaction
-                vec![] // TODO: Implement
+                move_tx.arguments.iter()
+                    .flat_map(|arg| match arg {
+                        CallArg::Pure(_) => vec![],
+                        CallArg::Object(object_ref) => vec![*object_ref],
+                        CallArg::ObjVec(refs) => refs.clone(),
+                    })
+                    .collect()
             }
             TransactionData::System(_) => {
                 // System transactions don't have input objects
@@ -174,4 +268,105 @@ impl Transaction {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::SignatureScheme;
+
+    fn test_transaction() -> Transaction {
+        Transaction::new(
+            TransactionData::Move(MoveTransaction {
+                module: None,
+                function: None,
+                type_arguments: vec![],
+                arguments: vec![],
+            }),
+            Address::from_bytes([1; 20]),
+            1_000,
+            1,
+            vec![],
+            0,
+            0,
+            "sui-local".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_transaction_bcs_digest() {
+        let transaction = test_transaction();
+        assert_eq!(
+            hex::encode(transaction.digest().as_bytes()),
+            "fd8f3be72eeca59c7479053369e840dac4137450717b7ac922ee30d66ef8e959"
+        );
+    }
+
+    #[test]
+    fn test_signing_keeps_digest() {
+        let mut transaction = test_transaction();
+        let digest = transaction.digest();
+        transaction.sign(&KeyPair::generate(SignatureScheme::Ed25519).unwrap());
+        assert_eq!(transaction.digest(), digest);
+        assert!(transaction.verify_signature());
+    }
+
+    #[test]
+    fn test_digest_with_custom_hasher() {
+        use crate::utils::Fnv1aHasher;
+
+        let transaction = test_transaction();
+        assert_eq!(transaction.digest_with::<Sha256Hasher>(), transaction.digest());
+
+        let fast = transaction.digest_with::<Fnv1aHasher>();
+        assert_ne!(fast, transaction.digest());
+        assert_eq!(fast, test_transaction().digest_with::<Fnv1aHasher>());
+    }
+
+    #[test]
+    fn test_multisig_transaction() {
+        let keypairs: Vec<_> = (0..3).map(|_| KeyPair::generate(SignatureScheme::Ed25519).unwrap()).collect();
+        let public_key = MultiSigPublicKey::new(
+            keypairs.iter().map(|k| (k.public(), 1)).collect(),
+            2,
+        ).unwrap();
+
+        let mut transaction = test_transaction();
+        let digest = transaction.digest();
+        transaction.sign_multisig(public_key.clone(), &keypairs[..2]).unwrap();
+        assert_eq!(transaction.digest(), digest);
+        assert!(transaction.verify_signature());
+
+        transaction.sign_multisig(public_key, &keypairs[2..]).unwrap();
+        assert!(!transaction.verify_signature());
+    }
+
+    #[test]
+    fn test_key_rotation_needs_old_key_and_proof() {
+        let old = KeyPair::generate(SignatureScheme::Ed25519).unwrap();
+        let new = KeyPair::generate(SignatureScheme::Ed25519).unwrap();
+        let other = KeyPair::generate(SignatureScheme::Ed25519).unwrap();
+        let rotation = |data: SystemTransaction, signer: &KeyPair| {
+            let mut transaction = Transaction {
+                data: TransactionData::System(data),
+                ..test_transaction()
+            };
+            transaction.sign(signer);
+            transaction
+        };
+
+        let announcement = SystemTransaction::rotate_key(old.public(), &new);
+        assert!(rotation(announcement.clone(), &old).verify_key_rotation().is_ok());
+
+        // Only the holder of the old key may rotate it
+        assert!(rotation(announcement, &other).verify_key_rotation().is_err());
+
+        // The new key must have signed the old one
+        let forged = SystemTransaction::RotateKey {
+            old_key: old.public(),
+            new_key: new.public(),
+            proof: other.sign(&old.public().to_bytes()),
+        };
+        assert!(rotation(forged, &old).verify_key_rotation().is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use super::{Transaction, TransactionDigest};
+use crate::crypto::PublicKey;
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Default number of verified signatures remembered
+pub const DEFAULT_SIGNATURE_CACHE_SIZE: usize = 100_000;
+
+/// Bounded cache of verified transaction signatures, shared by every
+/// component that verifies the same transaction. Entries are keyed on the
+/// recomputed digest, so an altered transaction never hits.
+pub struct SignatureCache {
+    /// Serialized signature verified for each digest and signer
+    verified: Mutex<LruCache<(TransactionDigest, PublicKey), Vec<u8>>>,
+    /// Signature verifications actually performed
+    verifications: AtomicU64,
+}
+
+impl SignatureCache {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            verified: Mutex::new(LruCache::new(capacity)),
+            verifications: AtomicU64::new(0),
+        }
+    }
+
+    /// Verify the transaction's signature, skipping the check if the same
+    /// signature over the same digest was verified before
+    pub fn verify(&self, transaction: &Transaction) -> bool {
+        let (Some(signature), Some(public_key)) = (&transaction.signature, &transaction.public_key) else {
+            return false;
+        };
+        let Ok(signature_bytes) = bincode::serialize(signature) else {
+            return false;
+        };
+        let digest = transaction.digest();
+        let key = (digest, public_key.clone());
+
+        if self.verified.lock().unwrap().get(&key) == Some(&signature_bytes) {
+            return true;
+        }
+
+        self.verifications.fetch_add(1, Ordering::Relaxed);
+        if !public_key.verify(digest.as_bytes(), signature) {
+            return false;
+        }
+        self.verified.lock().unwrap().put(key, signature_bytes);
+        true
+    }
+
+    /// Number of signature verifications performed, excluding cache hits
+    pub fn verifications(&self) -> u64 {
+        self.verifications.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for SignatureCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIGNATURE_CACHE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Address;
+    use crate::crypto::{KeyPair, SignatureScheme};
+    use crate::transaction::{MoveTransaction, TransactionData};
+
+    fn signed_transaction(keypair: &KeyPair) -> Transaction {
+        let mut transaction = Transaction::new(
+            TransactionData::Move(MoveTransaction {
+                module: None,
+                function: None,
+                type_arguments: vec![],
+                arguments: vec![],
+            }),
+            Address::from_bytes([1; 20]),
+            1_000,
+            1,
+            vec![],
+            0,
+            0,
+            "sui-local".to_string(),
+        );
+        transaction.sign(keypair);
+        transaction
+    }
+
+    #[test]
+    fn test_repeated_verification_hits_cache() {
+        let cache = SignatureCache::new(16);
+        let transaction = signed_transaction(&KeyPair::generate(SignatureScheme::Ed25519).unwrap());
+
+        assert!(cache.verify(&transaction));
+        assert!(cache.verify(&transaction));
+        assert_eq!(cache.verifications(), 1);
+    }
+
+    #[test]
+    fn test_altered_transaction_misses_cache() {
+        let cache = SignatureCache::new(16);
+        let keypair = KeyPair::generate(SignatureScheme::Ed25519).unwrap();
+        let transaction = signed_transaction(&keypair);
+        assert!(cache.verify(&transaction));
+
+        // Changing a field changes the digest, so the old signature fails
+        let mut altered = transaction.clone();
+        altered.gas_budget += 1;
+        assert!(!cache.verify(&altered));
+
+        // Another signer's signature over the same digest is checked afresh
+        let mut resigned = transaction;
+        resigned.public_key = Some(KeyPair::generate(SignatureScheme::Ed25519).unwrap().public());
+        assert!(!cache.verify(&resigned));
+        assert_eq!(cache.verifications(), 3);
+    }
+}
@@ -0,0 +1,29 @@
+//! Transaction fixtures shared by tests.
+
+use crate::crypto::{KeyPair, SignatureScheme};
+use crate::protocol::{SignedTransaction, TransactionData, TransactionKind};
+
+/// Empty publish transaction from a fresh sender
+pub(crate) fn signed_transaction(gas_price: u64, tip: u64, expiration: u64) -> SignedTransaction {
+    let sender = KeyPair::generate(SignatureScheme::Ed25519).unwrap();
+    signed_transaction_by(&sender, gas_price, tip, expiration)
+}
+
+/// Empty publish transaction from `sender`, signed over its BCS-encoded data
+pub(crate) fn signed_transaction_by(
+    sender: &KeyPair,
+    gas_price: u64,
+    tip: u64,
+    expiration: u64,
+) -> SignedTransaction {
+    let data = TransactionData {
+        sender: sender.public(),
+        kind: TransactionKind::Publish { modules: vec![] },
+        gas_budget: 1_000,
+        gas_price,
+        tip,
+        expiration,
+    };
+    let signature = sender.sign(&bcs::to_bytes(&data).unwrap());
+    SignedTransaction { data, signature }
+}
@@ -1,7 +1,10 @@
-use super::Transaction;
+use super::{SignatureCache, Transaction};
 use crate::core::ObjectID;
+use crate::execution::GasSchedule;
 use crate::protocol::{ProtocolError, ProtocolResult};
+use crate::storage::{self, Storage};
 use std::collections::HashSet;
+use std::sync::Arc;
 
 /// Validation result
 #[derive(Debug)]
@@ -20,22 +23,49 @@ pub struct ValidationResult {
 pub struct TransactionValidator {
     /// Maximum gas budget
     max_gas_budget: u64,
+    /// Minimum gas budget, enough to cover the base cost of any transaction
+    min_gas_budget: u64,
     /// Maximum transaction size
     max_transaction_size: usize,
     /// Maximum input objects
     max_input_objects: usize,
+    /// Maximum declared dependencies
+    max_dependencies: usize,
+    /// Chain id this node accepts
+    chain_id: String,
+    /// Storage holding the reference gas price
+    storage: Option<Arc<dyn Storage>>,
+    /// Cache of already verified signatures
+    signature_cache: Option<Arc<SignatureCache>>,
 }
 
 impl TransactionValidator {
     /// Create new validator
-    pub fn new() -> Self {
+    pub fn new(chain_id: String) -> Self {
         Self {
             max_gas_budget: 1_000_000,
+            min_gas_budget: GasSchedule::default().base_transaction_cost().value(),
             max_transaction_size: 128 * 1024, // 128KB
             max_input_objects: 2048,
+            max_dependencies: 64,
+            chain_id,
+            storage: None,
+            signature_cache: None,
         }
     }
 
+    /// Enforce the reference gas price persisted in storage
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Skip re-verifying signatures already verified through `cache`
+    pub fn with_signature_cache(mut self, cache: Arc<SignatureCache>) -> Self {
+        self.signature_cache = Some(cache);
+        self
+    }
+
     /// Validate transaction
     pub fn validate_transaction(
         &self,
@@ -44,6 +74,9 @@ impl TransactionValidator {
         // Validate basic fields
         self.validate_basic_fields(transaction)?;
 
+        // Validate chain id
+        self.validate_chain_id(transaction)?;
+
         // Validate signature
         self.validate_signature(transaction)?;
 
@@ -83,9 +116,24 @@ impl TransactionValidator {
         Ok(())
     }
 
+    /// Validate chain id
+    fn validate_chain_id(&self, transaction: &Transaction) -> ProtocolResult<()> {
+        if transaction.chain_id != self.chain_id {
+            return Err(ProtocolError::ChainIdMismatch {
+                expected: self.chain_id.clone(),
+                actual: transaction.chain_id.clone(),
+            });
+        }
+        Ok(())
+    }
+
     /// Validate signature
     fn validate_signature(&self, transaction: &Transaction) -> ProtocolResult<()> {
-        if !transaction.verify_signature() {
+        let valid = match &self.signature_cache {
+            Some(cache) => cache.verify(transaction),
+            None => transaction.verify_signature(),
+        };
+        if !valid {
             return Err(ProtocolError::InvalidSignature);
         }
         Ok(())
@@ -96,14 +144,46 @@ impl TransactionValidator {
         if transaction.gas_budget > self.max_gas_budget {
             return Err(ProtocolError::GasBudgetTooHigh);
         }
+        if transaction.gas_budget < self.min_gas_budget {
+            return Err(ProtocolError::GasBudgetTooLow {
+                budget: transaction.gas_budget,
+                min: self.min_gas_budget,
+            });
+        }
         if transaction.gas_price == 0 {
             return Err(ProtocolError::InvalidGasPrice);
         }
+        if let Some(storage) = &self.storage {
+            let floor = storage::reference_gas_price(storage.as_ref())?.unwrap_or(0);
+            if transaction.gas_price < floor {
+                return Err(ProtocolError::GasPriceTooLow {
+                    price: transaction.gas_price,
+                    floor,
+                });
+            }
+        }
         Ok(())
     }
 
+    /// Set minimum gas budget
+    pub fn set_min_gas_budget(&mut self, min_gas_budget: u64) {
+        self.min_gas_budget = min_gas_budget;
+    }
+
+    /// Set maximum declared dependencies
+    pub fn set_max_dependencies(&mut self, max_dependencies: usize) {
+        self.max_dependencies = max_dependencies;
+    }
+
     /// Validate dependencies
     fn validate_dependencies(&self, transaction: &Transaction) -> ProtocolResult<()> {
+        if transaction.dependencies.len() > self.max_dependencies {
+            return Err(ProtocolError::TooManyDependencies {
+                count: transaction.dependencies.len(),
+                max: self.max_dependencies,
+            });
+        }
+
         let mut seen = HashSet::new();
         for dep in &transaction.dependencies {
             if !seen.insert(dep) {
@@ -121,4 +201,111 @@ impl TransactionValidator {
         }
         Ok(input_objects)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Address;
+    use crate::crypto::{KeyPair, SignatureScheme};
+    use crate::transaction::{MoveTransaction, TransactionData, TransactionDigest};
+
+    fn signed_transaction(chain_id: &str) -> Transaction {
+        signed_transaction_with_dependencies(chain_id, vec![])
+    }
+
+    fn signed_transaction_with_dependencies(
+        chain_id: &str,
+        dependencies: Vec<TransactionDigest>,
+    ) -> Transaction {
+        signed_transaction_with(chain_id, dependencies, 1_000)
+    }
+
+    fn signed_transaction_with(
+        chain_id: &str,
+        dependencies: Vec<TransactionDigest>,
+        gas_budget: u64,
+    ) -> Transaction {
+        let mut transaction = Transaction::new(
+            TransactionData::Move(MoveTransaction {
+                module: None,
+                function: None,
+                type_arguments: vec![],
+                arguments: vec![],
+            }),
+            Address::from_bytes([1; 20]),
+            gas_budget,
+            1,
+            dependencies,
+            0,
+            0,
+            chain_id.to_string(),
+        );
+        transaction.sign(&KeyPair::generate(SignatureScheme::Ed25519).unwrap());
+        transaction
+    }
+
+    #[test]
+    fn test_matching_chain_id() {
+        let validator = TransactionValidator::new("sui-local".to_string());
+        assert!(validator.validate_transaction(&signed_transaction("sui-local")).is_ok());
+    }
+
+    #[test]
+    fn test_mismatching_chain_id() {
+        let validator = TransactionValidator::new("sui-local".to_string());
+        let result = validator.validate_transaction(&signed_transaction("sui-mainnet"));
+        assert!(matches!(
+            result,
+            Err(ProtocolError::ChainIdMismatch { ref expected, ref actual })
+                if expected == "sui-local" && actual == "sui-mainnet"
+        ));
+    }
+
+    #[test]
+    fn test_gas_price_floor() {
+        let storage = Arc::new(crate::storage::MemStorage::new());
+        let validator = TransactionValidator::new("sui-local".to_string())
+            .with_storage(storage.clone());
+
+        // Test transactions pay a gas price of 1
+        assert!(validator.validate_transaction(&signed_transaction("sui-local")).is_ok());
+
+        storage::set_reference_gas_price(storage.as_ref(), 10).unwrap();
+        assert!(matches!(
+            validator.validate_transaction(&signed_transaction("sui-local")),
+            Err(ProtocolError::GasPriceTooLow { price: 1, floor: 10 })
+        ));
+    }
+
+    #[test]
+    fn test_dependency_limit() {
+        let mut validator = TransactionValidator::new("sui-local".to_string());
+        validator.set_max_dependencies(2);
+
+        let dependencies: Vec<_> = (0..3u8)
+            .map(|i| TransactionDigest::from_bytes([i; 32]))
+            .collect();
+
+        let within = signed_transaction_with_dependencies("sui-local", dependencies[..2].to_vec());
+        assert!(validator.validate_transaction(&within).is_ok());
+
+        let over = signed_transaction_with_dependencies("sui-local", dependencies);
+        assert!(matches!(
+            validator.validate_transaction(&over),
+            Err(ProtocolError::TooManyDependencies { count: 3, max: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_min_gas_budget() {
+        let validator = TransactionValidator::new("sui-local".to_string());
+        let base_cost = GasSchedule::default().base_transaction_cost().value();
+
+        assert!(matches!(
+            validator.validate_transaction(&signed_transaction_with("sui-local", vec![], 0)),
+            Err(ProtocolError::GasBudgetTooLow { budget: 0, min }) if min == base_cost
+        ));
+        assert!(validator.validate_transaction(&signed_transaction_with("sui-local", vec![], base_cost)).is_ok());
+    }
+}
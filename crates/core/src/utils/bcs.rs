@@ -0,0 +1,27 @@
+//! Canonical BCS encoding used for digests and signed payloads.
+
+use super::{Hasher, Sha256Hasher};
+use crate::protocol::{ProtocolError, ProtocolResult};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Encode value as BCS bytes
+pub fn to_bytes<T: Serialize>(value: &T) -> ProtocolResult<Vec<u8>> {
+    ::bcs::to_bytes(value).map_err(|e| ProtocolError::SystemError(e.to_string()))
+}
+
+/// Decode value from BCS bytes
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> ProtocolResult<T> {
+    ::bcs::from_bytes(bytes).map_err(|e| ProtocolError::DeserializationError(e.to_string()))
+}
+
+/// SHA-256 over the BCS encoding of value
+pub fn hash<T: Serialize>(value: &T) -> ProtocolResult<[u8; 32]> {
+    hash_with::<Sha256Hasher, _>(value)
+}
+
+/// Digest of the BCS encoding of value using hasher `H`
+pub fn hash_with<H: Hasher, T: Serialize>(value: &T) -> ProtocolResult<[u8; 32]> {
+    let mut hasher = H::default();
+    hasher.update(&to_bytes(value)?);
+    Ok(hasher.finalize())
+}
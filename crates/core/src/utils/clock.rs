@@ -0,0 +1,53 @@
+use std::fmt::Debug;
+#[cfg(any(test, feature = "test-utils"))]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Source of the current time in seconds, injectable so time-dependent
+/// logic can be tested deterministically
+pub trait Clock: Debug + Send + Sync {
+    /// Current timestamp in seconds
+    fn now(&self) -> u64;
+}
+
+/// Wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        super::current_timestamp()
+    }
+}
+
+/// Clock that only moves when told to
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Debug, Default)]
+pub struct MockClock {
+    /// Current timestamp in seconds
+    now: AtomicU64,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl MockClock {
+    /// Create a clock stopped at `now`
+    pub fn new(now: u64) -> Self {
+        Self { now: AtomicU64::new(now) }
+    }
+
+    /// Set the current time
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    /// Move the clock forward by `seconds`
+    pub fn advance(&self, seconds: u64) {
+        self.now.fetch_add(seconds, Ordering::SeqCst);
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Clock for MockClock {
+    fn now(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,79 @@
+//! Pluggable 32-byte hashers for digests.
+
+use sha2::Digest;
+
+/// Incremental hasher producing a 32-byte digest
+pub trait Hasher: Default {
+    /// Feed data into the hasher
+    fn update(&mut self, data: &[u8]);
+
+    /// Consume the hasher and return the digest
+    fn finalize(self) -> [u8; 32];
+}
+
+/// SHA-256, the hasher used for all on-chain digests
+#[derive(Default)]
+pub struct Sha256Hasher(sha2::Sha256);
+
+impl Hasher for Sha256Hasher {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        self.0.finalize().into()
+    }
+}
+
+/// FNV-1a, a fast non-cryptographic hasher for property tests and benchmarks
+#[cfg(any(test, feature = "test-utils"))]
+pub struct Fnv1aHasher(u64);
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Fnv1aHasher {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Self(Self::OFFSET)
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Hasher for Fnv1aHasher {
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        // Spread the 64-bit state over four lanes
+        let mut digest = [0u8; 32];
+        for (lane, chunk) in digest.chunks_mut(8).enumerate() {
+            let value = (self.0 ^ lane as u64).wrapping_mul(Self::PRIME);
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest<H: Hasher>(data: &[u8]) -> [u8; 32] {
+        let mut hasher = H::default();
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    #[test]
+    fn test_sha256_hasher_matches_hash_message() {
+        assert_eq!(digest::<Sha256Hasher>(b"swift"), crate::utils::hash_message(b"swift"));
+        assert_ne!(digest::<Fnv1aHasher>(b"swift"), digest::<Sha256Hasher>(b"swift"));
+    }
+}
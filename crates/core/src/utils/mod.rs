@@ -1,9 +1,20 @@
 //! Utility functions and helpers.
 
+pub mod bcs;
+mod clock;
 mod crypto;
+mod hasher;
 
+pub use clock::{Clock, SystemClock};
+#[cfg(any(test, feature = "test-utils"))]
+pub use clock::MockClock;
 pub use crypto::{hash_message, verify_signature};
+pub use hasher::{Hasher, Sha256Hasher};
+#[cfg(any(test, feature = "test-utils"))]
+pub use hasher::Fnv1aHasher;
 
+use crate::protocol::{ProtocolError, ProtocolResult};
+use serde::de::DeserializeOwned;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Get current timestamp in milliseconds
@@ -40,4 +51,56 @@ pub fn pad_bytes(bytes: &[u8], length: usize) -> Vec<u8> {
 /// Truncate bytes to length
 pub fn truncate_bytes(bytes: &[u8], length: usize) -> Vec<u8> {
     bytes.iter().take(length).cloned().collect()
-}
\ No newline at end of file
+}
+
+/// Decode untrusted bytes, refusing inputs or length prefixes above `max_len`
+pub fn safe_decode<T: DeserializeOwned>(bytes: &[u8], max_len: u64) -> ProtocolResult<T> {
+    use bincode::Options;
+
+    if bytes.len() as u64 > max_len {
+        return Err(ProtocolError::DeserializationError(format!(
+            "Input of {} bytes exceeds limit of {} bytes",
+            bytes.len(),
+            max_len
+        )));
+    }
+
+    // Same encoding as `bincode::serialize`, with a size limit
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(max_len)
+        .deserialize(bytes)
+        .map_err(|e| ProtocolError::DeserializationError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_decode_roundtrip() -> ProtocolResult<()> {
+        let value = vec![1u64, 2, 3];
+        let bytes = bincode::serialize(&value).unwrap();
+        let decoded: Vec<u64> = safe_decode(&bytes, 1024)?;
+        assert_eq!(decoded, value);
+        Ok(())
+    }
+
+    #[test]
+    fn test_safe_decode_rejects_huge_length_prefix() {
+        // Length prefix claiming 16 GiB of elements
+        let mut bytes = (16u64 << 30).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[0u8; 8]);
+
+        let result: ProtocolResult<Vec<u8>> = safe_decode(&bytes, 1024);
+        assert!(matches!(result, Err(ProtocolError::DeserializationError(_))));
+    }
+
+    #[test]
+    fn test_safe_decode_rejects_oversized_input() {
+        let bytes = vec![0u8; 2048];
+        let result: ProtocolResult<Vec<u8>> = safe_decode(&bytes, 1024);
+        assert!(result.is_err());
+    }
+}